@@ -1,13 +1,22 @@
 use std::path::Path;
 use tauri::State;
 
-use crate::extractors::{extract_content, extract_content_structured};
-use crate::models::DocumentContent;
+use crate::extractors::{
+    annotate_text_direction, container_path, extract_child_content, extract_child_structured,
+    extract_content, extract_content_structured, extract_outline, outline_from_sections,
+    split_child_path,
+};
+use crate::models::{DocumentContent, OutlineNode};
 use crate::state::AppState;
 
 /// Extract file content for preview (plain text)
 #[tauri::command]
 pub async fn extract_file_content(file_path: String) -> Result<String, String> {
+    if let Some((container, child)) = split_child_path(&file_path) {
+        return extract_child_content(Path::new(container), child)
+            .ok_or_else(|| "Failed to extract content".to_string());
+    }
+
     let path = Path::new(&file_path);
     let ext = path
         .extension()
@@ -19,29 +28,65 @@ pub async fn extract_file_content(file_path: String) -> Result<String, String> {
 }
 
 /// Extract file content for rich preview (structured)
+///
+/// Sections whose extractor didn't declare a text direction get one from
+/// their first strong character.
 #[tauri::command]
 pub async fn extract_file_content_structured(file_path: String) -> Result<DocumentContent, String> {
-    let path = Path::new(&file_path);
-    let ext = path
-        .extension()
-        .and_then(|e| e.to_str())
-        .map(|e| e.to_lowercase())
-        .unwrap_or_default();
+    let content = match split_child_path(&file_path) {
+        Some((container, child)) => extract_child_structured(Path::new(container), child),
+        None => {
+            let path = Path::new(&file_path);
+            let ext = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| e.to_lowercase())
+                .unwrap_or_default();
+
+            extract_content_structured(path, &ext)
+        }
+    };
 
-    extract_content_structured(path, &ext)
-        .ok_or_else(|| "Failed to extract structured content".to_string())
+    let mut content = content.ok_or_else(|| "Failed to extract structured content".to_string())?;
+    annotate_text_direction(&mut content);
+    Ok(content)
+}
+
+/// Get a file's outline (table of contents), with section indexes into its preview
+#[tauri::command]
+pub async fn get_document_outline(file_path: String) -> Result<Vec<OutlineNode>, String> {
+    let outline = match split_child_path(&file_path) {
+        Some((container, child)) => extract_child_structured(Path::new(container), child)
+            .map(|content| outline_from_sections(&content.sections)),
+        None => {
+            let path = Path::new(&file_path);
+            let ext = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| e.to_lowercase())
+                .unwrap_or_default();
+
+            extract_outline(path, &ext)
+        }
+    };
+
+    outline.ok_or_else(|| "Failed to extract document outline".to_string())
 }
 
 /// Move file to trash
 #[tauri::command]
 pub async fn delete_file(file_path: String, state: State<'_, AppState>) -> Result<(), String> {
+    if split_child_path(&file_path).is_some() {
+        return Err("Email messages and attachments can't be deleted on their own".to_string());
+    }
+
     // Use trash crate to move to system trash
     trash::delete(&file_path).map_err(|e| e.to_string())?;
 
-    // Remove from Vec index
+    // Remove from Vec index (with any child entries of an email file)
     {
         let mut index = state.index.write().map_err(|e| e.to_string())?;
-        index.retain(|f| f.path != file_path);
+        index.retain(|f| container_path(&f.path) != file_path);
     }
 
     // Note: FTS5 index is automatically updated when save_index is called
@@ -50,15 +95,17 @@ pub async fn delete_file(file_path: String, state: State<'_, AppState>) -> Resul
     Ok(())
 }
 
-/// Open file with default application
+/// Open file with default application (email children open their container)
 #[tauri::command]
 pub async fn open_file(file_path: String) -> Result<(), String> {
-    opener::open(&file_path).map_err(|e| e.to_string())
+    opener::open(container_path(&file_path)).map_err(|e| e.to_string())
 }
 
 /// Show file in folder/explorer
 #[tauri::command]
 pub async fn show_in_folder(file_path: String) -> Result<(), String> {
+    let file_path = container_path(&file_path).to_string();
+
     #[cfg(target_os = "windows")]
     {
         std::process::Command::new("explorer")
@@ -103,6 +150,7 @@ pub async fn show_in_folder(file_path: String) -> Result<(), String> {
 /// Uses COM automation on Windows for Office documents
 #[tauri::command]
 pub async fn open_file_and_search(file_path: String, search_term: String) -> Result<(), String> {
+    let file_path = container_path(&file_path).to_string();
     let ext = Path::new(&file_path)
         .extension()
         .and_then(|e| e.to_str())
@@ -115,9 +163,9 @@ pub async fn open_file_and_search(file_path: String, search_term: String) -> Res
     }
 
     match ext.as_str() {
-        "doc" | "docx" => open_word_at_search(&file_path, &search_term),
-        "xls" | "xlsx" => open_excel_at_search(&file_path, &search_term),
-        "ppt" | "pptx" => open_powerpoint_at_search(&file_path, &search_term),
+        "doc" | "docx" | "docm" | "dotx" | "dotm" => open_word_at_search(&file_path, &search_term),
+        "xls" | "xlsx" | "xlsm" | "xltx" => open_excel_at_search(&file_path, &search_term),
+        "ppt" | "pptx" | "pptm" | "potx" => open_powerpoint_at_search(&file_path, &search_term),
         _ => {
             // For other files, copy search term to clipboard and open
             #[cfg(target_os = "windows")]
@@ -8,6 +8,8 @@ mod search;
 mod files;
 mod folders;
 mod persistence;
+mod settings;
+mod thumbnails;
 pub mod migrations;
 
 pub use scanning::*;
@@ -15,4 +17,6 @@ pub use search::*;
 pub use files::*;
 pub use folders::*;
 pub use persistence::*;
+pub use settings::*;
+pub use thumbnails::*;
 pub use migrations::{run_migrations, get_schema_version, CURRENT_SCHEMA_VERSION};
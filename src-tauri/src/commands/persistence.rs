@@ -3,13 +3,29 @@ use rusqlite::{params, Connection};
 use std::collections::HashSet;
 use std::fs;
 use std::path::Path;
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 use walkdir::WalkDir;
 
-use crate::extractors::extract_content;
+use crate::extractors::{container_path, detect_language, extract_content};
 use crate::models::{FileData, IndexingProgress};
+use crate::search::{
+    rebuild_fts5_index, register_fold_function, register_stem_function, CharFolding,
+};
 use crate::state::AppState;
 
+/// Open the index database with DocuFind's SQL functions registered
+///
+/// Use this for any connection that writes to `files`: the FTS5 sync triggers
+/// call `docufind_fold()` and `docufind_stem()`, which SQLite only knows
+/// per-connection.
+pub fn open_database(db_path: &Path) -> Result<Connection, String> {
+    let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+    let folding = CharFolding::new(&super::settings::load_char_equivalences(&conn));
+    register_fold_function(&conn, folding).map_err(|e| e.to_string())?;
+    register_stem_function(&conn).map_err(|e| e.to_string())?;
+    Ok(conn)
+}
+
 /// Initialize SQLite database schema
 pub fn init_database(conn: &Connection) -> Result<(), rusqlite::Error> {
     conn.execute(
@@ -19,11 +35,17 @@ pub fn init_database(conn: &Connection) -> Result<(), rusqlite::Error> {
             size INTEGER NOT NULL,
             last_modified TEXT NOT NULL,
             file_type TEXT NOT NULL,
-            content TEXT NOT NULL
+            content TEXT NOT NULL,
+            language TEXT,
+            has_macros INTEGER
         )",
         [],
     )?;
 
+    // Columns added after the first release - upgrade existing databases in place
+    ensure_column(conn, "files", "language", "TEXT")?;
+    ensure_column(conn, "files", "has_macros", "INTEGER")?;
+
     conn.execute(
         "CREATE TABLE IF NOT EXISTS folders (
             path TEXT PRIMARY KEY,
@@ -60,6 +82,7 @@ pub fn init_database(conn: &Connection) -> Result<(), rusqlite::Error> {
     // FTS5 Full-Text Search virtual table (Contentless - External Content)
     // Refers to 'files' table to avoid duplicating content storage
     // tokenize='unicode61 remove_diacritics 1' for multilingual support
+    // prefix='2 3' indexes short prefixes so `co*` doesn't scan every term
 
     // Check if FTS5 table exists and is contentless. If not, we drop and recreate.
     // This handles migration from old schema (duplicated content) to new (contentless).
@@ -69,7 +92,8 @@ pub fn init_database(conn: &Connection) -> Result<(), rusqlite::Error> {
             [],
             |row| {
                 let sql: String = row.get(0)?;
-                Ok(!sql.contains("content='files'"))
+                // Older tables lacked external content or prefix indexes
+                Ok(!sql.contains("content='files'") || !sql.contains("prefix="))
             },
         )
         .unwrap_or(false); // If table doesn't exist, it's false (or effectively strictly needed creation)
@@ -89,16 +113,74 @@ pub fn init_database(conn: &Connection) -> Result<(), rusqlite::Error> {
             file_type,
             content='files',
             content_rowid='rowid',
+            tokenize='unicode61 remove_diacritics 1',
+            prefix='2 3'
+        )",
+        [],
+    )?;
+
+    // Trigram index over the same folded columns: substrings and Chinese or
+    // Japanese text, which unicode61 keeps as whole runs (see `query_plan`)
+    conn.execute(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS files_trigram USING fts5(
+            path,
+            name,
+            content,
+            file_type,
+            content='files',
+            content_rowid='rowid',
+            tokenize='trigram remove_diacritics 1'
+        )",
+        [],
+    )?;
+
+    // Stemmed index over the folded columns: one stem per word, so positions
+    // (and highlights) line up with the text in `files` (see `stemming`)
+    conn.execute(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS files_stemmed USING fts5(
+            path,
+            name,
+            content,
+            file_type,
+            content='files',
+            content_rowid='rowid',
             tokenize='unicode61 remove_diacritics 1'
         )",
         [],
     )?;
 
+    // Triggers written before character folding indexed raw text, and those
+    // written before the trigram or stemmed index don't fill it. Recreate them
+    // and re-sync the FTS5 tables so the index matches the new write path.
+    let triggers_outdated = conn
+        .query_row(
+            "SELECT sql FROM sqlite_master WHERE type = 'trigger' AND name = 'files_ai'",
+            [],
+            |row| {
+                let sql: String = row.get(0)?;
+                Ok(!sql.contains("docufind_fold")
+                    || !sql.contains("files_trigram")
+                    || !sql.contains("files_stemmed"))
+            },
+        )
+        .unwrap_or(false);
+
+    if triggers_outdated {
+        conn.execute("DROP TRIGGER IF EXISTS files_ai", [])?;
+        conn.execute("DROP TRIGGER IF EXISTS files_ad", [])?;
+        conn.execute("DROP TRIGGER IF EXISTS files_au", [])?;
+    }
+
     // Triggers to keep FTS5 in sync with main 'files' table automatically
+    // Name and content go through the user's character folding table
     conn.execute(
         "CREATE TRIGGER IF NOT EXISTS files_ai AFTER INSERT ON files BEGIN
             INSERT INTO files_fts(rowid, path, name, content, file_type) 
-            VALUES (new.rowid, new.path, new.name, new.content, new.file_type);
+            VALUES (new.rowid, new.path, docufind_fold(new.name), docufind_fold(new.content), new.file_type);
+            INSERT INTO files_trigram(rowid, path, name, content, file_type)
+            VALUES (new.rowid, new.path, docufind_fold(new.name), docufind_fold(new.content), new.file_type);
+            INSERT INTO files_stemmed(rowid, path, name, content, file_type)
+            VALUES (new.rowid, docufind_stem(new.path), docufind_stem(docufind_fold(new.name)), docufind_stem(docufind_fold(new.content)), new.file_type);
         END;",
         [],
     )?;
@@ -106,7 +188,11 @@ pub fn init_database(conn: &Connection) -> Result<(), rusqlite::Error> {
     conn.execute(
         "CREATE TRIGGER IF NOT EXISTS files_ad AFTER DELETE ON files BEGIN
             INSERT INTO files_fts(files_fts, rowid, path, name, content, file_type) 
-            VALUES('delete', old.rowid, old.path, old.name, old.content, old.file_type);
+            VALUES('delete', old.rowid, old.path, docufind_fold(old.name), docufind_fold(old.content), old.file_type);
+            INSERT INTO files_trigram(files_trigram, rowid, path, name, content, file_type)
+            VALUES('delete', old.rowid, old.path, docufind_fold(old.name), docufind_fold(old.content), old.file_type);
+            INSERT INTO files_stemmed(files_stemmed, rowid, path, name, content, file_type)
+            VALUES('delete', old.rowid, docufind_stem(old.path), docufind_stem(docufind_fold(old.name)), docufind_stem(docufind_fold(old.content)), old.file_type);
         END;",
         [],
     )?;
@@ -114,13 +200,32 @@ pub fn init_database(conn: &Connection) -> Result<(), rusqlite::Error> {
     conn.execute(
         "CREATE TRIGGER IF NOT EXISTS files_au AFTER UPDATE ON files BEGIN
             INSERT INTO files_fts(files_fts, rowid, path, name, content, file_type)
-            VALUES('delete', old.rowid, old.path, old.name, old.content, old.file_type);
+            VALUES('delete', old.rowid, old.path, docufind_fold(old.name), docufind_fold(old.content), old.file_type);
             INSERT INTO files_fts(rowid, path, name, content, file_type)
-            VALUES (new.rowid, new.path, new.name, new.content, new.file_type);
+            VALUES (new.rowid, new.path, docufind_fold(new.name), docufind_fold(new.content), new.file_type);
+            INSERT INTO files_trigram(files_trigram, rowid, path, name, content, file_type)
+            VALUES('delete', old.rowid, old.path, docufind_fold(old.name), docufind_fold(old.content), old.file_type);
+            INSERT INTO files_trigram(rowid, path, name, content, file_type)
+            VALUES (new.rowid, new.path, docufind_fold(new.name), docufind_fold(new.content), new.file_type);
+            INSERT INTO files_stemmed(files_stemmed, rowid, path, name, content, file_type)
+            VALUES('delete', old.rowid, docufind_stem(old.path), docufind_stem(docufind_fold(old.name)), docufind_stem(docufind_fold(old.content)), old.file_type);
+            INSERT INTO files_stemmed(rowid, path, name, content, file_type)
+            VALUES (new.rowid, docufind_stem(new.path), docufind_stem(docufind_fold(new.name)), docufind_stem(docufind_fold(new.content)), new.file_type);
         END;",
         [],
     )?;
 
+    // A recreated table starts out empty
+    if triggers_outdated || fts_rebuild_needed {
+        crate::search::fts5_search::repopulate_fts5(conn, |_, _| {})?;
+    }
+
+    // Terms of the FTS5 index and how many files hold each (fuzzy search)
+    conn.execute(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS files_vocab USING fts5vocab('files_fts', 'row')",
+        [],
+    )?;
+
     // Create indexes for faster queries
     conn.execute(
         "CREATE INDEX IF NOT EXISTS idx_files_folder ON files(path)",
@@ -141,6 +246,25 @@ pub fn init_database(conn: &Connection) -> Result<(), rusqlite::Error> {
     Ok(())
 }
 
+/// Add a column to an existing table if it is missing
+fn ensure_column(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), rusqlite::Error> {
+    let exists = conn
+        .prepare(&format!("SELECT {} FROM {} LIMIT 0", column, table))
+        .is_ok();
+    if !exists {
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )?;
+    }
+    Ok(())
+}
+
 /// Check FTS5 index health and return status
 /// Returns (is_healthy, file_count, fts5_count)
 pub fn check_fts5_health(conn: &Connection) -> Result<(bool, i64, i64), String> {
@@ -183,8 +307,11 @@ pub fn save_index_internal(state: &State<'_, AppState>) -> Result<(), String> {
 
     fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
 
+    // Wait for an FTS5 rebuild (and open the connection after it, so the
+    // triggers fold with the table it saved)
+    let _writes = state.index_writes.lock().map_err(|e| e.to_string())?;
     let db_path = data_dir.join("docufind.db");
-    let mut conn = Connection::open(&db_path).map_err(|e| e.to_string())?;
+    let mut conn = open_database(&db_path)?;
 
     init_database(&conn).map_err(|e| e.to_string())?;
 
@@ -215,8 +342,8 @@ pub fn save_index_internal(state: &State<'_, AppState>) -> Result<(), String> {
     {
         let mut stmt = tx
             .prepare(
-                "INSERT INTO files (path, name, size, last_modified, file_type, content) 
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                "INSERT INTO files (path, name, size, last_modified, file_type, content, language, has_macros) 
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
             )
             .map_err(|e| e.to_string())?;
 
@@ -227,7 +354,9 @@ pub fn save_index_internal(state: &State<'_, AppState>) -> Result<(), String> {
                 file.size,
                 file.last_modified.to_rfc3339(),
                 file.file_type,
-                file.content
+                file.content,
+                file.language,
+                file.has_macros
             ])
             .map_err(|e| e.to_string())?;
         }
@@ -270,7 +399,7 @@ pub fn save_index_internal(state: &State<'_, AppState>) -> Result<(), String> {
     // Update connection in state
     {
         let mut db_guard = state.db.lock().map_err(|e| e.to_string())?;
-        *db_guard = Some(Connection::open(&db_path).map_err(|e| e.to_string())?);
+        *db_guard = Some(open_database(&db_path)?);
     }
 
     Ok(())
@@ -335,7 +464,28 @@ pub async fn load_index(state: State<'_, AppState>) -> Result<serde_json::Value,
     let load_start = std::time::Instant::now();
     println!("[Load] Starting index load from {:?}", db_path);
 
-    let conn = Connection::open(&db_path).map_err(|e| e.to_string())?;
+    let conn = open_database(&db_path)?;
+    init_database(&conn).map_err(|e| e.to_string())?;
+
+    // Load character folding table for query-side normalization
+    {
+        let mut folding = state.char_folding.write().map_err(|e| e.to_string())?;
+        *folding = CharFolding::new(&super::settings::load_char_equivalences(&conn));
+    }
+    {
+        let mut indexing = state.indexing_settings.write().map_err(|e| e.to_string())?;
+        *indexing = super::settings::load_indexing_settings(&conn);
+        *state.text_file_rules.write().map_err(|e| e.to_string())? =
+            super::settings::text_file_rules(&indexing);
+    }
+    {
+        let mut ranking = state.ranking_settings.write().map_err(|e| e.to_string())?;
+        *ranking = super::settings::load_ranking_settings(&conn);
+    }
+    {
+        let mut query = state.query_settings.write().map_err(|e| e.to_string())?;
+        *query = super::settings::load_query_settings(&conn);
+    }
 
     // Load folders
     let mut folder_stmt = conn
@@ -375,11 +525,18 @@ pub async fn load_index(state: State<'_, AppState>) -> Result<serde_json::Value,
 
     // Load files
     let mut file_stmt = conn
-        .prepare("SELECT path, name, size, last_modified, file_type, content FROM files")
+        .prepare(
+            "SELECT path, name, size, last_modified, file_type, content, language, has_macros FROM files",
+        )
         .map_err(|e| e.to_string())?;
 
     let file_rows = file_stmt
         .query_map([], |row| {
+            let content: String = row.get(5)?;
+            // Rows saved before language detection existed are detected on load
+            let language = row
+                .get::<_, Option<String>>(6)?
+                .unwrap_or_else(|| detect_language(&content).to_string());
             Ok(FileData {
                 path: row.get(0)?,
                 name: row.get(1)?,
@@ -388,7 +545,9 @@ pub async fn load_index(state: State<'_, AppState>) -> Result<serde_json::Value,
                     .map(|dt| dt.with_timezone(&Utc))
                     .unwrap_or_else(|_| Utc::now()),
                 file_type: row.get(4)?,
-                content: row.get(5)?,
+                content,
+                language,
+                has_macros: row.get::<_, Option<bool>>(7)?.unwrap_or(false),
             })
         })
         .map_err(|e| e.to_string())?;
@@ -399,7 +558,7 @@ pub async fn load_index(state: State<'_, AppState>) -> Result<serde_json::Value,
         if valid_folders
             .iter()
             .any(|folder| file.path.starts_with(folder))
-            && Path::new(&file.path).exists()
+            && Path::new(container_path(&file.path)).exists()
         {
             valid_files.push(file);
         }
@@ -422,10 +581,13 @@ pub async fn load_index(state: State<'_, AppState>) -> Result<serde_json::Value,
         *excluded = excluded_folders.iter().cloned().collect();
     }
 
+    // A rebuild below waits for (and holds off) other index writes
+    let _writes = state.index_writes.lock().map_err(|e| e.to_string())?;
+
     // Store connection (database is already v2+ since we deleted old ones above)
     {
         let mut db_guard = state.db.lock().map_err(|e| e.to_string())?;
-        let conn = Connection::open(&db_path).map_err(|e| e.to_string())?;
+        let conn = open_database(&db_path)?;
         *db_guard = Some(conn);
     }
 
@@ -444,8 +606,7 @@ pub async fn load_index(state: State<'_, AppState>) -> Result<serde_json::Value,
                 // This should rarely happen in v2+ databases, but handle it gracefully
                 println!("[FTS5] Index mismatch detected, rebuilding...");
                 let start = std::time::Instant::now();
-                conn.execute("INSERT INTO files_fts(files_fts) VALUES('rebuild')", [])
-                    .map_err(|e| e.to_string())?;
+                rebuild_fts5_index(conn)?;
                 println!("[FTS5] Rebuilt index in {:?}", start.elapsed());
             }
         }
@@ -488,6 +649,7 @@ pub async fn clear_index(state: State<'_, AppState>) -> Result<(), String> {
     }
     // Note: FTS5 index is cleared when files table is deleted below
     {
+        let _writes = state.index_writes.lock().map_err(|e| e.to_string())?;
         let db_guard = state.db.lock().map_err(|e| e.to_string())?;
         if let Some(conn) = db_guard.as_ref() {
             // Use immediate checks
@@ -504,6 +666,11 @@ pub async fn clear_index(state: State<'_, AppState>) -> Result<(), String> {
         }
     }
 
+    // Thumbnails of the cleared files are no longer needed
+    if let Some(data_dir) = state.get_data_dir() {
+        super::thumbnails::clear_thumbnail_cache(&data_dir);
+    }
+
     Ok(())
 }
 
@@ -543,7 +710,7 @@ fn mark_doc_migration_done(state: &State<'_, AppState>) -> Result<(), String> {
     };
 
     let db_path = data_dir.join("docufind.db");
-    let conn = Connection::open(&db_path).map_err(|e| e.to_string())?;
+    let conn = open_database(&db_path)?;
     init_database(&conn).map_err(|e| e.to_string())?;
 
     conn.execute(
@@ -600,7 +767,7 @@ pub async fn scan_for_new_doc_files(
         if let Ok(data_dir) = state.data_dir.lock() {
             if let Some(ref data_dir_path) = *data_dir {
                 let db_path = data_dir_path.join("docufind.db");
-                if let Ok(conn) = Connection::open(&db_path) {
+                if let Ok(conn) = open_database(&db_path) {
                     if let Ok(mut stmt) =
                         conn.prepare("SELECT path FROM files WHERE file_type = 'word'")
                     {
@@ -638,6 +805,9 @@ pub async fn scan_for_new_doc_files(
 
     // Spawn background task for BOTH scanning and indexing
     std::thread::spawn(move || {
+        // Database writes wait for an FTS5 rebuild
+        let app_state = app.state::<AppState>();
+
         // Find .doc files that aren't indexed yet (now in background)
         let mut new_doc_files: Vec<std::path::PathBuf> = Vec::new();
 
@@ -674,7 +844,7 @@ pub async fn scan_for_new_doc_files(
             // Mark migration done silently
             if let Some(ref data_dir_path) = data_dir {
                 let db_path = data_dir_path.join("docufind.db");
-                if let Ok(conn) = Connection::open(&db_path) {
+                if let Ok(conn) = open_database(&db_path) {
                     let _ = conn.execute(
                         "INSERT OR REPLACE INTO metadata (key, value) VALUES ('doc_migration_done', '1')",
                         [],
@@ -725,6 +895,7 @@ pub async fn scan_for_new_doc_files(
 
                 // Extract content
                 if let Some(content) = extract_content(file_path, "doc") {
+                    let language = detect_language(&content).to_string();
                     let file_data = FileData {
                         path: path_str.clone(),
                         name: file_name.clone(),
@@ -732,6 +903,8 @@ pub async fn scan_for_new_doc_files(
                         last_modified: modified,
                         file_type: "word".to_string(),
                         content,
+                        language,
+                        has_macros: false,
                     };
 
                     new_files.push(file_data);
@@ -746,17 +919,19 @@ pub async fn scan_for_new_doc_files(
                     if !new_files.is_empty() {
                         if let Some(ref data_dir_path) = data_dir {
                             let db_path = data_dir_path.join("docufind.db");
-                            if let Ok(conn) = Connection::open(&db_path) {
+                            let _writes = app_state.index_writes.lock();
+                            if let Ok(conn) = open_database(&db_path) {
                                 for file in &new_files {
                                     let _ = conn.execute(
-                                        "INSERT OR REPLACE INTO files (path, name, content, file_type, size, last_modified) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                                        "INSERT OR REPLACE INTO files (path, name, content, file_type, size, last_modified, language) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
                                         params![
                                             file.path,
                                             file.name,
                                             file.content,
                                             file.file_type,
                                             file.size as i64,
-                                            file.last_modified.timestamp()
+                                            file.last_modified.timestamp(),
+                                            file.language
                                         ],
                                     );
                                 }
@@ -793,18 +968,20 @@ pub async fn scan_for_new_doc_files(
             // Save newly indexed files to database
             if let Some(ref data_dir_path) = data_dir {
                 let db_path = data_dir_path.join("docufind.db");
-                if let Ok(conn) = Connection::open(&db_path) {
+                let _writes = app_state.index_writes.lock();
+                if let Ok(conn) = open_database(&db_path) {
                     // Only save newly indexed files (not the whole index)
                     for file in &new_files {
                         let _ = conn.execute(
-                            "INSERT OR REPLACE INTO files (path, name, content, file_type, size, last_modified) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                            "INSERT OR REPLACE INTO files (path, name, content, file_type, size, last_modified, language) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
                             params![
                                 file.path,
                                 file.name,
                                 file.content,
                                 file.file_type,
                                 file.size as i64,
-                                file.last_modified.timestamp()
+                                file.last_modified.timestamp(),
+                                file.language
                             ],
                         );
                     }
@@ -820,7 +997,7 @@ pub async fn scan_for_new_doc_files(
             // Mark migration done even if no files indexed
             if let Some(ref data_dir_path) = data_dir {
                 let db_path = data_dir_path.join("docufind.db");
-                if let Ok(conn) = Connection::open(&db_path) {
+                if let Ok(conn) = open_database(&db_path) {
                     let _ = conn.execute(
                         "INSERT OR REPLACE INTO metadata (key, value) VALUES ('doc_migration_done', '1')",
                         [],
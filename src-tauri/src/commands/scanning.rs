@@ -5,12 +5,17 @@ use std::sync::Arc;
 use tauri::{AppHandle, Emitter, State};
 use walkdir::WalkDir;
 
-use crate::extractors::{extract_content, get_file_type, is_supported_extension};
+use super::persistence::open_database;
+use super::settings::{load_email_attachments_scanned, save_email_attachments_scanned};
+use crate::extractors::{
+    child_path, detect_language, extract_content, extract_email_entries, file_type_for_path,
+    get_file_type, has_macros, is_supported_file, split_child_path, EMAIL_EXTENSIONS,
+};
 use crate::models::{FileData, FolderInfo, IndexingProgress};
 use crate::state::AppState;
 
-/// Scan a folder and index all supported documents (DOCX, PPTX, XLSX, TXT, MD)
-/// Scan a folder and index all supported documents (DOCX, PPTX, XLSX, TXT, MD)
+/// Scan a folder and index all supported documents (DOCX, PPTX, XLSX, TXT, MD,
+/// plus configured text/code extensions and file name patterns)
 #[tauri::command]
 pub async fn scan_folder(
     path: String,
@@ -20,6 +25,12 @@ pub async fn scan_folder(
 ) -> Result<Vec<FileData>, String> {
     let should_force = force_reindex.unwrap_or(false);
 
+    let text_rules = state
+        .text_file_rules
+        .read()
+        .map_err(|e| e.to_string())?
+        .clone();
+
     // Phase 1: Discover all files
     let _ = app.emit(
         "indexing-progress",
@@ -58,12 +69,8 @@ pub async fn scan_folder(
             continue;
         }
 
-        if let Some(ext) = entry.path().extension() {
-            let ext_str = ext.to_str().unwrap_or("").to_lowercase();
-
-            if is_supported_extension(&ext_str) {
-                entries.push(entry);
-            }
+        if is_supported_file(entry.path(), &text_rules) {
+            entries.push(entry);
         }
     }
 
@@ -92,14 +99,36 @@ pub async fn scan_folder(
     // par_iter will reference it.
     let existing_map_ref = &existing_files_map;
 
+    let index_attachments = state
+        .indexing_settings
+        .read()
+        .map_err(|e| e.to_string())?
+        .index_email_attachments;
+
+    // Email files indexed with the other attachment setting are read again,
+    // so their attachment entries are added or removed
+    let db_path = state.get_data_dir().map(|dir| dir.join("docufind.db"));
+    let attachments_scanned = db_path
+        .as_ref()
+        .and_then(|db_path| open_database(db_path).ok())
+        .and_then(|conn| load_email_attachments_scanned(&conn, &path));
+    let reread_email = attachments_scanned != Some(index_attachments);
+
+    // Each file yields its own entry plus, for email, child entries
+    // (mbox messages, attachments) - see `extract_email_entries`
     let new_files: Vec<FileData> = entries
         .par_iter()
-        .filter_map(|entry| {
+        .filter_map(|entry| -> Option<Vec<FileData>> {
             let file_path = entry.path();
             let file_name = entry.file_name().to_string_lossy().to_string();
-            let ext = file_path.extension()?.to_str()?.to_lowercase();
-
-            let file_type = get_file_type(&ext)?;
+            // Pattern-matched files (Dockerfile, ...) may have no extension
+            let ext = file_path
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| e.to_lowercase())
+                .unwrap_or_default();
+
+            let file_type = file_type_for_path(file_path, &text_rules)?;
             let metadata = entry.metadata().ok()?;
             let size = metadata.len();
 
@@ -123,8 +152,12 @@ pub async fn scan_folder(
             // If we return None, existing file is RETAINED. Correct!
 
             // Check if file is unchanged
+            let is_email = EMAIL_EXTENSIONS.contains(&ext.as_str());
             if let Some((old_size, old_mod_ts)) = existing_map_ref.get(&path_str) {
-                if *old_size == size && *old_mod_ts == modified.timestamp() {
+                if *old_size == size
+                    && *old_mod_ts == modified.timestamp()
+                    && !(is_email && reread_email)
+                {
                     // Update progress even if skipped
                     let current = progress_counter.fetch_add(1, Ordering::SeqCst) + 1;
                     // ... verify emit logic ...
@@ -149,6 +182,8 @@ pub async fn scan_folder(
 
             // If we are here, it's a new or modified file. EXTRACT!
             let content = extract_content(file_path, &ext).unwrap_or_default();
+            let language = detect_language(&content).to_string();
+            let has_macros = has_macros(file_path, &ext);
 
             // Update progress
             let current = progress_counter.fetch_add(1, Ordering::SeqCst) + 1;
@@ -168,15 +203,41 @@ pub async fn scan_folder(
                 );
             }
 
-            Some(FileData {
+            let mut files = Vec::new();
+            if is_email {
+                for child in extract_email_entries(file_path, &ext, index_attachments, &text_rules)
+                {
+                    let Some(child_type) = get_file_type(&child.ext)
+                        .or_else(|| text_rules.file_type(&child.name, &child.ext))
+                    else {
+                        continue;
+                    };
+                    files.push(FileData {
+                        path: child_path(&path_str, &child.child),
+                        name: child.name,
+                        size: child.size,
+                        last_modified: modified,
+                        file_type: child_type.to_string(),
+                        language: detect_language(&child.content).to_string(),
+                        content: child.content,
+                        has_macros: child.has_macros,
+                    });
+                }
+            }
+
+            files.push(FileData {
                 path: path_str,
                 name: file_name,
                 size,
                 last_modified: modified,
                 file_type: file_type.to_string(),
                 content,
-            })
+                language,
+                has_macros,
+            });
+            Some(files)
         })
+        .flatten()
         .collect();
 
     // Phase 3: Finalize
@@ -226,7 +287,12 @@ pub async fn scan_folder(
         let new_paths: std::collections::HashSet<String> =
             new_files.iter().map(|f| f.path.clone()).collect();
         if !new_paths.is_empty() {
-            index.retain(|f| !new_paths.contains(&f.path));
+            // Child entries of a re-indexed email file go too (messages/attachments may be gone)
+            index.retain(|f| {
+                !new_paths.contains(&f.path)
+                    && !split_child_path(&f.path)
+                        .is_some_and(|(container, _)| new_paths.contains(container))
+            });
             index.extend(new_files.clone());
         }
     }
@@ -246,6 +312,10 @@ pub async fn scan_folder(
     // Auto-save to SQLite (includes FTS5)
     let _ = crate::commands::persistence::save_index_internal(&state);
 
+    if let Some(conn) = db_path.and_then(|db_path| open_database(&db_path).ok()) {
+        let _ = save_email_attachments_scanned(&conn, &path, index_attachments);
+    }
+
     // Strip content from returned files to avoid huge IPC payload
     // The content is already in Memory Index and SQLite DB
     let lightweight_files = new_files
@@ -1,9 +1,16 @@
 use rusqlite::{Connection, OpenFlags};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use tauri::State;
 
-use crate::models::{FileData, SearchFilters, SearchHistoryEntry, SearchResult};
-use crate::search::{apply_filters, search_direct_content, search_fts5};
+use crate::models::{
+    FileData, FilterChip, MatchMode, QuerySuggestion, SearchFilters, SearchHistoryEntry,
+    SearchResponse, SearchResult,
+};
+use crate::search::{
+    add_query_filters, apply_filters, search_direct_content, search_direct_fuzzy,
+    search_direct_stemmed, search_fts5, search_fts5_fuzzy, search_fts5_stemmed,
+    suggest_queries_direct, suggest_queries_fts5, CharFolding, SearchQuery,
+};
 use crate::state::AppState;
 
 /// Default max results if not specified
@@ -15,6 +22,15 @@ const DEFAULT_MAX_RESULTS: usize = 100;
 /// 1. SQLite FTS5 (primary) - Instant search for ALL languages including Arabic
 /// 2. Direct content search (fallback) - Linear scan if FTS5 unavailable
 ///
+/// FTS5 results are ordered by BM25 relevance (see `RankingSettings`).
+///
+/// The query is parsed once (see `SearchQuery`) and handed to either engine;
+/// a malformed query is returned as an error naming the offending position.
+/// With fuzzy matching on (`QuerySettings`), files matching a term close to
+/// a typed one follow the exact hits. In smart mode (`MatchMode`), files with
+/// another form of a word (`contract` for `contracts`) follow them instead.
+/// A search that finds nothing comes back with "did you mean" suggestions.
+///
 /// FTS5 is the primary search engine because:
 /// - Works with Arabic, Chinese, Hebrew, and all Unicode
 /// - Uses inverted index (O(log n) vs O(n))
@@ -24,11 +40,11 @@ pub async fn search_index(
     query: String,
     filters: Option<SearchFilters>,
     state: State<'_, AppState>,
-) -> Result<Vec<SearchResult>, String> {
+) -> Result<SearchResponse, String> {
     let total_start = std::time::Instant::now();
 
     if query.trim().is_empty() {
-        return Ok(Vec::new());
+        return Ok(SearchResponse::default());
     }
 
     println!("[Search] Query: '{}'", query);
@@ -40,6 +56,10 @@ pub async fn search_index(
         .unwrap_or(DEFAULT_MAX_RESULTS);
     let offset = filters.as_ref().and_then(|f| f.offset).unwrap_or(0);
     let file_path_filter = filters.as_ref().and_then(|f| f.file_path.as_deref());
+    let match_mode = filters
+        .as_ref()
+        .and_then(|f| f.match_mode)
+        .unwrap_or_default();
 
     // Get excluded folders for filtering
     let excluded_folders: HashSet<String> = state
@@ -48,6 +68,33 @@ pub async fn search_index(
         .map_err(|e| e.to_string())?
         .clone();
 
+    // User-defined character equivalences (applied to the text the query searches for)
+    let folding = state
+        .char_folding
+        .read()
+        .map_err(|e| e.to_string())?
+        .clone();
+    let ranking = state
+        .ranking_settings
+        .read()
+        .map_err(|e| e.to_string())?
+        .clone();
+    let query_settings = state
+        .query_settings
+        .read()
+        .map_err(|e| e.to_string())?
+        .clone();
+
+    let mut parsed = SearchQuery::parse_folded(&query, query_settings.implicit_operator, &folding)
+        .map_err(|e| {
+            println!("[Search] Invalid query: {}", e);
+            e.to_string()
+        })?;
+    // Filters the engines must apply before their result limit
+    if let Some(ref f) = filters {
+        add_query_filters(&mut parsed, f);
+    }
+
     let mut results: Vec<SearchResult> = Vec::new();
     let mut used_fts5 = false;
 
@@ -68,14 +115,37 @@ pub async fn search_index(
 
                 println!("[Search] Using FTS5 (Dedicated Connection)");
                 let search_start = std::time::Instant::now();
-                match search_fts5(
-                    &conn,
-                    &query,
-                    max_results + offset,
-                    0,
-                    file_path_filter,
-                    &excluded_folders,
-                ) {
+                let search = if match_mode == MatchMode::Smart {
+                    search_fts5_stemmed(
+                        &conn,
+                        &parsed,
+                        max_results + offset,
+                        file_path_filter,
+                        &excluded_folders,
+                        &ranking,
+                        query_settings.fuzzy,
+                    )
+                } else if query_settings.fuzzy {
+                    search_fts5_fuzzy(
+                        &conn,
+                        &parsed,
+                        max_results + offset,
+                        file_path_filter,
+                        &excluded_folders,
+                        &ranking,
+                    )
+                } else {
+                    search_fts5(
+                        &conn,
+                        &parsed,
+                        max_results + offset,
+                        0,
+                        file_path_filter,
+                        &excluded_folders,
+                        &ranking,
+                    )
+                };
+                match search {
                     Ok(res) => {
                         println!(
                             "[Search] FTS5 search executed in {:?}",
@@ -96,8 +166,32 @@ pub async fn search_index(
         println!("[Search] Fallback: FTS5 unavailable, using direct content search.");
 
         let files = state.index.read().map_err(|e| e.to_string())?;
-        results =
-            search_direct_content(&query, &files, Some(max_results + offset), file_path_filter)?;
+        results = if match_mode == MatchMode::Smart {
+            search_direct_stemmed(
+                &parsed,
+                &files,
+                max_results + offset,
+                file_path_filter,
+                &folding,
+                query_settings.fuzzy,
+            )?
+        } else if query_settings.fuzzy {
+            search_direct_fuzzy(
+                &parsed,
+                &files,
+                max_results + offset,
+                file_path_filter,
+                &folding,
+            )?
+        } else {
+            search_direct_content(
+                &parsed,
+                &files,
+                Some(max_results + offset),
+                file_path_filter,
+                &folding,
+            )?
+        };
 
         // Filter excluded folders
         if file_path_filter.is_none() && !excluded_folders.is_empty() {
@@ -120,6 +214,13 @@ pub async fn search_index(
     }
     results.truncate(max_results);
 
+    // Nothing found: suggest close spellings and similar past searches
+    let suggestions = if results.is_empty() && offset == 0 && file_path_filter.is_none() {
+        suggest_queries(&query, &folding, used_fts5, &state)
+    } else {
+        Vec::new()
+    };
+
     // Add to search history (skip for single-file search)
     if file_path_filter.is_none() {
         if let Ok(mut history) = state.search_history.lock() {
@@ -143,7 +244,74 @@ pub async fn search_index(
         );
     }
 
-    Ok(results)
+    Ok(SearchResponse {
+        results,
+        suggestions,
+    })
+}
+
+/// "Did you mean" queries from the index vocabulary (or the in-memory index
+/// when FTS5 wasn't used) and searches that found files before
+fn suggest_queries(
+    query: &str,
+    folding: &CharFolding,
+    used_fts5: bool,
+    state: &AppState,
+) -> Vec<QuerySuggestion> {
+    let history = state
+        .search_history
+        .lock()
+        .map(|history| history.get_all())
+        .unwrap_or_default();
+
+    let suggestions = if used_fts5 {
+        let db_path = state.get_data_dir().map(|dir| dir.join("docufind.db"));
+        db_path
+            .ok_or_else(|| "No data directory".to_string())
+            .and_then(|path| {
+                Connection::open_with_flags(
+                    path,
+                    OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+                )
+                .map_err(|e| e.to_string())
+            })
+            .and_then(|conn| suggest_queries_fts5(&conn, query, folding, &history))
+    } else {
+        state
+            .index
+            .read()
+            .map_err(|e| e.to_string())
+            .map(|files| suggest_queries_direct(query, &files, folding, &history))
+    };
+
+    match suggestions {
+        Ok(suggestions) => {
+            println!("[Search] Suggestions: {:?}", suggestions);
+            suggestions
+        }
+        Err(e) => {
+            println!("[Search] Suggestions failed: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Parse a query without searching, returning its field qualifiers
+///
+/// The UI shows these as filter chips next to the results; a malformed query
+/// returns the same positioned error as `search_index`.
+#[tauri::command]
+pub async fn parse_search_query(
+    query: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<FilterChip>, String> {
+    let implicit = state
+        .query_settings
+        .read()
+        .map_err(|e| e.to_string())?
+        .implicit_operator;
+    let parsed = SearchQuery::parse(&query, implicit).map_err(|e| e.to_string())?;
+    Ok(parsed.filters)
 }
 
 /// Get search history
@@ -187,6 +355,11 @@ pub async fn get_index_stats(state: State<'_, AppState>) -> Result<serde_json::V
     let text_count = index.iter().filter(|f| f.file_type == "text").count();
     let total_size: u64 = index.iter().map(|f| f.size).sum();
 
+    let mut language_counts: HashMap<String, usize> = HashMap::new();
+    for file in index.iter() {
+        *language_counts.entry(file.language.clone()).or_insert(0) += 1;
+    }
+
     Ok(serde_json::json!({
         "totalFiles": index.len(),
         "wordFiles": word_count,
@@ -194,7 +367,8 @@ pub async fn get_index_stats(state: State<'_, AppState>) -> Result<serde_json::V
         "excelFiles": excel_count,
         "textFiles": text_count,
         "totalSize": total_size,
-        "folderCount": folders.len()
+        "folderCount": folders.len(),
+        "languages": language_counts
     }))
 }
 
@@ -213,6 +387,8 @@ pub async fn get_all_files(state: State<'_, AppState>) -> Result<Vec<FileData>,
             last_modified: f.last_modified,
             file_type: f.file_type.clone(),
             content: String::new(), // Don't send content
+            language: f.language.clone(),
+            has_macros: f.has_macros,
         })
         .collect())
 }
@@ -0,0 +1,362 @@
+//! User settings stored in the `metadata` table
+//!
+//! Settings are persisted as JSON values under well-known keys so they
+//! travel with the index database.
+
+use rusqlite::{params, Connection};
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::persistence::{init_database, open_database};
+use crate::extractors::{normalize_extension, TextFileRules};
+use crate::models::{
+    CharEquivalence, IndexingProgress, IndexingSettings, QuerySettings, RankingSettings,
+};
+use crate::search::{rebuild_fts5_index_with_progress, register_fold_function, CharFolding};
+use crate::state::AppState;
+
+/// Metadata key for the character equivalence table
+const CHAR_EQUIVALENCES_KEY: &str = "char_equivalences";
+
+/// Metadata key for indexing options
+const INDEXING_SETTINGS_KEY: &str = "indexing_settings";
+
+/// Metadata key for search ranking options
+const RANKING_SETTINGS_KEY: &str = "ranking_settings";
+
+/// Metadata key for query parsing options
+const QUERY_SETTINGS_KEY: &str = "query_settings";
+
+/// Metadata key prefix for whether a scanned folder's email files were
+/// indexed with their attachments (`email_attachments_scanned:<folder>`)
+const EMAIL_ATTACHMENTS_SCANNED_KEY: &str = "email_attachments_scanned";
+
+/// Read a JSON setting from the metadata table
+fn load_setting<T: serde::de::DeserializeOwned>(conn: &Connection, key: &str) -> Option<T> {
+    conn.query_row(
+        "SELECT value FROM metadata WHERE key = ?1",
+        params![key],
+        |row| row.get::<_, String>(0),
+    )
+    .ok()
+    .and_then(|v| serde_json::from_str(&v).ok())
+}
+
+/// Write a JSON setting to the metadata table
+fn save_setting<T: serde::Serialize>(conn: &Connection, key: &str, value: &T) -> Result<(), String> {
+    let json = serde_json::to_string(value).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO metadata (key, value) VALUES (?1, ?2)",
+        params![key, json],
+    )
+    .map_err(|e| format!("Failed to save setting '{}': {}", key, e))?;
+    Ok(())
+}
+
+/// Load the user's character equivalence table (empty if unset)
+pub fn load_char_equivalences(conn: &Connection) -> Vec<CharEquivalence> {
+    load_setting(conn, CHAR_EQUIVALENCES_KEY).unwrap_or_default()
+}
+
+/// Load indexing options (defaults if unset)
+pub fn load_indexing_settings(conn: &Connection) -> IndexingSettings {
+    load_setting(conn, INDEXING_SETTINGS_KEY).unwrap_or_default()
+}
+
+/// Load search ranking options (defaults if unset)
+pub fn load_ranking_settings(conn: &Connection) -> RankingSettings {
+    load_setting(conn, RANKING_SETTINGS_KEY).unwrap_or_default()
+}
+
+/// Load query parsing options (defaults if unset)
+pub fn load_query_settings(conn: &Connection) -> QuerySettings {
+    load_setting(conn, QUERY_SETTINGS_KEY).unwrap_or_default()
+}
+
+/// Whether `folder`'s email files were last indexed with their attachments
+/// (`None` if the folder hasn't been scanned since this was recorded)
+pub fn load_email_attachments_scanned(conn: &Connection, folder: &str) -> Option<bool> {
+    load_setting(
+        conn,
+        &format!("{}:{}", EMAIL_ATTACHMENTS_SCANNED_KEY, folder),
+    )
+}
+
+/// Record the attachment setting `folder`'s email files were indexed with
+pub fn save_email_attachments_scanned(
+    conn: &Connection,
+    folder: &str,
+    with_attachments: bool,
+) -> Result<(), String> {
+    save_setting(
+        conn,
+        &format!("{}:{}", EMAIL_ATTACHMENTS_SCANNED_KEY, folder),
+        &with_attachments,
+    )
+}
+
+/// Get the character equivalence table
+#[tauri::command]
+pub async fn get_char_equivalences(
+    state: State<'_, AppState>,
+) -> Result<Vec<CharEquivalence>, String> {
+    let Some(data_dir) = state.get_data_dir() else {
+        return Ok(Vec::new());
+    };
+
+    let db_path = data_dir.join("docufind.db");
+    if !db_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let conn = Connection::open(&db_path).map_err(|e| e.to_string())?;
+    Ok(load_char_equivalences(&conn))
+}
+
+/// Replace the character equivalence table
+///
+/// Rebuilds `files_fts`, `files_trigram` and `files_stemmed` in the
+/// background with the new table, saving it in the same transaction, then
+/// applies it to search; until then searches use the old table and index.
+/// One rebuild runs at a time (a second request fails) and index writes wait
+/// for it. Emits `fts-rebuild-progress` and `fts-rebuild-complete`.
+#[tauri::command]
+pub async fn set_char_equivalences(
+    equivalences: Vec<CharEquivalence>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if equivalences.iter().any(|eq| eq.from.is_empty()) {
+        return Err("Equivalence source text cannot be empty".to_string());
+    }
+
+    let data_dir = state
+        .get_data_dir()
+        .ok_or_else(|| "Data directory not set".to_string())?;
+    std::fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+    let db_path = data_dir.join("docufind.db");
+
+    if state.fts_rebuild_running.swap(true, Ordering::SeqCst) {
+        return Err("Search equivalences are still being applied".to_string());
+    }
+
+    println!(
+        "[Settings] Applying {} character equivalences, rebuilding FTS5...",
+        equivalences.len()
+    );
+
+    std::thread::spawn(move || {
+        let state = app.state::<AppState>();
+        let start = std::time::Instant::now();
+        let result = {
+            let _writes = state.index_writes.lock().unwrap_or_else(|e| e.into_inner());
+            apply_char_equivalences(&db_path, &equivalences, |current, total| {
+                let _ = app.emit(
+                    "fts-rebuild-progress",
+                    IndexingProgress {
+                        current,
+                        total,
+                        filename: "Applying search equivalences...".to_string(),
+                        phase: "rebuilding".to_string(),
+                    },
+                );
+            })
+            .and_then(|()| {
+                // Queries and the stored connection switch to the new table
+                // now that the index is folded with it
+                let mut folding = state.char_folding.write().map_err(|e| e.to_string())?;
+                *folding = CharFolding::new(&equivalences);
+                let mut db_guard = state.db.lock().map_err(|e| e.to_string())?;
+                if db_guard.is_some() {
+                    *db_guard = Some(open_database(&db_path)?);
+                }
+                Ok(())
+            })
+        };
+        state.fts_rebuild_running.store(false, Ordering::SeqCst);
+
+        match result {
+            Ok(()) => {
+                println!("[FTS5] Rebuilt folded index in {:?}", start.elapsed());
+                let _ = app.emit(
+                    "fts-rebuild-complete",
+                    serde_json::json!({ "success": true }),
+                );
+            }
+            Err(e) => {
+                println!("[FTS5] Rebuild failed: {}", e);
+                let _ = app.emit(
+                    "fts-rebuild-complete",
+                    serde_json::json!({ "success": false, "error": e }),
+                );
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Re-fold the FTS5 tables with `equivalences` and save them, in one
+/// transaction
+fn apply_char_equivalences<F>(
+    db_path: &Path,
+    equivalences: &[CharEquivalence],
+    on_progress: F,
+) -> Result<(), String>
+where
+    F: FnMut(usize, usize),
+{
+    let mut conn = open_database(db_path)?;
+    init_database(&conn).map_err(|e| e.to_string())?;
+    // The connection registered the saved table
+    register_fold_function(&conn, CharFolding::new(equivalences)).map_err(|e| e.to_string())?;
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    rebuild_fts5_index_with_progress(&tx, on_progress)?;
+    save_setting(&tx, CHAR_EQUIVALENCES_KEY, &equivalences)?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Get indexing options
+#[tauri::command]
+pub async fn get_indexing_settings(state: State<'_, AppState>) -> Result<IndexingSettings, String> {
+    Ok(state
+        .indexing_settings
+        .read()
+        .map_err(|e| e.to_string())?
+        .clone())
+}
+
+/// "Treat as text" rules for indexing options
+pub fn text_file_rules(settings: &IndexingSettings) -> TextFileRules {
+    TextFileRules::new(&settings.text_extensions, &settings.text_file_patterns)
+}
+
+/// Replace indexing options
+///
+/// Applies to files indexed from now on; existing entries change when their
+/// files are re-indexed (email files follow a change to attachment indexing
+/// on the next scan of their folder). Text extensions are stored lowercase
+/// without a dot.
+#[tauri::command]
+pub async fn set_indexing_settings(
+    mut settings: IndexingSettings,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let mut extensions: Vec<String> = Vec::new();
+    for ext in settings.text_extensions.iter().map(|e| normalize_extension(e)) {
+        if ext.is_empty() || ext.contains(['/', '\\']) {
+            continue;
+        }
+        if !extensions.contains(&ext) {
+            extensions.push(ext);
+        }
+    }
+    settings.text_extensions = extensions;
+    settings.text_file_patterns = settings
+        .text_file_patterns
+        .iter()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect();
+
+    let data_dir = state
+        .get_data_dir()
+        .ok_or_else(|| "Data directory not set".to_string())?;
+    std::fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+
+    {
+        let conn = open_database(&data_dir.join("docufind.db"))?;
+        init_database(&conn).map_err(|e| e.to_string())?;
+        save_setting(&conn, INDEXING_SETTINGS_KEY, &settings)?;
+    }
+
+    println!("[Settings] Saved indexing settings: {:?}", settings);
+
+    *state.text_file_rules.write().map_err(|e| e.to_string())? = text_file_rules(&settings);
+    let mut current = state.indexing_settings.write().map_err(|e| e.to_string())?;
+    *current = settings;
+    Ok(())
+}
+
+/// Get search ranking options
+#[tauri::command]
+pub async fn get_ranking_settings(state: State<'_, AppState>) -> Result<RankingSettings, String> {
+    Ok(state
+        .ranking_settings
+        .read()
+        .map_err(|e| e.to_string())?
+        .clone())
+}
+
+/// Replace search ranking options (applies to the next search)
+#[tauri::command]
+pub async fn set_ranking_settings(
+    settings: RankingSettings,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let weights = [
+        settings.name_weight,
+        settings.content_weight,
+        settings.path_weight,
+    ];
+    if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+        return Err("Ranking weights must be zero or positive".to_string());
+    }
+    let half_life = settings.recency_half_life_days;
+    if !half_life.is_finite() || half_life <= 0.0 {
+        return Err("Recency half-life must be positive".to_string());
+    }
+
+    let data_dir = state
+        .get_data_dir()
+        .ok_or_else(|| "Data directory not set".to_string())?;
+    std::fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+
+    {
+        let conn = open_database(&data_dir.join("docufind.db"))?;
+        init_database(&conn).map_err(|e| e.to_string())?;
+        save_setting(&conn, RANKING_SETTINGS_KEY, &settings)?;
+    }
+
+    println!("[Settings] Saved ranking settings: {:?}", settings);
+
+    let mut current = state.ranking_settings.write().map_err(|e| e.to_string())?;
+    *current = settings;
+    Ok(())
+}
+
+/// Get query parsing options
+#[tauri::command]
+pub async fn get_query_settings(state: State<'_, AppState>) -> Result<QuerySettings, String> {
+    Ok(state
+        .query_settings
+        .read()
+        .map_err(|e| e.to_string())?
+        .clone())
+}
+
+/// Replace query parsing options (applies to the next search)
+#[tauri::command]
+pub async fn set_query_settings(
+    settings: QuerySettings,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let data_dir = state
+        .get_data_dir()
+        .ok_or_else(|| "Data directory not set".to_string())?;
+    std::fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+
+    {
+        let conn = open_database(&data_dir.join("docufind.db"))?;
+        init_database(&conn).map_err(|e| e.to_string())?;
+        save_setting(&conn, QUERY_SETTINGS_KEY, &settings)?;
+    }
+
+    println!("[Settings] Saved query settings: {:?}", settings);
+
+    let mut current = state.query_settings.write().map_err(|e| e.to_string())?;
+    *current = settings;
+    Ok(())
+}
@@ -0,0 +1,167 @@
+//! Result-card thumbnails with an on-disk cache
+//!
+//! Thumbnails are cached under `<app data>/thumbnails`, one entry per path
+//! stamped with the file's modification time, so a changed file gets a fresh
+//! thumbnail (replacing the old entry) and browsing results never reopens
+//! the same package twice. Files without a thumbnail are cached too (as a
+//! `.none` marker holding only the stamp).
+
+use base64::Engine;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tauri::State;
+
+use crate::extractors::{extract_thumbnail, split_child_path, Thumbnail};
+use crate::state::AppState;
+
+/// Cache directory name inside the app data dir
+pub const THUMBNAIL_CACHE_DIR: &str = "thumbnails";
+
+/// Get a file's thumbnail as a `data:` URI (`None` if it has no usable thumbnail)
+#[tauri::command]
+pub async fn get_file_thumbnail(
+    file_path: String,
+    state: State<'_, AppState>,
+) -> Result<Option<String>, String> {
+    // Email messages and attachments have no file of their own
+    if split_child_path(&file_path).is_some() {
+        return Ok(None);
+    }
+
+    let path = Path::new(&file_path);
+    let thumbnail = match state.get_data_dir() {
+        Some(data_dir) => cached_thumbnail(&data_dir.join(THUMBNAIL_CACHE_DIR), path)?,
+        None => extract_thumbnail(path),
+    };
+
+    Ok(thumbnail.map(|t| {
+        format!(
+            "data:{};base64,{}",
+            t.mime,
+            base64::engine::general_purpose::STANDARD.encode(&t.data)
+        )
+    }))
+}
+
+/// Look a thumbnail up in the cache, extracting and storing it on a miss
+///
+/// An entry stamped with another modification time is removed.
+fn cached_thumbnail(cache_dir: &Path, path: &Path) -> Result<Option<Thumbnail>, String> {
+    let key = cache_key(path);
+    let stamp = modified_stamp(path)?;
+
+    for (ext, mime) in [
+        ("jpg", Some("image/jpeg")),
+        ("png", Some("image/png")),
+        ("none", None),
+    ] {
+        let entry = cache_dir.join(format!("{}.{}", key, ext));
+        let Ok(mut data) = fs::read(&entry) else {
+            continue;
+        };
+        if data.starts_with(&stamp) {
+            data.drain(..stamp.len());
+            return Ok(mime.map(|mime| Thumbnail { mime, data }));
+        }
+        // Left by an older version of the file
+        let _ = fs::remove_file(&entry);
+    }
+
+    let thumbnail = extract_thumbnail(path);
+
+    // A failed cache write only costs a re-extraction next time
+    if fs::create_dir_all(cache_dir).is_ok() {
+        let entry: PathBuf = match &thumbnail {
+            Some(t) => cache_dir.join(format!("{}.{}", key, t.extension())),
+            None => cache_dir.join(format!("{}.none", key)),
+        };
+        let data = thumbnail.as_ref().map_or(&[][..], |t| t.data.as_slice());
+        if let Err(e) = fs::write(&entry, [&stamp[..], data].concat()) {
+            println!("[Thumbnails] Failed to cache {}: {}", entry.display(), e);
+        }
+    }
+
+    Ok(thumbnail)
+}
+
+/// Stable cache key: FNV-1a of the path
+fn cache_key(path: &Path) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in path.to_string_lossy().as_bytes() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Modification time of a file (nanoseconds since the epoch, little-endian)
+fn modified_stamp(path: &Path) -> Result<[u8; 16], String> {
+    let modified = fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let nanos = modified
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    Ok(nanos.to_le_bytes())
+}
+
+/// Remove every cached thumbnail
+pub fn clear_thumbnail_cache(data_dir: &Path) {
+    let cache_dir = data_dir.join(THUMBNAIL_CACHE_DIR);
+    if cache_dir.exists() {
+        if let Err(e) = fs::remove_dir_all(&cache_dir) {
+            println!("[Thumbnails] Failed to clear cache: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_round_trip() {
+        let dir = std::env::temp_dir().join(format!("docufind_thumbs_{}", std::process::id()));
+        let cache_dir = dir.join(THUMBNAIL_CACHE_DIR);
+        fs::create_dir_all(&dir).unwrap();
+
+        // No thumbnail: cached as a marker
+        let text = dir.join("notes.txt");
+        fs::write(&text, "plain").unwrap();
+        assert!(cached_thumbnail(&cache_dir, &text).unwrap().is_none());
+        let key = cache_key(&text);
+        assert!(cache_dir.join(format!("{}.none", key)).exists());
+
+        // A small PNG is its own thumbnail, served from the cache afterwards
+        let png = dir.join("plan.png");
+        fs::write(&png, b"\x89PNG\r\n\x1a\n\0\0\0\0IEND").unwrap();
+        let first = cached_thumbnail(&cache_dir, &png).unwrap().unwrap();
+        assert_eq!(first.mime, "image/png");
+        let key = cache_key(&png);
+        assert!(cache_dir.join(format!("{}.png", key)).exists());
+        let cached = cached_thumbnail(&cache_dir, &png).unwrap().unwrap();
+        assert_eq!(cached.data, first.data);
+
+        // A changed file replaces its entry instead of adding one
+        fs::write(&png, "no longer an image").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&png)
+            .unwrap()
+            .set_modified(UNIX_EPOCH + std::time::Duration::from_secs(1_000_000))
+            .unwrap();
+        assert!(cached_thumbnail(&cache_dir, &png).unwrap().is_none());
+        assert!(!cache_dir.join(format!("{}.png", key)).exists());
+        assert!(cache_dir.join(format!("{}.none", key)).exists());
+        assert_eq!(fs::read_dir(&cache_dir).unwrap().count(), 2);
+
+        fs::remove_file(&png).unwrap();
+        assert!(cached_thumbnail(&cache_dir, &png).is_err());
+
+        clear_thumbnail_cache(&dir);
+        assert!(!cache_dir.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
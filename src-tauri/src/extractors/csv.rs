@@ -0,0 +1,411 @@
+use encoding_rs::{Encoding, UTF_16LE, WINDOWS_1252, WINDOWS_1256};
+use std::fs;
+use std::path::Path;
+
+use crate::models::{
+    ContentSection, DocumentContent, DocumentMetadata, SectionProperties, SectionType,
+};
+
+/// Delimiters considered when sniffing
+const CANDIDATE_DELIMITERS: &[char] = &[',', '\t', ';', '|'];
+
+/// Lines sampled for delimiter, quote and header sniffing
+const SNIFF_LINES: usize = 20;
+
+/// Rows included in the structured preview (the index always gets every row)
+const MAX_PREVIEW_ROWS: usize = 500;
+
+/// Sniffed dialect of a delimited file
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Dialect {
+    delimiter: char,
+    quote: char,
+}
+
+/// Extract text content from a CSV/TSV file (header and cell values, one row per line)
+pub fn extract_csv(path: &Path, ext: &str) -> Option<String> {
+    let (rows, _) = read_rows(path, ext)?;
+
+    let mut content = String::with_capacity(8192);
+    for row in &rows {
+        let mut first = true;
+        for cell in row.iter().filter(|c| !c.trim().is_empty()) {
+            if !first {
+                content.push(' ');
+            }
+            content.push_str(cell.trim());
+            first = false;
+        }
+        content.push('\n');
+    }
+
+    let content = content.trim();
+    if content.is_empty() {
+        None
+    } else {
+        Some(content.to_string())
+    }
+}
+
+/// Extract structured content from a CSV/TSV file (for rich preview)
+///
+/// Returns a `Table` of at most `MAX_PREVIEW_ROWS` rows. A detected header
+/// row is marked with `properties.header`.
+pub fn extract_csv_structured(path: &Path, ext: &str) -> Option<DocumentContent> {
+    let (rows, has_header) = read_rows(path, ext)?;
+    if rows.is_empty() {
+        return None;
+    }
+
+    let table_rows: Vec<ContentSection> = rows
+        .iter()
+        .take(MAX_PREVIEW_ROWS)
+        .enumerate()
+        .map(|(i, row)| ContentSection {
+            section_type: SectionType::TableRow,
+            content: None,
+            runs: None,
+            children: Some(
+                row.iter()
+                    .map(|cell| ContentSection {
+                        section_type: SectionType::TableCell,
+                        content: Some(cell.trim().to_string()),
+                        runs: None,
+                        children: None,
+                        properties: None,
+                    })
+                    .collect(),
+            ),
+            properties: (i == 0 && has_header).then(|| SectionProperties {
+                header: Some(true),
+                ..Default::default()
+            }),
+        })
+        .collect();
+
+    let mut sections = vec![ContentSection {
+        section_type: SectionType::Table,
+        content: None,
+        runs: None,
+        children: Some(table_rows),
+        properties: None,
+    }];
+
+    if rows.len() > MAX_PREVIEW_ROWS {
+        sections.push(ContentSection {
+            section_type: SectionType::Paragraph,
+            content: Some(format!(
+                "Showing first {} of {} rows",
+                MAX_PREVIEW_ROWS,
+                rows.len()
+            )),
+            runs: None,
+            children: None,
+            properties: None,
+        });
+    }
+
+    Some(DocumentContent {
+        doc_type: "csv".to_string(),
+        sections,
+        metadata: DocumentMetadata::default(),
+    })
+}
+
+/// Read, decode and parse a delimited file; returns rows and whether row 0 is a header
+fn read_rows(path: &Path, ext: &str) -> Option<(Vec<Vec<String>>, bool)> {
+    let bytes = fs::read(path).ok()?;
+    let text = decode_bytes(&bytes);
+    let dialect = sniff_dialect(&text, ext == "tsv");
+    let rows = parse_rows(&text, dialect);
+    let has_header = detect_header(&rows);
+    Some((rows, has_header))
+}
+
+/// Decode file bytes: BOM, then UTF-8, then UTF-16LE (NUL-heavy), then Windows-1256/1252
+pub(super) fn decode_bytes(bytes: &[u8]) -> String {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        let (text, _, _) = encoding.decode(&bytes[bom_len..]);
+        return text.into_owned();
+    }
+
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return text.to_string();
+    }
+
+    // BOM-less UTF-16LE: ASCII characters leave NULs in every odd byte
+    let sample = &bytes[..bytes.len().min(1024)];
+    let odd_nuls = sample.iter().skip(1).step_by(2).filter(|&&b| b == 0).count();
+    if sample.len() >= 2 && odd_nuls * 2 >= sample.len() / 2 {
+        let (text, _, _) = UTF_16LE.decode(bytes);
+        return text.into_owned();
+    }
+
+    // Legacy 8-bit: prefer Arabic if most non-ASCII characters decode to Arabic letters
+    let (arabic, _, _) = WINDOWS_1256.decode(bytes);
+    let non_ascii = arabic.chars().filter(|c| !c.is_ascii()).count();
+    let arabic_letters = arabic
+        .chars()
+        .filter(|c| ('\u{0600}'..='\u{06FF}').contains(c))
+        .count();
+    if non_ascii > 0 && arabic_letters * 2 > non_ascii {
+        return arabic.into_owned();
+    }
+
+    let (text, _, _) = WINDOWS_1252.decode(bytes);
+    text.into_owned()
+}
+
+/// Pick the delimiter with the most consistent non-zero count per line,
+/// and the quote character in use
+fn sniff_dialect(text: &str, prefer_tab: bool) -> Dialect {
+    let quote = sniff_quote(text);
+    let sample: Vec<&str> = text
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .take(SNIFF_LINES)
+        .collect();
+
+    let mut best: Option<(char, usize, usize)> = None; // (delimiter, consistent lines, count)
+
+    for &delimiter in CANDIDATE_DELIMITERS {
+        let counts: Vec<usize> = sample
+            .iter()
+            .map(|line| count_unquoted(line, delimiter, quote))
+            .collect();
+
+        // Most common per-line count (ties -> larger count)
+        let mut mode = 0;
+        let mut mode_lines = 0;
+        for &count in &counts {
+            let lines = counts.iter().filter(|&&c| c == count).count();
+            if count > 0 && (lines > mode_lines || (lines == mode_lines && count > mode)) {
+                mode = count;
+                mode_lines = lines;
+            }
+        }
+
+        if mode == 0 {
+            continue;
+        }
+
+        let better = match best {
+            None => true,
+            Some((_, best_lines, best_count)) => {
+                mode_lines > best_lines || (mode_lines == best_lines && mode > best_count)
+            }
+        };
+        if better {
+            best = Some((delimiter, mode_lines, mode));
+        }
+    }
+
+    let default = if prefer_tab { '\t' } else { ',' };
+    let delimiter = match best {
+        // A .tsv with any tab-separated lines stays tab-separated
+        Some(_) if prefer_tab && sample.iter().any(|l| l.contains('\t')) => '\t',
+        Some((delimiter, _, _)) => delimiter,
+        None => default,
+    };
+
+    Dialect { delimiter, quote }
+}
+
+/// Quote character: `'` only when fields are single-quoted and never double-quoted
+fn sniff_quote(text: &str) -> char {
+    let sample: String = text.lines().take(SNIFF_LINES).collect::<Vec<_>>().join("\n");
+    let opens = |q: char| {
+        sample
+            .match_indices(q)
+            .filter(|(i, _)| {
+                *i == 0
+                    || sample[..*i]
+                        .chars()
+                        .next_back()
+                        .is_some_and(|c| CANDIDATE_DELIMITERS.contains(&c) || c == '\n')
+            })
+            .count()
+    };
+
+    if opens('"') == 0 && opens('\'') > 0 {
+        '\''
+    } else {
+        '"'
+    }
+}
+
+/// Count delimiters outside quoted sections of one line
+fn count_unquoted(line: &str, delimiter: char, quote: char) -> usize {
+    let mut in_quotes = false;
+    let mut count = 0;
+    for c in line.chars() {
+        if c == quote {
+            in_quotes = !in_quotes;
+        } else if c == delimiter && !in_quotes {
+            count += 1;
+        }
+    }
+    count
+}
+
+/// RFC 4180 parsing: quoted fields, doubled quotes, line breaks inside quotes
+fn parse_rows(text: &str, dialect: Dialect) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c == dialect.quote {
+                if chars.peek() == Some(&dialect.quote) {
+                    field.push(c);
+                    chars.next();
+                } else {
+                    in_quotes = false;
+                }
+            } else {
+                field.push(c);
+            }
+            continue;
+        }
+
+        match c {
+            c if c == dialect.quote && field.trim().is_empty() => {
+                field.clear();
+                in_quotes = true;
+            }
+            c if c == dialect.delimiter => row.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                if !(row.len() == 1 && row[0].is_empty()) {
+                    rows.push(std::mem::take(&mut row));
+                }
+                row.clear();
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows
+}
+
+/// Header heuristic (after Python's csv.Sniffer)
+///
+/// Each column votes: a header cell whose type (numeric vs text) or length
+/// differs from the consistent values below it is a vote for a header.
+/// Without a signal, a first row of distinct, non-empty, non-numeric labels
+/// counts as a header.
+fn detect_header(rows: &[Vec<String>]) -> bool {
+    let Some(first) = rows.first() else {
+        return false;
+    };
+    if rows.len() < 2 {
+        return false;
+    }
+
+    let data: Vec<&Vec<String>> = rows.iter().skip(1).take(SNIFF_LINES).collect();
+    let mut votes: i32 = 0;
+
+    for (col, header_cell) in first.iter().enumerate() {
+        let values: Vec<&str> = data
+            .iter()
+            .filter_map(|r| r.get(col).map(|c| c.trim()))
+            .filter(|c| !c.is_empty())
+            .collect();
+        if values.is_empty() {
+            continue;
+        }
+
+        let header_cell = header_cell.trim();
+        let numeric = values.iter().filter(|v| is_numeric(v)).count();
+
+        if numeric * 2 > values.len() {
+            votes += if is_numeric(header_cell) { -1 } else { 1 };
+        } else {
+            let len = values[0].chars().count();
+            if values.iter().all(|v| v.chars().count() == len) {
+                votes += if header_cell.chars().count() == len { -1 } else { 1 };
+            }
+        }
+    }
+
+    if votes != 0 {
+        return votes > 0;
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    first.iter().all(|c| {
+        let c = c.trim();
+        !c.is_empty() && !is_numeric(c) && seen.insert(c)
+    })
+}
+
+fn is_numeric(s: &str) -> bool {
+    let cleaned: String = s
+        .chars()
+        .filter(|c| !matches!(c, ',' | ' ' | '%' | '$' | '€' | '£'))
+        .collect();
+    !cleaned.is_empty() && cleaned.parse::<f64>().is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_dialect() {
+        let semicolons = "name;city;amount\n\"Doe; Jane\";Mumbai;12\nAli;Karachi;7\n";
+        assert_eq!(
+            sniff_dialect(semicolons, false),
+            Dialect {
+                delimiter: ';',
+                quote: '"'
+            }
+        );
+        assert_eq!(sniff_dialect("a\tb\tc\n1\t2\t3\n", true).delimiter, '\t');
+        assert_eq!(sniff_dialect("'a,b',c\n'd',e\n", false).quote, '\'');
+    }
+
+    #[test]
+    fn test_parse_quoted_fields() {
+        let dialect = Dialect {
+            delimiter: ',',
+            quote: '"',
+        };
+        let rows = parse_rows("id,note\r\n1,\"said \"\"hi\"\"\nthen left\"\r\n2,plain\r\n", dialect);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1][1], "said \"hi\"\nthen left");
+        assert_eq!(rows[2], vec!["2", "plain"]);
+    }
+
+    #[test]
+    fn test_detect_header() {
+        let rows = |text: &str| {
+            parse_rows(
+                text,
+                Dialect {
+                    delimiter: ',',
+                    quote: '"',
+                },
+            )
+        };
+        assert!(detect_header(&rows("name,amount\nAli,12\nSara,7\n")));
+        assert!(detect_header(&rows("city,country\nMumbai,India\nKarachi,Pakistan\n")));
+        assert!(!detect_header(&rows("1,2\n3,4\n5,6\n")));
+    }
+
+    #[test]
+    fn test_decode_legacy_arabic() {
+        // "مرحبا,1" in windows-1256
+        let bytes = [0xE3, 0xD1, 0xCD, 0xC8, 0xC7, b',', b'1'];
+        assert_eq!(decode_bytes(&bytes), "مرحبا,1");
+    }
+}
@@ -2,12 +2,24 @@
 //!
 //! This module extracts text from older .doc files (Word 97-2003 format).
 //! These are OLE Compound Document files with text stored in the WordDocument stream.
+//!
+//! The structured preview follows the piece table (Clx) to the main document
+//! text and maps its control characters to sections: paragraph marks (0x0D),
+//! table cell/row marks (0x07) and page breaks (0x0C). Bold/italic/underline
+//! come from the CHPX FKPs and headings from the paragraph style and outline
+//! level in the PAPX FKPs, as do right-to-left paragraphs and runs.
 
 use std::fs::File;
 use std::io::Read;
 use std::path::Path;
 use cfb::CompoundFile;
 
+use super::language::paragraph_properties;
+use crate::models::{
+    ContentSection, DocumentContent, DocumentMetadata, SectionType, TextDirection, TextRun,
+    TextStyle,
+};
+
 /// Extract text content from a .doc file (Word 97-2003 binary format)
 pub fn extract_doc(path: &Path) -> Option<String> {
     let file = File::open(path).ok()?;
@@ -336,6 +348,537 @@ fn read_u32_le(data: &[u8], offset: usize) -> u32 {
     u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]])
 }
 
+// ============================================================================
+// Structured extraction
+// ============================================================================
+
+/// ccpText: length of the main document text, in characters
+const FIB_CCP_TEXT: usize = 0x004C;
+/// fcPlcfBteChpx/lcbPlcfBteChpx (FibRgFcLcb97 starts at 0x9A)
+const FIB_PLCF_BTE_CHPX: usize = 0x00FA;
+/// fcPlcfBtePapx/lcbPlcfBtePapx
+const FIB_PLCF_BTE_PAPX: usize = 0x0102;
+/// fcClx/lcbClx
+const FIB_CLX: usize = 0x01A2;
+
+/// Formatted disk pages are 512-byte pages of the WordDocument stream
+const FKP_SIZE: usize = 512;
+
+const SPRM_C_F_BOLD: u16 = 0x0835;
+const SPRM_C_F_ITALIC: u16 = 0x0836;
+const SPRM_C_F_STRIKE: u16 = 0x0837;
+const SPRM_C_KUL: u16 = 0x2A3E;
+const SPRM_C_F_BI_DI: u16 = 0x085A;
+const SPRM_P_F_IN_TABLE: u16 = 0x2416;
+const SPRM_P_F_TTP: u16 = 0x2417;
+const SPRM_P_F_INNER_TTP: u16 = 0x244C;
+const SPRM_P_OUT_LVL: u16 = 0x2640;
+const SPRM_P_F_BI_DI: u16 = 0x2441;
+const SPRM_P_ITAP: u16 = 0x6649;
+const SPRM_T_DEF_TABLE: u16 = 0xD608;
+
+/// A contiguous run of main document text (CP range stored at file offset `fc`)
+#[derive(Debug, PartialEq)]
+struct Piece {
+    cp_start: u32,
+    cp_end: u32,
+    fc: u32,
+    /// 8-bit Windows-1252 text rather than UTF-16LE
+    compressed: bool,
+}
+
+/// Character formatting from a CHPX (direct formatting only)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct CharFormat {
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    strikethrough: bool,
+    rtl: bool,
+}
+
+/// Paragraph properties from a PAPX
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct ParaFormat {
+    /// Paragraph style index (built-in Heading 1-9 are istd 1-9)
+    istd: u16,
+    outline_level: Option<u8>,
+    in_table: bool,
+    /// The paragraph is a table row end mark
+    row_end: bool,
+    rtl: bool,
+}
+
+/// A main document character with its formatting
+#[derive(Debug, Clone, Copy)]
+struct DocChar {
+    ch: u16,
+    chp: CharFormat,
+    /// `None` when the PAPX FKPs couldn't be read
+    pap: Option<ParaFormat>,
+}
+
+/// An FC range with the formatting that applies to it
+type FormatRun<T> = (u32, u32, T);
+
+/// Extract structured content from a .doc file (for rich preview)
+///
+/// Falls back to the plain text extraction, one paragraph per line, when the
+/// piece table can't be read.
+pub fn extract_doc_structured(path: &Path) -> Option<DocumentContent> {
+    let sections = read_doc_sections(path)
+        .or_else(|| {
+            let text = extract_doc(path)?;
+            Some(
+                text.lines()
+                    .map(|line| ContentSection {
+                        section_type: SectionType::Paragraph,
+                        content: Some(line.to_string()),
+                        runs: None,
+                        children: None,
+                        properties: None,
+                    })
+                    .collect(),
+            )
+        })?;
+
+    Some(DocumentContent {
+        doc_type: "doc".to_string(),
+        sections,
+        metadata: DocumentMetadata::default(),
+    })
+}
+
+fn read_stream<F: Read + std::io::Seek>(cfb: &mut CompoundFile<F>, name: &str) -> Option<Vec<u8>> {
+    let mut data = Vec::new();
+    cfb.open_stream(name).ok()?.read_to_end(&mut data).ok()?;
+    Some(data)
+}
+
+/// `lcb` bytes at `fc` of a stream, from an fc/lcb pair in the FIB
+fn fib_slice<'a>(word: &[u8], fib_offset: usize, stream: &'a [u8]) -> Option<&'a [u8]> {
+    let fc = read_u32_le(word, fib_offset) as usize;
+    let lcb = read_u32_le(word, fib_offset + 4) as usize;
+    if lcb == 0 {
+        return None;
+    }
+    stream.get(fc..fc.checked_add(lcb)?)
+}
+
+fn read_doc_sections(path: &Path) -> Option<Vec<ContentSection>> {
+    let file = File::open(path).ok()?;
+    let mut cfb = CompoundFile::open(file).ok()?;
+    let word = read_stream(&mut cfb, "/WordDocument")?;
+
+    let flags = read_u16_le(&word, 0x000A);
+    // fEncrypted: the text isn't readable
+    if flags & 0x0100 != 0 {
+        return None;
+    }
+    let table_stream_name = if flags & 0x0200 != 0 { "/1Table" } else { "/0Table" };
+    let table = read_stream(&mut cfb, table_stream_name)?;
+
+    let pieces = parse_clx(fib_slice(&word, FIB_CLX, &table)?)?;
+    let chpx = read_format_runs(&word, &table, FIB_PLCF_BTE_CHPX, parse_chpx_fkp);
+    let papx = read_format_runs(&word, &table, FIB_PLCF_BTE_PAPX, parse_papx_fkp);
+
+    let ccp_text = read_u32_le(&word, FIB_CCP_TEXT);
+    let mut chars = Vec::new();
+    for piece in pieces.iter().filter(|p| p.cp_start < ccp_text) {
+        for i in 0..piece.cp_end.min(ccp_text) - piece.cp_start {
+            let (fc, ch) = if piece.compressed {
+                let fc = piece.fc + i;
+                let byte = word.get(fc as usize..fc as usize + 1)?;
+                let (decoded, _, _) = encoding_rs::WINDOWS_1252.decode(byte);
+                (fc, decoded.chars().next().map_or(0, |c| c as u16))
+            } else {
+                let fc = piece.fc + i * 2;
+                if fc as usize + 2 > word.len() {
+                    return None;
+                }
+                (fc, read_u16_le(&word, fc as usize))
+            };
+            chars.push(DocChar {
+                ch,
+                chp: find_format(&chpx, fc).unwrap_or_default(),
+                pap: if papx.is_empty() {
+                    None
+                } else {
+                    Some(find_format(&papx, fc).unwrap_or_default())
+                },
+            });
+        }
+    }
+
+    let sections = build_sections(&chars);
+    if sections.is_empty() {
+        None
+    } else {
+        Some(sections)
+    }
+}
+
+/// Parse the piece table: skip the Prc entries, then read the PlcPcd
+fn parse_clx(clx: &[u8]) -> Option<Vec<Piece>> {
+    let mut pos = 0;
+    while *clx.get(pos)? == 0x01 {
+        pos += 3 + read_u16_le(clx, pos + 1) as usize;
+    }
+    if clx[pos] != 0x02 {
+        return None;
+    }
+    let plc = clx.get(pos + 5..pos + 5 + read_u32_le(clx, pos + 1) as usize)?;
+
+    // (n + 1) CPs followed by n 8-byte PCDs
+    let n = plc.len().checked_sub(4)? / 12;
+    let pieces = (0..n)
+        .map(|i| {
+            let fc_compressed = read_u32_le(plc, (n + 1) * 4 + i * 8 + 2);
+            let compressed = fc_compressed & 0x4000_0000 != 0;
+            let fc = fc_compressed & 0x3FFF_FFFF;
+            Piece {
+                cp_start: read_u32_le(plc, i * 4),
+                cp_end: read_u32_le(plc, (i + 1) * 4),
+                fc: if compressed { fc / 2 } else { fc },
+                compressed,
+            }
+        })
+        .filter(|p| p.cp_end > p.cp_start)
+        .collect();
+    Some(pieces)
+}
+
+/// Read every FKP listed in a PlcBteChpx/PlcBtePapx, sorted by FC
+fn read_format_runs<T>(
+    word: &[u8],
+    table: &[u8],
+    fib_offset: usize,
+    parse_fkp: fn(&[u8]) -> Vec<FormatRun<T>>,
+) -> Vec<FormatRun<T>> {
+    let Some(plc) = fib_slice(word, fib_offset, table) else {
+        return Vec::new();
+    };
+
+    // (n + 1) FCs followed by n page numbers
+    let n = plc.len().saturating_sub(4) / 8;
+    let mut runs: Vec<FormatRun<T>> = (0..n)
+        .filter_map(|i| {
+            let pn = (read_u32_le(plc, (n + 1) * 4 + i * 4) & 0x003F_FFFF) as usize;
+            word.get(pn * FKP_SIZE..(pn + 1) * FKP_SIZE)
+        })
+        .flat_map(parse_fkp)
+        .collect();
+    runs.sort_by_key(|run| run.0);
+    runs
+}
+
+fn find_format<T: Copy>(runs: &[FormatRun<T>], fc: u32) -> Option<T> {
+    let idx = runs.partition_point(|run| run.0 <= fc).checked_sub(1)?;
+    let (_, end, format) = runs[idx];
+    (fc < end).then_some(format)
+}
+
+/// ChpxFkp: crun at byte 511, (crun + 1) FCs, then crun word offsets of Chpx
+fn parse_chpx_fkp(page: &[u8]) -> Vec<FormatRun<CharFormat>> {
+    let crun = page[FKP_SIZE - 1] as usize;
+    (0..crun)
+        .filter_map(|i| {
+            let offset = *page.get((crun + 1) * 4 + i)? as usize * 2;
+            let mut format = CharFormat::default();
+            if offset != 0 {
+                let cb = *page.get(offset)? as usize;
+                for_each_sprm(page.get(offset + 1..offset + 1 + cb)?, |sprm, operand| {
+                    let on = matches!(operand.first(), Some(0x01) | Some(0x81));
+                    match sprm {
+                        SPRM_C_F_BOLD => format.bold = on,
+                        SPRM_C_F_ITALIC => format.italic = on,
+                        SPRM_C_F_STRIKE => format.strikethrough = on,
+                        SPRM_C_F_BI_DI => format.rtl = on,
+                        SPRM_C_KUL => format.underline = operand.first().is_some_and(|&k| k != 0),
+                        _ => {}
+                    }
+                });
+            }
+            Some((
+                read_u32_le(page, i * 4),
+                read_u32_le(page, (i + 1) * 4),
+                format,
+            ))
+        })
+        .collect()
+}
+
+/// PapxFkp: cpara at byte 511, (cpara + 1) FCs, then cpara 13-byte BXs whose
+/// first byte is the word offset of a PapxInFkp (`cb`, then istd and sprms)
+fn parse_papx_fkp(page: &[u8]) -> Vec<FormatRun<ParaFormat>> {
+    let cpara = page[FKP_SIZE - 1] as usize;
+    (0..cpara)
+        .filter_map(|i| {
+            let offset = *page.get((cpara + 1) * 4 + i * 13)? as usize * 2;
+            let mut format = ParaFormat::default();
+            if offset != 0 {
+                let cb = *page.get(offset)? as usize;
+                let (start, len) = if cb != 0 {
+                    (offset + 1, cb * 2 - 1)
+                } else {
+                    (offset + 2, *page.get(offset + 1)? as usize * 2)
+                };
+                let grpprl_and_istd = page.get(start..start + len)?;
+                format.istd = read_u16_le(grpprl_and_istd, 0);
+                for_each_sprm(grpprl_and_istd.get(2..)?, |sprm, operand| {
+                    let flag = operand.first().is_some_and(|&b| b != 0);
+                    match sprm {
+                        SPRM_P_F_IN_TABLE => format.in_table |= flag,
+                        SPRM_P_ITAP => format.in_table |= read_u32_le(operand, 0) > 0,
+                        SPRM_P_F_TTP | SPRM_P_F_INNER_TTP => format.row_end |= flag,
+                        SPRM_P_OUT_LVL => format.outline_level = operand.first().copied(),
+                        SPRM_P_F_BI_DI => format.rtl = flag,
+                        _ => {}
+                    }
+                });
+            }
+            Some((
+                read_u32_le(page, i * 4),
+                read_u32_le(page, (i + 1) * 4),
+                format,
+            ))
+        })
+        .collect()
+}
+
+/// Walk a grpprl, calling `f` with each sprm and its operand
+fn for_each_sprm(grpprl: &[u8], mut f: impl FnMut(u16, &[u8])) {
+    let mut pos = 0;
+    while pos + 2 <= grpprl.len() {
+        let sprm = read_u16_le(grpprl, pos);
+        pos += 2;
+        // The spra bits give the operand size; 6 means variable-length
+        let (skip, size) = match sprm >> 13 {
+            0 | 1 => (0, 1),
+            2 | 4 | 5 => (0, 2),
+            3 => (0, 4),
+            7 => (0, 3),
+            _ if sprm == SPRM_T_DEF_TABLE => {
+                (2, (read_u16_le(grpprl, pos) as usize).saturating_sub(1))
+            }
+            _ => match grpprl.get(pos) {
+                Some(&size) => (1, size as usize),
+                None => return,
+            },
+        };
+        let Some(operand) = grpprl.get(pos + skip..pos + skip + size) else {
+            return;
+        };
+        f(sprm, operand);
+        pos += skip + size;
+    }
+}
+
+/// Turn the character stream into sections
+///
+/// Field codes (between 0x13 and 0x14) are dropped, keeping field results.
+fn build_sections(chars: &[DocChar]) -> Vec<ContentSection> {
+    let mut builder = SectionBuilder::default();
+    // One entry per open field: true while in its code part
+    let mut fields: Vec<bool> = Vec::new();
+
+    for c in chars {
+        match c.ch {
+            0x13 => fields.push(true),
+            0x14 => {
+                if let Some(in_code) = fields.last_mut() {
+                    *in_code = false;
+                }
+            }
+            0x15 => {
+                fields.pop();
+            }
+            _ if fields.iter().any(|&in_code| in_code) => {}
+            0x0D => builder.end_paragraph(c.pap),
+            0x07 => builder.end_cell(c.pap),
+            0x0C => {
+                builder.end_paragraph(None);
+                builder.flush_table();
+                builder.sections.push(empty_section(SectionType::PageBreak));
+            }
+            0x0B => builder.push('\n' as u16, c.chp),
+            0x1E => builder.push('-' as u16, c.chp),
+            0x09 => builder.push(0x09, c.chp),
+            // Pictures, footnote/annotation references, drawn objects, optional hyphens
+            ch if ch < 0x20 => {}
+            ch => builder.push(ch, c.chp),
+        }
+    }
+
+    builder.end_paragraph(None);
+    builder.flush_table();
+    builder.sections
+}
+
+#[derive(Default)]
+struct SectionBuilder {
+    sections: Vec<ContentSection>,
+    /// Runs of the current paragraph (UTF-16, merged by format)
+    runs: Vec<(CharFormat, Vec<u16>)>,
+    /// Paragraphs of the current table cell
+    cell_runs: Vec<TextRun>,
+    row: Vec<ContentSection>,
+    rows: Vec<ContentSection>,
+    /// The previous mark closed a cell (row-end guess when PAPX is unavailable)
+    after_cell: bool,
+}
+
+impl SectionBuilder {
+    fn push(&mut self, ch: u16, format: CharFormat) {
+        self.after_cell = false;
+        match self.runs.last_mut() {
+            Some((last, text)) if *last == format => text.push(ch),
+            _ => self.runs.push((format, vec![ch])),
+        }
+    }
+
+    fn take_runs(&mut self) -> Vec<TextRun> {
+        self.runs
+            .drain(..)
+            .map(|(format, text)| TextRun {
+                text: String::from_utf16_lossy(&text),
+                style: TextStyle {
+                    bold: format.bold,
+                    italic: format.italic,
+                    underline: format.underline,
+                    strikethrough: format.strikethrough,
+                    direction: format.rtl.then_some(TextDirection::Rtl),
+                    ..Default::default()
+                },
+            })
+            .collect()
+    }
+
+    fn end_paragraph(&mut self, pap: Option<ParaFormat>) {
+        self.after_cell = false;
+        // Undeclared (no PAPX) directions are left to first-strong detection
+        let declared_direction = pap.as_ref().map(direction);
+        let pap = pap.unwrap_or_default();
+        let runs = self.take_runs();
+
+        // A paragraph inside a cell: the cell mark ends the cell
+        if pap.in_table {
+            if !self.cell_runs.is_empty() || !runs.is_empty() {
+                self.cell_runs.extend(runs);
+                self.cell_runs.push(TextRun {
+                    text: "\n".to_string(),
+                    style: TextStyle::default(),
+                });
+            }
+            return;
+        }
+
+        self.flush_table();
+        let content: String = runs.iter().map(|r| r.text.as_str()).collect();
+        if content.trim().is_empty() {
+            return;
+        }
+        let section_type = match heading_level(&pap) {
+            Some(level) => SectionType::Heading { level },
+            None => SectionType::Paragraph,
+        };
+        self.sections.push(ContentSection {
+            section_type,
+            content: Some(content.trim_end().to_string()),
+            properties: paragraph_properties(declared_direction, &runs),
+            runs: Some(runs),
+            children: None,
+        });
+    }
+
+    fn end_cell(&mut self, pap: Option<ParaFormat>) {
+        let row_end = match pap {
+            Some(pap) => pap.row_end,
+            // Without PAPX, a mark right after a cell mark ends the row
+            None => self.after_cell && self.runs.is_empty(),
+        };
+
+        if row_end {
+            self.runs.clear();
+            if !self.row.is_empty() {
+                self.rows.push(ContentSection {
+                    section_type: SectionType::TableRow,
+                    content: None,
+                    runs: None,
+                    children: Some(std::mem::take(&mut self.row)),
+                    properties: None,
+                });
+            }
+            self.after_cell = false;
+            return;
+        }
+
+        let runs = self.take_runs();
+        self.cell_runs.extend(runs);
+        while self.cell_runs.last().is_some_and(|r| r.text == "\n") {
+            self.cell_runs.pop();
+        }
+        let runs = std::mem::take(&mut self.cell_runs);
+        let content: String = runs.iter().map(|r| r.text.as_str()).collect();
+        self.row.push(ContentSection {
+            section_type: SectionType::TableCell,
+            content: Some(content.trim().to_string()),
+            properties: paragraph_properties(pap.as_ref().map(direction), &runs),
+            runs: Some(runs),
+            children: None,
+        });
+        self.after_cell = true;
+    }
+
+    fn flush_table(&mut self) {
+        // A row missing its end mark still belongs to the table
+        if !self.row.is_empty() {
+            self.end_cell(Some(ParaFormat {
+                row_end: true,
+                ..Default::default()
+            }));
+        }
+        if !self.rows.is_empty() {
+            self.sections.push(ContentSection {
+                section_type: SectionType::Table,
+                content: None,
+                runs: None,
+                children: Some(std::mem::take(&mut self.rows)),
+                properties: None,
+            });
+        }
+    }
+}
+
+fn direction(pap: &ParaFormat) -> TextDirection {
+    if pap.rtl {
+        TextDirection::Rtl
+    } else {
+        TextDirection::Ltr
+    }
+}
+
+/// Heading level from the outline level (9 = body text) or a built-in heading style
+fn heading_level(pap: &ParaFormat) -> Option<u8> {
+    let level = match pap.outline_level {
+        Some(level) if level < 9 => level + 1,
+        Some(_) => return None,
+        None if (1..=9).contains(&pap.istd) => pap.istd as u8,
+        None => return None,
+    };
+    Some(level.min(6))
+}
+
+fn empty_section(section_type: SectionType) -> ContentSection {
+    ContentSection {
+        section_type,
+        content: None,
+        runs: None,
+        children: None,
+        properties: None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -354,4 +897,103 @@ mod tests {
         assert!(!is_readable_text("\x00\x00\x00"));
         assert!(!is_readable_text("ab"));
     }
+
+    #[test]
+    fn test_parse_clx() {
+        // One Prc (2-byte grpprl), then a PlcPcd with two pieces
+        let mut clx = vec![0x01, 0x02, 0x00, 0xAA, 0xBB, 0x02];
+        let mut plc = Vec::new();
+        for cp in [0u32, 10, 15] {
+            plc.extend(cp.to_le_bytes());
+        }
+        for fc in [0x0800u32, 0x4000_0000 | 0x2000] {
+            plc.extend([0, 0]);
+            plc.extend(fc.to_le_bytes());
+            plc.extend([0, 0]);
+        }
+        clx.extend((plc.len() as u32).to_le_bytes());
+        clx.extend(plc);
+
+        let pieces = parse_clx(&clx).unwrap();
+        assert_eq!(
+            pieces,
+            vec![
+                Piece { cp_start: 0, cp_end: 10, fc: 0x0800, compressed: false },
+                Piece { cp_start: 10, cp_end: 15, fc: 0x1000, compressed: true },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_fkps() {
+        // CHPX page: two runs, the second bold + single underline
+        let mut page = vec![0u8; FKP_SIZE];
+        for (i, fc) in [0x400u32, 0x410, 0x420].iter().enumerate() {
+            page[i * 4..i * 4 + 4].copy_from_slice(&fc.to_le_bytes());
+        }
+        page[12] = 0; // first run: no CHPX
+        page[13] = 0xF0; // second run: CHPX at byte 0x1E0
+        page[0x1E0..0x1E7].copy_from_slice(&[6, 0x35, 0x08, 0x01, 0x3E, 0x2A, 0x01]);
+        page[FKP_SIZE - 1] = 2;
+
+        let runs = parse_chpx_fkp(&page);
+        assert_eq!(find_format(&runs, 0x405), Some(CharFormat::default()));
+        let bold = find_format(&runs, 0x418).unwrap();
+        assert!(bold.bold && bold.underline && !bold.italic);
+        assert_eq!(find_format(&runs, 0x420), None);
+
+        // PAPX page: one paragraph, istd 2 in a table
+        let mut page = vec![0u8; FKP_SIZE];
+        page[0..4].copy_from_slice(&0x400u32.to_le_bytes());
+        page[4..8].copy_from_slice(&0x420u32.to_le_bytes());
+        page[8] = 0xF0; // BX: PapxInFkp at byte 0x1E0
+        page[0x1E0..0x1E6].copy_from_slice(&[3, 0x02, 0x00, 0x16, 0x24, 0x01]);
+        page[FKP_SIZE - 1] = 1;
+
+        let pap = find_format(&parse_papx_fkp(&page), 0x410).unwrap();
+        assert_eq!(pap.istd, 2);
+        assert!(pap.in_table && !pap.row_end);
+        assert_eq!(heading_level(&pap), Some(2));
+    }
+
+    #[test]
+    fn test_build_sections() {
+        let heading = ParaFormat { istd: 1, ..Default::default() };
+        let body = ParaFormat::default();
+        let cell = ParaFormat { in_table: true, ..Default::default() };
+        let row_end = ParaFormat { in_table: true, row_end: true, ..Default::default() };
+        let bold = CharFormat { bold: true, ..Default::default() };
+
+        let mut chars = Vec::new();
+        let mut push = |text: &str, chp: CharFormat, pap: ParaFormat| {
+            chars.extend(text.encode_utf16().map(|ch| DocChar { ch, chp, pap: Some(pap) }));
+        };
+        push("Site report\r", CharFormat::default(), heading);
+        push("Soil is ", CharFormat::default(), body);
+        push("dry", bold, body);
+        push(" \u{13} PAGE \u{14}3\u{15}\r", CharFormat::default(), body);
+        push("Depth\u{7}Moisture\u{7}", CharFormat::default(), cell);
+        push("\u{7}", CharFormat::default(), row_end);
+        push("2 m\u{7}12%\u{7}", CharFormat::default(), cell);
+        push("\u{7}", CharFormat::default(), row_end);
+        push("\u{c}\rNext page\r", CharFormat::default(), body);
+
+        let sections = build_sections(&chars);
+        assert_eq!(sections[0].section_type, SectionType::Heading { level: 1 });
+        assert_eq!(sections[0].content.as_deref(), Some("Site report"));
+
+        assert_eq!(sections[1].content.as_deref(), Some("Soil is dry 3"));
+        let runs = sections[1].runs.as_ref().unwrap();
+        assert!(runs[1].style.bold && runs[1].text == "dry");
+
+        assert_eq!(sections[2].section_type, SectionType::Table);
+        let rows = sections[2].children.as_ref().unwrap();
+        assert_eq!(rows.len(), 2);
+        let cells = rows[1].children.as_ref().unwrap();
+        assert_eq!(cells[1].content.as_deref(), Some("12%"));
+
+        assert_eq!(sections[3].section_type, SectionType::PageBreak);
+        assert_eq!(sections[4].content.as_deref(), Some("Next page"));
+        assert_eq!(sections.len(), 5);
+    }
 }
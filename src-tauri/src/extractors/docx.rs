@@ -5,8 +5,10 @@ use std::io::{BufReader, Read};
 use std::path::Path;
 use zip::ZipArchive;
 
+use super::language::paragraph_properties;
 use crate::models::{
-    ContentSection, DocumentContent, DocumentMetadata, SectionType, TextRun, TextStyle,
+    ContentSection, DocumentContent, DocumentMetadata, SectionProperties, SectionType,
+    TextDirection, TextRun, TextStyle,
 };
 
 /// Extract text content from a DOCX file (plain text for indexing)
@@ -44,6 +46,9 @@ pub fn extract_docx(path: &Path) -> Option<String> {
         }
     }
 
+    // Charts and SmartArt live in separate parts referenced from document.xml
+    super::ooxml::append_embedded_text(&mut archive, "word/document.xml", &mut content);
+
     if content.is_empty() {
         None
     } else {
@@ -58,6 +63,7 @@ pub fn extract_docx(path: &Path) -> Option<String> {
 /// - Text runs with formatting (bold, italic, underline)
 /// - Lists (bullets and numbered)
 /// - Tables
+/// - Charts and SmartArt diagrams (labeled blocks at their anchor position)
 pub fn extract_docx_structured(path: &Path) -> Option<DocumentContent> {
     let file = fs::File::open(path).ok()?;
     let mut archive = ZipArchive::new(file).ok()?;
@@ -65,10 +71,13 @@ pub fn extract_docx_structured(path: &Path) -> Option<DocumentContent> {
     // Parse styles.xml to get style name mappings
     let style_map = parse_styles(&mut archive);
 
+    // Pre-extract charts/diagrams keyed by relationship ID
+    let mut embedded = parse_embedded_sections(&mut archive);
+
     // Parse document.xml for content
     let sections = if let Ok(document) = archive.by_name("word/document.xml") {
         let buf_reader = BufReader::new(document);
-        parse_document_xml_streaming(buf_reader, &style_map)
+        parse_document_xml_streaming(buf_reader, &style_map, &mut embedded)
     } else {
         Vec::new()
     };
@@ -195,10 +204,29 @@ fn detect_heading_level(style_id: &str, style_name: &str) -> Option<u8> {
     None
 }
 
+/// Build preview blocks for charts and SmartArt referenced by document.xml
+fn parse_embedded_sections(
+    archive: &mut ZipArchive<fs::File>,
+) -> std::collections::HashMap<String, ContentSection> {
+    let mut embedded = std::collections::HashMap::new();
+
+    for part in super::ooxml::embedded_parts(archive, "word/document.xml") {
+        if let Some(text) = super::ooxml::extract_embedded_text(archive, &part) {
+            embedded.insert(part.rel_id, text.to_section());
+        }
+    }
+
+    embedded
+}
+
 /// Parse document.xml using streaming parser and extract structured content
+///
+/// `embedded` maps relationship IDs to chart/diagram blocks; each is emitted
+/// once, where its `c:chart` / `dgm:relIds` reference appears.
 fn parse_document_xml_streaming<R: Read>(
     reader: R,
     style_map: &std::collections::HashMap<String, StyleInfo>,
+    embedded: &mut std::collections::HashMap<String, ContentSection>,
 ) -> Vec<ContentSection> {
     let mut sections = Vec::new();
     let mut xml_reader = Reader::from_reader(BufReader::new(reader));
@@ -219,6 +247,12 @@ fn parse_document_xml_streaming<R: Read>(
     let mut current_text = String::new();
     let mut current_style = TextStyle::default();
 
+    // Direction: <w:bidi/> in pPr, <w:bidiVisual/> in tblPr
+    let mut paragraph_direction = TextDirection::Ltr;
+    let mut table_direction: Option<TextDirection> = None;
+    // <w:sectPr> has its own <w:bidi/> (section direction) and may sit in a pPr
+    let mut in_section_properties = false;
+
     // List tracking
     let mut list_depth: u8 = 0;
     let is_ordered_list = false; // TODO: Detect from numFmt in numbering.xml
@@ -235,8 +269,54 @@ fn parse_document_xml_streaming<R: Read>(
                     b"p" => {
                         in_paragraph = true;
                         current_paragraph_style = None;
+                        paragraph_direction = TextDirection::Ltr;
+                        in_section_properties = false;
                         current_runs.clear();
                     }
+                    // Right-to-left paragraph
+                    b"bidi" => {
+                        if in_paragraph && !in_run && !in_section_properties && is_toggle_on(&e)
+                        {
+                            paragraph_direction = TextDirection::Rtl;
+                        }
+                    }
+                    // Section properties (cleared at their end or the next paragraph)
+                    b"sectPr" => {
+                        in_section_properties = true;
+                    }
+                    // Right-to-left table (columns ordered right to left)
+                    b"bidiVisual" => {
+                        if in_table && is_toggle_on(&e) {
+                            table_direction = Some(TextDirection::Rtl);
+                        }
+                    }
+                    // Right-to-left run
+                    b"rtl" => {
+                        if in_run && is_toggle_on(&e) {
+                            current_style.direction = Some(TextDirection::Rtl);
+                        }
+                    }
+                    // Run language: w:val (Latin), w:bidi (RTL scripts), w:eastAsia
+                    b"lang" => {
+                        if in_run {
+                            let mut latin = None;
+                            let mut bidi = None;
+                            for attr in e.attributes().filter_map(|a| a.ok()) {
+                                let value = String::from_utf8_lossy(&attr.value).to_string();
+                                match attr.key.local_name().as_ref() {
+                                    b"val" => latin = Some(value),
+                                    b"bidi" => bidi = Some(value),
+                                    _ => {}
+                                }
+                            }
+                            current_style.language =
+                                if current_style.direction == Some(TextDirection::Rtl) {
+                                    bidi.or(latin)
+                                } else {
+                                    latin.or(bidi)
+                                };
+                        }
+                    }
                     // Paragraph style
                     b"pStyle" => {
                         if in_paragraph {
@@ -336,6 +416,7 @@ fn parse_document_xml_streaming<R: Read>(
                     // Table
                     b"tbl" => {
                         in_table = true;
+                        table_direction = None;
                         table_rows.clear();
                     }
                     // Table row
@@ -351,6 +432,22 @@ fn parse_document_xml_streaming<R: Read>(
                             in_table_cell = true;
                         }
                     }
+                    // Chart (r:id) or SmartArt (r:dm) reference
+                    b"chart" | b"relIds" => {
+                        let key: &[u8] = if e.local_name().as_ref() == b"chart" {
+                            b"id"
+                        } else {
+                            b"dm"
+                        };
+                        for attr in e.attributes().filter_map(|a| a.ok()) {
+                            if attr.key.local_name().as_ref() == key {
+                                let rel_id = String::from_utf8_lossy(&attr.value);
+                                if let Some(section) = embedded.remove(rel_id.as_ref()) {
+                                    sections.push(section);
+                                }
+                            }
+                        }
+                    }
                     // Page break
                     b"lastRenderedPageBreak" | b"pageBreakBefore" => {
                         sections.push(ContentSection {
@@ -445,6 +542,9 @@ fn parse_document_xml_streaming<R: Read>(
                     b"r" => {
                         in_run = false;
                     }
+                    b"sectPr" => {
+                        in_section_properties = false;
+                    }
                     b"numPr" => {
                         // Don't reset in_list_item here, it applies to the paragraph
                     }
@@ -486,7 +586,10 @@ fn parse_document_xml_streaming<R: Read>(
                                     content: Some(combined_content),
                                     runs: Some(current_runs.clone()),
                                     children: None,
-                                    properties: None,
+                                    properties: paragraph_properties(
+                                        Some(paragraph_direction),
+                                        &current_runs,
+                                    ),
                                 });
                             } else {
                                 sections.push(ContentSection {
@@ -494,7 +597,10 @@ fn parse_document_xml_streaming<R: Read>(
                                     content: Some(combined_content),
                                     runs: Some(current_runs.clone()),
                                     children: None,
-                                    properties: None,
+                                    properties: paragraph_properties(
+                                        Some(paragraph_direction),
+                                        &current_runs,
+                                    ),
                                 });
                             }
                         }
@@ -526,7 +632,10 @@ fn parse_document_xml_streaming<R: Read>(
                                 content: None,
                                 runs: None,
                                 children: Some(table_rows.clone()),
-                                properties: None,
+                                properties: table_direction.map(|direction| SectionProperties {
+                                    direction: Some(direction),
+                                    ..Default::default()
+                                }),
                             });
                         }
                         in_table = false;
@@ -545,6 +654,14 @@ fn parse_document_xml_streaming<R: Read>(
     sections
 }
 
+/// On/off toggle element (`<w:bidi/>`, `<w:rtl w:val="0"/>`): on unless `w:val` says otherwise
+fn is_toggle_on(e: &quick_xml::events::BytesStart) -> bool {
+    !e.attributes().filter_map(|a| a.ok()).any(|a| {
+        a.key.local_name().as_ref() == b"val"
+            && matches!(a.value.as_ref(), b"false" | b"0" | b"off")
+    })
+}
+
 /// Parse core.xml for document metadata
 fn parse_metadata(archive: &mut ZipArchive<fs::File>) -> DocumentMetadata {
     let mut metadata = DocumentMetadata::default();
@@ -601,4 +718,39 @@ mod tests {
         assert_eq!(detect_heading_level("Title", "Title"), Some(1));
         assert_eq!(detect_heading_level("Normal", "Normal"), None);
     }
+
+    #[test]
+    fn test_direction_and_language() {
+        let xml = r#"<w:document xmlns:w="w"><w:body>
+<w:p><w:pPr><w:bidi/></w:pPr>
+<w:r><w:rPr><w:rtl/><w:lang w:val="en-US" w:bidi="ar-SA"/></w:rPr><w:t>مرحبا</w:t></w:r>
+<w:r><w:rPr><w:rtl/><w:lang w:val="en-US" w:bidi="ar-SA"/></w:rPr><w:t> بالعالم</w:t></w:r></w:p>
+<w:p><w:pPr><w:sectPr><w:bidi/></w:sectPr></w:pPr>
+<w:r><w:rPr><w:lang w:val="en-GB"/></w:rPr><w:t>Hello</w:t></w:r>
+<w:r><w:rPr><w:rtl/><w:lang w:bidi="ar-SA"/></w:rPr><w:t>عربي</w:t></w:r></w:p>
+<w:tbl><w:tblPr><w:bidiVisual/></w:tblPr><w:tr><w:tc><w:p><w:r><w:t>x</w:t></w:r></w:p></w:tc></w:tr></w:tbl>
+</w:body></w:document>"#;
+        let sections = parse_document_xml_streaming(
+            xml.as_bytes(),
+            &std::collections::HashMap::new(),
+            &mut std::collections::HashMap::new(),
+        );
+
+        let rtl = sections[0].properties.as_ref().unwrap();
+        assert_eq!(rtl.direction, Some(TextDirection::Rtl));
+        assert_eq!(rtl.language.as_deref(), Some("ar-SA"));
+        let run = &sections[0].runs.as_ref().unwrap()[0];
+        assert_eq!(run.style.direction, Some(TextDirection::Rtl));
+
+        // Section bidi doesn't leak into the paragraph; mixed languages aren't summarized
+        let mixed = sections[1].properties.as_ref().unwrap();
+        assert_eq!(mixed.direction, Some(TextDirection::Ltr));
+        assert_eq!(mixed.language, None);
+        let runs = sections[1].runs.as_ref().unwrap();
+        assert_eq!(runs[0].style.language.as_deref(), Some("en-GB"));
+        assert_eq!(runs[1].style.language.as_deref(), Some("ar-SA"));
+
+        let table = sections[2].properties.as_ref().unwrap();
+        assert_eq!(table.direction, Some(TextDirection::Rtl));
+    }
 }
@@ -0,0 +1,823 @@
+//! Email extraction (.eml, .mbox, Outlook .msg)
+//!
+//! - `.eml`: a single RFC 822 message, MIME parts parsed with `mime`
+//! - `.mbox`: messages concatenated, each introduced by a `From ` line
+//! - `.msg`: OLE compound file; properties live in `__substg1.0_<id><type>`
+//!   streams, attachments in `__attach_version1.0_#<n>` storages
+//!
+//! Messages inside an mbox and (optionally) supported attachments are indexed
+//! as child entries with virtual paths below the container file:
+//! `archive.mbox::msg3`, `mail.eml::att1::report.docx`,
+//! `archive.mbox::msg3::att1::report.docx`.
+
+use cfb::CompoundFile;
+use chrono::DateTime;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+use super::html::{decode_html_bytes, document_to_text, parse_html};
+use super::mime::{decode_charset, MimePart};
+use super::{extract_content, extract_content_structured, get_file_type, has_macros, TextFileRules};
+use super::EMAIL_EXTENSIONS;
+use crate::models::{ContentSection, DocumentContent, DocumentMetadata, SectionType};
+
+/// Separator between a container file and a child entry in virtual paths
+pub const CHILD_SEPARATOR: &str = "::";
+
+/// A parsed email message
+#[derive(Debug, Default)]
+struct EmailMessage {
+    from: Option<String>,
+    to: Option<String>,
+    cc: Option<String>,
+    subject: Option<String>,
+    date: Option<String>,
+    /// text/plain body
+    body: String,
+    /// text/html body (used when there is no plain body, and for preview)
+    html_body: Option<String>,
+    attachments: Vec<EmailAttachment>,
+}
+
+#[derive(Debug)]
+struct EmailAttachment {
+    name: String,
+    data: Vec<u8>,
+}
+
+/// A child entry (mbox message or attachment) to index below its container
+#[derive(Debug)]
+pub struct EmailEntry {
+    /// Child id relative to the container (`msg3`, `att1::report.docx`)
+    pub child: String,
+    /// Display name: message subject or attachment filename
+    pub name: String,
+    /// Extension that determines the file type (`eml` for mbox messages)
+    pub ext: String,
+    pub size: u64,
+    pub content: String,
+    pub has_macros: bool,
+}
+
+/// Build the virtual path of a child entry
+pub fn child_path(container: &str, child: &str) -> String {
+    format!("{}{}{}", container, CHILD_SEPARATOR, child)
+}
+
+/// Split a virtual path into (container file, child id)
+///
+/// Only splits after an email file name, so `::` elsewhere in a path is safe.
+pub fn split_child_path(path: &str) -> Option<(&str, &str)> {
+    path.match_indices(CHILD_SEPARATOR)
+        .map(|(i, _)| (&path[..i], &path[i + CHILD_SEPARATOR.len()..]))
+        .find(|(container, child)| !child.is_empty() && is_email_file(container))
+}
+
+/// The file on disk behind a path (the container for child entries)
+pub fn container_path(path: &str) -> &str {
+    split_child_path(path).map_or(path, |(container, _)| container)
+}
+
+fn is_email_file(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| EMAIL_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Extract text content from an email file (plain text for indexing)
+///
+/// For an mbox this is the list of messages; their text is indexed through
+/// the child entries from [`extract_email_entries`].
+pub fn extract_email(path: &Path, ext: &str) -> Option<String> {
+    match ext {
+        "mbox" => {
+            let data = fs::read(path).ok()?;
+            let summary: Vec<String> = split_mbox(&data)
+                .into_iter()
+                .map(|raw| message_summary(&parse_eml(raw)))
+                .collect();
+            Some(summary.join("\n"))
+        }
+        _ => load_message(path, ext).map(|message| message_text(&message)),
+    }
+}
+
+/// Extract structured content from an email file (for rich preview)
+pub fn extract_email_structured(path: &Path, ext: &str) -> Option<DocumentContent> {
+    match ext {
+        "mbox" => {
+            let data = fs::read(path).ok()?;
+            let mut sections = Vec::new();
+            for raw in split_mbox(&data) {
+                let message = parse_eml(raw);
+                sections.push(section(
+                    SectionType::Heading { level: 2 },
+                    message.subject.clone().unwrap_or_else(|| "(no subject)".to_string()),
+                ));
+                let byline: Vec<&str> = [message.from.as_deref(), message.date.as_deref()]
+                    .into_iter()
+                    .flatten()
+                    .collect();
+                if !byline.is_empty() {
+                    sections.push(section(SectionType::Paragraph, byline.join(" · ")));
+                }
+            }
+            Some(DocumentContent {
+                doc_type: "email".to_string(),
+                sections,
+                metadata: DocumentMetadata::default(),
+            })
+        }
+        _ => load_message(path, ext).map(|message| message_document(&message)),
+    }
+}
+
+/// Child entries of an email file: mbox messages, and supported attachments
+/// (built-in types, or text types under `rules`) when `include_attachments`
+/// is set
+pub fn extract_email_entries(
+    path: &Path,
+    ext: &str,
+    include_attachments: bool,
+    rules: &TextFileRules,
+) -> Vec<EmailEntry> {
+    match ext {
+        "mbox" => {
+            let Ok(data) = fs::read(path) else {
+                return Vec::new();
+            };
+            let mut entries = Vec::new();
+            for (i, raw) in split_mbox(&data).into_iter().enumerate() {
+                let message = parse_eml(raw);
+                let child = format!("msg{}", i + 1);
+                entries.push(EmailEntry {
+                    child: child.clone(),
+                    name: message
+                        .subject
+                        .clone()
+                        .unwrap_or_else(|| "(no subject)".to_string()),
+                    ext: "eml".to_string(),
+                    size: raw.len() as u64,
+                    content: message_text(&message),
+                    has_macros: false,
+                });
+                if include_attachments {
+                    entries.extend(attachment_entries(Some(&child), &message, rules));
+                }
+            }
+            entries
+        }
+        _ if include_attachments => load_message(path, ext)
+            .map(|message| attachment_entries(None, &message, rules))
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+/// Extract text content of a child entry
+pub fn extract_child_content(container: &Path, child: &str) -> Option<String> {
+    match resolve_child(container, child)? {
+        Child::Message(message) => Some(message_text(&message)),
+        Child::Attachment(attachment) => with_temp_file(&attachment, extract_content),
+    }
+}
+
+/// Extract structured content of a child entry (for rich preview)
+pub fn extract_child_structured(container: &Path, child: &str) -> Option<DocumentContent> {
+    match resolve_child(container, child)? {
+        Child::Message(message) => Some(message_document(&message)),
+        Child::Attachment(attachment) => with_temp_file(&attachment, extract_content_structured),
+    }
+}
+
+enum Child {
+    Message(EmailMessage),
+    Attachment(EmailAttachment),
+}
+
+/// Find a child entry from its id (`msg3`, `att1::name`, `msg3::att1::name`)
+fn resolve_child(container: &Path, child: &str) -> Option<Child> {
+    let ext = container.extension()?.to_str()?.to_lowercase();
+    let mut segments = child.split(CHILD_SEPARATOR);
+    let mut segment = segments.next()?;
+
+    let message = match segment.strip_prefix("msg") {
+        Some(number) => {
+            let index = number.parse::<usize>().ok()?.checked_sub(1)?;
+            let data = fs::read(container).ok()?;
+            let message = parse_eml(split_mbox(&data).into_iter().nth(index)?);
+            match segments.next() {
+                Some(next) => segment = next,
+                None => return Some(Child::Message(message)),
+            }
+            message
+        }
+        None => load_message(container, &ext)?,
+    };
+
+    let index = segment.strip_prefix("att")?.parse::<usize>().ok()?.checked_sub(1)?;
+    message.attachments.into_iter().nth(index).map(Child::Attachment)
+}
+
+/// Entries for the supported (non-email) attachments of a message
+fn attachment_entries(
+    parent: Option<&str>,
+    message: &EmailMessage,
+    rules: &TextFileRules,
+) -> Vec<EmailEntry> {
+    message
+        .attachments
+        .iter()
+        .enumerate()
+        .filter_map(|(i, attachment)| {
+            let ext = attachment_ext(attachment)?;
+            let supported = get_file_type(&ext).or_else(|| rules.file_type(&attachment.name, &ext));
+            if supported.is_none() || EMAIL_EXTENSIONS.contains(&ext.as_str()) {
+                return None;
+            }
+            let (content, macros) = with_temp_file(attachment, |path, ext| {
+                Some((extract_content(path, ext)?, has_macros(path, ext)))
+            })?;
+            // Path separators in the name would break the virtual path
+            let safe_name = attachment.name.replace(['/', '\\'], "_");
+            let id = format!("att{}{}{}", i + 1, CHILD_SEPARATOR, safe_name);
+            Some(EmailEntry {
+                child: match parent {
+                    Some(parent) => child_path(parent, &id),
+                    None => id,
+                },
+                name: attachment.name.clone(),
+                ext,
+                size: attachment.data.len() as u64,
+                content,
+                has_macros: macros,
+            })
+        })
+        .collect()
+}
+
+/// Lowercased extension of an attachment
+fn attachment_ext(attachment: &EmailAttachment) -> Option<String> {
+    Some(
+        Path::new(&attachment.name)
+            .extension()?
+            .to_str()?
+            .to_lowercase(),
+    )
+}
+
+/// Run an extractor on an attachment written to a temporary file
+///
+/// The file is created fresh (never through an existing name or symlink) in
+/// a directory private to this process.
+fn with_temp_file<T>(
+    attachment: &EmailAttachment,
+    extract: impl FnOnce(&Path, &str) -> Option<T>,
+) -> Option<T> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let ext = attachment_ext(attachment)?;
+    if attachment.data.is_empty() {
+        return None;
+    }
+
+    let temp_path = attachment_dir()?.join(format!(
+        "attachment-{}.{}",
+        COUNTER.fetch_add(1, Ordering::SeqCst),
+        ext
+    ));
+    let written = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&temp_path)
+        .and_then(|mut file| file.write_all(&attachment.data));
+    let result = written.ok().and_then(|()| extract(&temp_path, &ext));
+    let _ = fs::remove_file(&temp_path);
+    result
+}
+
+/// Directory for attachment files, created (owner-only on Unix) on first use
+fn attachment_dir() -> Option<&'static Path> {
+    static DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+    DIR.get_or_init(|| {
+        let mut builder = fs::DirBuilder::new();
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+        // A name already taken (by a stale or foreign directory) is skipped
+        (0..16).find_map(|attempt| {
+            let dir = std::env::temp_dir().join(format!(
+                "docufind-attachments-{}-{}",
+                std::process::id(),
+                attempt
+            ));
+            builder.create(&dir).ok().map(|()| dir)
+        })
+    })
+    .as_deref()
+}
+
+/// Load a single message (.eml or .msg)
+fn load_message(path: &Path, ext: &str) -> Option<EmailMessage> {
+    match ext {
+        "eml" => fs::read(path).ok().map(|data| parse_eml(&data)),
+        "msg" => parse_msg(path),
+        _ => None,
+    }
+}
+
+// ============================================================================
+// Text and preview
+// ============================================================================
+
+impl EmailMessage {
+    /// Plain body, or the HTML body converted to text
+    fn body_text(&self) -> String {
+        if !self.body.trim().is_empty() {
+            return self.body.trim().to_string();
+        }
+        self.html_body
+            .as_deref()
+            .and_then(|html| document_to_text(&parse_html(html)))
+            .unwrap_or_default()
+    }
+
+    /// Header lines shown in the preview and indexed with the body
+    fn header_fields(&self) -> Vec<(&'static str, &str)> {
+        [
+            ("From", self.from.as_deref()),
+            ("To", self.to.as_deref()),
+            ("Cc", self.cc.as_deref()),
+            ("Subject", self.subject.as_deref()),
+            ("Date", self.date.as_deref()),
+        ]
+        .into_iter()
+        .filter_map(|(label, value)| Some((label, value.filter(|v| !v.trim().is_empty())?)))
+        .collect()
+    }
+}
+
+/// Headers, body and attachment names as indexable text
+fn message_text(message: &EmailMessage) -> String {
+    let mut text: Vec<String> = message
+        .header_fields()
+        .into_iter()
+        .map(|(label, value)| format!("{}: {}", label, value))
+        .collect();
+
+    let body = message.body_text();
+    if !body.is_empty() {
+        text.push(String::new());
+        text.push(body);
+    }
+
+    if !message.attachments.is_empty() {
+        let names: Vec<&str> = message.attachments.iter().map(|a| a.name.as_str()).collect();
+        text.push(String::new());
+        text.push(format!("Attachments: {}", names.join(", ")));
+    }
+
+    text.join("\n")
+}
+
+/// One-line summary used for the message list of an mbox
+fn message_summary(message: &EmailMessage) -> String {
+    [
+        message.subject.as_deref(),
+        message.from.as_deref(),
+        message.date.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" — ")
+}
+
+/// Structured preview: subject, header table, body, attachment list
+fn message_document(message: &EmailMessage) -> DocumentContent {
+    let mut sections = Vec::new();
+
+    if let Some(subject) = &message.subject {
+        sections.push(section(SectionType::Heading { level: 1 }, subject.clone()));
+    }
+
+    let rows: Vec<ContentSection> = message
+        .header_fields()
+        .into_iter()
+        .filter(|(label, _)| *label != "Subject")
+        .map(|(label, value)| ContentSection {
+            section_type: SectionType::TableRow,
+            content: None,
+            runs: None,
+            children: Some(vec![
+                section(SectionType::TableCell, label.to_string()),
+                section(SectionType::TableCell, value.to_string()),
+            ]),
+            properties: None,
+        })
+        .collect();
+    if !rows.is_empty() {
+        sections.push(ContentSection {
+            section_type: SectionType::Table,
+            content: None,
+            runs: None,
+            children: Some(rows),
+            properties: None,
+        });
+    }
+
+    sections.push(ContentSection {
+        section_type: SectionType::HorizontalRule,
+        content: None,
+        runs: None,
+        children: None,
+        properties: None,
+    });
+
+    let html_sections = message
+        .html_body
+        .as_deref()
+        .map(|html| parse_html(html).sections)
+        .filter(|sections| !sections.is_empty());
+    match html_sections {
+        Some(html_sections) => sections.extend(html_sections),
+        None => sections.extend(
+            message
+                .body_text()
+                .split("\n\n")
+                .map(str::trim)
+                .filter(|p| !p.is_empty())
+                .map(|p| section(SectionType::Paragraph, p.to_string())),
+        ),
+    }
+
+    if !message.attachments.is_empty() {
+        sections.push(section(SectionType::Heading { level: 3 }, "Attachments".to_string()));
+        for attachment in &message.attachments {
+            sections.push(section(
+                SectionType::ListItem {
+                    ordered: false,
+                    depth: 0,
+                },
+                attachment.name.clone(),
+            ));
+        }
+    }
+
+    let recipients: Vec<&str> = [message.to.as_deref(), message.cc.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect();
+
+    DocumentContent {
+        doc_type: "email".to_string(),
+        sections,
+        metadata: DocumentMetadata {
+            title: message.subject.clone(),
+            author: message.from.clone(),
+            created: message.date.clone(),
+            recipients: (!recipients.is_empty()).then(|| recipients.join(", ")),
+            ..Default::default()
+        },
+    }
+}
+
+fn section(section_type: SectionType, content: String) -> ContentSection {
+    ContentSection {
+        section_type,
+        content: Some(content),
+        runs: None,
+        children: None,
+        properties: None,
+    }
+}
+
+// ============================================================================
+// RFC 822 / mbox
+// ============================================================================
+
+/// Parse an RFC 822 message: headers, bodies and attachments
+fn parse_eml(data: &[u8]) -> EmailMessage {
+    let root = MimePart::parse(data);
+    let mut message = EmailMessage {
+        from: root.decoded_header("from"),
+        to: root.decoded_header("to"),
+        cc: root.decoded_header("cc"),
+        subject: root.decoded_header("subject"),
+        date: root.header("date").map(str::to_string),
+        ..Default::default()
+    };
+
+    let mut plain_bodies = Vec::new();
+    for (i, part) in root.leaf_parts().into_iter().enumerate() {
+        if part.is_attachment() {
+            let name = part.filename().unwrap_or_else(|| {
+                if part.content_type() == "message/rfc822" {
+                    "message.eml".to_string()
+                } else {
+                    format!("attachment-{}", i + 1)
+                }
+            });
+            message.attachments.push(EmailAttachment {
+                name,
+                data: part.decoded_body(),
+            });
+            continue;
+        }
+
+        match part.content_type().as_str() {
+            "text/plain" => plain_bodies.push(part.decoded_text()),
+            "text/html" if message.html_body.is_none() => {
+                message.html_body = Some(part.decoded_text())
+            }
+            _ => {}
+        }
+    }
+
+    message.body = plain_bodies.join("\n\n");
+    message
+}
+
+/// Split an mbox into raw messages (without their `From ` separator lines)
+///
+/// A separator is a `From ` line at the start of the file or after a blank line.
+fn split_mbox(data: &[u8]) -> Vec<&[u8]> {
+    let mut messages = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut previous_blank = true;
+    let mut pos = 0;
+
+    while pos < data.len() {
+        let line_end = data[pos..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map(|p| pos + p + 1)
+            .unwrap_or(data.len());
+        let line = &data[pos..line_end];
+
+        if previous_blank && line.starts_with(b"From ") {
+            if let Some(begin) = start {
+                messages.push(&data[begin..pos]);
+            }
+            start = Some(line_end);
+        }
+
+        previous_blank = line.iter().all(|b| b.is_ascii_whitespace());
+        pos = line_end;
+    }
+
+    if let Some(begin) = start {
+        messages.push(&data[begin..]);
+    }
+
+    messages
+        .into_iter()
+        .filter(|m| m.iter().any(|b| !b.is_ascii_whitespace()))
+        .collect()
+}
+
+// ============================================================================
+// Outlook .msg (OLE compound file)
+// ============================================================================
+
+const PR_SUBJECT: u16 = 0x0037;
+const PR_CLIENT_SUBMIT_TIME: u16 = 0x0039;
+const PR_SENDER_NAME: u16 = 0x0C1A;
+const PR_SENDER_EMAIL_ADDRESS: u16 = 0x0C1F;
+const PR_DISPLAY_CC: u16 = 0x0E03;
+const PR_DISPLAY_TO: u16 = 0x0E04;
+const PR_MESSAGE_DELIVERY_TIME: u16 = 0x0E06;
+const PR_BODY: u16 = 0x1000;
+const PR_HTML: u16 = 0x1013;
+const PR_DISPLAY_NAME: u16 = 0x3001;
+const PR_ATTACH_DATA_BIN: u16 = 0x3701;
+const PR_ATTACH_FILENAME: u16 = 0x3704;
+const PR_ATTACH_LONG_FILENAME: u16 = 0x3707;
+const PR_SENDER_SMTP_ADDRESS: u16 = 0x5D01;
+
+const PT_STRING8: u16 = 0x001E;
+const PT_UNICODE: u16 = 0x001F;
+const PT_SYSTIME: u16 = 0x0040;
+const PT_BINARY: u16 = 0x0102;
+
+/// Parse an Outlook .msg file
+fn parse_msg(path: &Path) -> Option<EmailMessage> {
+    let file = fs::File::open(path).ok()?;
+    let mut cfb = CompoundFile::open(file).ok()?;
+
+    let sender_name = read_msg_string(&mut cfb, "/", PR_SENDER_NAME);
+    let sender_email = read_msg_string(&mut cfb, "/", PR_SENDER_SMTP_ADDRESS)
+        .or_else(|| read_msg_string(&mut cfb, "/", PR_SENDER_EMAIL_ADDRESS))
+        .filter(|email| email.contains('@'));
+    let from = match (sender_name, sender_email) {
+        (Some(name), Some(email)) if name != email => Some(format!("{} <{}>", name, email)),
+        (name, email) => email.or(name),
+    };
+
+    let html_body = read_msg_stream(&mut cfb, "/", PR_HTML, PT_BINARY)
+        .map(|bytes| decode_html_bytes(&bytes))
+        .or_else(|| read_msg_string(&mut cfb, "/", PR_HTML));
+
+    // Fixed-size properties (dates) sit in the property stream, after a 32-byte header
+    let date = read_stream(&mut cfb, "/__properties_version1.0").and_then(|props| {
+        read_systime(&props, 32, PR_CLIENT_SUBMIT_TIME)
+            .or_else(|| read_systime(&props, 32, PR_MESSAGE_DELIVERY_TIME))
+    });
+
+    let attachment_storages: Vec<String> = cfb
+        .read_root_storage()
+        .filter(|entry| entry.is_storage() && entry.name().starts_with("__attach_version1.0_#"))
+        .map(|entry| format!("/{}/", entry.name()))
+        .collect();
+
+    let mut attachments = Vec::new();
+    for storage in attachment_storages {
+        let name = read_msg_string(&mut cfb, &storage, PR_ATTACH_LONG_FILENAME)
+            .or_else(|| read_msg_string(&mut cfb, &storage, PR_ATTACH_FILENAME))
+            .or_else(|| read_msg_string(&mut cfb, &storage, PR_DISPLAY_NAME));
+        if let Some(name) = name.filter(|n| !n.trim().is_empty()) {
+            let data =
+                read_msg_stream(&mut cfb, &storage, PR_ATTACH_DATA_BIN, PT_BINARY).unwrap_or_default();
+            attachments.push(EmailAttachment { name, data });
+        }
+    }
+
+    Some(EmailMessage {
+        from,
+        to: read_msg_string(&mut cfb, "/", PR_DISPLAY_TO),
+        cc: read_msg_string(&mut cfb, "/", PR_DISPLAY_CC),
+        subject: read_msg_string(&mut cfb, "/", PR_SUBJECT),
+        date,
+        body: read_msg_string(&mut cfb, "/", PR_BODY).unwrap_or_default(),
+        html_body,
+        attachments,
+    })
+}
+
+fn read_stream<F: Read + std::io::Seek>(cfb: &mut CompoundFile<F>, path: &str) -> Option<Vec<u8>> {
+    let mut stream = cfb.open_stream(path).ok()?;
+    let mut bytes = Vec::new();
+    stream.read_to_end(&mut bytes).ok()?;
+    Some(bytes)
+}
+
+/// Read the `__substg1.0_<id><type>` stream of a property
+fn read_msg_stream<F: Read + std::io::Seek>(
+    cfb: &mut CompoundFile<F>,
+    storage: &str,
+    property: u16,
+    property_type: u16,
+) -> Option<Vec<u8>> {
+    read_stream(
+        cfb,
+        &format!("{}__substg1.0_{:04X}{:04X}", storage, property, property_type),
+    )
+}
+
+/// Read a string property (Unicode, falling back to 8-bit)
+fn read_msg_string<F: Read + std::io::Seek>(
+    cfb: &mut CompoundFile<F>,
+    storage: &str,
+    property: u16,
+) -> Option<String> {
+    let text = if let Some(bytes) = read_msg_stream(cfb, storage, property, PT_UNICODE) {
+        let (text, _) = encoding_rs::UTF_16LE.decode_without_bom_handling(&bytes);
+        text.into_owned()
+    } else {
+        let bytes = read_msg_stream(cfb, storage, property, PT_STRING8)?;
+        match String::from_utf8(bytes) {
+            Ok(text) => text,
+            Err(e) => decode_charset(e.as_bytes(), Some("windows-1252")),
+        }
+    };
+
+    let text = text.trim_end_matches('\0').trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Find a PT_SYSTIME property in a property stream and format it (RFC 2822)
+///
+/// Entries are 16 bytes: tag (type, id), flags, 8-byte value (a FILETIME).
+fn read_systime(props: &[u8], header_len: usize, property: u16) -> Option<String> {
+    props.get(header_len..)?.chunks_exact(16).find_map(|entry| {
+        let property_type = u16::from_le_bytes([entry[0], entry[1]]);
+        let id = u16::from_le_bytes([entry[2], entry[3]]);
+        if property_type != PT_SYSTIME || id != property {
+            return None;
+        }
+        let filetime = u64::from_le_bytes(entry[8..16].try_into().ok()?);
+        // FILETIME: 100ns ticks since 1601-01-01
+        let seconds = (filetime / 10_000_000) as i64 - 11_644_473_600;
+        DateTime::from_timestamp(seconds, 0).map(|dt| dt.to_rfc2822())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGE: &[u8] = b"From: =?UTF-8?B?2LLZitmG2Kg=?= <zainab@example.com>\r\n\
+To: team@example.com\r\n\
+Subject: =?UTF-8?Q?Site_report?= =?UTF-8?Q?_=E2=80=93_March?=\r\n\
+Date: Tue, 4 Mar 2025 10:00:00 +0300\r\n\
+Content-Type: multipart/mixed; boundary=\"outer\"\r\n\
+\r\n\
+--outer\r\n\
+Content-Type: multipart/alternative; boundary=\"inner\"\r\n\
+\r\n\
+--inner\r\n\
+Content-Type: text/plain; charset=utf-8\r\n\
+\r\n\
+Please find the report attached.\r\n\
+--inner\r\n\
+Content-Type: text/html; charset=utf-8\r\n\
+\r\n\
+<p>Please find the <b>report</b> attached.</p>\r\n\
+--inner--\r\n\
+--outer\r\n\
+Content-Type: text/plain; name=\"notes.txt\"\r\n\
+Content-Disposition: attachment; filename=\"notes.txt\"\r\n\
+Content-Transfer-Encoding: base64\r\n\
+\r\n\
+Y29uY3JldGUgY3VyaW5n\r\n\
+--outer--\r\n";
+
+    #[test]
+    fn test_parse_eml() {
+        let message = parse_eml(MESSAGE);
+        assert_eq!(message.from.as_deref(), Some("زينب <zainab@example.com>"));
+        assert_eq!(message.subject.as_deref(), Some("Site report – March"));
+        assert_eq!(message.body.trim(), "Please find the report attached.");
+        assert!(message.html_body.is_some());
+        assert_eq!(message.attachments.len(), 1);
+        assert_eq!(message.attachments[0].name, "notes.txt");
+        assert_eq!(message.attachments[0].data, b"concrete curing");
+
+        let text = message_text(&message);
+        assert!(text.contains("Subject: Site report – March"));
+        assert!(text.contains("Attachments: notes.txt"));
+
+        let doc = message_document(&message);
+        assert_eq!(doc.metadata.author.as_deref(), Some("زينب <zainab@example.com>"));
+        assert_eq!(doc.metadata.recipients.as_deref(), Some("team@example.com"));
+    }
+
+    #[test]
+    fn test_split_mbox() {
+        let mbox = b"From alice@example.com Mon Jan  1 00:00:00 2024\n\
+Subject: First\n\
+\n\
+Body one\n\
+From the middle of a paragraph is not a separator.\n\
+\n\
+From bob@example.com Tue Jan  2 00:00:00 2024\n\
+Subject: Second\n\
+\n\
+Body two\n";
+
+        let messages = split_mbox(mbox);
+        assert_eq!(messages.len(), 2);
+        assert!(String::from_utf8_lossy(messages[0]).contains("From the middle"));
+        assert_eq!(parse_eml(messages[1]).subject.as_deref(), Some("Second"));
+    }
+
+    #[test]
+    fn test_child_paths() {
+        let path = child_path("/mail/archive.mbox", "msg3::att1::report.docx");
+        assert_eq!(
+            split_child_path(&path),
+            Some(("/mail/archive.mbox", "msg3::att1::report.docx"))
+        );
+        assert_eq!(container_path(&path), "/mail/archive.mbox");
+        // `::` that does not follow an email file is part of the path
+        assert_eq!(split_child_path("/data/a::b/report.docx"), None);
+        assert_eq!(container_path("/data/report.docx"), "/data/report.docx");
+    }
+
+    #[test]
+    fn test_attachment_temp_file() {
+        let attachment = EmailAttachment {
+            name: "notes.txt".to_string(),
+            data: b"site visit notes".to_vec(),
+        };
+        let mut temp_path = PathBuf::new();
+        let text = with_temp_file(&attachment, |path, ext| {
+            temp_path = path.to_path_buf();
+            assert_eq!(ext, "txt");
+            fs::read_to_string(path).ok()
+        });
+        assert_eq!(text.as_deref(), Some("site visit notes"));
+        assert!(!temp_path.exists());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let dir = fs::metadata(temp_path.parent().unwrap()).unwrap();
+            assert_eq!(dir.permissions().mode() & 0o777, 0o700);
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+use std::fs;
+use std::io::{BufReader, Read, Seek};
+use std::path::Path;
+use zip::ZipArchive;
+
+use super::html::{decode_html_bytes, document_to_text, parse_html};
+use super::ooxml::resolve_target;
+use crate::models::{ContentSection, DocumentContent, DocumentMetadata, SectionType};
+
+/// Extract text content from an EPUB file (plain text for indexing)
+pub fn extract_epub(path: &Path) -> Option<String> {
+    document_to_text(&extract_epub_structured(path)?)
+}
+
+/// Extract structured content from an EPUB file (for rich preview)
+///
+/// EPUB files are ZIP archives:
+/// - META-INF/container.xml points to the OPF package document
+/// - The OPF holds Dublin Core metadata, the manifest and the reading order (spine)
+/// - Chapters are XHTML, parsed with the HTML extractor
+///
+/// Each spine item becomes a block led by a level-1 heading.
+pub fn extract_epub_structured(path: &Path) -> Option<DocumentContent> {
+    let file = fs::File::open(path).ok()?;
+    let mut archive = ZipArchive::new(file).ok()?;
+    parse_epub(&mut archive)
+}
+
+/// Parsed OPF package document
+#[derive(Default)]
+struct Package {
+    metadata: DocumentMetadata,
+    /// Manifest: item id -> href (resolved against the OPF path)
+    manifest: Vec<(String, String)>,
+    /// Reading order (manifest ids)
+    spine: Vec<String>,
+}
+
+fn parse_epub<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Option<DocumentContent> {
+    let opf_path = read_rootfile_path(archive)?;
+    let package = parse_opf(archive, &opf_path)?;
+
+    let mut sections = Vec::new();
+
+    for (chapter_num, idref) in package.spine.iter().enumerate() {
+        let Some((_, href)) = package.manifest.iter().find(|(id, _)| id == idref) else {
+            continue;
+        };
+
+        let mut bytes = Vec::new();
+        match archive.by_name(href) {
+            Ok(mut chapter) => {
+                if chapter.read_to_end(&mut bytes).is_err() {
+                    continue;
+                }
+            }
+            Err(_) => continue,
+        }
+
+        let chapter = parse_html(&decode_html_bytes(&bytes));
+        if chapter.sections.is_empty() {
+            continue;
+        }
+
+        let mut chapter_sections = chapter.sections;
+        let leads_with_heading = matches!(
+            chapter_sections.first().map(|s| &s.section_type),
+            Some(SectionType::Heading { .. })
+        );
+
+        if leads_with_heading {
+            chapter_sections[0].section_type = SectionType::Heading { level: 1 };
+        } else {
+            let title = chapter
+                .metadata
+                .title
+                .unwrap_or_else(|| format!("Chapter {}", chapter_num + 1));
+            sections.push(ContentSection {
+                section_type: SectionType::Heading { level: 1 },
+                content: Some(title),
+                runs: None,
+                children: None,
+                properties: None,
+            });
+        }
+
+        sections.extend(chapter_sections);
+    }
+
+    if sections.is_empty() {
+        None
+    } else {
+        Some(DocumentContent {
+            doc_type: "epub".to_string(),
+            sections,
+            metadata: package.metadata,
+        })
+    }
+}
+
+/// Read the OPF path from META-INF/container.xml
+fn read_rootfile_path<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Option<String> {
+    let container = archive.by_name("META-INF/container.xml").ok()?;
+    let mut reader = Reader::from_reader(BufReader::new(container));
+    reader.config_mut().trim_text(true);
+
+    let mut buf = Vec::with_capacity(256);
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e))
+                if e.local_name().as_ref() == b"rootfile" =>
+            {
+                for attr in e.attributes().filter_map(|a| a.ok()) {
+                    if attr.key.local_name().as_ref() == b"full-path" {
+                        return Some(String::from_utf8_lossy(&attr.value).to_string());
+                    }
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(_) => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    None
+}
+
+/// Parse the OPF: Dublin Core metadata, manifest and spine
+fn parse_opf<R: Read + Seek>(archive: &mut ZipArchive<R>, opf_path: &str) -> Option<Package> {
+    let opf = archive.by_name(opf_path).ok()?;
+    let mut reader = Reader::from_reader(BufReader::new(opf));
+    reader.config_mut().trim_text(true);
+
+    let mut buf = Vec::with_capacity(512);
+    let mut package = Package::default();
+    let mut current_element = String::new();
+    let mut in_metadata = false;
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => {
+                let local_name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                match local_name.as_str() {
+                    "metadata" => in_metadata = true,
+                    "item" => {
+                        let mut id = String::new();
+                        let mut href = String::new();
+                        for attr in e.attributes().filter_map(|a| a.ok()) {
+                            match attr.key.local_name().as_ref() {
+                                b"id" => id = String::from_utf8_lossy(&attr.value).to_string(),
+                                b"href" => href = String::from_utf8_lossy(&attr.value).to_string(),
+                                _ => {}
+                            }
+                        }
+                        if !id.is_empty() && !href.is_empty() {
+                            let href = percent_decode(&href);
+                            package.manifest.push((id, resolve_target(opf_path, &href)));
+                        }
+                    }
+                    "itemref" => {
+                        let mut idref = String::new();
+                        let mut linear = true;
+                        for attr in e.attributes().filter_map(|a| a.ok()) {
+                            match attr.key.local_name().as_ref() {
+                                b"idref" => {
+                                    idref = String::from_utf8_lossy(&attr.value).to_string()
+                                }
+                                b"linear" => linear = attr.value.as_ref() != b"no",
+                                _ => {}
+                            }
+                        }
+                        // Non-linear items (covers, pop-up notes) are outside the reading order
+                        if linear && !idref.is_empty() {
+                            package.spine.push(idref);
+                        }
+                    }
+                    _ => {}
+                }
+                current_element = local_name;
+            }
+            Ok(Event::End(e)) => {
+                if e.local_name().as_ref() == b"metadata" {
+                    in_metadata = false;
+                }
+                current_element.clear();
+            }
+            Ok(Event::Text(e)) if in_metadata => {
+                if let Ok(text) = e.unescape() {
+                    let text = text.trim().to_string();
+                    if text.is_empty() {
+                        buf.clear();
+                        continue;
+                    }
+                    let metadata = &mut package.metadata;
+                    // First value wins (titles/creators may repeat for subtitles, editors)
+                    match current_element.as_str() {
+                        "title" if metadata.title.is_none() => metadata.title = Some(text),
+                        "creator" if metadata.author.is_none() => metadata.author = Some(text),
+                        "language" if metadata.language.is_none() => {
+                            metadata.language = Some(text)
+                        }
+                        "date" if metadata.created.is_none() => metadata.created = Some(text),
+                        _ => {}
+                    }
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(_) => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Some(package)
+}
+
+/// Decode `%XX` escapes in manifest hrefs (e.g. `chapter%201.xhtml`)
+fn percent_decode(href: &str) -> String {
+    if !href.contains('%') {
+        return href.to_string();
+    }
+
+    let bytes = href.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::write::FileOptions;
+    use zip::ZipWriter;
+
+    #[test]
+    fn test_extract_nonexistent_file() {
+        let result = extract_epub(Path::new("/nonexistent/file.epub"));
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_spine_order_and_metadata() {
+        let files = [
+            (
+                "META-INF/container.xml",
+                r#"<container><rootfiles><rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/></rootfiles></container>"#,
+            ),
+            (
+                "OEBPS/content.opf",
+                r#"<package xmlns:dc="http://purl.org/dc/elements/1.1/">
+                    <metadata>
+                        <dc:title>Kitab al-Himma</dc:title>
+                        <dc:creator>Sayyidna Hatim</dc:creator>
+                        <dc:language>ar</dc:language>
+                    </metadata>
+                    <manifest>
+                        <item id="cover" href="cover.xhtml" media-type="application/xhtml+xml"/>
+                        <item id="c1" href="text/chapter%201.xhtml" media-type="application/xhtml+xml"/>
+                        <item id="c2" href="text/ch2.xhtml" media-type="application/xhtml+xml"/>
+                    </manifest>
+                    <spine>
+                        <itemref idref="cover" linear="no"/>
+                        <itemref idref="c2"/>
+                        <itemref idref="c1"/>
+                    </spine>
+                </package>"#,
+            ),
+            ("OEBPS/cover.xhtml", "<html><body><p>Cover</p></body></html>"),
+            (
+                "OEBPS/text/chapter 1.xhtml",
+                "<html><head><title>Preface</title></head><body><p>First words</p></body></html>",
+            ),
+            (
+                "OEBPS/text/ch2.xhtml",
+                "<html><body><h2>The Journey</h2><p>Second chapter</p></body></html>",
+            ),
+        ];
+
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in files {
+            writer.start_file(name, FileOptions::default()).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        let mut archive = ZipArchive::new(writer.finish().unwrap()).unwrap();
+
+        let doc = parse_epub(&mut archive).unwrap();
+        assert_eq!(doc.metadata.title.as_deref(), Some("Kitab al-Himma"));
+        assert_eq!(doc.metadata.author.as_deref(), Some("Sayyidna Hatim"));
+        assert_eq!(doc.metadata.language.as_deref(), Some("ar"));
+
+        // Spine order (c2 before c1), cover skipped, every chapter heading-led
+        let headings: Vec<_> = doc
+            .sections
+            .iter()
+            .filter(|s| s.section_type == SectionType::Heading { level: 1 })
+            .collect();
+        assert_eq!(headings.len(), 2);
+
+        let text = document_to_text(&doc).unwrap();
+        assert!(text.find("The Journey").unwrap() < text.find("Preface").unwrap());
+        assert!(!text.contains("Cover"));
+    }
+}
@@ -0,0 +1,901 @@
+//! HTML and MHTML extraction
+//!
+//! A small forgiving tokenizer (no DOM) that:
+//! - Drops `<script>`, `<style>`, comments and other markup
+//! - Decodes character references (`&amp;`, `&#1575;`, `&#x627;`)
+//! - Honors a BOM, `<meta charset>` or `http-equiv` charset, defaulting to UTF-8
+//!
+//! Block elements map onto `ContentSection` types for the rich preview:
+//! `h1`-`h6` -> Heading, `p`/`div` -> Paragraph, `li` -> ListItem,
+//! `table` -> Table, `pre` -> CodeBlock, a block made of a single `a` -> Link.
+//!
+//! MHTML (`.mht`/`.mhtml`) is a MIME archive; the first `text/html` part is
+//! decoded (quoted-printable/base64 + part charset) and parsed the same way.
+
+use encoding_rs::Encoding;
+use std::fs;
+use std::path::Path;
+
+use super::mime::{decode_charset, MimePart};
+use crate::models::{
+    ContentSection, DocumentContent, DocumentMetadata, SectionType, TextRun, TextStyle,
+};
+
+/// Bytes scanned for a `<meta>` charset declaration
+const CHARSET_SNIFF_BYTES: usize = 4096;
+
+/// Elements whose content is never shown
+const SKIPPED_RAW_ELEMENTS: &[&str] = &["script", "style", "noscript", "template"];
+
+/// Elements whose content is raw text (no nested markup)
+const TEXT_RAW_ELEMENTS: &[&str] = &["title", "textarea"];
+
+/// Elements that end the current block
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p", "div", "section", "article", "header", "footer", "main", "nav", "aside", "blockquote",
+    "address", "figure", "figcaption", "dd", "dt", "dl", "form", "center", "fieldset", "body",
+];
+
+/// Extract plain text from an HTML file (for indexing)
+pub fn extract_html(path: &Path) -> Option<String> {
+    let bytes = fs::read(path).ok()?;
+    let html = decode_html_bytes(&bytes);
+    document_to_text(&parse_html(&html))
+}
+
+/// Extract structured content from an HTML file (for rich preview)
+pub fn extract_html_structured(path: &Path) -> Option<DocumentContent> {
+    let bytes = fs::read(path).ok()?;
+    let html = decode_html_bytes(&bytes);
+    non_empty(parse_html(&html))
+}
+
+/// Extract plain text from an MHTML archive (for indexing)
+pub fn extract_mhtml(path: &Path) -> Option<String> {
+    let html = read_mhtml(path)?;
+    document_to_text(&parse_html(&html))
+}
+
+/// Extract structured content from an MHTML archive (for rich preview)
+pub fn extract_mhtml_structured(path: &Path) -> Option<DocumentContent> {
+    let html = read_mhtml(path)?;
+    non_empty(parse_html(&html))
+}
+
+/// Decode the main HTML part of an MHTML archive
+fn read_mhtml(path: &Path) -> Option<String> {
+    let bytes = fs::read(path).ok()?;
+    let root = MimePart::parse(&bytes);
+    let parts = root.leaf_parts();
+
+    let part = parts
+        .iter()
+        .find(|p| p.content_type() == "text/html")
+        .or_else(|| parts.iter().find(|p| p.content_type().starts_with("text/")))?;
+
+    let body = part.decoded_body();
+    let charset = part.header_param("content-type", "charset");
+    if charset.is_some() {
+        Some(decode_charset(&body, charset.as_deref()))
+    } else {
+        Some(decode_html_bytes(&body))
+    }
+}
+
+fn non_empty(doc: DocumentContent) -> Option<DocumentContent> {
+    if doc.sections.is_empty() {
+        None
+    } else {
+        Some(doc)
+    }
+}
+
+/// Flatten parsed sections to text: title first, one block per line
+pub(super) fn document_to_text(doc: &DocumentContent) -> Option<String> {
+    let mut text = String::with_capacity(8192);
+    if let Some(ref title) = doc.metadata.title {
+        text.push_str(title);
+        text.push('\n');
+    }
+    for section in &doc.sections {
+        append_section_text(section, &mut text);
+    }
+
+    let text = text.trim();
+    if text.is_empty() {
+        None
+    } else {
+        Some(text.to_string())
+    }
+}
+
+fn append_section_text(section: &ContentSection, out: &mut String) {
+    if let Some(ref runs) = section.runs {
+        for run in runs {
+            out.push_str(&run.text);
+        }
+        out.push('\n');
+    } else if let Some(ref content) = section.content {
+        out.push_str(content);
+        out.push('\n');
+    }
+    if let Some(ref children) = section.children {
+        for child in children {
+            append_section_text(child, out);
+        }
+    }
+}
+
+/// Decode raw HTML bytes: BOM, then `<meta>` charset, then UTF-8
+pub(super) fn decode_html_bytes(bytes: &[u8]) -> String {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        let (text, _, _) = encoding.decode(&bytes[bom_len..]);
+        return text.into_owned();
+    }
+
+    let charset = sniff_meta_charset(&bytes[..bytes.len().min(CHARSET_SNIFF_BYTES)]);
+
+    match charset.as_deref().and_then(|c| Encoding::for_label(c.as_bytes())) {
+        Some(encoding) => {
+            let (text, _, _) = encoding.decode(bytes);
+            text.into_owned()
+        }
+        None => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+/// Find `<meta charset="...">` or `<meta http-equiv content="...; charset=...">`
+fn sniff_meta_charset(head: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(head).to_ascii_lowercase();
+
+    for tag in text.split('<').filter(|t| t.starts_with("meta")) {
+        let tag = tag.split('>').next().unwrap_or(tag);
+        for (name, value) in parse_attributes(tag.trim_start_matches("meta")) {
+            if name == "charset" {
+                return Some(value);
+            }
+            if name == "content" {
+                if let Some(idx) = value.find("charset=") {
+                    let charset = value[idx + 8..]
+                        .split(|c: char| c == ';' || c.is_whitespace())
+                        .next()
+                        .unwrap_or("");
+                    if !charset.is_empty() {
+                        return Some(charset.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+// ============================================================================
+// Tokenizer
+// ============================================================================
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Start {
+        name: String,
+        attrs: Vec<(String, String)>,
+    },
+    End(String),
+    Text(String),
+}
+
+/// Split HTML into start tags, end tags and text (entities decoded)
+fn tokenize(html: &str) -> Vec<Token> {
+    let bytes = html.as_bytes();
+    // ASCII lowercasing keeps byte offsets identical
+    let lower = html.to_ascii_lowercase();
+    let mut tokens = Vec::new();
+    let mut text_start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] != b'<' {
+            i += 1;
+            continue;
+        }
+
+        let rest = &html[i..];
+
+        // Comments, doctype, processing instructions, CDATA
+        if rest.starts_with("<!--") {
+            push_text(&mut tokens, &html[text_start..i]);
+            i = lower[i + 4..].find("-->").map(|p| i + 4 + p + 3).unwrap_or(bytes.len());
+            text_start = i;
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            push_text(&mut tokens, &html[text_start..i]);
+            i = html[i..].find('>').map(|p| i + p + 1).unwrap_or(bytes.len());
+            text_start = i;
+            continue;
+        }
+
+        let is_end = bytes.get(i + 1) == Some(&b'/');
+        let name_start = i + 1 + usize::from(is_end);
+        if !bytes.get(name_start).is_some_and(|b| b.is_ascii_alphabetic()) {
+            // A literal '<' in text
+            i += 1;
+            continue;
+        }
+
+        push_text(&mut tokens, &html[text_start..i]);
+
+        let tag_end = find_tag_end(bytes, name_start);
+        let inner = &lower[name_start..tag_end];
+        let name_len = inner
+            .find(|c: char| c.is_ascii_whitespace() || c == '/' || c == '>')
+            .unwrap_or(inner.len());
+        let name = inner[..name_len].to_string();
+        i = (tag_end + 1).min(bytes.len());
+
+        if is_end {
+            tokens.push(Token::End(name));
+        } else {
+            let attrs = parse_attributes(&html[name_start + name_len..tag_end]);
+            let skipped = SKIPPED_RAW_ELEMENTS.contains(&name.as_str());
+            let raw_text = TEXT_RAW_ELEMENTS.contains(&name.as_str());
+            tokens.push(Token::Start {
+                name: name.clone(),
+                attrs,
+            });
+
+            if skipped || raw_text {
+                // Raw content runs to the matching end tag
+                let close = format!("</{}", name);
+                let content_end = lower[i..].find(&close).map(|p| i + p).unwrap_or(bytes.len());
+                if raw_text {
+                    push_text(&mut tokens, &html[i..content_end]);
+                }
+                tokens.push(Token::End(name));
+                i = html[content_end..]
+                    .find('>')
+                    .map(|p| content_end + p + 1)
+                    .unwrap_or(bytes.len());
+            }
+        }
+
+        text_start = i;
+    }
+
+    push_text(&mut tokens, &html[text_start..]);
+    tokens
+}
+
+fn push_text(tokens: &mut Vec<Token>, raw: &str) {
+    if !raw.is_empty() {
+        tokens.push(Token::Text(decode_entities(raw)));
+    }
+}
+
+/// Index of the closing '>' of a tag (quotes respected), or the end of input
+fn find_tag_end(bytes: &[u8], from: usize) -> usize {
+    let mut quote: Option<u8> = None;
+    for (offset, &b) in bytes[from..].iter().enumerate() {
+        match quote {
+            Some(q) if b == q => quote = None,
+            Some(_) => {}
+            None if b == b'"' || b == b'\'' => quote = Some(b),
+            None if b == b'>' => return from + offset,
+            None => {}
+        }
+    }
+    bytes.len()
+}
+
+/// Parse `name="value" name='value' name=value name` (names lowercased)
+fn parse_attributes(s: &str) -> Vec<(String, String)> {
+    let mut attrs = Vec::new();
+    let chars: Vec<char> = s.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        while i < chars.len() && (chars[i].is_whitespace() || chars[i] == '/') {
+            i += 1;
+        }
+        let name_start = i;
+        while i < chars.len() && !chars[i].is_whitespace() && !matches!(chars[i], '=' | '/' | '>') {
+            i += 1;
+        }
+        if i == name_start {
+            i += 1;
+            continue;
+        }
+        let name: String = chars[name_start..i].iter().collect::<String>().to_ascii_lowercase();
+
+        while i < chars.len() && chars[i].is_whitespace() {
+            i += 1;
+        }
+        let mut value = String::new();
+        if i < chars.len() && chars[i] == '=' {
+            i += 1;
+            while i < chars.len() && chars[i].is_whitespace() {
+                i += 1;
+            }
+            if i < chars.len() && (chars[i] == '"' || chars[i] == '\'') {
+                let quote = chars[i];
+                i += 1;
+                let value_start = i;
+                while i < chars.len() && chars[i] != quote {
+                    i += 1;
+                }
+                value = chars[value_start..i].iter().collect();
+                i += 1;
+            } else {
+                let value_start = i;
+                while i < chars.len() && !chars[i].is_whitespace() && chars[i] != '>' {
+                    i += 1;
+                }
+                value = chars[value_start..i].iter().collect();
+            }
+        }
+
+        attrs.push((name, decode_entities(&value)));
+    }
+
+    attrs
+}
+
+/// Decode numeric and common named character references
+fn decode_entities(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|&end| end > 0 && end <= 10)
+            .and_then(|end| decode_entity(&rest[1..=end]).map(|c| (c, end + 2)));
+
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+fn decode_entity(name: &str) -> Option<char> {
+    if let Some(num) = name.strip_prefix('#') {
+        let code = match num.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => num.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+
+    let c = match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{00A0}',
+        "shy" => '\u{00AD}',
+        "zwnj" => '\u{200C}',
+        "zwj" => '\u{200D}',
+        "lrm" => '\u{200E}',
+        "rlm" => '\u{200F}',
+        "ndash" => '–',
+        "mdash" => '—',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "laquo" => '«',
+        "raquo" => '»',
+        "hellip" => '…',
+        "bull" => '•',
+        "middot" => '·',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "deg" => '°',
+        "times" => '×',
+        "divide" => '÷',
+        "euro" => '€',
+        "pound" => '£',
+        "yen" => '¥',
+        "cent" => '¢',
+        "sect" => '§',
+        "para" => '¶',
+        _ => return None,
+    };
+    Some(c)
+}
+
+// ============================================================================
+// Section builder
+// ============================================================================
+
+/// Parse HTML into structured content
+pub(super) fn parse_html(html: &str) -> DocumentContent {
+    let mut builder = SectionBuilder::default();
+    for token in tokenize(html) {
+        builder.handle(token);
+    }
+    builder.finish()
+}
+
+#[derive(Default)]
+struct TableState {
+    rows: Vec<ContentSection>,
+    cells: Vec<ContentSection>,
+    cell_runs: Vec<TextRun>,
+    in_cell: bool,
+    header_cell: bool,
+}
+
+#[derive(Default)]
+struct SectionBuilder {
+    sections: Vec<ContentSection>,
+    metadata: DocumentMetadata,
+
+    // Current block
+    block_type: Option<SectionType>,
+    runs: Vec<TextRun>,
+    last_was_space: bool,
+    /// URL of a link that has covered all text of the block so far
+    block_link: Option<String>,
+
+    // Inline formatting depth counters
+    bold: u32,
+    italic: u32,
+    underline: u32,
+    strike: u32,
+    superscript: u32,
+    subscript: u32,
+    link: Option<String>,
+
+    /// Ordered flag per open list
+    lists: Vec<bool>,
+    pre_depth: u32,
+    in_title: bool,
+    in_head: bool,
+    table_depth: u32,
+    table: TableState,
+}
+
+impl SectionBuilder {
+    fn handle(&mut self, token: Token) {
+        match token {
+            Token::Start { name, attrs } => self.start(&name, &attrs),
+            Token::End(name) => self.end(&name),
+            Token::Text(text) => self.text(&text),
+        }
+    }
+
+    fn start(&mut self, name: &str, attrs: &[(String, String)]) {
+        let attr = |key: &str| {
+            attrs
+                .iter()
+                .find(|(n, _)| n == key)
+                .map(|(_, v)| v.clone())
+        };
+
+        match name {
+            "head" => self.in_head = true,
+            "body" => self.in_head = false,
+            "title" => self.in_title = true,
+            "meta" if attr("name").is_some_and(|n| n.eq_ignore_ascii_case("author")) => {
+                self.metadata.author = attr("content").filter(|c| !c.trim().is_empty());
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.finish_block();
+                let level = name[1..].parse().unwrap_or(1);
+                self.block_type = Some(SectionType::Heading { level });
+            }
+            "li" => {
+                self.finish_block();
+                self.block_type = Some(SectionType::ListItem {
+                    ordered: self.lists.last().copied().unwrap_or(false),
+                    depth: self.lists.len().saturating_sub(1) as u8,
+                });
+            }
+            "ul" | "ol" => {
+                self.finish_block();
+                self.lists.push(name == "ol");
+            }
+            "pre" => {
+                self.finish_block();
+                self.pre_depth += 1;
+                self.block_type = Some(SectionType::CodeBlock);
+            }
+            "hr" => {
+                self.finish_block();
+                if self.table_depth == 0 {
+                    self.sections.push(section(SectionType::HorizontalRule));
+                }
+            }
+            "br" => self.push_str("\n", true),
+            "table" => {
+                self.finish_block();
+                if self.table_depth == 0 {
+                    self.table = TableState::default();
+                }
+                self.table_depth += 1;
+            }
+            "tr" if self.table_depth == 1 => {
+                self.finish_block();
+                self.close_row();
+            }
+            "td" | "th" if self.table_depth == 1 => {
+                self.finish_block();
+                self.close_cell();
+                self.table.in_cell = true;
+                self.table.cell_runs.clear();
+                if name == "th" {
+                    self.table.header_cell = true;
+                    self.bold += 1;
+                }
+            }
+            "a" => {
+                if let Some(href) = attr("href").filter(|h| !h.starts_with('#')) {
+                    if self.block_is_empty() {
+                        self.block_link = Some(href.clone());
+                    }
+                    self.link = Some(href);
+                }
+            }
+            "b" | "strong" => self.bold += 1,
+            "i" | "em" | "cite" | "dfn" => self.italic += 1,
+            "u" | "ins" => self.underline += 1,
+            "s" | "strike" | "del" => self.strike += 1,
+            "sup" => self.superscript += 1,
+            "sub" => self.subscript += 1,
+            _ if BLOCK_ELEMENTS.contains(&name) => self.break_block(),
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, name: &str) {
+        match name {
+            "head" => self.in_head = false,
+            "title" => self.in_title = false,
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "li" => self.finish_block(),
+            "ul" | "ol" => {
+                self.finish_block();
+                self.lists.pop();
+            }
+            "pre" => {
+                self.finish_block();
+                self.pre_depth = self.pre_depth.saturating_sub(1);
+            }
+            "table" if self.table_depth > 0 => {
+                // Close the last cell (its end tag is optional) while its
+                // text still counts as table text
+                if self.table_depth == 1 {
+                    self.finish_block();
+                    self.close_row();
+                    let rows = std::mem::take(&mut self.table.rows);
+                    if !rows.is_empty() {
+                        let mut table = section(SectionType::Table);
+                        table.children = Some(rows);
+                        self.sections.push(table);
+                    }
+                }
+                self.table_depth -= 1;
+            }
+            "tr" if self.table_depth == 1 => {
+                self.finish_block();
+                self.close_row();
+            }
+            "td" | "th" if self.table_depth == 1 => {
+                self.finish_block();
+                self.close_cell();
+            }
+            "a" => self.link = None,
+            "b" | "strong" => self.bold = self.bold.saturating_sub(1),
+            "i" | "em" | "cite" | "dfn" => self.italic = self.italic.saturating_sub(1),
+            "u" | "ins" => self.underline = self.underline.saturating_sub(1),
+            "s" | "strike" | "del" => self.strike = self.strike.saturating_sub(1),
+            "sup" => self.superscript = self.superscript.saturating_sub(1),
+            "sub" => self.subscript = self.subscript.saturating_sub(1),
+            _ if BLOCK_ELEMENTS.contains(&name) => self.finish_block(),
+            _ => {}
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.in_title {
+            let title = collapse_whitespace(text);
+            if !title.is_empty() {
+                self.metadata.title = Some(title);
+            }
+            return;
+        }
+        if self.in_head {
+            return;
+        }
+        self.push_str(text, self.pre_depth > 0);
+    }
+
+    /// Append text to the current block, collapsing whitespace outside `<pre>`
+    fn push_str(&mut self, text: &str, preserve: bool) {
+        let mut collapsed = String::with_capacity(text.len());
+        for c in text.chars() {
+            if preserve {
+                collapsed.push(c);
+                self.last_was_space = c.is_whitespace();
+            } else if c.is_whitespace() || c == '\u{00A0}' {
+                if !self.last_was_space {
+                    collapsed.push(' ');
+                }
+                self.last_was_space = true;
+            } else {
+                collapsed.push(c);
+                self.last_was_space = false;
+            }
+        }
+
+        if collapsed.is_empty() {
+            return;
+        }
+        if self.link.is_none() && !collapsed.trim().is_empty() {
+            // Text outside the link - the block is not a bare link
+            self.block_link = None;
+        }
+
+        let style = self.current_style();
+        match self.runs.last_mut() {
+            Some(last) if same_style(&last.style, &style) => last.text.push_str(&collapsed),
+            _ => self.runs.push(TextRun {
+                text: collapsed,
+                style,
+            }),
+        }
+    }
+
+    fn current_style(&self) -> TextStyle {
+        TextStyle {
+            bold: self.bold > 0,
+            italic: self.italic > 0,
+            underline: self.underline > 0 || self.link.is_some(),
+            strikethrough: self.strike > 0,
+            superscript: self.superscript > 0,
+            subscript: self.subscript > 0,
+            ..Default::default()
+        }
+    }
+
+    fn block_is_empty(&self) -> bool {
+        self.runs.iter().all(|r| r.text.trim().is_empty())
+    }
+
+    /// Start a new generic block; an empty `<li>`/heading keeps its type
+    /// so `<li><p>text</p></li>` stays a list item
+    fn break_block(&mut self) {
+        if self.block_is_empty() {
+            self.runs.clear();
+        } else {
+            self.finish_block();
+        }
+    }
+
+    /// Close the current block into a section (or the open table cell)
+    fn finish_block(&mut self) {
+        let block_type = self.block_type.take().unwrap_or(SectionType::Paragraph);
+        let block_link = self.block_link.take();
+        let mut runs = std::mem::take(&mut self.runs);
+        self.last_was_space = true;
+
+        if block_type != SectionType::CodeBlock {
+            trim_runs(&mut runs);
+        }
+        if runs.is_empty() {
+            return;
+        }
+
+        if self.table_depth > 0 {
+            // Blocks inside a table become cell text
+            if !self.table.cell_runs.is_empty() {
+                self.table.cell_runs.push(TextRun {
+                    text: "\n".to_string(),
+                    style: TextStyle::default(),
+                });
+            }
+            self.table.cell_runs.extend(runs);
+            return;
+        }
+
+        let section_type = match (block_type, block_link) {
+            (SectionType::Paragraph, Some(url)) => SectionType::Link { url },
+            (block_type, _) => block_type,
+        };
+        let mut block = section(section_type);
+        block.runs = Some(runs);
+        self.sections.push(block);
+    }
+
+    fn close_cell(&mut self) {
+        if !self.table.in_cell {
+            return;
+        }
+        self.table.in_cell = false;
+        if self.table.header_cell {
+            self.table.header_cell = false;
+            self.bold = self.bold.saturating_sub(1);
+        }
+        let runs = std::mem::take(&mut self.table.cell_runs);
+        let mut cell = section(SectionType::TableCell);
+        cell.runs = Some(runs);
+        self.table.cells.push(cell);
+    }
+
+    fn close_row(&mut self) {
+        self.close_cell();
+        let cells = std::mem::take(&mut self.table.cells);
+        if !cells.is_empty() {
+            let mut row = section(SectionType::TableRow);
+            row.children = Some(cells);
+            self.table.rows.push(row);
+        }
+    }
+
+    fn finish(mut self) -> DocumentContent {
+        self.finish_block();
+        if self.table_depth > 0 {
+            self.table_depth = 1;
+            self.end("table");
+        }
+
+        DocumentContent {
+            doc_type: "html".to_string(),
+            sections: self.sections,
+            metadata: self.metadata,
+        }
+    }
+}
+
+fn section(section_type: SectionType) -> ContentSection {
+    ContentSection {
+        section_type,
+        content: None,
+        runs: None,
+        children: None,
+        properties: None,
+    }
+}
+
+fn same_style(a: &TextStyle, b: &TextStyle) -> bool {
+    a.bold == b.bold
+        && a.italic == b.italic
+        && a.underline == b.underline
+        && a.strikethrough == b.strikethrough
+        && a.superscript == b.superscript
+        && a.subscript == b.subscript
+}
+
+/// Trim leading whitespace of the first run and trailing of the last
+fn trim_runs(runs: &mut Vec<TextRun>) {
+    if let Some(first) = runs.first_mut() {
+        first.text = first.text.trim_start().to_string();
+    }
+    if let Some(last) = runs.last_mut() {
+        last.text = last.text.trim_end().to_string();
+    }
+    runs.retain(|r| !r.text.is_empty());
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runs_text(section: &ContentSection) -> String {
+        section
+            .runs
+            .as_ref()
+            .map(|runs| runs.iter().map(|r| r.text.as_str()).collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_tokenize_skips_scripts_and_decodes_entities() {
+        let tokens = tokenize(
+            "<p class=\"x\">Fish &amp; chips &#1575;&#x644;</p><script>var a = '<p>';</script><!-- c -->",
+        );
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Start {
+                    name: "p".to_string(),
+                    attrs: vec![("class".to_string(), "x".to_string())],
+                },
+                Token::Text("Fish & chips ال".to_string()),
+                Token::End("p".to_string()),
+                Token::Start {
+                    name: "script".to_string(),
+                    attrs: vec![],
+                },
+                Token::End("script".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sections_from_html() {
+        let doc = parse_html(
+            "<html><head><title>Annual  Report</title><style>p { color: red }</style></head>
+             <body>
+               <h2>Summary</h2>
+               <p>Revenue <b>grew</b>\n   strongly.</p>
+               <ul><li>First</li><li>Second<ol><li>Nested</li></ol></li></ul>
+               <p><a href=\"https://example.com\">Example site</a></p>
+               <table><tr><th>Region</th><td>Gulf</td></tr></table>
+               <pre>let x  = 1;\nlet y = 2;</pre>
+             </body></html>",
+        );
+
+        assert_eq!(doc.metadata.title.as_deref(), Some("Annual Report"));
+        let s = &doc.sections;
+        assert_eq!(s[0].section_type, SectionType::Heading { level: 2 });
+        assert_eq!(runs_text(&s[1]), "Revenue grew strongly.");
+        assert!(s[1].runs.as_ref().unwrap()[1].style.bold);
+        assert_eq!(s[2].section_type, SectionType::ListItem { ordered: false, depth: 0 });
+        assert_eq!(runs_text(&s[3]), "Second");
+        assert_eq!(s[4].section_type, SectionType::ListItem { ordered: true, depth: 1 });
+        assert_eq!(
+            s[5].section_type,
+            SectionType::Link {
+                url: "https://example.com".to_string()
+            }
+        );
+        assert_eq!(s[6].section_type, SectionType::Table);
+        let row = &s[6].children.as_ref().unwrap()[0];
+        assert_eq!(row.children.as_ref().unwrap().len(), 2);
+        assert_eq!(s[7].section_type, SectionType::CodeBlock);
+        assert_eq!(runs_text(&s[7]), "let x  = 1;\nlet y = 2;");
+
+        let text = document_to_text(&doc).unwrap();
+        assert!(text.starts_with("Annual Report\nSummary\n"));
+        assert!(!text.contains("color"));
+
+        // End tags of cells and rows are optional, also at the end of input
+        for html in ["<table><tr><th>a<td>b</table>", "<table><tr><th>a<td>b"] {
+            let doc = parse_html(html);
+            assert_eq!(doc.sections.len(), 1, "{}", html);
+            let row = &doc.sections[0].children.as_ref().unwrap()[0];
+            let cells = row.children.as_ref().unwrap();
+            assert_eq!(cells.len(), 2, "{}", html);
+            assert_eq!(runs_text(&cells[1]), "b");
+        }
+    }
+
+    #[test]
+    fn test_meta_charset() {
+        // "مرحبا" in windows-1256
+        let mut bytes = b"<html><head><meta charset=\"windows-1256\"></head><body><p>".to_vec();
+        bytes.extend_from_slice(&[0xE3, 0xD1, 0xCD, 0xC8, 0xC7]);
+        bytes.extend_from_slice(b"</p></body></html>");
+        let html = decode_html_bytes(&bytes);
+        assert!(html.contains("مرحبا"));
+
+        let http_equiv =
+            b"<meta http-equiv=\"Content-Type\" content=\"text/html; charset=iso-8859-1\">";
+        assert_eq!(sniff_meta_charset(http_equiv).as_deref(), Some("iso-8859-1"));
+    }
+}
@@ -0,0 +1,1018 @@
+//! Image metadata extraction (JPEG, PNG, TIFF)
+//!
+//! Pure-Rust readers for the three metadata blocks photos and scans carry:
+//! - EXIF: a TIFF structure (JPEG `APP1`, PNG `eXIf`, or the TIFF file itself)
+//! - XMP: RDF/XML (JPEG `APP1`, PNG `iTXt`, TIFF tag 700)
+//! - IPTC-IIM: binary records (JPEG `APP13` Photoshop resource, TIFF tag 33723)
+//!
+//! Descriptive fields only - GPS data is never read. When several blocks set the
+//! same field, XMP wins over IPTC, and IPTC over EXIF.
+
+use base64::Engine;
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use crate::models::{
+    ContentSection, DocumentContent, DocumentMetadata, SectionProperties, SectionType,
+};
+
+/// JPEG metadata precedes the image data; don't read further than this
+const JPEG_HEADER_BYTES: u64 = 1024 * 1024;
+
+/// Images up to this size are embedded whole when there is no EXIF thumbnail
+const INLINE_IMAGE_MAX_BYTES: u64 = 1024 * 1024;
+
+/// Descriptive image metadata, in display order
+#[derive(Debug, Default)]
+struct ImageInfo {
+    title: Option<String>,
+    headline: Option<String>,
+    description: Option<String>,
+    keywords: Vec<String>,
+    author: Option<String>,
+    copyright: Option<String>,
+    date_taken: Option<String>,
+    location: Option<String>,
+    camera: Option<String>,
+    lens: Option<String>,
+    software: Option<String>,
+    width: Option<u32>,
+    height: Option<u32>,
+    /// JPEG thumbnail from EXIF IFD1
+    thumbnail: Option<Vec<u8>>,
+}
+
+impl ImageInfo {
+    /// Labelled text fields (dimensions excluded)
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let keywords = (!self.keywords.is_empty()).then(|| self.keywords.join(", "));
+        [
+            ("Title", self.title.clone()),
+            ("Headline", self.headline.clone()),
+            ("Description", self.description.clone()),
+            ("Keywords", keywords),
+            ("Author", self.author.clone()),
+            ("Copyright", self.copyright.clone()),
+            ("Date taken", self.date_taken.clone()),
+            ("Location", self.location.clone()),
+            ("Camera", self.camera.clone()),
+            ("Lens", self.lens.clone()),
+            ("Software", self.software.clone()),
+        ]
+        .into_iter()
+        .filter_map(|(label, value)| Some((label, value?)))
+        .collect()
+    }
+}
+
+/// Set a field unless a higher-priority block already did
+fn set(field: &mut Option<String>, value: Option<String>) {
+    if field.is_none() {
+        *field = value
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+    }
+}
+
+/// Extract indexable text from image metadata (field values, one per line)
+pub fn extract_image(path: &Path) -> Option<String> {
+    let info = read_image_info(path)?;
+    let text: Vec<String> = info.fields().into_iter().map(|(_, value)| value).collect();
+    if text.is_empty() {
+        None
+    } else {
+        Some(text.join("\n"))
+    }
+}
+
+/// Extract structured content from an image (for rich preview)
+///
+/// A thumbnail (the EXIF one, or the image itself when small) followed by a
+/// metadata table.
+pub fn extract_image_structured(path: &Path) -> Option<DocumentContent> {
+    let info = read_image_info(path)?;
+    let mut sections = Vec::new();
+
+    if let Some((mime, bytes)) = thumbnail_or_image(path, info.thumbnail.clone()) {
+        sections.push(ContentSection {
+            section_type: SectionType::Image,
+            content: None,
+            runs: None,
+            children: None,
+            properties: Some(SectionProperties {
+                image_data: Some(format!(
+                    "data:{};base64,{}",
+                    mime,
+                    base64::engine::general_purpose::STANDARD.encode(bytes)
+                )),
+                ..Default::default()
+            }),
+        });
+    }
+
+    let mut rows = info.fields();
+    if let (Some(width), Some(height)) = (info.width, info.height) {
+        rows.push(("Dimensions", format!("{} × {}", width, height)));
+    }
+    if !rows.is_empty() {
+        sections.push(ContentSection {
+            section_type: SectionType::Table,
+            content: None,
+            runs: None,
+            children: Some(
+                rows.into_iter()
+                    .map(|(label, value)| ContentSection {
+                        section_type: SectionType::TableRow,
+                        content: None,
+                        runs: None,
+                        children: Some(vec![
+                            text_section(SectionType::TableCell, label.to_string()),
+                            text_section(SectionType::TableCell, value),
+                        ]),
+                        properties: None,
+                    })
+                    .collect(),
+            ),
+            properties: None,
+        });
+    }
+
+    Some(DocumentContent {
+        doc_type: "image".to_string(),
+        sections,
+        metadata: DocumentMetadata {
+            title: info.title.clone().or_else(|| info.headline.clone()),
+            author: info.author.clone(),
+            created: info.date_taken.clone(),
+            ..Default::default()
+        },
+    })
+}
+
+/// Thumbnail for an image as `(mime, bytes)` (for result cards)
+pub(super) fn image_thumbnail(path: &Path) -> Option<(&'static str, Vec<u8>)> {
+    thumbnail_or_image(path, read_image_info(path)?.thumbnail)
+}
+
+/// The EXIF thumbnail, or the JPEG/PNG itself when it is small enough to inline
+fn thumbnail_or_image(
+    path: &Path,
+    exif_thumbnail: Option<Vec<u8>>,
+) -> Option<(&'static str, Vec<u8>)> {
+    if let Some(jpeg) = exif_thumbnail {
+        return Some(("image/jpeg", jpeg));
+    }
+    let mime = match path.extension()?.to_str()?.to_lowercase().as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        _ => return None,
+    };
+    if fs::metadata(path).ok()?.len() > INLINE_IMAGE_MAX_BYTES {
+        return None;
+    }
+    Some((mime, fs::read(path).ok()?))
+}
+
+fn text_section(section_type: SectionType, content: String) -> ContentSection {
+    ContentSection {
+        section_type,
+        content: Some(content),
+        runs: None,
+        children: None,
+        properties: None,
+    }
+}
+
+/// Raw metadata blocks found in a file
+#[derive(Default)]
+struct MetadataBlocks {
+    exif: Option<Vec<u8>>,
+    xmp: Option<Vec<u8>>,
+    iptc: Option<Vec<u8>>,
+    /// Format-native text (PNG text chunks, JPEG comments): (keyword, value)
+    native: Vec<(String, String)>,
+    width: Option<u32>,
+    height: Option<u32>,
+}
+
+fn read_image_info(path: &Path) -> Option<ImageInfo> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    let blocks = match ext.as_str() {
+        "jpg" | "jpeg" => {
+            let mut bytes = Vec::new();
+            fs::File::open(path)
+                .ok()?
+                .take(JPEG_HEADER_BYTES)
+                .read_to_end(&mut bytes)
+                .ok()?;
+            read_jpeg(&bytes)?
+        }
+        "png" => read_png(&fs::read(path).ok()?)?,
+        "tif" | "tiff" => read_tiff_file(&fs::read(path).ok()?)?,
+        _ => return None,
+    };
+    Some(build_info(blocks))
+}
+
+/// Merge blocks in priority order: XMP, IPTC, EXIF, native text
+fn build_info(blocks: MetadataBlocks) -> ImageInfo {
+    let mut info = ImageInfo {
+        width: blocks.width,
+        height: blocks.height,
+        ..Default::default()
+    };
+
+    if let Some(xmp) = &blocks.xmp {
+        apply_xmp(&mut info, xmp);
+    }
+    if let Some(iptc) = &blocks.iptc {
+        apply_iptc(&mut info, iptc);
+    }
+    if let Some(exif) = &blocks.exif {
+        apply_exif(&mut info, exif);
+    }
+    for (keyword, value) in blocks.native {
+        match keyword.to_lowercase().as_str() {
+            "title" => set(&mut info.title, Some(value)),
+            "description" | "comment" => set(&mut info.description, Some(value)),
+            "author" => set(&mut info.author, Some(value)),
+            "copyright" => set(&mut info.copyright, Some(value)),
+            "creation time" => set(&mut info.date_taken, Some(value)),
+            "software" => set(&mut info.software, Some(value)),
+            _ => {}
+        }
+    }
+
+    info
+}
+
+// ============================================================================
+// Containers
+// ============================================================================
+
+/// Walk JPEG segments up to the start of scan
+fn read_jpeg(bytes: &[u8]) -> Option<MetadataBlocks> {
+    if !bytes.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+
+    let mut blocks = MetadataBlocks::default();
+    let mut pos = 2;
+
+    while pos + 4 <= bytes.len() {
+        if bytes[pos] != 0xFF {
+            break;
+        }
+        let marker = bytes[pos + 1];
+        // Fill bytes and standalone markers carry no length
+        if marker == 0xFF {
+            pos += 1;
+            continue;
+        }
+        if marker == 0x01 || (0xD0..=0xD7).contains(&marker) {
+            pos += 2;
+            continue;
+        }
+
+        let length = u16::from_be_bytes([bytes[pos + 2], bytes[pos + 3]]) as usize;
+        let Some(data) = length
+            .checked_sub(2)
+            .and_then(|len| bytes.get(pos + 4..pos + 4 + len))
+        else {
+            break;
+        };
+
+        match marker {
+            // Start of scan: image data follows
+            0xDA => break,
+            0xE1 if data.starts_with(b"Exif\0\0") => blocks.exif = Some(data[6..].to_vec()),
+            0xE1 if data.starts_with(b"http://ns.adobe.com/xap/1.0/\0") => {
+                blocks.xmp = Some(data[29..].to_vec())
+            }
+            0xED if data.starts_with(b"Photoshop 3.0\0") => {
+                blocks.iptc = photoshop_iptc(&data[14..]).map(<[u8]>::to_vec)
+            }
+            0xFE => blocks
+                .native
+                .push(("comment".to_string(), decode_text(data))),
+            // SOFn (not DHT/JPG/DAC): precision, height, width
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) && data.len() >= 5 => {
+                blocks.height = Some(u16::from_be_bytes([data[1], data[2]]) as u32);
+                blocks.width = Some(u16::from_be_bytes([data[3], data[4]]) as u32);
+            }
+            _ => {}
+        }
+
+        pos += 2 + length;
+    }
+
+    Some(blocks)
+}
+
+/// IPTC-IIM data from Photoshop image resources (`8BIM` resource 0x0404)
+fn photoshop_iptc(data: &[u8]) -> Option<&[u8]> {
+    let mut pos = 0;
+    while pos + 12 <= data.len() && &data[pos..pos + 4] == b"8BIM" {
+        let id = u16::from_be_bytes([data[pos + 4], data[pos + 5]]);
+        // Pascal name, padded to an even length (length byte included)
+        let name_len = data[pos + 6] as usize;
+        let mut cursor = pos + 6 + name_len + 1;
+        cursor += cursor % 2;
+        let size = u32::from_be_bytes(data.get(cursor..cursor + 4)?.try_into().ok()?) as usize;
+        let start = cursor + 4;
+        let block = data.get(start..start + size)?;
+        if id == 0x0404 {
+            return Some(block);
+        }
+        pos = start + size + size % 2;
+    }
+    None
+}
+
+/// Walk PNG chunks: IHDR, eXIf, tEXt and uncompressed iTXt
+fn read_png(bytes: &[u8]) -> Option<MetadataBlocks> {
+    if !bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return None;
+    }
+
+    let mut blocks = MetadataBlocks::default();
+    let mut pos = 8;
+
+    while pos + 8 <= bytes.len() {
+        let length = u32::from_be_bytes(bytes[pos..pos + 4].try_into().ok()?) as usize;
+        let chunk_type = &bytes[pos + 4..pos + 8];
+        let Some(data) = bytes.get(pos + 8..pos + 8 + length) else {
+            break;
+        };
+
+        match chunk_type {
+            b"IHDR" if data.len() >= 8 => {
+                blocks.width = Some(u32::from_be_bytes(data[0..4].try_into().ok()?));
+                blocks.height = Some(u32::from_be_bytes(data[4..8].try_into().ok()?));
+            }
+            b"eXIf" => blocks.exif = Some(data.to_vec()),
+            b"tEXt" => {
+                if let Some(split) = data.iter().position(|&b| b == 0) {
+                    let keyword = String::from_utf8_lossy(&data[..split]).to_string();
+                    let (text, _, _) = encoding_rs::WINDOWS_1252.decode(&data[split + 1..]);
+                    blocks.native.push((keyword, text.into_owned()));
+                }
+            }
+            b"iTXt" => {
+                if let Some((keyword, text)) = parse_itxt(data) {
+                    if keyword == "XML:com.adobe.xmp" {
+                        blocks.xmp = Some(text.into_bytes());
+                    } else {
+                        blocks.native.push((keyword, text));
+                    }
+                }
+            }
+            b"IEND" => break,
+            _ => {}
+        }
+
+        // length, type, data, CRC
+        pos += 12 + length;
+    }
+
+    Some(blocks)
+}
+
+/// `keyword\0 compressed method language\0 translated\0 text` (uncompressed only)
+fn parse_itxt(data: &[u8]) -> Option<(String, String)> {
+    let keyword_end = data.iter().position(|&b| b == 0)?;
+    let keyword = String::from_utf8_lossy(&data[..keyword_end]).to_string();
+    if *data.get(keyword_end + 1)? != 0 {
+        return None;
+    }
+    let rest = data.get(keyword_end + 3..)?;
+    let language_end = rest.iter().position(|&b| b == 0)?;
+    let rest = &rest[language_end + 1..];
+    let translated_end = rest.iter().position(|&b| b == 0)?;
+    let text = String::from_utf8_lossy(&rest[translated_end + 1..]).to_string();
+    Some((keyword, text))
+}
+
+/// A TIFF file is itself an EXIF structure; XMP and IPTC sit in IFD0 tags
+fn read_tiff_file(bytes: &[u8]) -> Option<MetadataBlocks> {
+    let tiff = Tiff::new(bytes)?;
+    let ifd0 = tiff.ifd(tiff.first_ifd_offset()?)?;
+
+    let mut blocks = MetadataBlocks {
+        width: ifd0.find(TAG_IMAGE_WIDTH).and_then(|e| tiff.uint(e)),
+        height: ifd0.find(TAG_IMAGE_LENGTH).and_then(|e| tiff.uint(e)),
+        ..Default::default()
+    };
+    blocks.xmp = ifd0
+        .find(TAG_XMP)
+        .and_then(|e| tiff.value_bytes(e))
+        .map(<[u8]>::to_vec);
+    blocks.iptc = ifd0
+        .find(TAG_IPTC)
+        .and_then(|e| tiff.value_bytes(e))
+        .map(<[u8]>::to_vec);
+    blocks.exif = Some(bytes.to_vec());
+    Some(blocks)
+}
+
+// ============================================================================
+// EXIF (TIFF structure)
+// ============================================================================
+
+const TAG_IMAGE_WIDTH: u16 = 0x0100;
+const TAG_IMAGE_LENGTH: u16 = 0x0101;
+const TAG_IMAGE_DESCRIPTION: u16 = 0x010E;
+const TAG_MAKE: u16 = 0x010F;
+const TAG_MODEL: u16 = 0x0110;
+const TAG_SOFTWARE: u16 = 0x0131;
+const TAG_DATE_TIME: u16 = 0x0132;
+const TAG_ARTIST: u16 = 0x013B;
+const TAG_THUMBNAIL_OFFSET: u16 = 0x0201;
+const TAG_THUMBNAIL_LENGTH: u16 = 0x0202;
+const TAG_XMP: u16 = 0x02BC;
+const TAG_COPYRIGHT: u16 = 0x8298;
+const TAG_IPTC: u16 = 0x83BB;
+const TAG_EXIF_IFD: u16 = 0x8769;
+const TAG_DATE_TIME_ORIGINAL: u16 = 0x9003;
+const TAG_USER_COMMENT: u16 = 0x9286;
+const TAG_XP_TITLE: u16 = 0x9C9B;
+const TAG_XP_COMMENT: u16 = 0x9C9C;
+const TAG_XP_AUTHOR: u16 = 0x9C9D;
+const TAG_XP_KEYWORDS: u16 = 0x9C9E;
+const TAG_XP_SUBJECT: u16 = 0x9C9F;
+const TAG_PIXEL_X_DIMENSION: u16 = 0xA002;
+const TAG_PIXEL_Y_DIMENSION: u16 = 0xA003;
+const TAG_LENS_MODEL: u16 = 0xA434;
+
+struct Tiff<'a> {
+    data: &'a [u8],
+    little_endian: bool,
+}
+
+#[derive(Clone, Copy)]
+struct IfdEntry {
+    tag: u16,
+    field_type: u16,
+    count: u32,
+    /// Offset of the 4-byte value/offset field
+    value_pos: usize,
+}
+
+struct Ifd {
+    entries: Vec<IfdEntry>,
+    next: u32,
+}
+
+impl Ifd {
+    fn find(&self, tag: u16) -> Option<IfdEntry> {
+        self.entries.iter().copied().find(|e| e.tag == tag)
+    }
+}
+
+impl<'a> Tiff<'a> {
+    fn new(data: &'a [u8]) -> Option<Self> {
+        let little_endian = match data.get(0..4)? {
+            b"II*\0" => true,
+            b"MM\0*" => false,
+            _ => return None,
+        };
+        Some(Self {
+            data,
+            little_endian,
+        })
+    }
+
+    fn u16_at(&self, pos: usize) -> Option<u16> {
+        let bytes: [u8; 2] = self.data.get(pos..pos + 2)?.try_into().ok()?;
+        Some(if self.little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        })
+    }
+
+    fn u32_at(&self, pos: usize) -> Option<u32> {
+        let bytes: [u8; 4] = self.data.get(pos..pos + 4)?.try_into().ok()?;
+        Some(if self.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    fn first_ifd_offset(&self) -> Option<u32> {
+        self.u32_at(4)
+    }
+
+    fn ifd(&self, offset: u32) -> Option<Ifd> {
+        let offset = offset as usize;
+        let count = self.u16_at(offset)? as usize;
+        let entries = (0..count)
+            .filter_map(|i| {
+                let pos = offset + 2 + i * 12;
+                Some(IfdEntry {
+                    tag: self.u16_at(pos)?,
+                    field_type: self.u16_at(pos + 2)?,
+                    count: self.u32_at(pos + 4)?,
+                    value_pos: pos + 8,
+                })
+            })
+            .collect();
+        let next = self.u32_at(offset + 2 + count * 12).unwrap_or(0);
+        Some(Ifd { entries, next })
+    }
+
+    /// Raw value bytes (inline when they fit in 4 bytes)
+    fn value_bytes(&self, entry: IfdEntry) -> Option<&'a [u8]> {
+        let unit = match entry.field_type {
+            1 | 2 | 6 | 7 => 1,
+            3 | 8 => 2,
+            4 | 9 | 11 => 4,
+            5 | 10 | 12 => 8,
+            _ => return None,
+        };
+        let size = unit * entry.count as usize;
+        let start = if size <= 4 {
+            entry.value_pos
+        } else {
+            self.u32_at(entry.value_pos)? as usize
+        };
+        self.data.get(start..start.checked_add(size)?)
+    }
+
+    /// SHORT or LONG value
+    fn uint(&self, entry: IfdEntry) -> Option<u32> {
+        match entry.field_type {
+            3 => self.u16_at(entry.value_pos).map(u32::from),
+            4 => self.u32_at(entry.value_pos),
+            _ => None,
+        }
+    }
+
+    /// ASCII value, trimmed at the first NUL
+    fn ascii(&self, entry: IfdEntry) -> Option<String> {
+        let bytes = self.value_bytes(entry)?;
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        Some(decode_text(&bytes[..end]))
+    }
+
+    /// Windows `XP*` tags: UCS-2LE bytes
+    fn ucs2(&self, entry: IfdEntry) -> Option<String> {
+        let (text, _) = encoding_rs::UTF_16LE.decode_without_bom_handling(self.value_bytes(entry)?);
+        Some(text.trim_end_matches('\0').to_string())
+    }
+
+    /// UserComment: 8-byte character code, then the text
+    fn user_comment(&self, entry: IfdEntry) -> Option<String> {
+        let bytes = self.value_bytes(entry)?;
+        let (code, text) = (bytes.get(..8)?, &bytes[8..]);
+        let text = if code.starts_with(b"UNICODE") {
+            let encoding = if self.little_endian {
+                encoding_rs::UTF_16LE
+            } else {
+                encoding_rs::UTF_16BE
+            };
+            encoding.decode_without_bom_handling(text).0.into_owned()
+        } else {
+            decode_text(text)
+        };
+        Some(text.trim_end_matches(['\0', ' ']).to_string())
+    }
+}
+
+fn apply_exif(info: &mut ImageInfo, exif: &[u8]) {
+    let Some(tiff) = Tiff::new(exif) else {
+        return;
+    };
+    let Some(ifd0) = tiff.first_ifd_offset().and_then(|offset| tiff.ifd(offset)) else {
+        return;
+    };
+    let ascii = |tag| ifd0.find(tag).and_then(|e| tiff.ascii(e));
+    let ucs2 = |tag| ifd0.find(tag).and_then(|e| tiff.ucs2(e));
+
+    set(&mut info.title, ucs2(TAG_XP_TITLE));
+    set(&mut info.description, ascii(TAG_IMAGE_DESCRIPTION));
+    set(
+        &mut info.description,
+        ucs2(TAG_XP_COMMENT).or_else(|| ucs2(TAG_XP_SUBJECT)),
+    );
+    set(
+        &mut info.author,
+        ascii(TAG_ARTIST).or_else(|| ucs2(TAG_XP_AUTHOR)),
+    );
+    set(&mut info.copyright, ascii(TAG_COPYRIGHT));
+    set(&mut info.software, ascii(TAG_SOFTWARE));
+
+    if info.keywords.is_empty() {
+        if let Some(keywords) = ucs2(TAG_XP_KEYWORDS) {
+            info.keywords = split_keywords(&keywords);
+        }
+    }
+
+    let make = ascii(TAG_MAKE).unwrap_or_default();
+    let model = ascii(TAG_MODEL).unwrap_or_default();
+    // Models usually repeat the make ("Canon" + "Canon EOS R6")
+    let camera = if model.starts_with(make.trim()) {
+        model
+    } else {
+        format!("{} {}", make.trim(), model)
+    };
+    set(&mut info.camera, Some(camera));
+
+    let exif_ifd = ifd0
+        .find(TAG_EXIF_IFD)
+        .and_then(|e| tiff.uint(e))
+        .and_then(|offset| tiff.ifd(offset));
+    if let Some(exif_ifd) = &exif_ifd {
+        let date = exif_ifd
+            .find(TAG_DATE_TIME_ORIGINAL)
+            .and_then(|e| tiff.ascii(e));
+        set(&mut info.date_taken, date.map(|d| normalize_exif_date(&d)));
+        set(
+            &mut info.description,
+            exif_ifd
+                .find(TAG_USER_COMMENT)
+                .and_then(|e| tiff.user_comment(e)),
+        );
+        set(
+            &mut info.lens,
+            exif_ifd.find(TAG_LENS_MODEL).and_then(|e| tiff.ascii(e)),
+        );
+        if info.width.is_none() {
+            info.width = exif_ifd
+                .find(TAG_PIXEL_X_DIMENSION)
+                .and_then(|e| tiff.uint(e));
+            info.height = exif_ifd
+                .find(TAG_PIXEL_Y_DIMENSION)
+                .and_then(|e| tiff.uint(e));
+        }
+    }
+    set(
+        &mut info.date_taken,
+        ascii(TAG_DATE_TIME).map(|d| normalize_exif_date(&d)),
+    );
+
+    // IFD1 holds the thumbnail
+    if let Some(ifd1) = (ifd0.next != 0).then(|| tiff.ifd(ifd0.next)).flatten() {
+        let offset = ifd1.find(TAG_THUMBNAIL_OFFSET).and_then(|e| tiff.uint(e));
+        let length = ifd1.find(TAG_THUMBNAIL_LENGTH).and_then(|e| tiff.uint(e));
+        if let (Some(offset), Some(length)) = (offset, length) {
+            let (offset, length) = (offset as usize, length as usize);
+            info.thumbnail = exif
+                .get(offset..offset.saturating_add(length))
+                .filter(|jpeg| jpeg.starts_with(&[0xFF, 0xD8]))
+                .map(<[u8]>::to_vec);
+        }
+    }
+}
+
+/// `2023:05:01 10:00:00` -> `2023-05-01 10:00:00`
+fn normalize_exif_date(date: &str) -> String {
+    match date.split_once(' ') {
+        Some((day, time)) => format!("{} {}", day.replace(':', "-"), time),
+        None => date.replace(':', "-"),
+    }
+}
+
+fn split_keywords(keywords: &str) -> Vec<String> {
+    keywords
+        .split([';', ','])
+        .map(|k| k.trim().to_string())
+        .filter(|k| !k.is_empty())
+        .collect()
+}
+
+// ============================================================================
+// IPTC-IIM
+// ============================================================================
+
+/// Application record (2) datasets: `0x1C 2 <dataset> <len:u16> <data>`
+fn apply_iptc(info: &mut ImageInfo, iptc: &[u8]) {
+    let mut pos = 0;
+    let mut keywords = Vec::new();
+    let mut location: Vec<String> = Vec::new();
+    let mut date = None;
+
+    while pos + 5 <= iptc.len() && iptc[pos] == 0x1C {
+        let (record, dataset) = (iptc[pos + 1], iptc[pos + 2]);
+        let length = u16::from_be_bytes([iptc[pos + 3], iptc[pos + 4]]) as usize;
+        // Extended (>32 KB) datasets are not text we need
+        if length & 0x8000 != 0 {
+            break;
+        }
+        let Some(data) = iptc.get(pos + 5..pos + 5 + length) else {
+            break;
+        };
+        pos += 5 + length;
+
+        if record != 2 {
+            continue;
+        }
+        let value = Some(decode_text(data));
+        match dataset {
+            5 => set(&mut info.title, value),
+            25 => keywords.extend(value),
+            55 => date = value,
+            80 => set(&mut info.author, value),
+            90 | 95 | 101 => location.extend(value),
+            105 => set(&mut info.headline, value),
+            116 => set(&mut info.copyright, value),
+            120 => set(&mut info.description, value),
+            _ => {}
+        }
+    }
+
+    if info.keywords.is_empty() {
+        info.keywords = keywords
+            .into_iter()
+            .filter(|k| !k.trim().is_empty())
+            .collect();
+    }
+    if !location.is_empty() {
+        set(&mut info.location, Some(location.join(", ")));
+    }
+    // CCYYMMDD (digits only, so the slices fall on characters)
+    set(
+        &mut info.date_taken,
+        date.filter(|d| d.len() == 8 && d.bytes().all(|b| b.is_ascii_digit()))
+            .map(|d| format!("{}-{}-{}", &d[..4], &d[4..6], &d[6..])),
+    );
+}
+
+// ============================================================================
+// XMP
+// ============================================================================
+
+/// Wrapper elements around property values
+const RDF_CONTAINERS: &[&[u8]] = &[
+    b"li",
+    b"Alt",
+    b"Seq",
+    b"Bag",
+    b"Description",
+    b"RDF",
+    b"xmpmeta",
+];
+
+fn apply_xmp(info: &mut ImageInfo, xmp: &[u8]) {
+    let mut values: Vec<(String, String)> = Vec::new();
+    let mut reader = Reader::from_reader(xmp);
+    reader.config_mut().trim_text(true);
+
+    let mut buf = Vec::new();
+    let mut stack: Vec<Vec<u8>> = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => {
+                if e.local_name().as_ref() == b"Description" {
+                    collect_xmp_attributes(&e, &mut values);
+                }
+                stack.push(e.local_name().as_ref().to_vec());
+            }
+            Ok(Event::Empty(e)) if e.local_name().as_ref() == b"Description" => {
+                collect_xmp_attributes(&e, &mut values);
+            }
+            Ok(Event::End(_)) => {
+                stack.pop();
+            }
+            Ok(Event::Text(e)) => {
+                let property = stack
+                    .iter()
+                    .rev()
+                    .find(|name| !RDF_CONTAINERS.contains(&name.as_slice()));
+                if let (Some(property), Ok(text)) = (property, e.unescape()) {
+                    values.push((
+                        String::from_utf8_lossy(property).to_string(),
+                        text.to_string(),
+                    ));
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    let first = |name: &str| {
+        values
+            .iter()
+            .find(|(property, _)| property == name)
+            .map(|(_, value)| value.clone())
+    };
+    let all = |name: &str| -> Vec<String> {
+        values
+            .iter()
+            .filter(|(property, _)| property == name)
+            .map(|(_, value)| value.clone())
+            .collect()
+    };
+
+    set(&mut info.title, first("title"));
+    set(&mut info.headline, first("Headline"));
+    set(&mut info.description, first("description"));
+    let creators = all("creator");
+    set(
+        &mut info.author,
+        (!creators.is_empty()).then(|| creators.join(", ")),
+    );
+    set(&mut info.copyright, first("rights"));
+    set(
+        &mut info.date_taken,
+        first("DateTimeOriginal")
+            .or_else(|| first("DateCreated"))
+            .or_else(|| first("CreateDate"))
+            .map(|d| d.replacen('T', " ", 1)),
+    );
+    set(&mut info.software, first("CreatorTool"));
+
+    let location: Vec<String> = ["City", "State", "Country"]
+        .into_iter()
+        .filter_map(first)
+        .collect();
+    if !location.is_empty() {
+        set(&mut info.location, Some(location.join(", ")));
+    }
+    if info.keywords.is_empty() {
+        info.keywords = all("subject");
+    }
+}
+
+/// Simple properties written as attributes: `<rdf:Description xmp:CreateDate="...">`
+fn collect_xmp_attributes(e: &quick_xml::events::BytesStart, values: &mut Vec<(String, String)>) {
+    for attr in e.attributes().filter_map(|a| a.ok()) {
+        if let Ok(value) = attr.unescape_value() {
+            let name = String::from_utf8_lossy(attr.key.local_name().as_ref()).to_string();
+            values.push((name, value.to_string()));
+        }
+    }
+}
+
+/// UTF-8, falling back to Windows-1252 (common in older EXIF/IPTC)
+fn decode_text(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => encoding_rs::WINDOWS_1252.decode(bytes).0.into_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Little-endian TIFF with IFD0 (description, make, model) and an Exif IFD (date)
+    fn sample_exif() -> Vec<u8> {
+        let mut tiff = b"II*\0".to_vec();
+        tiff.extend(8u32.to_le_bytes());
+
+        let strings_offset = 8 + 2 + 4 * 12 + 4;
+        let description = b"Site survey, north wall\0";
+        let make = b"Canon\0";
+        let model = b"Canon EOS R6\0";
+        let exif_ifd_offset = strings_offset + description.len() + make.len() + model.len();
+
+        let entry = |tag: u16, field_type: u16, count: u32, value: u32| {
+            let mut e = tag.to_le_bytes().to_vec();
+            e.extend(field_type.to_le_bytes());
+            e.extend(count.to_le_bytes());
+            e.extend(value.to_le_bytes());
+            e
+        };
+
+        tiff.extend(4u16.to_le_bytes());
+        tiff.extend(entry(
+            TAG_IMAGE_DESCRIPTION,
+            2,
+            description.len() as u32,
+            strings_offset as u32,
+        ));
+        tiff.extend(entry(
+            TAG_MAKE,
+            2,
+            make.len() as u32,
+            (strings_offset + description.len()) as u32,
+        ));
+        tiff.extend(entry(
+            TAG_MODEL,
+            2,
+            model.len() as u32,
+            (strings_offset + description.len() + make.len()) as u32,
+        ));
+        tiff.extend(entry(TAG_EXIF_IFD, 4, 1, exif_ifd_offset as u32));
+        tiff.extend(0u32.to_le_bytes());
+        tiff.extend(description);
+        tiff.extend(make);
+        tiff.extend(model);
+
+        let date = b"2023:05:01 10:00:00\0";
+        tiff.extend(1u16.to_le_bytes());
+        tiff.extend(entry(
+            TAG_DATE_TIME_ORIGINAL,
+            2,
+            date.len() as u32,
+            (exif_ifd_offset + 2 + 12 + 4) as u32,
+        ));
+        tiff.extend(0u32.to_le_bytes());
+        tiff.extend(date);
+        tiff
+    }
+
+    #[test]
+    fn test_jpeg_exif_iptc_xmp() {
+        let segment = |marker: u8, data: &[u8]| {
+            let mut s = vec![0xFF, marker];
+            s.extend(((data.len() + 2) as u16).to_be_bytes());
+            s.extend(data);
+            s
+        };
+
+        let mut app1 = b"Exif\0\0".to_vec();
+        app1.extend(sample_exif());
+
+        let mut iptc = Vec::new();
+        for (dataset, value) in [(25u8, "drainage"), (25, "concrete"), (120, "IPTC caption")] {
+            iptc.extend([0x1C, 2, dataset]);
+            iptc.extend((value.len() as u16).to_be_bytes());
+            iptc.extend(value.as_bytes());
+        }
+        let mut app13 = b"Photoshop 3.0\08BIM".to_vec();
+        app13.extend(0x0404u16.to_be_bytes());
+        app13.extend([0, 0]); // empty Pascal name, padded
+        app13.extend((iptc.len() as u32).to_be_bytes());
+        app13.extend(&iptc);
+
+        let mut xmp = b"http://ns.adobe.com/xap/1.0/\0".to_vec();
+        xmp.extend(
+            br#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+<rdf:Description xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:photoshop="http://ns.adobe.com/photoshop/1.0/" photoshop:City="Muscat">
+<dc:title><rdf:Alt><rdf:li xml:lang="x-default">Retaining wall</rdf:li></rdf:Alt></dc:title>
+<dc:creator><rdf:Seq><rdf:li>Maryam</rdf:li></rdf:Seq></dc:creator>
+</rdf:Description></rdf:RDF></x:xmpmeta>"#,
+        );
+
+        let mut jpeg = vec![0xFF, 0xD8];
+        jpeg.extend(segment(0xE1, &app1));
+        jpeg.extend(segment(0xED, &app13));
+        jpeg.extend(segment(0xE1, &xmp));
+        jpeg.extend(segment(0xC0, &[8, 0x01, 0xE0, 0x02, 0x80, 3]));
+        jpeg.extend([0xFF, 0xDA, 0, 2]);
+
+        let info = build_info(read_jpeg(&jpeg).unwrap());
+        assert_eq!(info.title.as_deref(), Some("Retaining wall"));
+        assert_eq!(info.author.as_deref(), Some("Maryam"));
+        // IPTC caption wins over the EXIF description
+        assert_eq!(info.description.as_deref(), Some("IPTC caption"));
+        assert_eq!(info.keywords, vec!["drainage", "concrete"]);
+        assert_eq!(info.location.as_deref(), Some("Muscat"));
+        assert_eq!(info.camera.as_deref(), Some("Canon EOS R6"));
+        assert_eq!(info.date_taken.as_deref(), Some("2023-05-01 10:00:00"));
+        assert_eq!((info.width, info.height), (Some(640), Some(480)));
+    }
+
+    #[test]
+    fn test_iptc_date() {
+        let iptc_date = |value: &str| {
+            let mut iptc = vec![0x1C, 2, 55];
+            iptc.extend((value.len() as u16).to_be_bytes());
+            iptc.extend(value.as_bytes());
+            let mut info = ImageInfo::default();
+            apply_iptc(&mut info, &iptc);
+            info.date_taken
+        };
+        assert_eq!(iptc_date("20230501").as_deref(), Some("2023-05-01"));
+        // Eight bytes, but not eight digits
+        assert_eq!(iptc_date("202é010"), None);
+        assert_eq!(iptc_date("2023-5-1"), None);
+    }
+
+    #[test]
+    fn test_png_text_chunks() {
+        let chunk = |kind: &[u8], data: &[u8]| {
+            let mut c = (data.len() as u32).to_be_bytes().to_vec();
+            c.extend(kind);
+            c.extend(data);
+            c.extend([0, 0, 0, 0]); // CRC (not checked)
+            c
+        };
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        let mut ihdr = 1200u32.to_be_bytes().to_vec();
+        ihdr.extend(800u32.to_be_bytes());
+        ihdr.extend([8, 2, 0, 0, 0]);
+        png.extend(chunk(b"IHDR", &ihdr));
+        png.extend(chunk(b"tEXt", b"Title\0Floor plan"));
+        png.extend(chunk(
+            b"iTXt",
+            "Description\0\0\0ar\0\0مخطط الطابق".as_bytes(),
+        ));
+        png.extend(chunk(b"IEND", b""));
+
+        let info = build_info(read_png(&png).unwrap());
+        assert_eq!(info.title.as_deref(), Some("Floor plan"));
+        assert_eq!(info.description.as_deref(), Some("مخطط الطابق"));
+        assert_eq!((info.width, info.height), (Some(1200), Some(800)));
+    }
+}
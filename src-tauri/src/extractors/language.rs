@@ -0,0 +1,240 @@
+//! Offline language detection
+//!
+//! Script-based detection run once per document at extraction time.
+//! Counts letters per Unicode script over a sample of the text, picks the
+//! dominant script, then refines where a script is shared by several
+//! languages we care about:
+//! - Arabic script: Urdu-specific letters (ٹ ڈ ڑ ں ے ہ ھ) mark Urdu
+//! - Latin script: English stopword ratio marks English
+//! - Han script: any kana marks Japanese
+//!
+//! Returns ISO 639-1 codes, or `UNDETERMINED` when no language is clear.
+//!
+//! Also home to the text direction helpers used by the structured preview:
+//! first-strong-character detection for sources that don't declare a direction.
+
+use crate::models::{
+    ContentSection, DocumentContent, SectionProperties, SectionType, TextDirection, TextRun,
+};
+
+/// Code stored for empty documents or unrecognized text
+pub const UNDETERMINED: &str = "und";
+
+/// Number of characters sampled from the start of a document
+const SAMPLE_CHARS: usize = 20_000;
+
+/// Minimum letters needed before we trust a script count
+const MIN_LETTERS: usize = 10;
+
+/// Common English function words (lowercase)
+const ENGLISH_STOPWORDS: &[&str] = &[
+    "the", "and", "of", "to", "in", "is", "for", "that", "with", "on", "as", "are", "this",
+    "be", "by", "it", "from", "or", "at", "an", "was", "will", "not", "have", "which",
+];
+
+#[derive(Default)]
+struct ScriptCounts {
+    latin: usize,
+    arabic: usize,
+    urdu_markers: usize,
+    gujarati: usize,
+    devanagari: usize,
+    hebrew: usize,
+    han: usize,
+    kana: usize,
+    hangul: usize,
+}
+
+/// Detect the dominant language of a document's text
+pub fn detect_language(text: &str) -> &'static str {
+    let mut counts = ScriptCounts::default();
+
+    for c in text.chars().take(SAMPLE_CHARS) {
+        match c as u32 {
+            0x0041..=0x005A | 0x0061..=0x007A | 0x00C0..=0x024F => counts.latin += 1,
+            // Urdu-specific letters (counted as Arabic script too)
+            0x0679 | 0x0688 | 0x0691 | 0x06BA | 0x06BE | 0x06C1 | 0x06D2 | 0x06D3 => {
+                counts.arabic += 1;
+                counts.urdu_markers += 1;
+            }
+            0x0620..=0x064A | 0x066E..=0x06D3 | 0x0750..=0x077F | 0x08A0..=0x08FF
+            | 0xFB50..=0xFDFF | 0xFE70..=0xFEFF => counts.arabic += 1,
+            0x0A80..=0x0AFF => counts.gujarati += 1,
+            0x0900..=0x097F => counts.devanagari += 1,
+            0x05D0..=0x05EA => counts.hebrew += 1,
+            0x3040..=0x30FF => counts.kana += 1,
+            0x4E00..=0x9FFF | 0x3400..=0x4DBF => counts.han += 1,
+            0xAC00..=0xD7AF => counts.hangul += 1,
+            _ => {}
+        }
+    }
+
+    let candidates = [
+        (counts.latin, "latin"),
+        (counts.arabic, "arabic"),
+        (counts.gujarati, "gu"),
+        (counts.devanagari, "hi"),
+        (counts.hebrew, "he"),
+        (counts.han + counts.kana, "cjk"),
+        (counts.hangul, "ko"),
+    ];
+
+    let Some(&(letters, script)) = candidates.iter().max_by_key(|(n, _)| *n) else {
+        return UNDETERMINED;
+    };
+
+    if letters < MIN_LETTERS {
+        return UNDETERMINED;
+    }
+
+    match script {
+        "arabic" => {
+            // Urdu markers are frequent in Urdu text (ے and ہ alone are common);
+            // 2% of Arabic-script letters is well above noise from loanwords
+            if counts.urdu_markers * 50 >= counts.arabic {
+                "ur"
+            } else {
+                "ar"
+            }
+        }
+        "latin" => {
+            if is_english(text) {
+                "en"
+            } else {
+                UNDETERMINED
+            }
+        }
+        "cjk" => {
+            if counts.kana > 0 {
+                "ja"
+            } else {
+                "zh"
+            }
+        }
+        other => other,
+    }
+}
+
+/// Check English stopword density over the sampled words
+fn is_english(text: &str) -> bool {
+    let mut words = 0usize;
+    let mut hits = 0usize;
+
+    for word in text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .take(SAMPLE_CHARS / 5)
+    {
+        words += 1;
+        if ENGLISH_STOPWORDS
+            .iter()
+            .any(|sw| word.eq_ignore_ascii_case(sw))
+        {
+            hits += 1;
+        }
+    }
+
+    // Running English prose is ~25-40% stopwords; 5% tolerates lists and tables
+    hits > 0 && hits * 20 >= words
+}
+
+/// Base direction of a text from its first strong character (Unicode bidi rule P2)
+///
+/// Letters of the Hebrew, Arabic, Syriac, Thaana and NKo blocks (and their
+/// presentation forms) are right-to-left, any other letter left-to-right.
+/// `None` when the text has no letters.
+pub fn detect_direction(text: &str) -> Option<TextDirection> {
+    text.chars().find_map(|c| {
+        if !c.is_alphabetic() {
+            None
+        } else if matches!(c as u32, 0x0590..=0x08FF | 0xFB1D..=0xFDFF | 0xFE70..=0xFEFF) {
+            Some(TextDirection::Rtl)
+        } else {
+            Some(TextDirection::Ltr)
+        }
+    })
+}
+
+/// Properties for a paragraph with a declared direction: the language is set
+/// when all of its runs that declare one agree
+pub(super) fn paragraph_properties(
+    direction: Option<TextDirection>,
+    runs: &[TextRun],
+) -> Option<SectionProperties> {
+    let mut languages = runs.iter().filter_map(|r| r.style.language.as_deref());
+    let first = languages.next();
+    let language = first
+        .filter(|first| languages.all(|l| l == *first))
+        .map(str::to_string);
+
+    if direction.is_none() && language.is_none() {
+        return None;
+    }
+    Some(SectionProperties {
+        direction,
+        language,
+        ..Default::default()
+    })
+}
+
+/// Fill in the direction of text sections the extractor left unannotated,
+/// from their first strong character (code blocks stay left-to-right)
+pub fn annotate_text_direction(content: &mut DocumentContent) {
+    annotate_sections(&mut content.sections);
+}
+
+fn annotate_sections(sections: &mut [ContentSection]) {
+    for section in sections {
+        if let Some(children) = section.children.as_mut() {
+            annotate_sections(children);
+        }
+        if section.section_type == SectionType::CodeBlock
+            || section.properties.as_ref().is_some_and(|p| p.direction.is_some())
+        {
+            continue;
+        }
+
+        let direction = match (&section.content, &section.runs) {
+            (Some(content), _) => detect_direction(content),
+            (None, Some(runs)) => runs.iter().find_map(|r| detect_direction(&r.text)),
+            (None, None) => None,
+        };
+        if let Some(direction) = direction {
+            section
+                .properties
+                .get_or_insert_with(SectionProperties::default)
+                .direction = Some(direction);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_common_languages() {
+        assert_eq!(
+            detect_language("The committee will review the report and respond to the board."),
+            "en"
+        );
+        assert_eq!(detect_language("بسم الله الرحمن الرحيم الحمد لله رب العالمين"), "ar");
+        assert_eq!(detect_language("یہ ایک اردو جملہ ہے اور ہم اسے پڑھ رہے ہیں"), "ur");
+        assert_eq!(detect_language("આ એક ગુજરાતી વાક્ય છે અને તે વાંચી શકાય"), "gu");
+    }
+
+    #[test]
+    fn test_undetermined() {
+        assert_eq!(detect_language(""), UNDETERMINED);
+        assert_eq!(detect_language("12345 67890"), UNDETERMINED);
+        assert_eq!(detect_language("Lorem ipsum dolor sit amet consectetur"), UNDETERMINED);
+    }
+
+    #[test]
+    fn test_detect_direction() {
+        assert_eq!(detect_direction("«١٢» مرحبا world"), Some(TextDirection::Rtl));
+        assert_eq!(detect_direction("2024: Report عن المشروع"), Some(TextDirection::Ltr));
+        assert_eq!(detect_direction("שלום"), Some(TextDirection::Rtl));
+        assert_eq!(detect_direction("123 - ..."), None);
+    }
+}
@@ -0,0 +1,852 @@
+use std::fs;
+use std::path::Path;
+
+use super::csv::decode_bytes;
+use super::html::document_to_text;
+use crate::models::{ContentSection, DocumentContent, DocumentMetadata, SectionType};
+
+/// Sectioning commands and their depth (`\part` is the outermost)
+const SECTION_COMMANDS: &[(&str, u8)] = &[
+    ("part", 0),
+    ("chapter", 1),
+    ("section", 2),
+    ("subsection", 3),
+    ("subsubsection", 4),
+    ("paragraph", 5),
+    ("subparagraph", 6),
+];
+
+/// Commands dropped together with their mandatory arguments (name, count)
+///
+/// Other commands only lose their name: `\textbf{x}` and `\emph{x}` keep `x`.
+const DROPPED_COMMANDS: &[(&str, usize)] = &[
+    ("label", 1),
+    ("ref", 1),
+    ("eqref", 1),
+    ("pageref", 1),
+    ("autoref", 1),
+    ("cref", 1),
+    ("Cref", 1),
+    ("cite", 1),
+    ("citep", 1),
+    ("citet", 1),
+    ("nocite", 1),
+    ("thanks", 1),
+    ("includegraphics", 1),
+    ("input", 1),
+    ("include", 1),
+    ("usepackage", 1),
+    ("documentclass", 1),
+    ("bibliography", 1),
+    ("bibliographystyle", 1),
+    ("vspace", 1),
+    ("hspace", 1),
+    ("cline", 1),
+    ("cmidrule", 1),
+    ("pagestyle", 1),
+    ("thispagestyle", 1),
+    ("setlength", 2),
+    ("addtolength", 2),
+    ("setcounter", 2),
+    ("newcommand", 2),
+    ("renewcommand", 2),
+    ("providecommand", 2),
+    ("newenvironment", 3),
+    ("renewenvironment", 3),
+];
+
+/// Commands whose leading arguments are dropped and the rest kept (name, dropped count)
+const LEADING_ARGS_DROPPED: &[(&str, usize)] = &[
+    ("href", 1),
+    ("multicolumn", 2),
+    ("multirow", 2),
+    ("textcolor", 1),
+    ("colorbox", 1),
+];
+
+/// Environments kept verbatim as code blocks (`comment` is dropped)
+const VERBATIM_ENVIRONMENTS: &[&str] = &["verbatim", "Verbatim", "lstlisting", "minted", "comment"];
+
+/// Display math environments (dropped)
+const MATH_ENVIRONMENTS: &[&str] = &[
+    "equation", "equation*", "align", "align*", "gather", "gather*", "multline", "multline*",
+    "eqnarray", "eqnarray*", "displaymath", "math",
+];
+
+/// Extract text content from a LaTeX file (plain text for indexing)
+pub fn extract_latex(path: &Path) -> Option<String> {
+    document_to_text(&extract_latex_structured(path)?)
+}
+
+/// Extract structured content from a LaTeX file (for rich preview)
+///
+/// Comments, math and commands are stripped; `\section` and friends become
+/// headings (the outermost level used becomes level 1), list environments
+/// become list items, `tabular` becomes a table and verbatim environments
+/// become code blocks. `\title`, `\author` and `\date` go into metadata.
+pub fn extract_latex_structured(path: &Path) -> Option<DocumentContent> {
+    let bytes = fs::read(path).ok()?;
+    parse_latex(&decode_bytes(&bytes))
+}
+
+fn parse_latex(source: &str) -> Option<DocumentContent> {
+    let source = strip_comments(source);
+
+    let (preamble, body) = match source.find("\\begin{document}") {
+        Some(start) => {
+            let body = &source[start + "\\begin{document}".len()..];
+            let body = body.find("\\end{document}").map_or(body, |end| &body[..end]);
+            (&source[..start], body)
+        }
+        None => ("", source.as_str()),
+    };
+
+    let mut parser = BlockParser::new(body);
+    for command in ["title", "author", "date"] {
+        if let Some(value) = find_command_argument(preamble, command) {
+            parser.set_metadata(command, &value);
+        }
+    }
+    parser.run();
+
+    let BlockParser {
+        mut sections,
+        headings,
+        metadata,
+        ..
+    } = parser;
+
+    // The outermost heading depth in use becomes level 1
+    let top = headings.iter().filter_map(|(_, depth)| *depth).min().unwrap_or(0);
+    for (index, depth) in headings {
+        let level = depth.map_or(1, |d| d - top + 1).min(6);
+        sections[index].section_type = SectionType::Heading { level };
+    }
+
+    if sections.is_empty() && metadata.title.is_none() {
+        return None;
+    }
+
+    Some(DocumentContent {
+        doc_type: "latex".to_string(),
+        sections,
+        metadata,
+    })
+}
+
+/// Remove `%` comments (and the line break they swallow) outside verbatim
+fn strip_comments(source: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut verbatim_end: Option<String> = None;
+    let mut join_next = false;
+
+    for line in source.lines() {
+        if let Some(end) = &verbatim_end {
+            if line.contains(end.as_str()) {
+                verbatim_end = None;
+            }
+            out.push_str(line);
+            out.push('\n');
+            continue;
+        }
+
+        let line = if join_next { line.trim_start() } else { line };
+        join_next = false;
+
+        if let Some(env) = VERBATIM_ENVIRONMENTS
+            .iter()
+            .find(|env| line.contains(&format!("\\begin{{{}}}", env)))
+        {
+            verbatim_end = Some(format!("\\end{{{}}}", env));
+            out.push_str(line);
+            out.push('\n');
+            continue;
+        }
+
+        match find_comment(line) {
+            Some(start) => {
+                out.push_str(&line[..start]);
+                // `%` at the end of a line also removes the line break
+                join_next = true;
+            }
+            None => {
+                out.push_str(line);
+                out.push('\n');
+            }
+        }
+    }
+
+    out
+}
+
+/// Byte offset of the first unescaped `%`
+fn find_comment(line: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, b) in line.bytes().enumerate() {
+        if b == b'%' && !escaped {
+            return Some(i);
+        }
+        escaped = b == b'\\' && !escaped;
+    }
+    None
+}
+
+/// First `{...}` argument of `\command` in a source fragment
+fn find_command_argument(source: &str, command: &str) -> Option<String> {
+    let chars: Vec<char> = source.chars().collect();
+    let needle: Vec<char> = format!("\\{}", command).chars().collect();
+
+    let mut i = 0;
+    while i + needle.len() <= chars.len() {
+        let end = i + needle.len();
+        if chars[i..end] == needle[..] && !chars.get(end).is_some_and(|c| c.is_ascii_alphabetic()) {
+            let (group, _) = read_argument(&chars, skip_optional_args(&chars, end))?;
+            return Some(group);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Block-level pass: headings, environments, lists and paragraph breaks
+struct BlockParser {
+    chars: Vec<char>,
+    pos: usize,
+    sections: Vec<ContentSection>,
+    /// Section index and depth of each heading (`None`: always top level)
+    headings: Vec<(usize, Option<u8>)>,
+    metadata: DocumentMetadata,
+    /// Raw LaTeX of the paragraph being collected
+    pending: String,
+    /// Open list environments (ordered?)
+    lists: Vec<bool>,
+    /// The pending text started with `\item`
+    in_item: bool,
+}
+
+impl BlockParser {
+    fn new(body: &str) -> Self {
+        Self {
+            chars: body.chars().collect(),
+            pos: 0,
+            sections: Vec::new(),
+            headings: Vec::new(),
+            metadata: DocumentMetadata::default(),
+            pending: String::new(),
+            lists: Vec::new(),
+            in_item: false,
+        }
+    }
+
+    fn set_metadata(&mut self, command: &str, raw: &str) {
+        let value = inline_text(raw);
+        if value.is_empty() {
+            return;
+        }
+        match command {
+            "title" => self.metadata.title = Some(value),
+            "author" => self.metadata.author = Some(value),
+            "date" => self.metadata.created = Some(value),
+            _ => {}
+        }
+    }
+
+    fn run(&mut self) {
+        while self.pos < self.chars.len() {
+            let c = self.chars[self.pos];
+
+            if c == '\\' {
+                let name = command_name(&self.chars, self.pos + 1);
+                if !name.is_empty() && self.block_command(&name) {
+                    continue;
+                }
+                // Inline command or control symbol: leave it to `inline_text`
+                self.pending.push(c);
+                if let Some(&next) = self.chars.get(self.pos + 1) {
+                    if name.is_empty() {
+                        self.pending.push(next);
+                        self.pos += 1;
+                    }
+                }
+                self.pos += 1;
+                continue;
+            }
+
+            if c == '\n' {
+                let mut next = self.pos + 1;
+                while self.chars.get(next).is_some_and(|c| *c == ' ' || *c == '\t') {
+                    next += 1;
+                }
+                if self.chars.get(next) == Some(&'\n') {
+                    self.flush();
+                    self.pos = next + 1;
+                    continue;
+                }
+            }
+
+            self.pending.push(c);
+            self.pos += 1;
+        }
+
+        self.flush();
+    }
+
+    /// Handle a block-level command at `self.pos`; false if it is inline
+    fn block_command(&mut self, name: &str) -> bool {
+        let mut after = self.pos + 1 + name.chars().count();
+        if self.chars.get(after) == Some(&'*') {
+            after += 1;
+        }
+
+        if let Some(&(_, depth)) = SECTION_COMMANDS.iter().find(|(n, _)| *n == name) {
+            self.flush();
+            let start = skip_optional_args(&self.chars, after);
+            let Some((title, end)) = read_argument(&self.chars, start) else {
+                self.pos = after;
+                return true;
+            };
+            self.push_heading(inline_text(&title), Some(depth));
+            self.pos = end;
+            return true;
+        }
+
+        match name {
+            "title" | "author" | "date" => {
+                if let Some((value, end)) = read_argument(&self.chars, after) {
+                    self.set_metadata(name, &value);
+                    self.pos = end;
+                } else {
+                    self.pos = after;
+                }
+            }
+            "par" => {
+                self.flush();
+                self.pos = after;
+            }
+            "item" | "bibitem" => {
+                self.flush();
+                self.pos = after;
+                let label = match self.chars.get(after) {
+                    Some('[') => read_bracketed(&self.chars, after).map(|(label, end)| {
+                        self.pos = end;
+                        inline_text(&label)
+                    }),
+                    _ => None,
+                };
+                if name == "bibitem" {
+                    // Citation key
+                    if let Some((_, end)) = read_argument(&self.chars, self.pos) {
+                        self.pos = end;
+                    }
+                } else if let Some(label) = label.filter(|l| !l.is_empty()) {
+                    self.pending = format!("{}: ", label);
+                }
+                self.in_item = true;
+            }
+            "begin" => {
+                self.flush();
+                match read_argument(&self.chars, after) {
+                    Some((env, end)) => {
+                        self.pos = end;
+                        self.begin_environment(env.trim());
+                    }
+                    None => self.pos = after,
+                }
+            }
+            "end" => {
+                self.flush();
+                match read_argument(&self.chars, after) {
+                    Some((env, end)) => {
+                        self.pos = end;
+                        if matches!(
+                            env.trim(),
+                            "itemize" | "enumerate" | "description" | "thebibliography"
+                        ) {
+                            self.lists.pop();
+                        }
+                    }
+                    None => self.pos = after,
+                }
+            }
+            "maketitle" | "tableofcontents" | "newpage" | "clearpage" => self.pos = after,
+            _ => return false,
+        }
+        true
+    }
+
+    fn begin_environment(&mut self, env: &str) {
+        match env {
+            "itemize" | "description" => self.lists.push(false),
+            "enumerate" => self.lists.push(true),
+            "thebibliography" => {
+                if let Some((_, end)) = read_argument(&self.chars, self.pos) {
+                    self.pos = end;
+                }
+                self.push_heading("References".to_string(), None);
+                self.lists.push(true);
+            }
+            "abstract" => self.push_heading("Abstract".to_string(), None),
+            "tabular" | "tabular*" | "tabularx" | "tabulary" | "longtable" => {
+                // Column spec, plus a width for the starred/x variants
+                let args = if matches!(env, "tabular" | "longtable") { 1 } else { 2 };
+                let mut start = skip_optional_args(&self.chars, self.pos);
+                for _ in 0..args {
+                    if let Some((_, end)) = read_argument(&self.chars, start) {
+                        start = end;
+                    }
+                }
+                let (raw, end) = self.raw_until_end(start, env);
+                self.pos = end;
+                if let Some(table) = parse_tabular(&raw) {
+                    self.sections.push(table);
+                }
+            }
+            env if VERBATIM_ENVIRONMENTS.contains(&env) => {
+                let mut start = skip_optional_args(&self.chars, self.pos);
+                if env == "minted" {
+                    // Language argument
+                    if let Some((_, end)) = read_argument(&self.chars, start) {
+                        start = end;
+                    }
+                }
+                let (raw, end) = self.raw_until_end(start, env);
+                self.pos = end;
+                let code = raw.trim_matches('\n').trim_end();
+                if env != "comment" && !code.is_empty() {
+                    self.sections.push(section(SectionType::CodeBlock, code.to_string()));
+                }
+            }
+            env if MATH_ENVIRONMENTS.contains(&env) => {
+                let (_, end) = self.raw_until_end(self.pos, env);
+                self.pos = end;
+            }
+            "minipage" | "multicols" | "wrapfigure" => {
+                let args = if env == "wrapfigure" { 2 } else { 1 };
+                let mut start = skip_optional_args(&self.chars, self.pos);
+                for _ in 0..args {
+                    if let Some((_, end)) = read_argument(&self.chars, start) {
+                        start = skip_optional_args(&self.chars, end);
+                    }
+                }
+                self.pos = start;
+            }
+            // figure, table, center, quote, ...: placement options only
+            _ => self.pos = skip_optional_args(&self.chars, self.pos),
+        }
+    }
+
+    /// Raw source up to `\end{env}` and the position after it
+    fn raw_until_end(&self, start: usize, env: &str) -> (String, usize) {
+        let end_tag: Vec<char> = format!("\\end{{{}}}", env).chars().collect();
+        let mut i = start;
+        while i + end_tag.len() <= self.chars.len() {
+            if self.chars[i..i + end_tag.len()] == end_tag[..] {
+                return (self.chars[start..i].iter().collect(), i + end_tag.len());
+            }
+            i += 1;
+        }
+        (self.chars[start.min(self.chars.len())..].iter().collect(), self.chars.len())
+    }
+
+    fn push_heading(&mut self, text: String, depth: Option<u8>) {
+        if text.is_empty() {
+            return;
+        }
+        self.headings.push((self.sections.len(), depth));
+        self.sections.push(section(SectionType::Heading { level: 1 }, text));
+    }
+
+    fn flush(&mut self) {
+        let text = inline_text(&self.pending);
+        self.pending.clear();
+        let in_item = std::mem::take(&mut self.in_item);
+        if text.is_empty() {
+            return;
+        }
+
+        let section_type = match self.lists.last() {
+            Some(&ordered) if in_item => SectionType::ListItem {
+                ordered,
+                depth: (self.lists.len() - 1).min(8) as u8,
+            },
+            _ => SectionType::Paragraph,
+        };
+        self.sections.push(section(section_type, text));
+    }
+}
+
+/// `tabular` body: rows split on `\\`, cells on unescaped `&`
+fn parse_tabular(raw: &str) -> Option<ContentSection> {
+    let rows: Vec<ContentSection> = raw
+        .split("\\\\")
+        .filter_map(|row| {
+            let cells: Vec<String> = split_cells(row).iter().map(|c| inline_text(c)).collect();
+            if cells.iter().all(|c| c.is_empty()) {
+                return None;
+            }
+            Some(ContentSection {
+                section_type: SectionType::TableRow,
+                content: None,
+                runs: None,
+                children: Some(
+                    cells
+                        .into_iter()
+                        .map(|cell| section(SectionType::TableCell, cell))
+                        .collect(),
+                ),
+                properties: None,
+            })
+        })
+        .collect();
+
+    if rows.is_empty() {
+        return None;
+    }
+
+    Some(ContentSection {
+        section_type: SectionType::Table,
+        content: None,
+        runs: None,
+        children: Some(rows),
+        properties: None,
+    })
+}
+
+fn split_cells(row: &str) -> Vec<String> {
+    let mut cells = vec![String::new()];
+    let mut escaped = false;
+    for c in row.chars() {
+        if c == '&' && !escaped {
+            cells.push(String::new());
+        } else if let Some(cell) = cells.last_mut() {
+            cell.push(c);
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    cells
+}
+
+/// Inline pass: strip commands, braces and math, keep the text
+fn inline_text(raw: &str) -> String {
+    let chars: Vec<char> = raw.chars().collect();
+    let mut out = String::with_capacity(raw.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => {
+                let name = command_name(&chars, i + 1);
+                if name.is_empty() {
+                    i = control_symbol(&chars, i, &mut out);
+                    continue;
+                }
+
+                i += 1 + name.chars().count();
+                if chars.get(i) == Some(&'*') {
+                    i += 1;
+                }
+
+                if let Some(&(_, count)) = DROPPED_COMMANDS.iter().find(|(n, _)| *n == name) {
+                    i = skip_arguments(&chars, i, count);
+                    continue;
+                }
+                if let Some(&(_, count)) = LEADING_ARGS_DROPPED.iter().find(|(n, _)| *n == name) {
+                    i = skip_arguments(&chars, i, count);
+                    continue;
+                }
+
+                match name.as_str() {
+                    "LaTeX" => out.push_str("LaTeX"),
+                    "TeX" => out.push_str("TeX"),
+                    "ldots" | "dots" => out.push('…'),
+                    "textbackslash" => out.push('\\'),
+                    // Author separator
+                    "and" => {
+                        out.truncate(out.trim_end().len());
+                        out.push_str(", ");
+                    }
+                    "S" => out.push('§'),
+                    _ => {}
+                }
+                // Drop options of kept commands; their `{...}` text stays
+                if chars.get(i) == Some(&'[') {
+                    if let Some((_, end)) = read_bracketed(&chars, i) {
+                        i = end;
+                    }
+                }
+            }
+            '{' | '}' => i += 1,
+            '$' => {
+                // Inline `$...$` or display `$$...$$` math
+                let display = chars.get(i + 1) == Some(&'$');
+                let start = if display { i + 2 } else { i + 1 };
+                let mut j = start;
+                while j < chars.len() {
+                    if chars[j] == '\\' {
+                        j += 2;
+                        continue;
+                    }
+                    if chars[j] == '$' {
+                        break;
+                    }
+                    j += 1;
+                }
+                i = if display { j + 2 } else { j + 1 };
+            }
+            '~' => {
+                out.push(' ');
+                i += 1;
+            }
+            '-' if chars.get(i + 1) == Some(&'-') => {
+                if chars.get(i + 2) == Some(&'-') {
+                    out.push('—');
+                    i += 3;
+                } else {
+                    out.push('–');
+                    i += 2;
+                }
+            }
+            '`' if chars.get(i + 1) == Some(&'`') => {
+                out.push('“');
+                i += 2;
+            }
+            '\'' if chars.get(i + 1) == Some(&'\'') => {
+                out.push('”');
+                i += 2;
+            }
+            c => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Control symbols (`\%`, `\\`, `\(`...): returns the position after
+fn control_symbol(chars: &[char], i: usize, out: &mut String) -> usize {
+    match chars.get(i + 1) {
+        // Line break, with an optional `[2pt]` spacing
+        Some('\\') => {
+            out.push(' ');
+            let next = i + 2;
+            if chars.get(next) == Some(&'[') {
+                if let Some((_, end)) = read_bracketed(chars, next) {
+                    return end;
+                }
+            }
+            next
+        }
+        Some('(') => skip_past(chars, i + 2, &['\\', ')']),
+        Some('[') => skip_past(chars, i + 2, &['\\', ']']),
+        Some(&c) if "&%$#_{}".contains(c) => {
+            out.push(c);
+            i + 2
+        }
+        Some(',' | ';' | ':' | ' ') => {
+            out.push(' ');
+            i + 2
+        }
+        // Accents (`\'e`) and the like: the accented letter follows
+        Some(_) => i + 2,
+        None => i + 1,
+    }
+}
+
+/// Position after the next occurrence of `pattern`
+fn skip_past(chars: &[char], start: usize, pattern: &[char]) -> usize {
+    let mut i = start;
+    while i + pattern.len() <= chars.len() {
+        if chars[i..i + pattern.len()] == *pattern {
+            return i + pattern.len();
+        }
+        i += 1;
+    }
+    chars.len()
+}
+
+/// Letters of a control word starting at `start` (empty for control symbols)
+fn command_name(chars: &[char], start: usize) -> String {
+    chars
+        .get(start..)
+        .unwrap_or(&[])
+        .iter()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect()
+}
+
+/// Skip `[...]` options and `count` mandatory arguments
+fn skip_arguments(chars: &[char], start: usize, count: usize) -> usize {
+    let mut i = start;
+    for _ in 0..count {
+        i = skip_optional_args(chars, i);
+        match read_argument(chars, i) {
+            Some((_, end)) => i = end,
+            None => return i,
+        }
+    }
+    skip_optional_args(chars, i)
+}
+
+/// Skip any `[...]` option groups (and spaces before them)
+fn skip_optional_args(chars: &[char], start: usize) -> usize {
+    let mut i = start;
+    loop {
+        let next = skip_spaces(chars, i);
+        if chars.get(next) != Some(&'[') {
+            return i;
+        }
+        match read_bracketed(chars, next) {
+            Some((_, end)) => i = end,
+            None => return i,
+        }
+    }
+}
+
+fn skip_spaces(chars: &[char], start: usize) -> usize {
+    let mut i = start;
+    while chars.get(i).is_some_and(|c| *c == ' ' || *c == '\t' || *c == '\n') {
+        i += 1;
+    }
+    i
+}
+
+/// A mandatory argument: a `{...}` group or a single control word / character
+fn read_argument(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let i = skip_spaces(chars, start);
+    match chars.get(i)? {
+        '{' => read_delimited(chars, i, '{', '}'),
+        '\\' => {
+            let name = command_name(chars, i + 1);
+            let end = i + 1 + name.chars().count().max(1);
+            Some((chars[i..end.min(chars.len())].iter().collect(), end))
+        }
+        &c => Some((c.to_string(), i + 1)),
+    }
+}
+
+fn read_bracketed(chars: &[char], start: usize) -> Option<(String, usize)> {
+    read_delimited(chars, start, '[', ']')
+}
+
+/// Content of a balanced group starting at `start` and the position after it
+fn read_delimited(chars: &[char], start: usize, open: char, close: char) -> Option<(String, usize)> {
+    if chars.get(start) != Some(&open) {
+        return None;
+    }
+    let mut depth = 0;
+    let mut i = start;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => {
+                i += 2;
+                continue;
+            }
+            c if c == open => depth += 1,
+            c if c == close => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((chars[start + 1..i].iter().collect(), i + 1));
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+fn section(section_type: SectionType, content: String) -> ContentSection {
+    ContentSection {
+        section_type,
+        content: Some(content),
+        runs: None,
+        children: None,
+        properties: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_nonexistent_file() {
+        let result = extract_latex(Path::new("/nonexistent/file.tex"));
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_parse_document() {
+        let source = r#"\documentclass{article}
+\usepackage[utf8]{inputenc} % encoding
+\title{Groundwater \emph{Recharge}}
+\author{Fatema Ali \and Yusuf Khan\thanks{Funded by a grant}}
+\begin{document}
+\maketitle
+\begin{abstract}
+We measure recharge~rates.
+\end{abstract}
+
+\section{Introduction}\label{sec:intro}
+Rainfall of $x^2$ mm --- as in \cite[p.~3]{smith} --- matters 50\% more.
+% a full-line comment
+
+Second paragraph with \textbf{bold} and \href{http://example.com}{a link}.
+
+\subsection*{Sites}
+\begin{itemize}
+  \item Wadi one
+  \item[Site B] Wadi two
+\end{itemize}
+
+\begin{tabular}{|l|r|}
+\hline
+Site & Depth \\ \hline
+A & 12 \\
+\end{tabular}
+
+\begin{equation}
+E = mc^2
+\end{equation}
+
+\begin{verbatim}
+raw % not a comment
+\end{verbatim}
+\end{document}
+"#;
+
+        let doc = parse_latex(source).unwrap();
+        assert_eq!(doc.metadata.title.as_deref(), Some("Groundwater Recharge"));
+        assert_eq!(doc.metadata.author.as_deref(), Some("Fatema Ali, Yusuf Khan"));
+
+        let kinds: Vec<&SectionType> = doc.sections.iter().map(|s| &s.section_type).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                &SectionType::Heading { level: 1 }, // Abstract
+                &SectionType::Paragraph,
+                &SectionType::Heading { level: 1 }, // Introduction
+                &SectionType::Paragraph,
+                &SectionType::Paragraph,
+                &SectionType::Heading { level: 2 }, // Sites
+                &SectionType::ListItem { ordered: false, depth: 0 },
+                &SectionType::ListItem { ordered: false, depth: 0 },
+                &SectionType::Table,
+                &SectionType::CodeBlock,
+            ]
+        );
+
+        let content = |i: usize| doc.sections[i].content.as_deref().unwrap();
+        assert_eq!(content(1), "We measure recharge rates.");
+        assert_eq!(content(3), "Rainfall of mm — as in — matters 50% more.");
+        assert_eq!(content(4), "Second paragraph with bold and a link.");
+        assert_eq!(content(7), "Site B: Wadi two");
+        assert_eq!(content(9), "raw % not a comment");
+
+        let rows = doc.sections[8].children.as_ref().unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].children.as_ref().unwrap()[1].content.as_deref(), Some("12"));
+    }
+}
@@ -0,0 +1,391 @@
+//! Minimal MIME parsing for MHTML archives and email
+//!
+//! Handles what saved web pages and mail clients actually use:
+//! - Header parsing with folded (continued) lines
+//! - RFC 2047 encoded words in headers, RFC 2231 encoded filenames
+//! - `multipart/*` bodies split on their boundary (recursively)
+//! - `quoted-printable` and `base64` transfer encodings
+//! - Charset decoding via `encoding_rs`
+
+use base64::Engine;
+use encoding_rs::Encoding;
+
+/// A MIME entity: headers plus raw (still transfer-encoded) body
+#[derive(Debug, Clone)]
+pub struct MimePart {
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl MimePart {
+    /// Parse headers and body (separated by the first blank line)
+    pub fn parse(data: &[u8]) -> Self {
+        let (header_bytes, body) = split_header_block(data);
+        let header_text = String::from_utf8_lossy(header_bytes);
+
+        let mut headers: Vec<(String, String)> = Vec::new();
+        for line in header_text.lines() {
+            if line.starts_with([' ', '\t']) {
+                // Folded continuation of the previous header
+                if let Some((_, value)) = headers.last_mut() {
+                    value.push(' ');
+                    value.push_str(line.trim());
+                }
+            } else if let Some((name, value)) = line.split_once(':') {
+                headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+            }
+        }
+
+        Self {
+            headers,
+            body: body.to_vec(),
+        }
+    }
+
+    /// First header with this (case-insensitive) name
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let name = name.to_ascii_lowercase();
+        self.headers
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Lowercased media type, `text/plain` if absent
+    pub fn content_type(&self) -> String {
+        self.header("content-type")
+            .and_then(|v| v.split(';').next())
+            .map(|v| v.trim().to_ascii_lowercase())
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| "text/plain".to_string())
+    }
+
+    /// Parameter of a structured header, e.g. `charset` of `Content-Type`
+    pub fn header_param(&self, header: &str, param: &str) -> Option<String> {
+        let value = self.header(header)?;
+        value.split(';').skip(1).find_map(|part| {
+            let (name, val) = part.split_once('=')?;
+            if name.trim().eq_ignore_ascii_case(param) {
+                Some(val.trim().trim_matches('"').to_string())
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Header with RFC 2047 encoded words decoded (`=?UTF-8?B?...?=`)
+    pub fn decoded_header(&self, name: &str) -> Option<String> {
+        self.header(name).map(decode_header_value)
+    }
+
+    /// Attachment filename from `Content-Disposition` or the `Content-Type` name
+    pub fn filename(&self) -> Option<String> {
+        if let Some(encoded) = self.header_param("content-disposition", "filename*") {
+            // RFC 2231: charset'language'percent-encoded
+            let mut pieces = encoded.splitn(3, '\'');
+            if let (Some(charset), Some(_), Some(value)) = (pieces.next(), pieces.next(), pieces.next()) {
+                let bytes = percent_decode_bytes(value);
+                return Some(decode_charset(&bytes, Some(charset)));
+            }
+        }
+
+        self.header_param("content-disposition", "filename")
+            .or_else(|| self.header_param("content-type", "name"))
+            .map(|name| decode_header_value(&name))
+            .filter(|name| !name.trim().is_empty())
+    }
+
+    /// Whether this part is an attachment rather than a message body
+    pub fn is_attachment(&self) -> bool {
+        let disposition = self
+            .header("content-disposition")
+            .map(|v| v.trim().to_ascii_lowercase())
+            .unwrap_or_default();
+        if disposition.starts_with("attachment") {
+            return true;
+        }
+        let content_type = self.content_type();
+        content_type == "message/rfc822"
+            || (!content_type.starts_with("text/") && self.filename().is_some())
+    }
+
+    /// Body decoded to text using the part's `charset`
+    pub fn decoded_text(&self) -> String {
+        let charset = self.header_param("content-type", "charset");
+        decode_charset(&self.decoded_body(), charset.as_deref())
+    }
+
+    /// Body with the transfer encoding removed
+    pub fn decoded_body(&self) -> Vec<u8> {
+        let encoding = self
+            .header("content-transfer-encoding")
+            .map(|v| v.trim().to_ascii_lowercase())
+            .unwrap_or_default();
+
+        match encoding.as_str() {
+            "quoted-printable" => decode_quoted_printable(&self.body),
+            "base64" => decode_base64(&self.body),
+            _ => self.body.clone(),
+        }
+    }
+
+    /// Sub-parts of a multipart entity (empty for leaf parts)
+    pub fn subparts(&self) -> Vec<MimePart> {
+        if !self.content_type().starts_with("multipart/") {
+            return Vec::new();
+        }
+        match self.header_param("content-type", "boundary") {
+            Some(boundary) => split_multipart(&self.body, &boundary),
+            None => Vec::new(),
+        }
+    }
+
+    /// All non-multipart parts, depth-first in document order
+    pub fn leaf_parts(&self) -> Vec<MimePart> {
+        let subparts = self.subparts();
+        if subparts.is_empty() {
+            return vec![self.clone()];
+        }
+        subparts.iter().flat_map(|p| p.leaf_parts()).collect()
+    }
+}
+
+/// Split raw data at the first blank line (CRLF or LF)
+fn split_header_block(data: &[u8]) -> (&[u8], &[u8]) {
+    let mut i = 0;
+    while i < data.len() {
+        if data[i] == b'\n' {
+            if data.get(i + 1) == Some(&b'\n') {
+                return (&data[..i], &data[i + 2..]);
+            }
+            if data.get(i + 1) == Some(&b'\r') && data.get(i + 2) == Some(&b'\n') {
+                return (&data[..i], &data[i + 3..]);
+            }
+        }
+        i += 1;
+    }
+    (data, &[])
+}
+
+/// Split a multipart body on `--boundary` delimiter lines
+fn split_multipart(body: &[u8], boundary: &str) -> Vec<MimePart> {
+    let delimiter = format!("--{}", boundary);
+    let delimiter = delimiter.as_bytes();
+
+    let mut parts = Vec::new();
+    let mut current: Option<usize> = None;
+    let mut pos = 0;
+
+    while pos < body.len() {
+        let line_end = body[pos..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map(|p| pos + p + 1)
+            .unwrap_or(body.len());
+        let line = trim_line_end(&body[pos..line_end]);
+
+        if line.starts_with(delimiter) {
+            if let Some(start) = current {
+                // The line break before a delimiter belongs to the delimiter
+                let content = trim_line_end(&body[start..pos]);
+                parts.push(MimePart::parse(content));
+            }
+            if line[delimiter.len()..].starts_with(b"--") {
+                break;
+            }
+            current = Some(line_end);
+        }
+
+        pos = line_end;
+    }
+
+    parts
+}
+
+fn trim_line_end(line: &[u8]) -> &[u8] {
+    let mut end = line.len();
+    while end > 0 && (line[end - 1] == b'\n' || line[end - 1] == b'\r') {
+        end -= 1;
+    }
+    &line[..end]
+}
+
+/// Decode a quoted-printable body (soft line breaks removed)
+pub fn decode_quoted_printable(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut i = 0;
+
+    while i < data.len() {
+        if data[i] == b'=' {
+            // Soft line break: "=\r\n" or "=\n"
+            if data.get(i + 1) == Some(&b'\r') && data.get(i + 2) == Some(&b'\n') {
+                i += 3;
+                continue;
+            }
+            if data.get(i + 1) == Some(&b'\n') {
+                i += 2;
+                continue;
+            }
+            let hex = data.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(data[i]);
+        i += 1;
+    }
+
+    out
+}
+
+/// Decode a base64 body, ignoring line breaks and other whitespace
+pub fn decode_base64(data: &[u8]) -> Vec<u8> {
+    let cleaned: Vec<u8> = data
+        .iter()
+        .copied()
+        .filter(|b| !b.is_ascii_whitespace())
+        .collect();
+    base64::engine::general_purpose::STANDARD
+        .decode(&cleaned)
+        .unwrap_or_default()
+}
+
+/// Decode RFC 2047 encoded words (`=?charset?B|Q?text?=`) in a header value
+///
+/// Whitespace between adjacent encoded words is dropped, as the RFC requires.
+pub fn decode_header_value(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    let mut after_encoded_word = false;
+
+    while let Some(start) = rest.find("=?") {
+        let (before, candidate) = rest.split_at(start);
+        match decode_encoded_word(candidate) {
+            Some((decoded, consumed)) => {
+                if !(after_encoded_word && before.trim().is_empty()) {
+                    out.push_str(before);
+                }
+                out.push_str(&decoded);
+                rest = &candidate[consumed..];
+                after_encoded_word = true;
+            }
+            None => {
+                out.push_str(before);
+                out.push_str("=?");
+                rest = &candidate[2..];
+                after_encoded_word = false;
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Decode one encoded word at the start of `s`, returning it and its length
+fn decode_encoded_word(s: &str) -> Option<(String, usize)> {
+    let inner = s.strip_prefix("=?")?;
+    let (charset, rest) = inner.split_once('?')?;
+    let (encoding, rest) = rest.split_once('?')?;
+    let end = rest.find("?=")?;
+    let text = &rest[..end];
+    if text.contains(char::is_whitespace) {
+        return None;
+    }
+
+    // `rest` is a suffix of `s`; the word ends after the closing `?=`
+    let consumed = s.len() - rest.len() + end + 2;
+
+    let bytes = match encoding {
+        "B" | "b" => decode_base64(text.as_bytes()),
+        "Q" | "q" => decode_quoted_printable(text.replace('_', " ").as_bytes()),
+        _ => return None,
+    };
+    // RFC 2231 allows a language suffix: `UTF-8*ar`
+    let charset = charset.split('*').next().unwrap_or(charset);
+
+    Some((decode_charset(&bytes, Some(charset)), consumed))
+}
+
+/// Decode `%XX` escapes to raw bytes
+fn percent_decode_bytes(value: &str) -> Vec<u8> {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    out
+}
+
+/// Decode text in a named charset (UTF-8 if unknown or absent)
+pub fn decode_charset(bytes: &[u8], charset: Option<&str>) -> String {
+    let encoding = charset
+        .and_then(|label| Encoding::for_label(label.trim().as_bytes()))
+        .unwrap_or(encoding_rs::UTF_8);
+    let (text, _, _) = encoding.decode(bytes);
+    text.into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multipart_and_transfer_encodings() {
+        let message = b"MIME-Version: 1.0\r\n\
+Content-Type: multipart/related;\r\n\tboundary=\"----=_NextPart_01\"\r\n\
+\r\n\
+------=_NextPart_01\r\n\
+Content-Type: text/html; charset=\"windows-1256\"\r\n\
+Content-Transfer-Encoding: quoted-printable\r\n\
+\r\n\
+<p>=E3=D1=CD=C8=C7 caf=\r\n\
+e</p>\r\n\
+------=_NextPart_01\r\n\
+Content-Type: text/plain\r\n\
+Content-Transfer-Encoding: base64\r\n\
+\r\n\
+aGVsbG8g\r\nd29ybGQ=\r\n\
+------=_NextPart_01--\r\n";
+
+        let root = MimePart::parse(message);
+        let parts = root.leaf_parts();
+        assert_eq!(parts.len(), 2);
+
+        assert_eq!(parts[0].content_type(), "text/html");
+        let charset = parts[0].header_param("content-type", "charset");
+        let html = decode_charset(&parts[0].decoded_body(), charset.as_deref());
+        assert_eq!(html, "<p>مرحبا cafe</p>");
+
+        assert_eq!(parts[1].decoded_body(), b"hello world");
+    }
+
+    #[test]
+    fn test_encoded_headers_and_filenames() {
+        assert_eq!(
+            decode_header_value("=?UTF-8?Q?caf=C3=A9?= =?UTF-8?B?INmF2LHYrdio2Kc=?= team"),
+            "café مرحبا team"
+        );
+        assert_eq!(decode_header_value("50% =?off"), "50% =?off");
+
+        let part = MimePart::parse(
+            b"Content-Type: application/pdf\r\n\
+Content-Disposition: attachment; filename*=UTF-8''%D8%AA%D9%82%D8%B1%D9%8A%D8%B1.pdf\r\n\
+\r\n",
+        );
+        assert!(part.is_attachment());
+        assert_eq!(part.filename().as_deref(), Some("تقرير.pdf"));
+    }
+}
@@ -2,32 +2,92 @@
 //!
 //! This module provides text extraction for various document formats:
 //! - DOC (Legacy Microsoft Word 97-2003)
-//! - DOCX (Microsoft Word)
-//! - PPTX (Microsoft PowerPoint)  
-//! - XLSX (Microsoft Excel)
-//! - TXT/MD (Plain text)
+//! - DOCX (Microsoft Word, incl. .docm/.dotx/.dotm)
+//! - PPTX (Microsoft PowerPoint, incl. .pptm/.potx)
+//! - XLSX (Microsoft Excel, incl. .xlsm/.xltx)
+//! - TXT/MD (Plain text), plus user-configured text/code extensions and file name patterns
+//! - HTML/MHTML (Saved web pages, "Save as Web Page" exports)
+//! - EPUB (E-books, chapters in spine order)
+//! - CSV/TSV (Delimited data, dialect and header sniffing)
+//! - EML/MBOX/MSG (Email, mbox messages and attachments as child entries)
+//! - IPYNB (Jupyter notebooks: markdown, code cells and text outputs)
+//! - SRT/VTT (Subtitles, cue timestamps kept as locations)
+//! - TEX (LaTeX, commands and comments stripped)
+//! - JPEG/PNG/TIFF (Images: EXIF, XMP and IPTC descriptive metadata)
+//!
+//! Thumbnails for result cards come from `thumbnail` (embedded previews only),
+//! and document outlines (tables of contents) from `outline`.
+//!
+//! Chart and SmartArt text in OOXML packages is reached through the shared
+//! relationship helpers in `ooxml`.
+//!
+//! It also detects each document's language from its extracted text.
 
+mod csv;
 mod doc;
 mod docx;
+mod email;
+mod epub;
+mod html;
+mod image;
+mod language;
+mod latex;
+mod mime;
+mod notebook;
+mod ooxml;
+mod outline;
 mod pptx;
+mod subtitles;
 mod text;
+mod thumbnail;
 mod xlsx;
 
-pub use doc::extract_doc;
+pub use csv::{extract_csv, extract_csv_structured};
+pub use doc::{extract_doc, extract_doc_structured};
+pub use language::{annotate_text_direction, detect_direction, detect_language, UNDETERMINED};
+pub use latex::{extract_latex, extract_latex_structured};
+pub use notebook::{extract_notebook, extract_notebook_structured};
 pub use docx::extract_docx;
 pub use docx::extract_docx_structured;
+pub use email::{
+    child_path, container_path, extract_child_content, extract_child_structured, extract_email,
+    extract_email_entries, extract_email_structured, split_child_path, EmailEntry,
+};
+pub use epub::{extract_epub, extract_epub_structured};
+pub use html::{extract_html, extract_html_structured, extract_mhtml, extract_mhtml_structured};
+pub use image::{extract_image, extract_image_structured};
+pub use outline::{extract_outline, outline_from_sections};
 pub use pptx::extract_pptx;
 pub use pptx::extract_pptx_structured;
-pub use text::extract_text;
+pub use subtitles::{extract_subtitles, extract_subtitles_structured};
+pub use text::{
+    extract_text, known_text_type, normalize_extension, TextFileRules, DEFAULT_TEXT_EXTENSIONS,
+    DEFAULT_TEXT_PATTERNS,
+};
+pub use thumbnail::{extract_thumbnail, Thumbnail, THUMBNAIL_MAX_BYTES};
 pub use xlsx::extract_xlsx;
 
 use crate::models::DocumentContent;
 use std::path::Path;
 
 /// Supported file extensions
-pub const SUPPORTED_EXTENSIONS: &[&str] = &["doc", "docx", "pptx", "xlsx", "txt", "md"];
+pub const SUPPORTED_EXTENSIONS: &[&str] = &[
+    "doc", "docx", "docm", "dotx", "dotm", "pptx", "pptm", "potx", "xlsx", "xlsm", "xltx", "txt",
+    "md", "htm", "html", "mht", "mhtml", "epub", "csv", "tsv", "eml", "mbox", "msg", "ipynb", "srt",
+    "vtt", "tex", "jpg", "jpeg", "png", "tif", "tiff",
+];
+
+/// OOXML (ZIP-based) extensions that can carry a VBA project
+pub const OOXML_EXTENSIONS: &[&str] = &[
+    "docx", "docm", "dotx", "dotm", "pptx", "pptm", "potx", "xlsx", "xlsm", "xltx",
+];
 
-/// All supported extensions (alias for compatibility)
+/// Email containers whose messages/attachments may be indexed as child entries
+pub const EMAIL_EXTENSIONS: &[&str] = &["eml", "mbox", "msg"];
+
+/// All built-in extensions (alias for compatibility)
+///
+/// Configured text/code types are not listed; use [`is_supported_file`].
 pub const ALL_EXTENSIONS: &[&str] = SUPPORTED_EXTENSIONS;
 
 /// Extract content from any supported file type
@@ -35,34 +95,48 @@ pub fn extract_content(path: &Path, ext: &str) -> Option<String> {
     match ext {
         "txt" | "md" => extract_text(path),
         "doc" => extract_doc(path),
-        "docx" => extract_docx(path),
-        "pptx" => extract_pptx(path),
-        "xlsx" => extract_xlsx(path),
-        _ => None,
+        "docx" | "docm" | "dotx" | "dotm" => extract_docx(path),
+        "pptx" | "pptm" | "potx" => extract_pptx(path),
+        "xlsx" | "xlsm" | "xltx" => extract_xlsx(path),
+        "htm" | "html" => extract_html(path),
+        "mht" | "mhtml" => extract_mhtml(path),
+        "epub" => extract_epub(path),
+        "csv" | "tsv" => extract_csv(path, ext),
+        "eml" | "mbox" | "msg" => extract_email(path, ext),
+        "ipynb" => extract_notebook(path),
+        "srt" | "vtt" => extract_subtitles(path),
+        "tex" => extract_latex(path),
+        "jpg" | "jpeg" | "png" | "tif" | "tiff" => extract_image(path),
+        // Configured text types (callers check the text rules)
+        _ => extract_text(path),
     }
 }
 
 /// Extract structured content from any supported file type (for rich preview)
 pub fn extract_content_structured(path: &Path, ext: &str) -> Option<DocumentContent> {
     match ext {
-        "docx" => extract_docx_structured(path),
-        // For .doc files, we return plain text wrapped in a simple structure
-        "doc" => extract_doc(path).map(|content| DocumentContent {
-            doc_type: "doc".to_string(),
-            sections: vec![crate::models::ContentSection {
-                section_type: crate::models::SectionType::Paragraph,
-                content: Some(content),
-                runs: None,
-                children: None,
-                properties: None,
-            }],
-            metadata: crate::models::DocumentMetadata::default(),
-        }),
+        "docx" | "docm" | "dotx" | "dotm" => extract_docx_structured(path),
+        "doc" => extract_doc_structured(path),
         // TODO: Add structured extraction for other formats
-        "pptx" => extract_pptx_structured(path),
+        "pptx" | "pptm" | "potx" => extract_pptx_structured(path),
+        "htm" | "html" => extract_html_structured(path),
+        "mht" | "mhtml" => extract_mhtml_structured(path),
+        "epub" => extract_epub_structured(path),
+        "csv" | "tsv" => extract_csv_structured(path, ext),
+        "eml" | "mbox" | "msg" => extract_email_structured(path, ext),
+        "ipynb" => extract_notebook_structured(path),
+        "srt" | "vtt" => extract_subtitles_structured(path),
+        "tex" => extract_latex_structured(path),
+        "jpg" | "jpeg" | "png" | "tif" | "tiff" => extract_image_structured(path),
         // "xlsx" => extract_xlsx_structured(path),
-        // For txt/md, we return plain text wrapped in a simple structure
-        "txt" | "md" => extract_text(path).map(|content| DocumentContent {
+        // Markdown keeps its headings, lists and code blocks (and so an outline)
+        "md" => extract_text(path).map(|content| DocumentContent {
+            doc_type: "text".to_string(),
+            sections: notebook::markdown_sections(&content),
+            metadata: crate::models::DocumentMetadata::default(),
+        }),
+        // For txt, we return plain text wrapped in a simple structure
+        "txt" => extract_text(path).map(|content| DocumentContent {
             doc_type: "text".to_string(),
             sections: vec![crate::models::ContentSection {
                 section_type: crate::models::SectionType::Paragraph,
@@ -73,22 +147,78 @@ pub fn extract_content_structured(path: &Path, ext: &str) -> Option<DocumentCont
             }],
             metadata: crate::models::DocumentMetadata::default(),
         }),
+        // Configured text types: source as a code block, the rest as plain text
+        // (callers check the text rules; built-in types without a structured
+        // extractor have no rich preview)
+        _ if get_file_type(ext).is_none() => {
+            let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            let file_type = text::text_kind(file_name, ext);
+            let section_type = match file_type {
+                "code" => crate::models::SectionType::CodeBlock,
+                _ => crate::models::SectionType::Paragraph,
+            };
+            extract_text(path).map(|content| DocumentContent {
+                doc_type: file_type.to_string(),
+                sections: vec![crate::models::ContentSection {
+                    section_type,
+                    content: Some(content),
+                    runs: None,
+                    children: None,
+                    properties: None,
+                }],
+                metadata: crate::models::DocumentMetadata::default(),
+            })
+        }
         _ => None,
     }
 }
 
-/// Check if extension is supported
-pub fn is_supported_extension(ext: &str) -> bool {
-    ALL_EXTENSIONS.contains(&ext.to_lowercase().as_str())
+/// Check if extension is supported (built-in or configured as text)
+pub fn is_supported_extension(ext: &str, rules: &TextFileRules) -> bool {
+    get_file_type(ext)
+        .or_else(|| rules.file_type("", ext))
+        .is_some()
+}
+
+/// Check if a file is supported, by extension or configured file name pattern
+pub fn is_supported_file(path: &Path, rules: &TextFileRules) -> bool {
+    file_type_for_path(path, rules).is_some()
+}
+
+/// Get file type string for a file, honoring configured file name patterns
+pub fn file_type_for_path(path: &Path, rules: &TextFileRules) -> Option<&'static str> {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+
+    get_file_type(&ext).or_else(|| rules.file_type(file_name, &ext))
 }
 
-/// Get file type string from extension
+/// Get file type string from a built-in extension
+///
+/// Configured text types are covered by [`file_type_for_path`].
 pub fn get_file_type(ext: &str) -> Option<&'static str> {
     match ext.to_lowercase().as_str() {
-        "doc" | "docx" => Some("word"),
-        "pptx" => Some("powerpoint"),
-        "xlsx" => Some("excel"),
+        "doc" | "docx" | "docm" | "dotx" | "dotm" => Some("word"),
+        "pptx" | "pptm" | "potx" => Some("powerpoint"),
+        "xlsx" | "xlsm" | "xltx" => Some("excel"),
         "txt" | "md" => Some("text"),
+        "htm" | "html" | "mht" | "mhtml" => Some("html"),
+        "epub" => Some("epub"),
+        "csv" | "tsv" => Some("csv"),
+        "eml" | "mbox" | "msg" => Some("email"),
+        "ipynb" => Some("notebook"),
+        "srt" | "vtt" => Some("subtitles"),
+        "tex" => Some("latex"),
+        "jpg" | "jpeg" | "png" | "tif" | "tiff" => Some("image"),
         _ => None,
     }
 }
+
+/// Check whether a file contains macros (an OOXML package with `vbaProject.bin`)
+pub fn has_macros(path: &Path, ext: &str) -> bool {
+    OOXML_EXTENSIONS.contains(&ext.to_lowercase().as_str()) && ooxml::has_vba_project(path)
+}
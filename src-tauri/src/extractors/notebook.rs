@@ -0,0 +1,340 @@
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+use super::html::document_to_text;
+use crate::models::{
+    ContentSection, DocumentContent, DocumentMetadata, SectionProperties, SectionType,
+};
+
+/// Extract text content from a Jupyter notebook (plain text for indexing)
+pub fn extract_notebook(path: &Path) -> Option<String> {
+    document_to_text(&extract_notebook_structured(path)?)
+}
+
+/// Extract structured content from a Jupyter notebook (for rich preview)
+///
+/// Notebooks (nbformat 4) are JSON:
+/// - Markdown cells become headings, list items and paragraphs
+/// - Code cells become code blocks located by their execution count (`In [3]`)
+/// - Text outputs (streams, results, errors) follow as code blocks (`Out [3]`),
+///   PNG/JPEG outputs as images
+pub fn extract_notebook_structured(path: &Path) -> Option<DocumentContent> {
+    let bytes = fs::read(path).ok()?;
+    let notebook: Value = serde_json::from_slice(&bytes).ok()?;
+    parse_notebook(&notebook)
+}
+
+fn parse_notebook(notebook: &Value) -> Option<DocumentContent> {
+    let cells = notebook.get("cells")?.as_array()?;
+    let mut sections = Vec::new();
+
+    for cell in cells {
+        let source = multiline_text(cell.get("source"));
+        match cell.get("cell_type").and_then(Value::as_str) {
+            Some("markdown") => sections.extend(markdown_sections(&source)),
+            Some("code") => {
+                let execution_count = cell.get("execution_count").and_then(Value::as_u64);
+                if !source.trim().is_empty() {
+                    sections.push(code_section(
+                        source.trim_end(),
+                        Some(cell_location("In", execution_count)),
+                    ));
+                }
+                if let Some(outputs) = cell.get("outputs").and_then(Value::as_array) {
+                    for output in outputs {
+                        sections.extend(output_section(output, execution_count));
+                    }
+                }
+            }
+            Some("raw") if !source.trim().is_empty() => {
+                sections.push(code_section(source.trim_end(), None))
+            }
+            _ => {}
+        }
+    }
+
+    let nb_metadata = notebook.get("metadata");
+    let title = nb_metadata
+        .and_then(|m| m.get("title"))
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let authors: Vec<&str> = nb_metadata
+        .and_then(|m| m.get("authors"))
+        .and_then(Value::as_array)
+        .map(|authors| {
+            authors
+                .iter()
+                .filter_map(|a| a.get("name").and_then(Value::as_str))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if sections.is_empty() {
+        return None;
+    }
+
+    Some(DocumentContent {
+        doc_type: "notebook".to_string(),
+        sections,
+        metadata: DocumentMetadata {
+            title,
+            author: (!authors.is_empty()).then(|| authors.join(", ")),
+            ..Default::default()
+        },
+    })
+}
+
+/// nbformat stores multi-line strings as a string or a list of lines
+fn multiline_text(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(lines)) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+fn cell_location(prefix: &str, execution_count: Option<u64>) -> String {
+    match execution_count {
+        Some(n) => format!("{} [{}]", prefix, n),
+        None => format!("{} [ ]", prefix),
+    }
+}
+
+/// One output of a code cell: text as a code block, PNG/JPEG as an image
+fn output_section(output: &Value, execution_count: Option<u64>) -> Option<ContentSection> {
+    let location = Some(cell_location("Out", execution_count));
+
+    match output.get("output_type").and_then(Value::as_str)? {
+        "stream" => {
+            let text = multiline_text(output.get("text"));
+            (!text.trim().is_empty()).then(|| code_section(text.trim_end(), location))
+        }
+        "execute_result" | "display_data" => {
+            let data = output.get("data")?;
+            for mime in ["image/png", "image/jpeg"] {
+                if let Some(encoded) = data.get(mime).and_then(Value::as_str) {
+                    let encoded: String = encoded.split_whitespace().collect();
+                    return Some(ContentSection {
+                        section_type: SectionType::Image,
+                        content: None,
+                        runs: None,
+                        children: None,
+                        properties: Some(SectionProperties {
+                            image_data: Some(format!("data:{};base64,{}", mime, encoded)),
+                            location,
+                            ..Default::default()
+                        }),
+                    });
+                }
+            }
+            let text = multiline_text(data.get("text/plain"));
+            (!text.trim().is_empty()).then(|| code_section(text.trim_end(), location))
+        }
+        "error" => {
+            let name = output.get("ename").and_then(Value::as_str).unwrap_or("Error");
+            let value = output.get("evalue").and_then(Value::as_str).unwrap_or("");
+            Some(code_section(&format!("{}: {}", name, value), location))
+        }
+        _ => None,
+    }
+}
+
+fn code_section(code: &str, location: Option<String>) -> ContentSection {
+    ContentSection {
+        section_type: SectionType::CodeBlock,
+        content: Some(code.to_string()),
+        runs: None,
+        children: None,
+        properties: location.map(|location| SectionProperties {
+            location: Some(location),
+            ..Default::default()
+        }),
+    }
+}
+
+/// Block-level Markdown: ATX headings, list items, fenced code, paragraphs
+pub(super) fn markdown_sections(source: &str) -> Vec<ContentSection> {
+    let mut sections = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut fence: Option<Vec<&str>> = None;
+
+    let flush = |paragraph: &mut Vec<&str>, sections: &mut Vec<ContentSection>| {
+        if !paragraph.is_empty() {
+            sections.push(text_section(SectionType::Paragraph, &paragraph.join(" ")));
+            paragraph.clear();
+        }
+    };
+
+    for line in source.lines() {
+        let trimmed = line.trim();
+
+        if let Some(code) = fence.as_mut() {
+            if trimmed.starts_with("```") {
+                sections.push(code_section(&code.join("\n"), None));
+                fence = None;
+            } else {
+                code.push(line);
+            }
+            continue;
+        }
+
+        if trimmed.starts_with("```") {
+            flush(&mut paragraph, &mut sections);
+            fence = Some(Vec::new());
+        } else if trimmed.is_empty() {
+            flush(&mut paragraph, &mut sections);
+        } else if let Some(level) = heading_level(trimmed) {
+            flush(&mut paragraph, &mut sections);
+            let text = trimmed.trim_start_matches('#').trim().trim_end_matches('#').trim();
+            sections.push(text_section(SectionType::Heading { level }, text));
+        } else if let Some((ordered, text)) = list_item(trimmed) {
+            flush(&mut paragraph, &mut sections);
+            let indent = line.len() - line.trim_start().len();
+            sections.push(text_section(
+                SectionType::ListItem {
+                    ordered,
+                    depth: (indent / 2).min(8) as u8,
+                },
+                text,
+            ));
+        } else {
+            paragraph.push(trimmed);
+        }
+    }
+
+    if let Some(code) = fence {
+        sections.push(code_section(&code.join("\n"), None));
+    }
+    flush(&mut paragraph, &mut sections);
+    sections
+}
+
+fn heading_level(line: &str) -> Option<u8> {
+    let hashes = line.chars().take_while(|&c| c == '#').count();
+    let rest = &line[hashes..];
+    ((1..=6).contains(&hashes) && (rest.is_empty() || rest.starts_with(' ')))
+        .then_some(hashes as u8)
+}
+
+/// `- item`, `* item`, `+ item` or `1. item` / `1) item`
+fn list_item(line: &str) -> Option<(bool, &str)> {
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(text) = line.strip_prefix(bullet) {
+            return Some((false, text.trim()));
+        }
+    }
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    let rest = &line[digits..];
+    if digits > 0 && (rest.starts_with(". ") || rest.starts_with(") ")) {
+        return Some((true, rest[2..].trim()));
+    }
+    None
+}
+
+fn text_section(section_type: SectionType, text: &str) -> ContentSection {
+    ContentSection {
+        section_type,
+        content: Some(strip_inline_markdown(text)),
+        runs: None,
+        children: None,
+        properties: None,
+    }
+}
+
+/// Drop emphasis markers and keep link text: `**a** [b](url)` -> `a b`
+fn strip_inline_markdown(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        // Links and images: [text](target) / ![alt](src)
+        if c == '[' || (c == '!' && chars.get(i + 1) == Some(&'[')) {
+            let start = if c == '!' { i + 2 } else { i + 1 };
+            let close = chars[start..].iter().position(|&c| c == ']').map(|p| start + p);
+            if let Some(close) = close.filter(|&close| chars.get(close + 1) == Some(&'(')) {
+                if let Some(end) = chars[close + 2..].iter().position(|&c| c == ')') {
+                    let label: String = chars[start..close].iter().collect();
+                    out.push_str(&strip_inline_markdown(&label));
+                    i = close + 2 + end + 1;
+                    continue;
+                }
+            }
+        }
+
+        // `_` inside a word (snake_case) is not emphasis
+        let inside_word = i > 0
+            && chars[i - 1].is_alphanumeric()
+            && chars.get(i + 1).is_some_and(|c| c.is_alphanumeric());
+        match c {
+            '*' | '`' => {}
+            '_' if !inside_word => {}
+            _ => out.push(c),
+        }
+        i += 1;
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_nonexistent_file() {
+        let result = extract_notebook(Path::new("/nonexistent/file.ipynb"));
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_cells_and_outputs() {
+        let notebook: Value = serde_json::from_str(
+            r##"{
+                "metadata": {"authors": [{"name": "Huda"}]},
+                "nbformat": 4,
+                "cells": [
+                    {"cell_type": "markdown", "source": ["# Rainfall analysis\n", "\n", "Uses the **2023** [station data](data.csv) for `rain_mm`.\n", "- daily totals"]},
+                    {"cell_type": "code", "execution_count": 3, "source": "df.mean()",
+                     "outputs": [
+                        {"output_type": "stream", "name": "stdout", "text": ["loaded 365 rows\n"]},
+                        {"output_type": "execute_result", "data": {"text/plain": ["12.4"]}},
+                        {"output_type": "display_data", "data": {"image/png": "iVBORw0K\nGgo=\n", "text/plain": ["<Figure>"]}}
+                     ]},
+                    {"cell_type": "code", "execution_count": null, "source": "", "outputs": []}
+                ]
+            }"##,
+        )
+        .unwrap();
+
+        let doc = parse_notebook(&notebook).unwrap();
+        assert_eq!(doc.metadata.author.as_deref(), Some("Huda"));
+        assert_eq!(doc.sections[0].section_type, SectionType::Heading { level: 1 });
+        assert_eq!(
+            doc.sections[1].content.as_deref(),
+            Some("Uses the 2023 station data for rain_mm.")
+        );
+        assert!(matches!(doc.sections[2].section_type, SectionType::ListItem { ordered: false, .. }));
+
+        let code = &doc.sections[3];
+        assert_eq!(code.section_type, SectionType::CodeBlock);
+        assert_eq!(
+            code.properties.as_ref().unwrap().location.as_deref(),
+            Some("In [3]")
+        );
+        assert_eq!(doc.sections[4].content.as_deref(), Some("loaded 365 rows"));
+        assert_eq!(doc.sections[5].content.as_deref(), Some("12.4"));
+        assert_eq!(
+            doc.sections[6].properties.as_ref().unwrap().image_data.as_deref(),
+            Some("data:image/png;base64,iVBORw0KGgo=")
+        );
+        assert_eq!(doc.sections.len(), 7);
+
+        let text = document_to_text(&doc).unwrap();
+        assert!(text.contains("df.mean()"));
+        assert!(text.contains("loaded 365 rows"));
+    }
+}
@@ -0,0 +1,504 @@
+//! Shared OOXML package helpers
+//!
+//! DOCX/PPTX/XLSX parts reference charts and SmartArt through relationship
+//! (`.rels`) files rather than embedding their text. This module follows
+//! those relationships and extracts the text of:
+//! - Charts (`*/charts/chartN.xml`): title, axis titles, series names, categories
+//! - SmartArt data (`*/diagrams/dataN.xml`): node text
+//!
+//! Spreadsheets reference both through drawings (`xl/drawings/drawingN.xml`),
+//! which are followed one level deep.
+
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+use std::fs;
+use std::io::{BufReader, Read, Seek};
+use std::path::Path;
+use zip::ZipArchive;
+
+use crate::models::{ContentSection, SectionType};
+
+/// A relationship from a `.rels` part, with its target resolved to a package path
+#[derive(Debug, Clone)]
+pub struct Relationship {
+    pub id: String,
+    pub rel_type: String,
+    pub target: String,
+}
+
+/// Kind of embedded part carrying indexable text
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EmbeddedKind {
+    Chart,
+    Diagram,
+}
+
+/// A chart or diagram part referenced from a source part
+#[derive(Debug, Clone)]
+pub struct EmbeddedPart {
+    /// Relationship ID in the source part (e.g. `rId5`)
+    pub rel_id: String,
+    pub kind: EmbeddedKind,
+    pub path: String,
+}
+
+/// Text extracted from a chart or diagram part
+#[derive(Debug, Clone)]
+pub struct EmbeddedText {
+    pub kind: EmbeddedKind,
+    pub title: Option<String>,
+    pub labels: Vec<String>,
+}
+
+impl EmbeddedText {
+    /// Flatten to a single line for the search index
+    pub fn to_plain_text(&self) -> String {
+        self.title
+            .iter()
+            .chain(self.labels.iter())
+            .map(|s| s.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Build a labeled preview block with one paragraph per label
+    pub fn to_section(&self) -> ContentSection {
+        let section_type = match self.kind {
+            EmbeddedKind::Chart => SectionType::Chart {
+                title: self.title.clone(),
+            },
+            EmbeddedKind::Diagram => SectionType::Diagram,
+        };
+
+        let children = self
+            .labels
+            .iter()
+            .map(|label| ContentSection {
+                section_type: SectionType::Paragraph,
+                content: Some(label.clone()),
+                runs: None,
+                children: None,
+                properties: None,
+            })
+            .collect();
+
+        ContentSection {
+            section_type,
+            content: None,
+            runs: None,
+            children: Some(children),
+            properties: None,
+        }
+    }
+}
+
+/// Path of the `.rels` part for a source part
+///
+/// `word/document.xml` -> `word/_rels/document.xml.rels`
+pub fn rels_path(part: &str) -> String {
+    match part.rfind('/') {
+        Some(idx) => format!("{}/_rels/{}.rels", &part[..idx], &part[idx + 1..]),
+        None => format!("_rels/{}.rels", part),
+    }
+}
+
+/// Resolve a relationship target relative to its source part
+///
+/// `("ppt/slides/slide1.xml", "../charts/chart1.xml")` -> `ppt/charts/chart1.xml`
+pub fn resolve_target(part: &str, target: &str) -> String {
+    // Absolute targets are relative to the package root
+    if let Some(stripped) = target.strip_prefix('/') {
+        return stripped.to_string();
+    }
+
+    let mut segments: Vec<&str> = part.split('/').collect();
+    segments.pop(); // Drop the source file name
+
+    for segment in target.split('/') {
+        match segment {
+            ".." => {
+                segments.pop();
+            }
+            "." | "" => {}
+            other => segments.push(other),
+        }
+    }
+
+    segments.join("/")
+}
+
+/// Read the relationships of a part (empty if it has none)
+pub fn read_relationships<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    part: &str,
+) -> Vec<Relationship> {
+    let mut relationships = Vec::new();
+
+    let Ok(rels_file) = archive.by_name(&rels_path(part)) else {
+        return relationships;
+    };
+
+    let mut reader = Reader::from_reader(BufReader::new(rels_file));
+    reader.config_mut().trim_text(true);
+    let mut buf = Vec::with_capacity(512);
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e))
+                if e.local_name().as_ref() == b"Relationship" =>
+            {
+                let mut id = String::new();
+                let mut rel_type = String::new();
+                let mut target = String::new();
+                let mut external = false;
+
+                for attr in e.attributes().filter_map(|a| a.ok()) {
+                    let value = String::from_utf8_lossy(&attr.value).to_string();
+                    match attr.key.local_name().as_ref() {
+                        b"Id" => id = value,
+                        b"Type" => rel_type = value,
+                        b"Target" => target = value,
+                        b"TargetMode" => external = value == "External",
+                        _ => {}
+                    }
+                }
+
+                if !external && !target.is_empty() {
+                    relationships.push(Relationship {
+                        id,
+                        rel_type,
+                        target: resolve_target(part, &target),
+                    });
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(_) => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    relationships
+}
+
+/// Find charts and SmartArt referenced by a part (following drawings one level)
+pub fn embedded_parts<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    part: &str,
+) -> Vec<EmbeddedPart> {
+    let mut parts = Vec::new();
+
+    for rel in read_relationships(archive, part) {
+        if rel.rel_type.ends_with("/chart") || rel.rel_type.ends_with("/chartEx") {
+            parts.push(EmbeddedPart {
+                rel_id: rel.id,
+                kind: EmbeddedKind::Chart,
+                path: rel.target,
+            });
+        } else if rel.rel_type.ends_with("/diagramData") {
+            parts.push(EmbeddedPart {
+                rel_id: rel.id,
+                kind: EmbeddedKind::Diagram,
+                path: rel.target,
+            });
+        } else if rel.rel_type.ends_with("/drawing") {
+            // Spreadsheet drawings hold the chart/diagram relationships
+            for nested in read_relationships(archive, &rel.target) {
+                let kind = if nested.rel_type.ends_with("/chart")
+                    || nested.rel_type.ends_with("/chartEx")
+                {
+                    EmbeddedKind::Chart
+                } else if nested.rel_type.ends_with("/diagramData") {
+                    EmbeddedKind::Diagram
+                } else {
+                    continue;
+                };
+                parts.push(EmbeddedPart {
+                    rel_id: nested.id,
+                    kind,
+                    path: nested.target,
+                });
+            }
+        }
+    }
+
+    parts
+}
+
+/// Extract text from a chart or diagram part
+pub fn extract_embedded_text<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    part: &EmbeddedPart,
+) -> Option<EmbeddedText> {
+    let file = archive.by_name(&part.path).ok()?;
+    let reader = BufReader::new(file);
+
+    let text = match part.kind {
+        EmbeddedKind::Chart => parse_chart_xml(reader),
+        EmbeddedKind::Diagram => parse_diagram_xml(reader),
+    };
+
+    if text.title.is_none() && text.labels.is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+/// Append the text of every chart/diagram referenced by `part` to `content`
+pub fn append_embedded_text<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    part: &str,
+    content: &mut String,
+) {
+    for embedded in embedded_parts(archive, part) {
+        if let Some(text) = extract_embedded_text(archive, &embedded) {
+            content.push_str(&text.to_plain_text());
+            content.push(' ');
+        }
+    }
+}
+
+/// Check whether a package contains a VBA project (`word/vbaProject.bin`, etc.)
+///
+/// Only the ZIP central directory is read.
+pub fn has_vba_project(path: &Path) -> bool {
+    let Ok(file) = fs::File::open(path) else {
+        return false;
+    };
+    let Ok(archive) = ZipArchive::new(file) else {
+        return false;
+    };
+    let found = archive
+        .file_names()
+        .any(|name| name.to_ascii_lowercase().ends_with("vbaproject.bin"));
+    found
+}
+
+/// Parse chart XML (`c:chartSpace`)
+///
+/// - Chart title: first `c:title` outside the plot area
+/// - Labels: axis titles, series names (`c:tx`), categories (`c:cat`)
+/// - Numeric values (`c:val`) are skipped
+///
+/// Text is read untrimmed: a title split into runs (`Market ` + `Entry`)
+/// keeps the spaces between its words.
+fn parse_chart_xml<R: Read>(reader: R) -> EmbeddedText {
+    let mut xml_reader = Reader::from_reader(BufReader::new(reader));
+
+    let mut buf = Vec::with_capacity(512);
+    let mut stack: Vec<Vec<u8>> = Vec::new();
+    let mut title: Option<String> = None;
+    let mut labels: Vec<String> = Vec::new();
+    let mut current_title = String::new();
+
+    loop {
+        match xml_reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => {
+                // Paragraphs of a title are separate lines
+                if e.local_name().as_ref() == b"p" && !current_title.is_empty() {
+                    current_title.push(' ');
+                }
+                stack.push(e.local_name().as_ref().to_vec());
+            }
+            Ok(Event::End(e)) => {
+                if e.local_name().as_ref() == b"title" {
+                    let text = collapse_whitespace(&current_title);
+                    if !text.is_empty() {
+                        let in_plot_area = stack.iter().any(|n| n == b"plotArea");
+                        if title.is_none() && !in_plot_area {
+                            title = Some(text);
+                        } else {
+                            push_unique(&mut labels, text);
+                        }
+                    }
+                    current_title.clear();
+                }
+                stack.pop();
+            }
+            Ok(Event::Text(e)) => {
+                let Some(current) = stack.last() else {
+                    buf.clear();
+                    continue;
+                };
+                if current != b"t" && current != b"v" {
+                    buf.clear();
+                    continue;
+                }
+                let Ok(text) = e.unescape() else {
+                    buf.clear();
+                    continue;
+                };
+
+                if stack.iter().any(|n| n == b"title") {
+                    current_title.push_str(&text);
+                } else if stack
+                    .iter()
+                    .any(|n| n == b"val" || n == b"yVal" || n == b"xVal")
+                {
+                    // Numeric data - not useful for search
+                } else if current == b"t" || stack.iter().any(|n| n == b"tx" || n == b"cat") {
+                    push_unique(&mut labels, text.trim().to_string());
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(_) => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    EmbeddedText {
+        kind: EmbeddedKind::Chart,
+        title,
+        labels,
+    }
+}
+
+/// Parse SmartArt data XML (`dgm:dataModel`), one label per node (`dgm:pt`)
+///
+/// Text is read untrimmed, as in charts.
+fn parse_diagram_xml<R: Read>(reader: R) -> EmbeddedText {
+    let mut xml_reader = Reader::from_reader(BufReader::new(reader));
+
+    let mut buf = Vec::with_capacity(512);
+    let mut labels: Vec<String> = Vec::new();
+    let mut in_text = false;
+    let mut current_node = String::new();
+
+    loop {
+        match xml_reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => match e.local_name().as_ref() {
+                b"pt" => current_node.clear(),
+                b"t" => in_text = true,
+                b"p" if !current_node.is_empty() => current_node.push(' '),
+                _ => {}
+            },
+            Ok(Event::End(e)) => match e.local_name().as_ref() {
+                b"pt" => {
+                    push_unique(&mut labels, collapse_whitespace(&current_node));
+                    current_node.clear();
+                }
+                b"t" => in_text = false,
+                _ => {}
+            },
+            Ok(Event::Text(e)) if in_text => {
+                if let Ok(text) = e.unescape() {
+                    current_node.push_str(&text);
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(_) => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    EmbeddedText {
+        kind: EmbeddedKind::Diagram,
+        title: None,
+        labels,
+    }
+}
+
+/// Text with runs of whitespace (line breaks, spaces at run edges) as one space
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Add a non-empty label once, keeping first-seen order
+fn push_unique(labels: &mut Vec<String>, label: String) {
+    if !label.is_empty() && !labels.contains(&label) {
+        labels.push(label);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::write::FileOptions;
+    use zip::ZipWriter;
+
+    const CHART_REL: &str =
+        "http://schemas.openxmlformats.org/officeDocument/2006/relationships/chart";
+    const DIAGRAM_REL: &str =
+        "http://schemas.openxmlformats.org/officeDocument/2006/relationships/diagramData";
+
+    fn build_package(files: &[(&str, &str)]) -> ZipArchive<Cursor<Vec<u8>>> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in files {
+            writer.start_file(*name, FileOptions::default()).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        ZipArchive::new(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_target() {
+        assert_eq!(
+            rels_path("word/document.xml"),
+            "word/_rels/document.xml.rels"
+        );
+        assert_eq!(
+            resolve_target("ppt/slides/slide1.xml", "../charts/chart1.xml"),
+            "ppt/charts/chart1.xml"
+        );
+        assert_eq!(
+            resolve_target("word/document.xml", "charts/chart1.xml"),
+            "word/charts/chart1.xml"
+        );
+        assert_eq!(
+            resolve_target("xl/workbook.xml", "/xl/sheet.xml"),
+            "xl/sheet.xml"
+        );
+    }
+
+    #[test]
+    fn test_chart_and_diagram_text() {
+        let rels = format!(
+            r#"<Relationships>
+                <Relationship Id="rId1" Type="{}" Target="../charts/chart1.xml"/>
+                <Relationship Id="rId2" Type="{}" Target="../diagrams/data1.xml"/>
+            </Relationships>"#,
+            CHART_REL, DIAGRAM_REL
+        );
+        let chart = r#"<c:chartSpace xmlns:c="c" xmlns:a="a"><c:chart>
+            <c:title><c:tx><c:rich><a:p><a:r><a:t>Revenue </a:t></a:r><a:r><a:t>by Region</a:t></a:r></a:p><a:p><a:r><a:t>FY2024</a:t></a:r></a:p></c:rich></c:tx></c:title>
+            <c:plotArea><c:barChart><c:ser>
+                <c:tx><c:strRef><c:strCache><c:pt idx="0"><c:v>FY2024</c:v></c:pt></c:strCache></c:strRef></c:tx>
+                <c:cat><c:strRef><c:strCache><c:pt idx="0"><c:v>Gulf</c:v></c:pt><c:pt idx="1"><c:v>Levant</c:v></c:pt></c:strCache></c:strRef></c:cat>
+                <c:val><c:numRef><c:numCache><c:pt idx="0"><c:v>42</c:v></c:pt></c:numCache></c:numRef></c:val>
+            </c:ser></c:barChart></c:plotArea>
+        </c:chart></c:chartSpace>"#;
+        let diagram = r#"<dgm:dataModel xmlns:dgm="d" xmlns:a="a"><dgm:ptLst>
+            <dgm:pt modelId="1"><dgm:t><a:p><a:r><a:t>Market </a:t></a:r><a:r><a:t>Entry</a:t></a:r></a:p></dgm:t></dgm:pt>
+            <dgm:pt modelId="2"><dgm:t><a:p><a:r><a:t>Partner Network</a:t></a:r></a:p></dgm:t></dgm:pt>
+        </dgm:ptLst></dgm:dataModel>"#;
+
+        let mut archive = build_package(&[
+            ("ppt/slides/_rels/slide1.xml.rels", rels.as_str()),
+            ("ppt/charts/chart1.xml", chart),
+            ("ppt/diagrams/data1.xml", diagram),
+        ]);
+
+        let parts = embedded_parts(&mut archive, "ppt/slides/slide1.xml");
+        assert_eq!(parts.len(), 2);
+
+        let chart_text = extract_embedded_text(&mut archive, &parts[0]).unwrap();
+        assert_eq!(
+            chart_text.title.as_deref(),
+            Some("Revenue by Region FY2024")
+        );
+        assert_eq!(chart_text.labels, vec!["FY2024", "Gulf", "Levant"]);
+
+        let diagram_text = extract_embedded_text(&mut archive, &parts[1]).unwrap();
+        assert_eq!(diagram_text.labels, vec!["Market Entry", "Partner Network"]);
+
+        let mut content = String::new();
+        append_embedded_text(&mut archive, "ppt/slides/slide1.xml", &mut content);
+        assert!(content.contains("Revenue by Region"));
+        assert!(content.contains("Partner Network"));
+        assert!(!content.contains("42"));
+    }
+}
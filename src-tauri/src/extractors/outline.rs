@@ -0,0 +1,243 @@
+//! Document outlines (tables of contents)
+//!
+//! Built from the structured preview so every node can point at the section
+//! the preview should scroll to:
+//! - DOCX, Markdown, EPUB and other previews: headings, nested by level
+//! - PPTX: one node per slide, titled by its title placeholder
+//! - XLSX: sheet names from `xl/workbook.xml` (no preview sections to point at)
+
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+use std::fs;
+use std::io::BufReader;
+use std::path::Path;
+use zip::ZipArchive;
+
+use super::extract_content_structured;
+use crate::models::{ContentSection, OutlineNode, SectionType};
+
+/// Longest title kept for an outline node
+const MAX_TITLE_CHARS: usize = 120;
+
+/// Extract the outline of a file (`None` if it can't be read)
+pub fn extract_outline(path: &Path, ext: &str) -> Option<Vec<OutlineNode>> {
+    match ext {
+        "xlsx" | "xlsm" | "xltx" => sheet_outline(path),
+        "pptx" | "pptm" | "potx" => {
+            extract_content_structured(path, ext).map(|content| slide_outline(&content.sections))
+        }
+        _ => extract_content_structured(path, ext)
+            .map(|content| outline_from_sections(&content.sections)),
+    }
+}
+
+/// Nest the top-level headings of a preview by level
+pub fn outline_from_sections(sections: &[ContentSection]) -> Vec<OutlineNode> {
+    let mut roots: Vec<OutlineNode> = Vec::new();
+    // Open headings, outermost first; closed into their parent when a
+    // heading at the same or a higher level arrives
+    let mut stack: Vec<OutlineNode> = Vec::new();
+
+    for (index, section) in sections.iter().enumerate() {
+        let SectionType::Heading { level } = section.section_type else {
+            continue;
+        };
+        let Some(title) = section_title(section) else {
+            continue;
+        };
+
+        while stack.last().is_some_and(|open| open.level >= level) {
+            close_node(&mut stack, &mut roots);
+        }
+        stack.push(OutlineNode {
+            title,
+            level,
+            section_index: Some(index),
+            children: Vec::new(),
+        });
+    }
+    while !stack.is_empty() {
+        close_node(&mut stack, &mut roots);
+    }
+
+    roots
+}
+
+fn close_node(stack: &mut Vec<OutlineNode>, roots: &mut Vec<OutlineNode>) {
+    if let Some(node) = stack.pop() {
+        match stack.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None => roots.push(node),
+        }
+    }
+}
+
+/// One node per slide break, titled by the slide's first heading
+fn slide_outline(sections: &[ContentSection]) -> Vec<OutlineNode> {
+    let mut slides: Vec<OutlineNode> = Vec::new();
+    let mut titled = false;
+
+    for (index, section) in sections.iter().enumerate() {
+        match section.section_type {
+            SectionType::SlideBreak { slide_number } => {
+                slides.push(OutlineNode {
+                    title: format!("Slide {}", slide_number),
+                    level: 1,
+                    section_index: Some(index),
+                    children: Vec::new(),
+                });
+                titled = false;
+            }
+            SectionType::Heading { .. } if !titled => {
+                if let (Some(slide), Some(title)) = (slides.last_mut(), section_title(section)) {
+                    slide.title = title;
+                    titled = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    slides
+}
+
+/// Visible sheet names in workbook order
+fn sheet_outline(path: &Path) -> Option<Vec<OutlineNode>> {
+    let file = fs::File::open(path).ok()?;
+    let mut archive = ZipArchive::new(file).ok()?;
+    let workbook = archive.by_name("xl/workbook.xml").ok()?;
+
+    let mut reader = Reader::from_reader(BufReader::new(workbook));
+    let mut buf = Vec::with_capacity(512);
+    let mut sheets = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) if e.local_name().as_ref() == b"sheet" => {
+                let mut name = None;
+                let mut hidden = false;
+                for attr in e.attributes().filter_map(|a| a.ok()) {
+                    match attr.key.as_ref() {
+                        b"name" => {
+                            name = attr.unescape_value().ok().map(|v| v.to_string());
+                        }
+                        b"state" => hidden = attr.value.as_ref() != b"visible",
+                        _ => {}
+                    }
+                }
+                if let Some(name) = name.filter(|_| !hidden) {
+                    sheets.push(OutlineNode {
+                        title: name,
+                        level: 1,
+                        section_index: None,
+                        children: Vec::new(),
+                    });
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Some(sheets)
+}
+
+/// A section's text on one line, shortened for display
+///
+/// Formatted headings (HTML, EPUB) keep their text in runs only.
+fn section_title(section: &ContentSection) -> Option<String> {
+    let text = match (&section.content, &section.runs) {
+        (Some(content), _) => content.clone(),
+        (None, Some(runs)) => runs.iter().map(|run| run.text.as_str()).collect(),
+        (None, None) => return None,
+    };
+    let title = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if title.is_empty() {
+        return None;
+    }
+    if title.chars().count() > MAX_TITLE_CHARS {
+        let mut short: String = title.chars().take(MAX_TITLE_CHARS - 1).collect();
+        short.push('…');
+        return Some(short);
+    }
+    Some(title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extractors::html::parse_html;
+
+    fn section(section_type: SectionType, text: Option<&str>) -> ContentSection {
+        ContentSection {
+            section_type,
+            content: text.map(String::from),
+            runs: None,
+            children: None,
+            properties: None,
+        }
+    }
+
+    fn heading(level: u8, text: &str) -> ContentSection {
+        section(SectionType::Heading { level }, Some(text))
+    }
+
+    #[test]
+    fn test_headings_nest_by_level() {
+        let sections = vec![
+            heading(1, "Intro"),
+            section(SectionType::Paragraph, Some("text")),
+            heading(2, "Scope"),
+            heading(3, "Out of  scope"),
+            heading(2, "Terms"),
+            heading(1, "Design"),
+            heading(3, "Skipped level"),
+        ];
+        let outline = outline_from_sections(&sections);
+
+        assert_eq!(outline.len(), 2);
+        assert_eq!(outline[0].title, "Intro");
+        assert_eq!(outline[0].section_index, Some(0));
+        let scope = &outline[0].children[0];
+        assert_eq!(
+            (scope.title.as_str(), scope.section_index),
+            ("Scope", Some(2))
+        );
+        assert_eq!(scope.children[0].title, "Out of scope");
+        assert_eq!(outline[0].children[1].title, "Terms");
+        assert_eq!(outline[1].children[0].title, "Skipped level");
+        assert_eq!(outline[1].children[0].section_index, Some(6));
+    }
+
+    #[test]
+    fn test_html_headings() {
+        let content =
+            parse_html("<h1>Chapter <em>One</em></h1><p>Text</p><h2>A  section</h2><h1>Two</h1>");
+        let outline = outline_from_sections(&content.sections);
+
+        assert_eq!(outline.len(), 2);
+        assert_eq!(outline[0].title, "Chapter One");
+        assert_eq!(outline[0].children[0].title, "A section");
+        assert_eq!(outline[1].title, "Two");
+    }
+
+    #[test]
+    fn test_slide_outline() {
+        let sections = vec![
+            section(SectionType::SlideBreak { slide_number: 1 }, None),
+            heading(2, "Roadmap"),
+            section(SectionType::Paragraph, Some("Q1")),
+            heading(2, "Second placeholder"),
+            section(SectionType::SlideBreak { slide_number: 2 }, None),
+            section(SectionType::Paragraph, Some("No title here")),
+        ];
+        let outline = slide_outline(&sections);
+
+        assert_eq!(outline.len(), 2);
+        assert_eq!(outline[0].title, "Roadmap");
+        assert_eq!(outline[0].section_index, Some(0));
+        assert_eq!(outline[1].title, "Slide 2");
+        assert_eq!(outline[1].section_index, Some(4));
+    }
+}
@@ -1,4 +1,8 @@
-use crate::models::{ContentSection, DocumentContent, DocumentMetadata, SectionType};
+use super::language::paragraph_properties;
+use crate::models::{
+    ContentSection, DocumentContent, DocumentMetadata, SectionType, TextDirection, TextRun,
+    TextStyle,
+};
 use quick_xml::events::Event;
 use quick_xml::reader::Reader;
 use std::fs;
@@ -10,7 +14,7 @@ use zip::ZipArchive;
 ///
 /// PPTX files are ZIP archives containing XML files.
 /// Slides are stored in ppt/slides/slide1.xml, slide2.xml, etc.
-/// Text is in <a:t> elements.
+/// Text is in <a:t> elements; charts and SmartArt are followed via slide rels.
 ///
 /// Uses quick-xml streaming parser for 10-50x faster extraction.
 /// Uses direct ZIP entry access by name instead of iterating all entries.
@@ -55,6 +59,9 @@ pub fn extract_pptx(path: &Path) -> Option<String> {
                 break;
             }
         }
+
+        // Charts and SmartArt referenced from this slide
+        super::ooxml::append_embedded_text(&mut archive, &slide_name, &mut content);
     }
 
     if content.is_empty() {
@@ -99,6 +106,12 @@ pub fn extract_pptx_structured(path: &Path) -> Option<DocumentContent> {
                 let mut buf = Vec::with_capacity(1024);
                 let mut current_paragraph_text = String::new();
                 let mut in_details = false; // crude way to track if we found text in this paragraph
+                // <a:pPr rtl="1"> and the current run's <a:rPr lang="...">
+                let mut paragraph_direction = TextDirection::Ltr;
+                let mut run_language: Option<String> = None;
+                let mut current_runs: Vec<TextRun> = Vec::new();
+                // Paragraphs of the title placeholder become the slide's heading
+                let mut in_title_shape = false;
 
                 // Simple parsing strategy:
                 // Treat each <a:p> (paragraph) as a potential text block.
@@ -112,21 +125,61 @@ pub fn extract_pptx_structured(path: &Path) -> Option<DocumentContent> {
 
                 loop {
                     match reader.read_event_into(&mut buf) {
-                        Ok(Event::Start(ref e)) => {
-                            if e.name().as_ref() == b"a:p" {
-                                current_paragraph_text.clear();
-                                in_details = true;
+                        Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                            match e.name().as_ref() {
+                                b"a:p" => {
+                                    current_paragraph_text.clear();
+                                    current_runs.clear();
+                                    paragraph_direction = TextDirection::Ltr;
+                                    in_details = true;
+                                }
+                                b"a:pPr" => {
+                                    let rtl = e.attributes().filter_map(|a| a.ok()).any(|a| {
+                                        a.key.as_ref() == b"rtl"
+                                            && (a.value.as_ref() == b"1"
+                                                || a.value.as_ref() == b"true")
+                                    });
+                                    if rtl {
+                                        paragraph_direction = TextDirection::Rtl;
+                                    }
+                                }
+                                b"p:sp" => in_title_shape = false,
+                                b"p:ph" => {
+                                    in_title_shape = e.attributes().filter_map(|a| a.ok()).any(|a| {
+                                        a.key.as_ref() == b"type"
+                                            && matches!(a.value.as_ref(), b"title" | b"ctrTitle")
+                                    });
+                                }
+                                b"a:r" => run_language = None,
+                                b"a:rPr" => {
+                                    run_language = e
+                                        .attributes()
+                                        .filter_map(|a| a.ok())
+                                        .find(|a| a.key.as_ref() == b"lang")
+                                        .map(|a| String::from_utf8_lossy(&a.value).to_string());
+                                }
+                                _ => {}
                             }
                         }
                         Ok(Event::End(ref e)) => {
                             if e.name().as_ref() == b"a:p" {
                                 if !current_paragraph_text.is_empty() {
+                                    if let Some(last) = current_runs.last_mut() {
+                                        last.text.truncate(last.text.trim_end().len());
+                                    }
                                     sections.push(ContentSection {
-                                        section_type: SectionType::Paragraph,
+                                        section_type: if in_title_shape {
+                                            SectionType::Heading { level: 2 }
+                                        } else {
+                                            SectionType::Paragraph
+                                        },
                                         content: Some(current_paragraph_text.trim().to_string()),
-                                        runs: None, // We could extract runs separately but let's start simple
+                                        properties: paragraph_properties(
+                                            Some(paragraph_direction),
+                                            &current_runs,
+                                        ),
+                                        runs: Some(std::mem::take(&mut current_runs)),
                                         children: None,
-                                        properties: None,
                                     });
                                     current_paragraph_text = String::new();
                                 }
@@ -139,6 +192,18 @@ pub fn extract_pptx_structured(path: &Path) -> Option<DocumentContent> {
                                     if !text.trim().is_empty() {
                                         current_paragraph_text.push_str(&text);
                                         current_paragraph_text.push(' ');
+                                        let text = if current_runs.is_empty() {
+                                            text.trim_start()
+                                        } else {
+                                            &text
+                                        };
+                                        current_runs.push(TextRun {
+                                            text: format!("{} ", text),
+                                            style: TextStyle {
+                                                language: run_language.clone(),
+                                                ..Default::default()
+                                            },
+                                        });
                                     }
                                 }
                             }
@@ -149,13 +214,19 @@ pub fn extract_pptx_structured(path: &Path) -> Option<DocumentContent> {
                     }
                     buf.clear();
                 }
-
                 slide_num += 1;
             }
             Err(_) => {
                 break;
             }
         }
+
+        // Charts and SmartArt follow the slide's text as labeled blocks
+        for part in super::ooxml::embedded_parts(&mut archive, &slide_name) {
+            if let Some(text) = super::ooxml::extract_embedded_text(&mut archive, &part) {
+                sections.push(text.to_section());
+            }
+        }
     }
 
     Some(DocumentContent {
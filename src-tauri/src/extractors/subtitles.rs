@@ -0,0 +1,208 @@
+use std::fs;
+use std::path::Path;
+
+use super::csv::decode_bytes;
+use crate::models::{
+    ContentSection, DocumentContent, DocumentMetadata, SectionProperties, SectionType,
+};
+
+/// A subtitle cue: timing and text
+#[derive(Debug, PartialEq)]
+struct Cue {
+    start: String,
+    end: String,
+    text: String,
+}
+
+/// Extract text content from an SRT/VTT file (plain text for indexing)
+///
+/// Cue text only, one cue per line; a cue repeating the previous one
+/// (common in auto-generated captions) is dropped.
+pub fn extract_subtitles(path: &Path) -> Option<String> {
+    let cues = read_cues(path)?;
+    let mut lines: Vec<&str> = Vec::with_capacity(cues.len());
+    for cue in &cues {
+        if lines.last() != Some(&cue.text.as_str()) {
+            lines.push(&cue.text);
+        }
+    }
+    Some(lines.join("\n"))
+}
+
+/// Extract structured content from an SRT/VTT file (for rich preview)
+///
+/// Each cue becomes a paragraph whose location is its time range.
+pub fn extract_subtitles_structured(path: &Path) -> Option<DocumentContent> {
+    let cues = read_cues(path)?;
+    let sections = cues
+        .into_iter()
+        .map(|cue| ContentSection {
+            section_type: SectionType::Paragraph,
+            content: Some(cue.text),
+            runs: None,
+            children: None,
+            properties: Some(SectionProperties {
+                location: Some(format!("{} → {}", cue.start, cue.end)),
+                ..Default::default()
+            }),
+        })
+        .collect();
+
+    Some(DocumentContent {
+        doc_type: "subtitles".to_string(),
+        sections,
+        metadata: DocumentMetadata::default(),
+    })
+}
+
+fn read_cues(path: &Path) -> Option<Vec<Cue>> {
+    let bytes = fs::read(path).ok()?;
+    let cues = parse_cues(&decode_bytes(&bytes));
+    if cues.is_empty() {
+        None
+    } else {
+        Some(cues)
+    }
+}
+
+/// Parse SRT and WebVTT alike: blocks separated by blank lines, each with a
+/// `start --> end` timing line followed by text lines
+///
+/// SRT counters, VTT cue identifiers and VTT `NOTE`/`STYLE`/`REGION` blocks
+/// (no timing line) are skipped.
+fn parse_cues(text: &str) -> Vec<Cue> {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let mut cues = Vec::new();
+
+    for block in text.split("\n\n") {
+        let mut lines = block.lines().skip_while(|l| !l.contains("-->"));
+        let Some(timing) = lines.next() else {
+            continue;
+        };
+        let Some((start, end)) = parse_timing(timing) else {
+            continue;
+        };
+
+        let cue_text: Vec<String> = lines
+            .map(strip_cue_markup)
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty())
+            .collect();
+        if !cue_text.is_empty() {
+            cues.push(Cue {
+                start,
+                end,
+                text: cue_text.join(" "),
+            });
+        }
+    }
+
+    cues
+}
+
+/// `00:01:02,500 --> 00:01:05,000 [settings]` -> normalized start and end
+fn parse_timing(line: &str) -> Option<(String, String)> {
+    let (start, rest) = line.split_once("-->")?;
+    let end = rest.split_whitespace().next()?;
+    Some((normalize_timestamp(start.trim())?, normalize_timestamp(end)?))
+}
+
+/// Normalize to `HH:MM:SS.mmm` (SRT uses a comma, VTT may omit hours)
+fn normalize_timestamp(timestamp: &str) -> Option<String> {
+    let timestamp = timestamp.replace(',', ".");
+    let (clock, millis) = timestamp.split_once('.').unwrap_or((&timestamp, "0"));
+    let parts: Vec<u32> = clock
+        .split(':')
+        .map(|p| p.parse().ok())
+        .collect::<Option<_>>()?;
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [h, m, s] => (*h, *m, *s),
+        [m, s] => (0, *m, *s),
+        _ => return None,
+    };
+    let millis: u32 = format!("{:0<3}", millis).get(..3)?.parse().ok()?;
+    Some(format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        hours, minutes, seconds, millis
+    ))
+}
+
+/// Remove `<i>`, `<v Speaker>`, `<00:00:01.000>` tags and SSA `{\an8}` overrides,
+/// keeping VTT voice names as a `Speaker:` prefix and decoding basic entities
+fn strip_cue_markup(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '<' => {
+                let tag: String = chars.by_ref().take_while(|&c| c != '>').collect();
+                // Voice span: `<v Name>` or `<v.class Name>`
+                if let Some(voice) = tag.strip_prefix('v').filter(|v| v.starts_with([' ', '.'])) {
+                    let name = voice.split_once(' ').map_or("", |(_, name)| name).trim();
+                    if !name.is_empty() {
+                        out.push_str(name);
+                        out.push_str(": ");
+                    }
+                }
+            }
+            '{' if chars.peek() == Some(&'\\') => {
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        break;
+                    }
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+
+    out.replace("&amp;", "&")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&nbsp;", " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_nonexistent_file() {
+        let result = extract_subtitles(Path::new("/nonexistent/file.srt"));
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_parse_srt() {
+        let srt = "1\r\n00:00:01,000 --> 00:00:03,500\r\n<i>Welcome to the</i>\r\nlecture\r\n\r\n\
+2\r\n00:00:04,000 --> 00:00:06,000\r\n{\\an8}Today: irrigation\r\n";
+        let cues = parse_cues(srt);
+        assert_eq!(
+            cues,
+            vec![
+                Cue {
+                    start: "00:00:01.000".to_string(),
+                    end: "00:00:03.500".to_string(),
+                    text: "Welcome to the lecture".to_string(),
+                },
+                Cue {
+                    start: "00:00:04.000".to_string(),
+                    end: "00:00:06.000".to_string(),
+                    text: "Today: irrigation".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_vtt() {
+        let vtt = "WEBVTT\n\nNOTE recorded live\n\nintro\n01:02.5 --> 01:04.250 align:start\n\
+<v Dr. Amina>Soil <00:01:03.000><c.hl>moisture</c> matters\n";
+        let cues = parse_cues(vtt);
+        assert_eq!(cues.len(), 1);
+        assert_eq!(cues[0].start, "00:01:02.500");
+        assert_eq!(cues[0].end, "00:01:04.250");
+        assert_eq!(cues[0].text, "Dr. Amina: Soil moisture matters");
+    }
+}
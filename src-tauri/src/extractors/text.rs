@@ -1,20 +1,185 @@
 use std::fs;
 use std::path::Path;
 
-/// Extract content from plain text files (txt, md)
-/// 
-/// Simply reads the file content as UTF-8 text.
+/// Extensions treated as text until the user's settings are loaded
+pub const DEFAULT_TEXT_EXTENSIONS: &[&str] = &[
+    "log", "json", "xml", "yaml", "yml", "toml", "ini", "cfg", "conf", "sql", "py", "js", "ts",
+    "rs", "java", "c", "h", "cpp", "cs", "go", "rb", "php", "sh", "ps1", "bat",
+];
+
+/// File name patterns treated as text until the user's settings are loaded
+pub const DEFAULT_TEXT_PATTERNS: &[&str] = &["Dockerfile", "Makefile", "README", "LICENSE"];
+
+/// Source and config formats, previewed as code (monospace, whitespace kept)
+const CODE_EXTENSIONS: &[&str] = &[
+    "json", "jsonc", "xml", "xsd", "xsl", "yaml", "yml", "toml", "ini", "cfg", "conf", "sql",
+    "py", "js", "mjs", "cjs", "jsx", "ts", "tsx", "rs", "java", "kt", "kts", "scala", "groovy",
+    "c", "h", "cc", "cpp", "cxx", "hpp", "hh", "cs", "fs", "vb", "go", "swift", "m", "mm", "rb",
+    "php", "pl", "pm", "lua", "r", "jl", "dart", "ex", "exs", "erl", "hs", "clj", "sh", "bash",
+    "zsh", "fish", "ps1", "psm1", "bat", "cmd", "css", "scss", "less", "vue", "svelte", "gradle",
+    "proto", "graphql", "tf",
+];
+
+/// Extension-less files that hold code
+const CODE_FILE_NAMES: &[&str] = &[
+    "dockerfile", "makefile", "gnumakefile", "jenkinsfile", "vagrantfile", "rakefile", "gemfile",
+];
+
+/// User-managed "treat as text" rules, kept in `AppState`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextFileRules {
+    extensions: Vec<String>,
+    patterns: Vec<String>,
+}
+
+impl TextFileRules {
+    /// Rules for the given "treat as text" extensions and file name patterns
+    ///
+    /// Extensions are matched case-insensitively without the leading dot;
+    /// patterns match whole file names with `*` and `?` wildcards.
+    pub fn new(extensions: &[String], patterns: &[String]) -> Self {
+        Self {
+            extensions: extensions.iter().map(|e| normalize_extension(e)).collect(),
+            patterns: patterns.iter().map(|p| p.trim().to_lowercase()).collect(),
+        }
+    }
+
+    /// File type ("code" or "text") for a file covered by the rules
+    ///
+    /// Pass an empty `file_name` to check the extension alone.
+    pub fn file_type(&self, file_name: &str, ext: &str) -> Option<&'static str> {
+        let ext = ext.to_lowercase();
+        let name = file_name.to_lowercase();
+
+        let matched = (!ext.is_empty() && self.extensions.contains(&ext))
+            || (!name.is_empty() && self.patterns.iter().any(|p| glob_match(p, &name)));
+        matched.then(|| text_kind(&name, &ext))
+    }
+}
+
+/// Rules used until the user's settings are loaded
+impl Default for TextFileRules {
+    fn default() -> Self {
+        let to_strings = |items: &[&str]| items.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+        Self::new(
+            &to_strings(DEFAULT_TEXT_EXTENSIONS),
+            &to_strings(DEFAULT_TEXT_PATTERNS),
+        )
+    }
+}
+
+/// Lowercase an extension and strip a leading `.` / `*.`
+pub fn normalize_extension(ext: &str) -> String {
+    ext.trim()
+        .trim_start_matches('*')
+        .trim_start_matches('.')
+        .to_lowercase()
+}
+
+/// How a text file is previewed: "code" for source/config formats, else "text"
+pub fn text_kind(file_name: &str, ext: &str) -> &'static str {
+    let ext = ext.to_lowercase();
+    let name = file_name.to_lowercase();
+    if CODE_EXTENSIONS.contains(&ext.as_str()) || CODE_FILE_NAMES.contains(&name.as_str()) {
+        "code"
+    } else {
+        "text"
+    }
+}
+
+/// File type of a well-known text or code extension, configured or not
+///
+/// Lets `type:py` mean code without access to the user's rules.
+pub fn known_text_type(ext: &str) -> Option<&'static str> {
+    let ext = ext.to_lowercase();
+    (CODE_EXTENSIONS.contains(&ext.as_str()) || DEFAULT_TEXT_EXTENSIONS.contains(&ext.as_str()))
+        .then(|| text_kind("", &ext))
+}
+
+/// Match a file name against a pattern with `*` (any run) and `?` (one char)
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    // Last `*` seen and the name position it currently absorbs up to
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Extract content from plain text files (txt, md, and configured text/code types)
+///
+/// Reads the file as UTF-8; invalid sequences are replaced unless the file
+/// looks binary (NUL bytes near the start).
 pub fn extract_text(path: &Path) -> Option<String> {
-    fs::read_to_string(path).ok()
+    let bytes = fs::read(path).ok()?;
+    match String::from_utf8(bytes) {
+        Ok(text) => Some(text),
+        Err(e) => {
+            let bytes = e.into_bytes();
+            if bytes.iter().take(8192).any(|&b| b == 0) {
+                return None;
+            }
+            Some(String::from_utf8_lossy(&bytes).into_owned())
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+
     #[test]
     fn test_extract_nonexistent_file() {
         let result = extract_text(Path::new("/nonexistent/file.txt"));
         assert!(result.is_none());
     }
+
+    #[test]
+    fn test_default_rules() {
+        let rules = TextFileRules::default();
+        assert_eq!(rules.file_type("", "log"), Some("text"));
+        assert_eq!(rules.file_type("", "py"), Some("code"));
+        assert_eq!(rules.file_type("Dockerfile", ""), Some("code"));
+        assert_eq!(rules.file_type("LICENSE", ""), Some("text"));
+        assert_eq!(rules.file_type("setup.exe", "exe"), None);
+    }
+
+    #[test]
+    fn test_configured_rules() {
+        let rules = TextFileRules::new(&[".NFO".to_string()], &["*.Conf".to_string()]);
+        assert_eq!(rules.file_type("readme.nfo", "nfo"), Some("text"));
+        assert_eq!(rules.file_type("nginx.conf", "conf"), Some("code"));
+        assert_eq!(rules.file_type("", "log"), None);
+        assert_eq!(known_text_type("kt"), Some("code"));
+        assert_eq!(known_text_type("nfo"), None);
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("dockerfile", "dockerfile"));
+        assert!(glob_match("*.conf", "nginx.conf"));
+        assert!(glob_match("readme*", "readme"));
+        assert!(glob_match("log-????.txt", "log-2024.txt"));
+        assert!(glob_match("*a*b", "xaxxb"));
+        assert!(!glob_match("*.conf", "nginx.config"));
+        assert!(!glob_match("log-????.txt", "log-24.txt"));
+    }
 }
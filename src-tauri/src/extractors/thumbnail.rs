@@ -0,0 +1,221 @@
+//! Thumbnails for result cards
+//!
+//! Uses the preview image a document already carries rather than rendering it:
+//! - OOXML: the package thumbnail relationship (usually `docProps/thumbnail.jpeg`),
+//!   then for presentations the first picture on the first slide
+//! - ODF: `Thumbnails/thumbnail.png`
+//! - Images: the EXIF thumbnail, or the file itself when small
+//!
+//! Only PNG and JPEG are returned (EMF/WMF thumbnails can't be shown in the
+//! webview), and nothing larger than [`THUMBNAIL_MAX_BYTES`].
+
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::Path;
+use zip::ZipArchive;
+
+use super::image::image_thumbnail;
+use super::ooxml::read_relationships;
+
+/// Largest thumbnail returned (embedded images can be full-size photos)
+pub const THUMBNAIL_MAX_BYTES: usize = 512 * 1024;
+
+/// Package-level thumbnail relationship (OPC)
+const THUMBNAIL_REL_TYPE: &str =
+    "http://schemas.openxmlformats.org/package/2006/relationships/metadata/thumbnail";
+
+/// A thumbnail image
+#[derive(Debug, Clone)]
+pub struct Thumbnail {
+    /// `image/png` or `image/jpeg`
+    pub mime: &'static str,
+    pub data: Vec<u8>,
+}
+
+impl Thumbnail {
+    /// Sniff the format from the image bytes, enforcing the size limit
+    fn from_bytes(data: Vec<u8>) -> Option<Self> {
+        if data.len() > THUMBNAIL_MAX_BYTES {
+            return None;
+        }
+        let mime = image_mime(&data)?;
+        Some(Self { mime, data })
+    }
+
+    /// File extension for the cached copy
+    pub fn extension(&self) -> &'static str {
+        match self.mime {
+            "image/png" => "png",
+            _ => "jpg",
+        }
+    }
+}
+
+/// `image/png` / `image/jpeg` by magic bytes
+pub fn image_mime(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else {
+        None
+    }
+}
+
+/// Extract a thumbnail for a file, if it carries one
+pub fn extract_thumbnail(path: &Path) -> Option<Thumbnail> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    match ext.as_str() {
+        "docx" | "docm" | "dotx" | "dotm" | "xlsx" | "xlsm" | "xltx" => {
+            let mut archive = open_archive(path)?;
+            package_thumbnail(&mut archive)
+        }
+        "pptx" | "pptm" | "potx" => {
+            let mut archive = open_archive(path)?;
+            package_thumbnail(&mut archive).or_else(|| first_slide_picture(&mut archive))
+        }
+        "odt" | "ott" | "ods" | "ots" | "odp" | "otp" | "odg" => {
+            let mut archive = open_archive(path)?;
+            read_part(&mut archive, "Thumbnails/thumbnail.png")
+        }
+        "jpg" | "jpeg" | "png" | "tif" | "tiff" => {
+            let (_, data) = image_thumbnail(path)?;
+            Thumbnail::from_bytes(data)
+        }
+        _ => None,
+    }
+}
+
+fn open_archive(path: &Path) -> Option<ZipArchive<BufReader<File>>> {
+    ZipArchive::new(BufReader::new(File::open(path).ok()?)).ok()
+}
+
+fn read_part<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str) -> Option<Thumbnail> {
+    let part = archive.by_name(name).ok()?;
+    if part.size() as usize > THUMBNAIL_MAX_BYTES {
+        return None;
+    }
+    let mut data = Vec::with_capacity(part.size() as usize);
+    part.take(THUMBNAIL_MAX_BYTES as u64 + 1)
+        .read_to_end(&mut data)
+        .ok()?;
+    Thumbnail::from_bytes(data)
+}
+
+/// The thumbnail referenced from `_rels/.rels` (falling back to the usual names)
+fn package_thumbnail<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Option<Thumbnail> {
+    let targets: Vec<String> = read_relationships(archive, "")
+        .into_iter()
+        .filter(|rel| rel.rel_type == THUMBNAIL_REL_TYPE)
+        .map(|rel| rel.target)
+        .chain(["docProps/thumbnail.jpeg", "docProps/thumbnail.png"].map(String::from))
+        .collect();
+    targets.iter().find_map(|target| read_part(archive, target))
+}
+
+/// The first PNG/JPEG picture on the first slide (in presentation order)
+fn first_slide_picture<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Option<Thumbnail> {
+    let first_slide_id = first_slide_rel_id(archive)?;
+    let slide = read_relationships(archive, "ppt/presentation.xml")
+        .into_iter()
+        .find(|rel| rel.id == first_slide_id)?
+        .target;
+
+    let pictures: Vec<String> = read_relationships(archive, &slide)
+        .into_iter()
+        .filter(|rel| rel.rel_type.ends_with("/image"))
+        .map(|rel| rel.target)
+        .collect();
+    pictures
+        .iter()
+        .find_map(|picture| read_part(archive, picture))
+}
+
+/// Relationship ID of the first `<p:sldId>` in `ppt/presentation.xml`
+fn first_slide_rel_id<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Option<String> {
+    let part = archive.by_name("ppt/presentation.xml").ok()?;
+    let mut reader = Reader::from_reader(BufReader::new(part));
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) if e.local_name().as_ref() == b"sldId" => {
+                return e
+                    .attributes()
+                    .filter_map(|a| a.ok())
+                    // `r:id` (the unprefixed `id` is the slide's own number)
+                    .find(|a| a.key.prefix().is_some() && a.key.local_name().as_ref() == b"id")
+                    .map(|a| String::from_utf8_lossy(&a.value).to_string());
+            }
+            Ok(Event::Eof) | Err(_) => return None,
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::write::FileOptions;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+    const JPEG: &[u8] = &[0xFF, 0xD8, 0xFF, 0xE0, 0, 16];
+
+    fn package(parts: &[(&str, &[u8])]) -> ZipArchive<Cursor<Vec<u8>>> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data) in parts {
+            writer.start_file(*name, FileOptions::default()).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        ZipArchive::new(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_package_thumbnail() {
+        let rels = format!(
+            r#"<Relationships><Relationship Id="rId2" Type="{}" Target="docProps/thumbnail.emf"/></Relationships>"#,
+            THUMBNAIL_REL_TYPE
+        );
+        // EMF thumbnail is skipped for the conventional JPEG name
+        let mut archive = package(&[
+            ("_rels/.rels", rels.as_bytes()),
+            ("docProps/thumbnail.emf", b"\x01\0\0\0EMF"),
+            ("docProps/thumbnail.jpeg", JPEG),
+        ]);
+        let thumbnail = package_thumbnail(&mut archive).unwrap();
+        assert_eq!(thumbnail.mime, "image/jpeg");
+        assert_eq!(thumbnail.extension(), "jpg");
+    }
+
+    #[test]
+    fn test_first_slide_picture() {
+        let presentation = br#"<p:presentation xmlns:p="p" xmlns:r="r"><p:sldIdLst><p:sldId id="257" r:id="rId3"/><p:sldId id="256" r:id="rId2"/></p:sldIdLst></p:presentation>"#;
+        let presentation_rels = br#"<Relationships>
+<Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/slide" Target="slides/slide1.xml"/>
+<Relationship Id="rId3" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/slide" Target="slides/slide2.xml"/>
+</Relationships>"#;
+        let slide_rels = br#"<Relationships><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="../media/image7.png"/></Relationships>"#;
+
+        let mut archive = package(&[
+            ("ppt/presentation.xml", presentation),
+            ("ppt/_rels/presentation.xml.rels", presentation_rels),
+            ("ppt/slides/_rels/slide2.xml.rels", slide_rels),
+            ("ppt/media/image7.png", PNG),
+        ]);
+        assert!(package_thumbnail(&mut archive).is_none());
+        let thumbnail = first_slide_picture(&mut archive).unwrap();
+        assert_eq!(thumbnail.mime, "image/png");
+    }
+
+    #[test]
+    fn test_size_limit() {
+        let mut large = JPEG.to_vec();
+        large.resize(THUMBNAIL_MAX_BYTES + 1, 0);
+        assert!(Thumbnail::from_bytes(large).is_none());
+        assert!(Thumbnail::from_bytes(b"GIF89a".to_vec()).is_none());
+    }
+}
@@ -11,7 +11,8 @@ use zip::ZipArchive;
 /// - xl/sharedStrings.xml contains the string table (most text content)
 /// - xl/worksheets/sheet1.xml, sheet2.xml, etc. contain cell data
 ///
-/// We extract from sharedStrings.xml for the text content.
+/// We extract from sharedStrings.xml for the text content, plus chart and
+/// SmartArt text reached through each worksheet's drawing.
 /// Uses quick-xml streaming parser for 10-50x faster extraction.
 pub fn extract_xlsx(path: &Path) -> Option<String> {
     let file = fs::File::open(path).ok()?;
@@ -53,6 +54,16 @@ pub fn extract_xlsx(path: &Path) -> Option<String> {
         }
     }
 
+    // Worksheets reference drawings, which reference charts/diagrams
+    let sheets: Vec<String> = super::ooxml::read_relationships(&mut archive, "xl/workbook.xml")
+        .into_iter()
+        .filter(|rel| rel.rel_type.ends_with("/worksheet"))
+        .map(|rel| rel.target)
+        .collect();
+    for sheet in &sheets {
+        super::ooxml::append_embedded_text(&mut archive, sheet, &mut content);
+    }
+
     if content.is_empty() {
         None
     } else {
@@ -155,6 +155,8 @@ mod tests {
             last_modified: Utc::now(),
             file_type: "word".to_string(),
             content: "test".to_string(),
+            language: "en".to_string(),
+            has_macros: false,
         }
     }
     
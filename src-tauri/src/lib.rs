@@ -45,6 +45,7 @@ pub fn run() {
             
             // Search
             commands::search_index,
+            commands::parse_search_query,
             commands::get_index_stats,
             commands::get_all_files,
             commands::get_search_history,
@@ -54,10 +55,12 @@ pub fn run() {
             // Files
             commands::extract_file_content,
             commands::extract_file_content_structured,
+            commands::get_document_outline,
             commands::delete_file,
             commands::open_file,
             commands::open_file_and_search,
             commands::show_in_folder,
+            commands::get_file_thumbnail,
             
             // Folders
             commands::get_folder_tree,
@@ -74,6 +77,16 @@ pub fn run() {
             commands::clear_index,
             commands::scan_for_new_doc_files,
             
+            // Settings
+            commands::get_char_equivalences,
+            commands::set_char_equivalences,
+            commands::get_indexing_settings,
+            commands::set_indexing_settings,
+            commands::get_ranking_settings,
+            commands::set_ranking_settings,
+            commands::get_query_settings,
+            commands::set_query_settings,
+            
             // Watching
             start_watching,
             stop_watching,
@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Core file data structure representing an indexed document
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -10,6 +11,12 @@ pub struct FileData {
     pub last_modified: DateTime<Utc>,
     pub file_type: String,
     pub content: String,
+    /// Detected language code ("en", "ar", "ur", "gu", ... or "und")
+    #[serde(default)]
+    pub language: String,
+    /// OOXML package contains a VBA project (`vbaProject.bin`)
+    #[serde(default)]
+    pub has_macros: bool,
 }
 
 /// Search result with match highlights and score
@@ -18,14 +25,65 @@ pub struct SearchResult {
     pub file: FileData,
     pub matches: Vec<Match>,
     pub score: f32,
+    /// Fuzzy-mode expansions this file matched instead of the typed terms
+    #[serde(default)]
+    pub expansions: Vec<TermExpansion>,
+}
+
+/// What a search returns: the results, plus guidance when there are none
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SearchResponse {
+    pub results: Vec<SearchResult>,
+    /// "Did you mean" queries (only when nothing was found)
+    pub suggestions: Vec<QuerySuggestion>,
+}
+
+/// A query to try instead of one that found nothing
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct QuerySuggestion {
+    pub query: String,
+    pub source: SuggestionSource,
+}
+
+/// Where a suggested query came from
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestionSource {
+    /// The query with its words replaced by index terms close to them, or by
+    /// their Arabic spelling variants
+    Vocabulary,
+    /// A similar earlier search that found files
+    History,
+}
+
+/// A query term expanded to a similar index term (`clinet` -> `client`)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TermExpansion {
+    /// The term as typed (normalized)
+    pub term: String,
+    /// The index term it was expanded to
+    pub expansion: String,
+    /// Edits between the two (a transposition is one edit)
+    pub distance: usize,
 }
 
 /// Individual match within a document
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Match {
     pub text: String,
+    /// Byte offset of the match in the file's content
     pub index: usize,
     pub context: String,
+    /// Matched query terms within `context` (empty when not computed)
+    #[serde(default)]
+    pub highlights: Vec<TextSpan>,
+}
+
+/// A range of a string in UTF-16 code units (so the UI can slice it directly)
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct TextSpan {
+    pub start: usize,
+    pub end: usize,
 }
 
 /// Information about an indexed folder
@@ -65,7 +123,7 @@ pub struct SearchHistoryEntry {
 /// Search filters for advanced filtering
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct SearchFilters {
-    pub file_types: Option<Vec<String>>,      // ["word", "powerpoint", "excel", "text"]
+    pub file_types: Option<Vec<String>>,      // ["word", "powerpoint", "excel", "text", "code"]
     pub date_from: Option<DateTime<Utc>>,
     pub date_to: Option<DateTime<Utc>>,
     pub min_size: Option<u64>,
@@ -74,6 +132,121 @@ pub struct SearchFilters {
     pub file_path: Option<String>,            // Search in a single specific file
     pub max_results: Option<usize>,           // Limit number of results (default 100)
     pub offset: Option<usize>,                // Skip first N results (for pagination)
+    pub languages: Option<Vec<String>>,       // ["en", "ar", "ur", "gu"]
+    pub has_macros: Option<bool>,             // Only files with (true) / without (false) macros
+    pub match_mode: Option<MatchMode>,        // Exact (default) or smart (stemmed) matching
+}
+
+/// How query words match indexed words
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchMode {
+    /// `contracts` finds `contracts` only
+    #[default]
+    Exact,
+    /// `contracts` also finds `contract` and `contracting` (English and
+    /// Arabic stemming), after the exact-form hits
+    Smart,
+}
+
+/// Indexing options (stored in settings)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct IndexingSettings {
+    /// Extract supported email attachments and index them as child entries
+    pub index_email_attachments: bool,
+    /// Extra extensions read as plain text / source code (["log", "py", ...])
+    pub text_extensions: Vec<String>,
+    /// File name patterns read as plain text (["Dockerfile", "*.conf", ...])
+    pub text_file_patterns: Vec<String>,
+}
+
+impl Default for IndexingSettings {
+    fn default() -> Self {
+        Self {
+            index_email_attachments: false,
+            text_extensions: crate::extractors::DEFAULT_TEXT_EXTENSIONS
+                .iter()
+                .map(|e| e.to_string())
+                .collect(),
+            text_file_patterns: crate::extractors::DEFAULT_TEXT_PATTERNS
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// Search ranking options (stored in settings)
+///
+/// Weights are BM25 column weights for the `files_fts` columns; a match in a
+/// column with weight 10 counts ten times as much as one with weight 1.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct RankingSettings {
+    pub name_weight: f64,
+    pub content_weight: f64,
+    pub path_weight: f64,
+    /// Rank recently modified files higher
+    pub recency_boost: bool,
+    /// Age at which the recency boost has halved
+    pub recency_half_life_days: f64,
+}
+
+impl Default for RankingSettings {
+    fn default() -> Self {
+        Self {
+            name_weight: 10.0,
+            content_weight: 1.0,
+            path_weight: 0.5,
+            recency_boost: false,
+            recency_half_life_days: 90.0,
+        }
+    }
+}
+
+/// How adjacent query terms without an operator combine
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ImplicitOperator {
+    /// `budget report` finds files with both words
+    #[default]
+    And,
+    /// `budget report` finds files with either word
+    Or,
+}
+
+/// Query language options (stored in settings)
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct QuerySettings {
+    pub implicit_operator: ImplicitOperator,
+    /// Also match index terms a typo or two away from the typed ones
+    pub fuzzy: bool,
+}
+
+/// A field qualifier recognized in a query (`name:budget`, `size:>2MB`),
+/// echoed back so the UI can show it as a filter chip
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FilterChip {
+    /// "name", "path", "type", "ext", "modified" or "size"
+    pub field: String,
+    /// The value as typed (`>2MB`, `(draft OR final)`)
+    pub value: String,
+    /// Inside an exclusion (`-ext:tmp`)
+    pub excluded: bool,
+    /// Where the qualifier sits in the query, so a chip can remove it
+    pub span: TextSpan,
+}
+
+/// User-defined search equivalence (character or sequence)
+///
+/// `from` is replaced by `to` in both indexed text and queries,
+/// e.g. `{ from: "ة", to: "ه" }`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CharEquivalence {
+    pub from: String,
+    pub to: String,
 }
 
 /// Index statistics for dashboard
@@ -86,6 +259,7 @@ pub struct IndexStats {
     pub text_files: usize,
     pub total_size: u64,
     pub folder_count: usize,
+    pub languages: HashMap<String, usize>, // File count per detected language
 }
 
 // ============================================================================
@@ -103,6 +277,18 @@ pub struct DocumentContent {
     pub metadata: DocumentMetadata,
 }
 
+/// A node of a document outline (table of contents)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct OutlineNode {
+    pub title: String,
+    /// Heading level (1 = top level); slides and sheets are level 1
+    pub level: u8,
+    /// Index into `DocumentContent.sections` for the preview to scroll to
+    /// (`None` when the preview has no section for it, e.g. XLSX sheets)
+    pub section_index: Option<usize>,
+    pub children: Vec<OutlineNode>,
+}
+
 /// Document metadata extracted from file properties
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct DocumentMetadata {
@@ -113,6 +299,10 @@ pub struct DocumentMetadata {
     pub page_count: Option<usize>,
     pub slide_count: Option<usize>,
     pub sheet_count: Option<usize>,
+    /// Declared language (e.g. EPUB `dc:language`)
+    pub language: Option<String>,
+    /// Email recipients (To and Cc)
+    pub recipients: Option<String>,
 }
 
 /// A section of content within a document
@@ -158,6 +348,10 @@ pub enum SectionType {
     HorizontalRule,
     /// Hyperlink
     Link { url: String },
+    /// Embedded chart (children hold series names, categories, axis titles)
+    Chart { title: Option<String> },
+    /// SmartArt diagram (children hold node text)
+    Diagram,
 }
 
 /// A run of text with consistent formatting
@@ -179,10 +373,20 @@ pub struct TextStyle {
     pub highlight: Option<String>,  // Highlight color if any
     pub color: Option<String>,       // Text color if specified
     pub font_size: Option<f32>,      // Font size in points
+    pub direction: Option<TextDirection>, // Explicit run direction (DOCX w:rtl)
+    pub language: Option<String>,    // Language tag (e.g. "ar-SA")
+}
+
+/// Base text direction, as used for the preview's `dir` attribute
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TextDirection {
+    Ltr,
+    Rtl,
 }
 
 /// Additional properties for specific section types
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct SectionProperties {
     /// For tables: column widths
     pub column_widths: Option<Vec<f32>>,
@@ -194,4 +398,13 @@ pub struct SectionProperties {
     pub width: Option<u32>,
     /// For images: height in pixels
     pub height: Option<u32>,
+    /// For table rows: this is the header row
+    pub header: Option<bool>,
+    /// Where this section sits in the source: cue time range ("00:01:02.500 → 00:01:05.000"),
+    /// notebook cell ("In [3]")
+    pub location: Option<String>,
+    /// For text sections: base direction (from the source, or the first strong character)
+    pub direction: Option<TextDirection>,
+    /// For text sections: language tag shared by all of its runs
+    pub language: Option<String>,
 }
@@ -1,4 +1,4 @@
-use super::{get_context_around_match, matches_parsed_query, parse_simple_query};
+use super::{get_context_around_match, CharFolding, SearchDocument, SearchQuery, TextColumn};
 use crate::models::{FileData, Match, SearchResult};
 use rayon::prelude::*;
 use std::sync::atomic::{AtomicUsize, Ordering};
@@ -6,60 +6,34 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 /// Default maximum results to collect
 const DEFAULT_MAX_RESULTS: usize = 100;
 
-/// Check if a string is primarily Arabic/RTL script (no case transformation needed)
-#[inline]
-fn is_caseless_script(s: &str) -> bool {
-    // Arabic, Hebrew, and other RTL scripts don't have case
-    // Check first few chars to determine script
-    s.chars()
-        .take(20)
-        .filter(|c| c.is_alphabetic())
-        .take(5)
-        .all(|c| {
-            let code = c as u32;
-            // Arabic: 0x0600-0x06FF, 0x0750-0x077F, 0xFB50-0xFDFF, 0xFE70-0xFEFF
-            // Hebrew: 0x0590-0x05FF
-            // Chinese: 0x4E00-0x9FFF
-            // Japanese Hiragana/Katakana: 0x3040-0x30FF
-            (0x0600..=0x06FF).contains(&code) ||  // Arabic
-            (0x0750..=0x077F).contains(&code) ||  // Arabic Supplement
-            (0xFB50..=0xFDFF).contains(&code) ||  // Arabic Presentation Forms-A
-            (0xFE70..=0xFEFF).contains(&code) ||  // Arabic Presentation Forms-B
-            (0x0590..=0x05FF).contains(&code) ||  // Hebrew
-            (0x4E00..=0x9FFF).contains(&code) ||  // CJK Unified Ideographs
-            (0x3040..=0x30FF).contains(&code) // Japanese
-        })
-}
-
-/// Direct substring search through all indexed content (for Arabic, Chinese, etc.)
-/// Also supports basic AND/OR operators and exact phrase matching
+/// Direct search through all indexed content (fallback when FTS5 is unavailable)
+///
+/// Queries are parsed by the same `SearchQuery` parser as the FTS5 engine and
+/// evaluated against each file's path, name and content tokenized the way the
+/// FTS5 index tokenizes them, so both engines return the same files.
 ///
 /// OPTIMIZATIONS APPLIED:
-/// 1. Skip lowercase for caseless scripts (Arabic, Chinese, Hebrew)
-/// 2. Single lowercase conversion per file for Latin scripts
-/// 3. Early termination once max_results found
-/// 4. Parallel processing with rayon
-/// 5. Optional single-file search mode
+/// 1. Early termination once max_results found
+/// 2. Parallel processing with rayon
+/// 3. Optional single-file search mode
+///
+/// The query is parsed from the folded user input; the user's character
+/// folding table is applied to each file's name and content to match (the
+/// FTS5 index folds those two, not the path).
 pub fn search_direct_content(
-    query: &str,
+    parsed_query: &SearchQuery,
     files: &[FileData],
     max_results: Option<usize>,
     file_path_filter: Option<&str>,
+    folding: &CharFolding,
 ) -> Result<Vec<SearchResult>, String> {
     let max_results = max_results.unwrap_or(DEFAULT_MAX_RESULTS);
+    if parsed_query.is_empty() {
+        return Ok(Vec::new());
+    }
 
-    // Detect if query is in a caseless script (Arabic, Chinese, etc.)
-    let query_is_caseless = is_caseless_script(query);
-
-    // For caseless scripts, use query as-is; otherwise lowercase
-    let query_normalized = if query_is_caseless {
-        query.to_string()
-    } else {
-        query.to_lowercase()
-    };
-
-    // Parse the query for operators
-    let parsed_query = parse_simple_query(&query_normalized);
+    // For highlighting, use the first term or phrase found in the file
+    let highlight_terms = parsed_query.positive_terms();
 
     // Atomic counter for early termination across threads
     let found_count = AtomicUsize::new(0);
@@ -80,64 +54,35 @@ pub fn search_direct_content(
                 return None;
             }
 
-            // OPTIMIZATION: Check for match WITHOUT allocating first
-            // This is the biggest performance win for large datasets
-            let (content_has_match, name_has_match) = if query_is_caseless {
-                // For Arabic/etc, strict contains is fine (no case)
-                (
-                    file.content.contains(&query_normalized),
-                    file.name.contains(&query_normalized),
-                )
-            } else {
-                // For English/Latin, use optimized check that avoids allocation if possible
-                (
-                    contains_ignore_case(&file.content, &query_normalized),
-                    contains_ignore_case(&file.name, &query_normalized),
-                )
-            };
-
-            if !content_has_match && !name_has_match {
-                return None;
-            }
-
-            // ONLY allocated if we found a potential match (for highlighting/result generation)
-            // This skips allocation for 99% of files that don't match!
-            let (content_normalized, name_normalized) = if query_is_caseless {
-                (file.content.clone(), file.name.clone())
-            } else {
-                (file.content.to_lowercase(), file.name.to_lowercase())
-            };
-
-            // Check if file matches the parsed query (for AND/OR operators)
-            if !parsed_query.required_terms.is_empty()
-                || !parsed_query.optional_terms.is_empty()
-                || !parsed_query.excluded_terms.is_empty()
-            {
-                let combined = format!("{} {}", name_normalized, content_normalized);
-                if !matches_parsed_query(&combined, &parsed_query) {
-                    return None;
-                }
-            }
-
-            // For highlighting, use the first required term or the original query
-            let highlight_term = parsed_query
-                .required_terms
-                .first()
-                .or(parsed_query.optional_terms.first())
-                .map(|s| s.as_str())
-                .unwrap_or(&query_normalized);
-
-            // Find matches using the normalized content
-            let matches = find_matches_fast(
-                &content_normalized,
-                &name_normalized,
-                &file.name,
-                highlight_term,
+            let doc = SearchDocument::new(
+                file,
+                &folding.fold(&file.name),
+                &folding.fold(&file.content),
             );
 
-            if matches.is_empty() {
+            if !parsed_query.matches(&doc) {
                 return None;
             }
+            let name_has_match = parsed_query.matches_in(&doc, &[TextColumn::Name]);
+
+            // Find matches in the normalized (lowercased) text; a query of
+            // metadata qualifiers only has nothing to highlight
+            let mut matches = highlight_terms
+                .iter()
+                .map(|term| {
+                    find_matches_fast(doc.content.text(), doc.name.text(), &file.name, term)
+                })
+                .find(|matches| !matches.is_empty())
+                .unwrap_or_default();
+            if matches.is_empty() {
+                // Matched through the path or metadata only
+                matches.push(Match {
+                    text: highlight_terms.first().cloned().unwrap_or_default(),
+                    index: 0,
+                    context: format!("Path: {}", file.path),
+                    highlights: Vec::new(),
+                });
+            }
 
             // Increment found counter
             found_count.fetch_add(1, Ordering::Relaxed);
@@ -149,6 +94,7 @@ pub fn search_direct_content(
                 file: (*file).clone(),
                 matches,
                 score,
+                expansions: Vec::new(),
             })
         })
         .collect();
@@ -166,31 +112,6 @@ pub fn search_direct_content(
     Ok(results)
 }
 
-/// Helper: Check if haystack contains needle (ignoring case) without full allocation
-fn contains_ignore_case(haystack: &str, needle_lower: &str) -> bool {
-    // Fast path for empty needle
-    if needle_lower.is_empty() {
-        return true;
-    }
-
-    // CASING OPTIMIZATION:
-    // If both are ASCII, we can use byte-level comparison without allocating
-    // This covers 99% of file content (code, english text)
-    if haystack.is_ascii() {
-        // Simple byte-window search (safe for ASCII)
-        // This avoids allocating the entire lowercased file content
-        return haystack
-            .as_bytes()
-            .windows(needle_lower.len())
-            .any(|window| window.eq_ignore_ascii_case(needle_lower.as_bytes()));
-    }
-
-    // Slow path for Unicode:
-    // We MUST allocate here to be correct with Unicode casing
-    // But we only do this for non-ASCII files (minority)
-    haystack.to_lowercase().contains(needle_lower)
-}
-
 /// Fast match finding using pre-computed lowercase strings
 /// Avoids redundant lowercase conversions
 #[inline]
@@ -210,6 +131,7 @@ fn find_matches_fast(
             text: query_lower.to_string(),
             index: byte_idx,
             context,
+            highlights: Vec::new(),
         });
     }
 
@@ -219,6 +141,7 @@ fn find_matches_fast(
             text: query_lower.to_string(),
             index: 0,
             context: format!("Filename: {}", original_name),
+            highlights: Vec::new(),
         });
     }
 
@@ -1,5 +1,19 @@
+use super::qualifiers::FieldFilter;
+use super::query_parser::SearchQuery;
 use crate::models::{SearchResult, SearchFilters};
 
+/// Add the language and macro filters to the query, so the engines apply
+/// them before their result limit (listing every Arabic file, or every file
+/// with macros, must not depend on the top-ranked page)
+pub fn add_query_filters(query: &mut SearchQuery, filters: &SearchFilters) {
+    if let Some(languages) = filters.languages.as_ref().filter(|l| !l.is_empty()) {
+        query.require(FieldFilter::Language(languages.clone()));
+    }
+    if let Some(has_macros) = filters.has_macros {
+        query.require(FieldFilter::Macros(has_macros));
+    }
+}
+
 /// Apply filters to search results
 pub fn apply_filters(results: Vec<SearchResult>, filters: &SearchFilters) -> Vec<SearchResult> {
     results.into_iter().filter(|r| {
@@ -49,9 +63,20 @@ pub fn apply_filters(results: Vec<SearchResult>, filters: &SearchFilters) -> Vec
 mod tests {
     use super::*;
     use chrono::Utc;
-    use crate::models::{FileData, Match};
+    use rusqlite::{params, Connection};
+    use std::collections::HashSet;
+    use crate::commands::init_database;
+    use crate::models::{FileData, ImplicitOperator, Match, RankingSettings};
+    use crate::search::{
+        register_fold_function, register_stem_function, search_direct_content, search_fts5,
+        CharFolding,
+    };
     
     fn make_result(file_type: &str, size: u64) -> SearchResult {
+        make_result_with_language(file_type, size, "en")
+    }
+    
+    fn make_result_with_language(file_type: &str, size: u64, language: &str) -> SearchResult {
         SearchResult {
             file: FileData {
                 path: "/test/file.docx".to_string(),
@@ -60,13 +85,17 @@ mod tests {
                 last_modified: Utc::now(),
                 file_type: file_type.to_string(),
                 content: "test content".to_string(),
+                language: language.to_string(),
+                has_macros: false,
             },
             matches: vec![Match {
                 text: "test".to_string(),
                 index: 0,
                 context: "test content".to_string(),
+                highlights: Vec::new(),
             }],
             score: 1.0,
+            expansions: Vec::new(),
         }
     }
     
@@ -105,4 +134,97 @@ mod tests {
         assert_eq!(filtered.len(), 1);
         assert_eq!(filtered[0].file.size, 500);
     }
+    
+    /// Files in English, Arabic and Gujarati (only the Arabic one with
+    /// macros), in the database and in memory
+    fn language_fixture() -> (Connection, Vec<FileData>) {
+        let conn = Connection::open_in_memory().unwrap();
+        register_fold_function(&conn, CharFolding::default()).unwrap();
+        register_stem_function(&conn).unwrap();
+        init_database(&conn).unwrap();
+        let mut files = Vec::new();
+        for (path, language, has_macros) in [
+            ("/docs/a.txt", "en", false),
+            ("/docs/b.txt", "en", false),
+            ("/docs/c.txt", "ar", true),
+            ("/docs/d.txt", "gu", false),
+        ] {
+            let mut file = make_result_with_language("text", 100, language).file;
+            file.path = path.to_string();
+            file.name = path.trim_start_matches("/docs/").to_string();
+            file.content = "budget report".to_string();
+            file.has_macros = has_macros;
+            conn.execute(
+                "INSERT INTO files (path, name, size, last_modified, file_type, content,
+                                    language, has_macros)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    file.path,
+                    file.name,
+                    file.size as i64,
+                    file.last_modified.to_rfc3339(),
+                    file.file_type,
+                    file.content,
+                    file.language,
+                    file.has_macros
+                ],
+            )
+            .unwrap();
+            files.push(file);
+        }
+        (conn, files)
+    }
+
+    /// Paths found by both engines with room for one result (the filters
+    /// must hold before the limit), checking they agree
+    fn search_one(
+        conn: &Connection,
+        files: &[FileData],
+        query: &str,
+        filters: &SearchFilters,
+    ) -> Vec<String> {
+        let mut parsed = SearchQuery::parse(query, ImplicitOperator::And).unwrap();
+        add_query_filters(&mut parsed, filters);
+        let paths = |results: Vec<SearchResult>| -> Vec<String> {
+            results.into_iter().map(|r| r.file.path).collect()
+        };
+        let ranking = RankingSettings::default();
+        let fts = paths(search_fts5(conn, &parsed, 1, 0, None, &HashSet::new(), &ranking).unwrap());
+        let direct = paths(
+            search_direct_content(&parsed, files, Some(1), None, &CharFolding::default()).unwrap(),
+        );
+        assert_eq!(fts, direct, "engines disagree on {:?}", query);
+        fts
+    }
+
+    #[test]
+    fn test_filter_by_language() {
+        let (conn, files) = language_fixture();
+        let filters = SearchFilters {
+            languages: Some(vec!["ar".to_string()]),
+            ..Default::default()
+        };
+        // Words, a substring and a metadata-only query
+        for query in ["budget", "*udge", "ext:txt"] {
+            assert_eq!(search_one(&conn, &files, query, &filters), vec!["/docs/c.txt"]);
+        }
+
+        let filters = SearchFilters {
+            languages: Some(vec!["gu".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(search_one(&conn, &files, "budget", &filters), vec!["/docs/d.txt"]);
+    }
+
+    #[test]
+    fn test_filter_by_macros() {
+        let (conn, files) = language_fixture();
+        let filters = SearchFilters {
+            has_macros: Some(true),
+            ..Default::default()
+        };
+        for query in ["budget", "*udge", "ext:txt"] {
+            assert_eq!(search_one(&conn, &files, query, &filters), vec!["/docs/c.txt"]);
+        }
+    }
 }
@@ -0,0 +1,146 @@
+//! User-configurable character folding
+//!
+//! Lets users declare spellings that should be treated as equal when searching,
+//! e.g. Lisan-ud-Dawat / Gujarati written in Arabic script with local conventions
+//! that no built-in normalizer knows about.
+//!
+//! The same table is applied in three places so that both sides of a match agree:
+//! - FTS5 write path (`docufind_fold()` SQL function used by the triggers)
+//! - FTS5 query string before `MATCH`
+//! - Direct content search (query and content)
+
+use rusqlite::functions::FunctionFlags;
+use rusqlite::Connection;
+use rustc_hash::FxHashMap;
+use std::borrow::Cow;
+
+use crate::models::CharEquivalence;
+
+/// Name of the SQL function registered on write connections
+pub const FOLD_FUNCTION_NAME: &str = "docufind_fold";
+
+/// Compiled equivalence table
+///
+/// Rules are grouped by their first character and sorted longest-first,
+/// so sequence rules win over single-character rules at the same position.
+/// Replacement is a single left-to-right pass: output is never re-folded.
+#[derive(Debug, Clone, Default)]
+pub struct CharFolding {
+    rules: FxHashMap<char, Vec<(Vec<char>, String)>>,
+}
+
+impl CharFolding {
+    /// Build a folding table from user-defined equivalences
+    ///
+    /// Entries with an empty `from` are ignored.
+    pub fn new(equivalences: &[CharEquivalence]) -> Self {
+        let mut rules: FxHashMap<char, Vec<(Vec<char>, String)>> = FxHashMap::default();
+
+        for eq in equivalences {
+            let from: Vec<char> = eq.from.chars().collect();
+            if let Some(&first) = from.first() {
+                rules.entry(first).or_default().push((from, eq.to.clone()));
+            }
+        }
+
+        for group in rules.values_mut() {
+            group.sort_by_key(|rule| std::cmp::Reverse(rule.0.len()));
+        }
+
+        Self { rules }
+    }
+
+    /// True if no rules are configured (folding is a no-op)
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Apply the equivalence table to text
+    ///
+    /// Returns the input unchanged (no allocation) when no rule applies.
+    pub fn fold<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if self.rules.is_empty() || !text.chars().any(|c| self.rules.contains_key(&c)) {
+            return Cow::Borrowed(text);
+        }
+
+        let chars: Vec<char> = text.chars().collect();
+        let mut out = String::with_capacity(text.len());
+        let mut i = 0;
+
+        'outer: while i < chars.len() {
+            if let Some(group) = self.rules.get(&chars[i]) {
+                for (from, to) in group {
+                    if chars[i..].starts_with(from) {
+                        out.push_str(to);
+                        i += from.len();
+                        continue 'outer;
+                    }
+                }
+            }
+            out.push(chars[i]);
+            i += 1;
+        }
+
+        Cow::Owned(out)
+    }
+}
+
+/// Register `docufind_fold(text)` on a connection
+///
+/// Must be called on every connection that writes to `files`, because the
+/// FTS5 sync triggers call it.
+pub fn register_fold_function(conn: &Connection, folding: CharFolding) -> rusqlite::Result<()> {
+    conn.create_scalar_function(
+        FOLD_FUNCTION_NAME,
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        move |ctx| {
+            let text: Option<String> = ctx.get(0)?;
+            Ok(text.map(|t| folding.fold(&t).into_owned()))
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eq(from: &str, to: &str) -> CharEquivalence {
+        CharEquivalence {
+            from: from.to_string(),
+            to: to.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_empty_table_is_noop() {
+        let folding = CharFolding::new(&[]);
+        assert!(folding.is_empty());
+        assert!(matches!(folding.fold("hello"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_char_and_sequence_rules() {
+        let folding = CharFolding::new(&[eq("ي", "ى"), eq("ة", "ه"), eq("اً", "ا")]);
+        assert_eq!(folding.fold("مكتبة"), "مكتبه");
+        assert_eq!(folding.fold("علي"), "على");
+        assert_eq!(folding.fold("شكراً"), "شكرا");
+    }
+
+    #[test]
+    fn test_longest_rule_wins_and_no_refold() {
+        let folding = CharFolding::new(&[eq("a", "b"), eq("ab", "x"), eq("b", "c")]);
+        // "ab" matches the sequence rule; the lone "a" becomes "b" and is not re-folded to "c"
+        assert_eq!(folding.fold("aba"), "xb");
+    }
+
+    #[test]
+    fn test_sql_function() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_fold_function(&conn, CharFolding::new(&[eq("ة", "ه")])).unwrap();
+        let folded: String = conn
+            .query_row("SELECT docufind_fold('مدرسة')", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(folded, "مدرسه");
+    }
+}
@@ -7,79 +7,165 @@
 //! 1. FTS5 uses an inverted index (like Tantivy)
 //! 2. Searches are O(log n) instead of O(n)
 //! 3. No need to scan all file contents
+//!
+//! Results are ranked with BM25 using per-column weights from
+//! `RankingSettings`, optionally boosted for recently modified files.
+//! Match context comes from FTS5 `highlight()` (see `snippets`).
+//!
+//! Field qualifiers on metadata (`ext:`, `modified:`, `size:`) are SQL
+//! conditions on `files` (see `query_plan`); a query made only of those lists
+//! the newest matching files first.
+//!
+//! A second FTS5 table, `files_trigram`, indexes the same columns as
+//! character trigrams. It serves substrings (`*4471`) and Chinese or Japanese
+//! words, which unicode61 doesn't split; queries made only of those are
+//! matched and ranked there. A third, `files_stemmed`, holds the stem of
+//! each word for smart mode (see `stemming`).
 
-use chrono::Utc;
-use rusqlite::{params, Connection};
+use chrono::{DateTime, Utc};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection};
 use std::collections::HashSet;
 
-use crate::models::{FileData, Match, SearchResult};
+use super::query_parser::SearchQuery;
+use super::query_plan::{plan_query, MODIFIED_UNIX_SQL};
+use super::snippets::{snippet_matches, SNIPPET_BUDGET};
+use crate::extractors::UNDETERMINED;
+use crate::models::{FileData, Match, RankingSettings, SearchResult};
+
+/// Rows re-indexed between progress reports during a rebuild
+const REBUILD_BATCH_SIZE: i64 = 500;
+
+/// Largest boost for a file modified just now (+50%), halving every half-life
+const RECENCY_BOOST: f64 = 0.5;
+
+/// With the recency boost on, BM25 picks this many times the requested
+/// results and the boost reorders that pool (keeps `ORDER BY rank` fast)
+const RECENCY_POOL_FACTOR: usize = 4;
+
+/// First placeholder of the query plan's condition (after the MATCH
+/// expression, rank function, limit, offset and file path)
+const CONDITION_FIRST_PARAM: usize = 6;
 
 /// Search using SQLite FTS5 full-text search
 ///
 /// This provides instant search for ANY language including Arabic, Chinese, etc.
-/// Returns up to `max_results` files matching the query.
+/// Returns up to `max_results` files matching the query, best match first.
+///
+/// The query is parsed from the folded user input (see `CharFolding`), so it
+/// matches the folded index.
 pub fn search_fts5(
     conn: &Connection,
-    query: &str,
+    query: &SearchQuery,
     max_results: usize,
     offset: usize,
     file_path_filter: Option<&str>,
     excluded_folders: &HashSet<String>,
+    ranking: &RankingSettings,
 ) -> Result<Vec<SearchResult>, String> {
     let start = std::time::Instant::now();
 
-    // Prepare FTS5 query - just use the query as-is, FTS5 handles tokenization
-    let fts_query = query.trim().to_string();
-    if fts_query.is_empty() {
+    // Escaped MATCH expression (user input never reaches FTS5 syntax) plus
+    // SQL for metadata qualifiers
+    let Some(root) = &query.root else {
         return Ok(Vec::new());
-    }
+    };
+    let plan = plan_query(root, CONDITION_FIRST_PARAM);
+    let query_text = query.positive_terms().join(" ");
 
     println!(
-        "[FTS5] Searching for: '{}' (max: {}, offset: {})",
-        fts_query, max_results, offset
+        "[FTS5] Searching for: '{}' where {} (max: {}, offset: {})",
+        plan.match_expr.as_deref().unwrap_or("*"),
+        plan.condition.as_deref().unwrap_or("-"),
+        max_results,
+        offset
     );
 
-    let mut results = Vec::new();
+    // The boost reorders a larger BM25 pool, then we page through that
+    let (limit, sql_offset) = if ranking.recency_boost {
+        ((max_results + offset) * RECENCY_POOL_FACTOR, 0)
+    } else {
+        (max_results, offset)
+    };
 
-    // Super simple, fast query - NO snippet, NO ordering (both are slow!)
-    // Just get the matching file paths/names
-    let sql = if file_path_filter.is_some() {
-        "SELECT path, name, file_type
-         FROM files_fts 
-         WHERE files_fts MATCH ?1 AND path = ?2
-         LIMIT ?3 OFFSET ?4"
+    // Rank inside FTS5 (ORDER BY rank with a LIMIT is optimized there), then
+    // join the page onto files for the language/macro flags. Snippets come
+    // after, per row and under a time budget (snippet() in the scan is slow!)
+    let condition = plan
+        .condition
+        .as_ref()
+        .map(|condition| format!(" AND {}", condition))
+        .unwrap_or_default();
+    let table = plan.index.table();
+    let sql = if plan.match_expr.is_some() {
+        format!(
+            "SELECT files.path, files.name, files.file_type, files.language,
+                    files.has_macros, files.last_modified, ranked.rank, ranked.rowid, files.size
+             FROM (
+                 SELECT {table}.rowid, {table}.rank FROM {table}{join}
+                 WHERE {table} MATCH ?1 AND {table}.rank MATCH ?2
+                   AND (?5 IS NULL OR {table}.path = ?5){condition}
+                 ORDER BY {table}.rank
+                 LIMIT ?3 OFFSET ?4
+             ) AS ranked
+             JOIN files ON files.rowid = ranked.rowid
+             ORDER BY ranked.rank",
+            table = table,
+            join = if plan.condition.is_some() {
+                format!(" JOIN files ON files.rowid = {}.rowid", table)
+            } else {
+                String::new()
+            },
+            condition = condition
+        )
     } else {
-        "SELECT path, name, file_type
-         FROM files_fts 
-         WHERE files_fts MATCH ?1
-         LIMIT ?2 OFFSET ?3"
+        // Metadata only: nothing to rank, newest first
+        format!(
+            "SELECT files.path, files.name, files.file_type, files.language,
+                    files.has_macros, files.last_modified, 0.0, files.rowid, files.size
+             FROM files
+             WHERE (?5 IS NULL OR files.path = ?5){}
+             ORDER BY {} DESC
+             LIMIT ?3 OFFSET ?4",
+            condition, MODIFIED_UNIX_SQL
+        )
     };
 
-    let mut stmt = conn.prepare(sql).map_err(|e| {
+    let mut stmt = conn.prepare(&sql).map_err(|e| {
         println!("[FTS5] SQL Error: {}", e);
         e.to_string()
     })?;
 
-    let rows_result = if let Some(file_path) = file_path_filter {
-        stmt.query(params![
-            &fts_query,
-            file_path,
-            max_results as i64,
-            offset as i64
-        ])
-    } else {
-        stmt.query(params![&fts_query, max_results as i64, offset as i64])
-    };
+    // ?1 and ?2 go unused without a MATCH expression (binding them is harmless)
+    let mut values = vec![
+        plan.match_expr.clone().map_or(Value::Null, Value::Text),
+        Value::Text(bm25_rank_function(ranking)),
+        Value::Integer(limit as i64),
+        Value::Integer(sql_offset as i64),
+        file_path_filter.map_or(Value::Null, |path| Value::Text(path.to_string())),
+    ];
+    values.extend(plan.params);
+    let rows_result = stmt.query(params_from_iter(values));
 
     let mut rows = rows_result.map_err(|e| {
         println!("[FTS5] Query Error: {}", e);
         e.to_string()
     })?;
 
+    let now = Utc::now();
+    // (rowid, result) so snippets can be fetched once the order is final
+    let mut results: Vec<(i64, SearchResult)> = Vec::new();
+
     while let Some(row) = rows.next().map_err(|e| e.to_string())? {
         let path: String = row.get(0).unwrap_or_default();
         let name: String = row.get(1).unwrap_or_default();
         let file_type: String = row.get(2).unwrap_or_default();
+        let language: Option<String> = row.get(3).unwrap_or_default();
+        let has_macros: Option<bool> = row.get(4).unwrap_or_default();
+        let last_modified = parse_last_modified(&row.get::<_, String>(5).unwrap_or_default());
+        let rank: f64 = row.get(6).unwrap_or_default();
+        let rowid: i64 = row.get(7).unwrap_or_default();
+        let size: i64 = row.get(8).unwrap_or_default();
 
         // Skip excluded folders
         if !excluded_folders.is_empty() {
@@ -88,39 +174,124 @@ pub fn search_fts5(
             }
         }
 
+        // bm25() is negative, more negative for better matches
+        let mut score = -rank;
+        if ranking.recency_boost {
+            if let Some(modified) = last_modified {
+                score *= recency_factor(modified, now, ranking.recency_half_life_days);
+            }
+        }
+
         // Create minimal FileData - we'll fetch full content only when user opens the file
         let file_data = FileData {
             path: path.clone(),
             name,
-            size: 0,                   // We don't need size for search results
-            last_modified: Utc::now(), // Placeholder
+            size: size.max(0) as u64,
+            last_modified: last_modified.unwrap_or(now),
             file_type,
             content: String::new(), // Don't fetch full content - it's slow!
+            language: language.unwrap_or_else(|| UNDETERMINED.to_string()),
+            has_macros: has_macros.unwrap_or(false),
         };
 
-        // Simple match - context will be loaded when user clicks on result
-        let matches = vec![Match {
-            text: query.to_string(),
-            index: 0,
-            context: format!("Match found for '{}'", query),
-        }];
+        results.push((
+            rowid,
+            SearchResult {
+                file: file_data,
+                matches: Vec::new(),
+                score: score as f32,
+                expansions: Vec::new(),
+            },
+        ));
+    }
 
-        results.push(SearchResult {
-            file: file_data,
-            matches,
-            score: 1.0,
+    if ranking.recency_boost {
+        results.sort_by(|(_, a), (_, b)| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
         });
+        results = results.into_iter().skip(offset).take(max_results).collect();
     }
 
+    // Real context for the best results first; the rest keep a placeholder
+    // and get context when the user opens them
+    let snippet_start = std::time::Instant::now();
+    let mut with_snippets = 0;
+    let results: Vec<SearchResult> = results
+        .into_iter()
+        .map(|(rowid, mut result)| {
+            if let Some(fts_query) = plan
+                .match_expr
+                .as_ref()
+                .filter(|_| snippet_start.elapsed() < SNIPPET_BUDGET)
+            {
+                match snippet_matches(conn, table, fts_query, rowid) {
+                    Ok(matches) => {
+                        result.matches = matches;
+                        with_snippets += 1;
+                    }
+                    Err(e) => println!("[FTS5] Snippet error for {}: {}", result.file.path, e),
+                }
+            }
+            if result.matches.is_empty() {
+                let context = if query_text.is_empty() {
+                    format!("Path: {}", result.file.path)
+                } else {
+                    format!("Match found for '{}'", query_text)
+                };
+                result.matches.push(Match {
+                    text: query_text.clone(),
+                    index: 0,
+                    context,
+                    highlights: Vec::new(),
+                });
+            }
+            result
+        })
+        .collect();
+
     println!(
-        "[FTS5] Found {} results in {:?}",
+        "[FTS5] Found {} results ({} with snippets) in {:?}",
         results.len(),
+        with_snippets,
         start.elapsed()
     );
 
     Ok(results)
 }
 
+/// The `rank MATCH` argument selecting BM25 with the configured column weights
+///
+/// Column order follows `files_fts`: path, name, content, file_type.
+fn bm25_rank_function(ranking: &RankingSettings) -> String {
+    let weight = |w: f64| if w.is_finite() { w.max(0.0) } else { 0.0 };
+    format!(
+        "bm25({:?}, {:?}, {:?}, 0.0)",
+        weight(ranking.path_weight),
+        weight(ranking.name_weight),
+        weight(ranking.content_weight)
+    )
+}
+
+/// Multiplier for a file's score: `1 + RECENCY_BOOST` when just modified,
+/// halving the extra every `half_life_days`
+fn recency_factor(modified: DateTime<Utc>, now: DateTime<Utc>, half_life_days: f64) -> f64 {
+    if half_life_days <= 0.0 {
+        return 1.0;
+    }
+    let age_days = (now - modified).num_seconds().max(0) as f64 / 86_400.0;
+    1.0 + RECENCY_BOOST * 0.5f64.powf(age_days / half_life_days)
+}
+
+/// `files.last_modified` is RFC 3339 (older .doc rows hold Unix seconds)
+fn parse_last_modified(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .ok()
+        .or_else(|| DateTime::from_timestamp(value.parse().ok()?, 0))
+}
+
 /// Check if database has FTS5 table populated
 pub fn has_fts5_data(conn: &Connection) -> bool {
     conn.query_row("SELECT COUNT(*) FROM files_fts", [], |row| {
@@ -132,16 +303,363 @@ pub fn has_fts5_data(conn: &Connection) -> bool {
 
 /// Rebuild FTS5 index from files table
 /// Call this if FTS5 table is empty but files table has data
+///
+/// The connection must have `docufind_fold()` and `docufind_stem()` registered
+/// (see `open_database`).
 pub fn rebuild_fts5_index(conn: &Connection) -> Result<(), String> {
-    // Clear existing FTS5 data
-    conn.execute("DELETE FROM files_fts", [])
-        .map_err(|e| e.to_string())?;
+    rebuild_fts5_index_with_progress(conn, |_, _| {})
+}
 
-    // Populate from files table
-    // Use the standard rebuild command which is optimized and correct for all FTS5 configurations
-    // especially contentless/external content tables
-    conn.execute("INSERT INTO files_fts(files_fts) VALUES('rebuild')", [])
-        .map_err(|e| e.to_string())?;
+/// Rebuild FTS5 index in batches, reporting `(done, total)` after each batch
+///
+/// FTS5's built-in 'rebuild' command reads raw text from the content table,
+/// so we repopulate manually to apply character folding to name and content.
+pub fn rebuild_fts5_index_with_progress<F>(conn: &Connection, on_progress: F) -> Result<(), String>
+where
+    F: FnMut(usize, usize),
+{
+    repopulate_fts5(conn, on_progress).map_err(|e| e.to_string())
+}
+
+/// Batched repopulation of `files_fts`, `files_trigram` and `files_stemmed`,
+/// shared by the rebuild commands and schema upgrades
+///
+/// Runs in one savepoint (its own transaction unless the caller has one
+/// open): searches keep seeing the old index until it is done, and a failure
+/// leaves the old index in place.
+pub(crate) fn repopulate_fts5<F>(conn: &Connection, on_progress: F) -> rusqlite::Result<()>
+where
+    F: FnMut(usize, usize),
+{
+    conn.execute_batch("SAVEPOINT repopulate_fts5")?;
+    match refill_fts5(conn, on_progress) {
+        Ok(()) => conn.execute_batch("RELEASE repopulate_fts5"),
+        Err(e) => {
+            let _ = conn.execute_batch("ROLLBACK TO repopulate_fts5; RELEASE repopulate_fts5");
+            Err(e)
+        }
+    }
+}
+
+fn refill_fts5<F>(conn: &Connection, mut on_progress: F) -> rusqlite::Result<()>
+where
+    F: FnMut(usize, usize),
+{
+    let total: i64 = conn.query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0))?;
+
+    // Clear existing FTS5 data (external content tables need 'delete-all', not DELETE)
+    conn.execute("INSERT INTO files_fts(files_fts) VALUES('delete-all')", [])?;
+    conn.execute(
+        "INSERT INTO files_trigram(files_trigram) VALUES('delete-all')",
+        [],
+    )?;
+    conn.execute(
+        "INSERT INTO files_stemmed(files_stemmed) VALUES('delete-all')",
+        [],
+    )?;
+
+    let mut done: i64 = 0;
+    let mut last_rowid: i64 = 0;
+
+    loop {
+        let batch_end: Option<i64> = conn.query_row(
+            "SELECT MAX(rowid) FROM (
+                SELECT rowid FROM files WHERE rowid > ?1 ORDER BY rowid LIMIT ?2
+             )",
+            params![last_rowid, REBUILD_BATCH_SIZE],
+            |row| row.get(0),
+        )?;
+
+        let Some(batch_end) = batch_end else {
+            break;
+        };
+
+        let inserted = conn.execute(
+            "INSERT INTO files_fts(rowid, path, name, content, file_type)
+             SELECT rowid, path, docufind_fold(name), docufind_fold(content), file_type
+             FROM files WHERE rowid > ?1 AND rowid <= ?2",
+            params![last_rowid, batch_end],
+        )?;
+        conn.execute(
+            "INSERT INTO files_trigram(rowid, path, name, content, file_type)
+             SELECT rowid, path, docufind_fold(name), docufind_fold(content), file_type
+             FROM files WHERE rowid > ?1 AND rowid <= ?2",
+            params![last_rowid, batch_end],
+        )?;
+        conn.execute(
+            "INSERT INTO files_stemmed(rowid, path, name, content, file_type)
+             SELECT rowid, docufind_stem(path), docufind_stem(docufind_fold(name)),
+                    docufind_stem(docufind_fold(content)), file_type
+             FROM files WHERE rowid > ?1 AND rowid <= ?2",
+            params![last_rowid, batch_end],
+        )?;
+
+        done += inserted as i64;
+        last_rowid = batch_end;
+        on_progress(done as usize, total as usize);
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init_database;
+    use crate::models::{CharEquivalence, ImplicitOperator};
+    use crate::search::{register_fold_function, register_stem_function, CharFolding};
+
+    fn setup(equivalences: &[CharEquivalence]) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        register_fold_function(&conn, CharFolding::new(equivalences)).unwrap();
+        register_stem_function(&conn).unwrap();
+        init_database(&conn).unwrap();
+        conn
+    }
+
+    fn insert(conn: &Connection, path: &str, content: &str) {
+        insert_file(conn, path, path, content, "2024-01-01T00:00:00+00:00");
+    }
+
+    fn insert_file(conn: &Connection, path: &str, name: &str, content: &str, modified: &str) {
+        conn.execute(
+            "INSERT INTO files (path, name, size, last_modified, file_type, content, language)
+             VALUES (?1, ?2, 1, ?4, 'text', ?3, 'ar')",
+            params![path, name, content, modified],
+        )
+        .unwrap();
+    }
+
+    fn parse(query: &str) -> SearchQuery {
+        SearchQuery::parse(query, ImplicitOperator::And).unwrap()
+    }
+
+    fn search(conn: &Connection, query: &str, ranking: &RankingSettings) -> Vec<SearchResult> {
+        search_fts5(conn, &parse(query), 10, 0, None, &HashSet::new(), ranking).unwrap()
+    }
+
+    #[test]
+    fn test_folded_write_and_query() {
+        let equivalences = vec![CharEquivalence {
+            from: "ة".to_string(),
+            to: "ه".to_string(),
+        }];
+        let conn = setup(&equivalences);
+        insert(&conn, "a.txt", "المدرسة الكبيرة");
+
+        let folding = CharFolding::new(&equivalences);
+        let excluded = HashSet::new();
+        // Both spellings find the document
+        for query in ["المدرسة", "المدرسه"] {
+            let parsed = SearchQuery::parse_folded(query, ImplicitOperator::And, &folding);
+            let results = search_fts5(
+                &conn,
+                &parsed.unwrap(),
+                10,
+                0,
+                None,
+                &excluded,
+                &RankingSettings::default(),
+            )
+            .unwrap();
+            assert_eq!(results.len(), 1, "query {}", query);
+            assert_eq!(results[0].file.language, "ar");
+        }
+
+        // Paths are indexed as they are, so `path:` values aren't folded
+        insert_file(
+            &conn,
+            "/المدرسة/b.txt",
+            "b.txt",
+            "notes",
+            "2024-01-01T00:00:00+00:00",
+        );
+        let parsed =
+            SearchQuery::parse_folded("path:المدرسة notes", ImplicitOperator::And, &folding);
+        let results = search_fts5(
+            &conn,
+            &parsed.unwrap(),
+            10,
+            0,
+            None,
+            &excluded,
+            &RankingSettings::default(),
+        )
+        .unwrap();
+        assert_eq!(results.len(), 1);
+    }
+
+    #[test]
+    fn test_rebuild_reports_progress() {
+        let conn = setup(&[]);
+        for i in 0..3 {
+            insert(&conn, &format!("{}.txt", i), "hello world");
+        }
+
+        let mut last = (0, 0);
+        rebuild_fts5_index_with_progress(&conn, |done, total| last = (done, total)).unwrap();
+        assert_eq!(last, (3, 3));
+        assert!(has_fts5_data(&conn));
+    }
+
+    #[test]
+    fn test_failed_rebuild_keeps_old_index() {
+        let conn = setup(&[]);
+        insert(&conn, "a.txt", "hello world");
+        insert(&conn, "b.txt", "broken text");
+
+        // Folding fails part way through the rebuild
+        conn.create_scalar_function("docufind_fold", 1, Default::default(), |ctx| {
+            let text: String = ctx.get(0)?;
+            if text.contains("broken") {
+                return Err(rusqlite::Error::UserFunctionError("fold failed".into()));
+            }
+            Ok(text)
+        })
+        .unwrap();
+        assert!(rebuild_fts5_index(&conn).is_err());
+
+        let ranking = RankingSettings::default();
+        assert_eq!(search(&conn, "hello", &ranking).len(), 1);
+        assert_eq!(search(&conn, "*roke", &ranking).len(), 1);
+        assert!(conn.is_autocommit());
+    }
+
+    #[test]
+    fn test_index_without_prefixes_is_rebuilt() {
+        let conn = setup(&[]);
+        insert(&conn, "a.txt", "contract renewal");
+        // The table as created before prefix indexes
+        conn.execute_batch(
+            "DROP TABLE files_fts;
+             CREATE VIRTUAL TABLE files_fts USING fts5(
+                 path, name, content, file_type,
+                 content='files', content_rowid='rowid',
+                 tokenize='unicode61 remove_diacritics 1'
+             );",
+        )
+        .unwrap();
+
+        init_database(&conn).unwrap();
+        let sql: String = conn
+            .query_row(
+                "SELECT sql FROM sqlite_master WHERE name = 'files_fts'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(sql.contains("prefix='2 3'"));
+        let results = search(&conn, "co*", &RankingSettings::default());
+        assert_eq!(results.len(), 1);
+    }
+
+    #[test]
+    fn test_trigram_index_follows_files() {
+        let conn = setup(&[]);
+        insert(&conn, "/parts.txt", "Order AB-44712 and 会议记录");
+        insert(&conn, "/other.txt", "Order CD-90210");
+        let paths = |query| -> Vec<String> {
+            search(&conn, query, &RankingSettings::default())
+                .into_iter()
+                .map(|r| r.file.path)
+                .collect()
+        };
+
+        assert_eq!(paths("*4471"), ["/parts.txt"]);
+        assert_eq!(paths("会议"), ["/parts.txt"]);
+        assert_eq!(paths("order -*4471"), ["/other.txt"]);
+        let results = search(&conn, "*4471", &RankingSettings::default());
+        let highlight = results[0].matches[0].highlights[0];
+        let units: Vec<u16> = results[0].matches[0].context.encode_utf16().collect();
+        assert_eq!(
+            String::from_utf16(&units[highlight.start..highlight.end]).unwrap(),
+            "4471"
+        );
+
+        conn.execute(
+            "UPDATE files SET content = 'Order AB-55555' WHERE path = '/parts.txt'",
+            [],
+        )
+        .unwrap();
+        assert!(paths("*4471").is_empty());
+        conn.execute("DELETE FROM files WHERE path = '/other.txt'", [])
+            .unwrap();
+        assert!(paths("*9021").is_empty());
+    }
+
+    #[test]
+    fn test_trigram_index_added_to_existing_database() {
+        let conn = setup(&[]);
+        insert(&conn, "a.txt", "part AB-44712");
+        // The schema as it was before the trigram index
+        conn.execute_batch(
+            "DROP TRIGGER files_ai;
+             DROP TRIGGER files_ad;
+             DROP TRIGGER files_au;
+             DROP TABLE files_trigram;
+             CREATE TRIGGER files_ai AFTER INSERT ON files BEGIN
+                 INSERT INTO files_fts(rowid, path, name, content, file_type)
+                 VALUES (new.rowid, new.path, docufind_fold(new.name),
+                         docufind_fold(new.content), new.file_type);
+             END;",
+        )
+        .unwrap();
+
+        init_database(&conn).unwrap();
+        let results = search(&conn, "*4471", &RankingSettings::default());
+        assert_eq!(results.len(), 1);
+        assert_eq!(search(&conn, "part", &RankingSettings::default()).len(), 1);
+    }
+
+    #[test]
+    fn test_bm25_ranks_name_above_content() {
+        let conn = setup(&[]);
+        insert(&conn, "/docs/a.txt", "quarterly budget notes and more budget");
+        insert(&conn, "/docs/unrelated.txt", "nothing to see");
+        insert_file(&conn, "/docs/b.txt", "budget.xlsx", "figures", "2024-01-01T00:00:00+00:00");
+
+        let results = search(&conn, "budget", &RankingSettings::default());
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].file.path, "/docs/b.txt");
+        assert!(results[0].score > results[1].score && results[1].score > 0.0);
+
+        // With name weighted like content, the repeated content term wins
+        let flat = RankingSettings {
+            name_weight: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(search(&conn, "budget", &flat)[0].file.path, "/docs/a.txt");
+    }
+
+    #[test]
+    fn test_recency_boost() {
+        let conn = setup(&[]);
+        let recent = Utc::now().to_rfc3339();
+        insert_file(&conn, "/old.txt", "old", "release plan", "2015-06-01T00:00:00+00:00");
+        insert_file(&conn, "/new.txt", "new", "release plan", &recent);
+
+        let plain = search(&conn, "release", &RankingSettings::default());
+        assert_eq!(plain[0].score, plain[1].score);
+
+        let boosted = RankingSettings {
+            recency_boost: true,
+            ..Default::default()
+        };
+        let results = search(&conn, "release", &boosted);
+        assert_eq!(results[0].file.path, "/new.txt");
+        assert!(results[0].score > results[1].score);
+        assert!(parse_last_modified("1700000000").is_some());
+    }
+
+    #[test]
+    fn test_results_carry_snippets() {
+        let conn = setup(&[]);
+        insert(&conn, "/a.txt", "The annual budget was approved. Budget cuts follow.");
+
+        let results = search(&conn, "budget", &RankingSettings::default());
+        let matches = &results[0].matches;
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].index, 11);
+        assert_eq!(matches[0].highlights.len(), 2);
+    }
+}
@@ -0,0 +1,524 @@
+//! Typo-tolerant (fuzzy) search
+//!
+//! Fuzzy mode expands each query term to the index terms within a small edit
+//! distance (a transposition counts as one edit), so `clinet` also finds
+//! `client`. The vocabulary comes from the `files_vocab` fts5vocab table, or
+//! from the files in memory for the direct-search fallback. It is walked in
+//! term order by one Levenshtein automaton per query term, which reuses the
+//! work for a shared prefix and gives up on prefixes that can no longer match.
+//!
+//! Expansions rank below exact hits: the exact query runs first, and files
+//! only found through an expansion follow, each listing the expansions it
+//! matched. Excluded terms, phrases, prefixes, NEAR groups and `type:` values
+//! are never expanded.
+
+use rusqlite::{params, Connection};
+use std::collections::{HashMap, HashSet};
+
+use super::query_parser::{compile_fts5, QueryNode, SearchQuery, TextColumn};
+use super::{search_direct_content, search_fts5, CharFolding, SearchDocument};
+use crate::models::{FileData, RankingSettings, SearchResult, TermExpansion};
+
+/// Index terms a query term expands to, closest (then most common) first
+const MAX_EXPANSIONS_PER_TERM: usize = 5;
+
+/// Files found only through an expansion score at most this fraction of the
+/// weakest exact hit
+const FUZZY_SCORE_CEILING: f32 = 0.5;
+
+/// Edits allowed for a term; short terms have too many neighbours to expand
+pub(super) fn max_edits(term: &str) -> usize {
+    match term.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+/// Edit distance (optimal string alignment) from one query term, fed the
+/// vocabulary in term order
+///
+/// Keeps one DP row per character of the last term, so a term sharing a
+/// prefix with the previous one only computes the rows after it.
+pub(super) struct LevenshteinAutomaton {
+    query: Vec<char>,
+    max_edits: usize,
+    /// The last term's characters (as far as they were followed)
+    chars: Vec<char>,
+    /// `rows[i]`: distances from the first `i` of `chars` to each query prefix
+    rows: Vec<Vec<usize>>,
+}
+
+impl LevenshteinAutomaton {
+    pub(super) fn new(query: &str, max_edits: usize) -> Self {
+        let query: Vec<char> = query.chars().collect();
+        let first_row = (0..=query.len()).collect();
+        Self {
+            query,
+            max_edits,
+            chars: Vec::new(),
+            rows: vec![first_row],
+        }
+    }
+
+    /// The distance to `term`, if within `max_edits`
+    pub(super) fn distance(&mut self, term: &str) -> Option<usize> {
+        let term: Vec<char> = term.chars().collect();
+        if term.len().abs_diff(self.query.len()) > self.max_edits {
+            return None;
+        }
+        let common = self
+            .chars
+            .iter()
+            .zip(&term)
+            .take_while(|(a, b)| a == b)
+            .count();
+        self.chars.truncate(common);
+        self.rows.truncate(common + 1);
+
+        for &c in &term[common..] {
+            // Every extension of this prefix is too far away
+            if self.rows[self.chars.len()]
+                .iter()
+                .all(|&d| d > self.max_edits)
+            {
+                return None;
+            }
+            let row = self.step(c);
+            self.chars.push(c);
+            self.rows.push(row);
+        }
+        let distance = self.rows[self.chars.len()][self.query.len()];
+        (distance <= self.max_edits).then_some(distance)
+    }
+
+    /// The row after appending `c` to `chars`
+    fn step(&self, c: char) -> Vec<usize> {
+        let i = self.chars.len();
+        let prev = &self.rows[i];
+        let mut row = Vec::with_capacity(prev.len());
+        row.push(prev[0] + 1);
+        for j in 1..=self.query.len() {
+            let substitution = prev[j - 1] + usize::from(self.query[j - 1] != c);
+            let mut distance = substitution.min(prev[j] + 1).min(row[j - 1] + 1);
+            let transposed =
+                i > 0 && j > 1 && self.query[j - 1] == self.chars[i - 1] && self.query[j - 2] == c;
+            if transposed {
+                distance = distance.min(self.rows[i - 1][j - 2] + 1);
+            }
+            row.push(distance);
+        }
+        row
+    }
+}
+
+/// Run `f` over the FTS5 index vocabulary: (term, files holding it) in
+/// term order
+pub(super) fn with_fts5_vocabulary<T>(
+    conn: &Connection,
+    f: impl FnOnce(&mut dyn Iterator<Item = (String, i64)>) -> T,
+) -> Result<T, String> {
+    let mut stmt = conn
+        .prepare("SELECT term, doc FROM files_vocab")
+        .map_err(|e| e.to_string())?;
+    let mut vocabulary = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .filter_map(Result::ok);
+    Ok(f(&mut vocabulary))
+}
+
+/// The vocabulary the FTS5 index would hold for `files`, in term order
+pub(super) fn files_vocabulary(files: &[FileData], folding: &CharFolding) -> Vec<(String, i64)> {
+    let mut documents: HashMap<String, i64> = HashMap::new();
+    for file in files {
+        let doc = SearchDocument::new(
+            file,
+            &folding.fold(&file.name),
+            &folding.fold(&file.content),
+        );
+        let words: HashSet<&str> = doc
+            .path
+            .tokens()
+            .chain(doc.name.tokens())
+            .chain(doc.content.tokens())
+            .collect();
+        for word in words {
+            *documents.entry(word.to_string()).or_insert(0) += 1;
+        }
+    }
+    let mut vocabulary: Vec<(String, i64)> = documents.into_iter().collect();
+    vocabulary.sort();
+    vocabulary
+}
+
+/// Terms that may be expanded: required or alternative single words
+fn fuzzy_terms(node: &QueryNode, terms: &mut Vec<String>) {
+    match node {
+        QueryNode::Term(term) if max_edits(term) > 0 && !terms.contains(term) => {
+            terms.push(term.clone())
+        }
+        QueryNode::And(children) | QueryNode::Or(children) => {
+            children.iter().for_each(|c| fuzzy_terms(c, terms))
+        }
+        QueryNode::Column(TextColumn::Type, _) => {}
+        QueryNode::Column(_, inner) => fuzzy_terms(inner, terms),
+        _ => {}
+    }
+}
+
+/// Expand `terms` against a vocabulary of (term, files holding it)
+fn expand_terms(
+    terms: &[String],
+    vocabulary: impl IntoIterator<Item = (String, i64)>,
+) -> Vec<TermExpansion> {
+    let mut automata: Vec<LevenshteinAutomaton> = terms
+        .iter()
+        .map(|term| LevenshteinAutomaton::new(term, max_edits(term)))
+        .collect();
+    let mut found: Vec<Vec<(usize, i64, String)>> = vec![Vec::new(); terms.len()];
+    for (word, documents) in vocabulary {
+        for (automaton, found) in automata.iter_mut().zip(&mut found) {
+            // Distance 0 is the term itself
+            if let Some(distance) = automaton.distance(&word).filter(|&d| d > 0) {
+                found.push((distance, documents, word.clone()));
+            }
+        }
+    }
+
+    let mut expansions = Vec::new();
+    for (term, mut found) in terms.iter().zip(found) {
+        found.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+        expansions.extend(found.into_iter().take(MAX_EXPANSIONS_PER_TERM).map(
+            |(distance, _, expansion)| TermExpansion {
+                term: term.clone(),
+                expansion,
+                distance,
+            },
+        ));
+    }
+    expansions
+}
+
+/// Replace each expanded term with (term OR expansions...)
+fn expand_node(node: &QueryNode, expansions: &[TermExpansion]) -> QueryNode {
+    match node {
+        QueryNode::Term(term) => {
+            let mut alternatives = vec![node.clone()];
+            alternatives.extend(
+                expansions
+                    .iter()
+                    .filter(|e| &e.term == term)
+                    .map(|e| QueryNode::Term(e.expansion.clone())),
+            );
+            if alternatives.len() == 1 {
+                node.clone()
+            } else {
+                QueryNode::Or(alternatives)
+            }
+        }
+        QueryNode::And(children) => QueryNode::And(
+            children
+                .iter()
+                .map(|c| expand_node(c, expansions))
+                .collect(),
+        ),
+        QueryNode::Or(children) => QueryNode::Or(
+            children
+                .iter()
+                .map(|c| expand_node(c, expansions))
+                .collect(),
+        ),
+        QueryNode::Column(column, inner) if *column != TextColumn::Type => {
+            QueryNode::Column(*column, Box::new(expand_node(inner, expansions)))
+        }
+        _ => node.clone(),
+    }
+}
+
+/// The query with its expandable terms expanded by `expand`, and the
+/// expansions (`None` if nothing was expanded)
+fn expand_query(
+    query: &SearchQuery,
+    expand: impl FnOnce(&[String]) -> Result<Vec<TermExpansion>, String>,
+) -> Result<Option<(SearchQuery, Vec<TermExpansion>)>, String> {
+    let Some(root) = &query.root else {
+        return Ok(None);
+    };
+    let mut terms = Vec::new();
+    fuzzy_terms(root, &mut terms);
+    if terms.is_empty() {
+        return Ok(None);
+    }
+    let expansions = expand(&terms)?;
+    if expansions.is_empty() {
+        return Ok(None);
+    }
+    println!(
+        "[Fuzzy] Expanded: {}",
+        expansions
+            .iter()
+            .map(|e| format!("{} -> {}", e.term, e.expansion))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let expanded = SearchQuery {
+        root: Some(expand_node(root, &expansions)),
+        filters: query.filters.clone(),
+    };
+    Ok(Some((expanded, expansions)))
+}
+
+/// Exact hits, then the files only a looser query found (scored below the
+/// weakest exact hit); returns the results and where the looser ones start
+pub(super) fn merge_results(
+    mut exact: Vec<SearchResult>,
+    fuzzy: Vec<SearchResult>,
+    max_results: usize,
+) -> (Vec<SearchResult>, usize) {
+    let seen: HashSet<String> = exact.iter().map(|r| r.file.path.clone()).collect();
+    let weakest = exact.iter().map(|r| r.score).fold(f32::INFINITY, f32::min);
+    let best = fuzzy.iter().map(|r| r.score).fold(0.0, f32::max);
+    let scale = if weakest.is_finite() && best > 0.0 {
+        FUZZY_SCORE_CEILING * weakest.max(0.0) / best
+    } else {
+        1.0
+    };
+
+    let first_fuzzy = exact.len();
+    exact.extend(
+        fuzzy
+            .into_iter()
+            .filter(|r| !seen.contains(&r.file.path))
+            .map(|mut r| {
+                r.score *= scale;
+                r
+            }),
+    );
+    exact.truncate(max_results);
+    let first_fuzzy = first_fuzzy.min(exact.len());
+    (exact, first_fuzzy)
+}
+
+/// `search_fts5` that also matches index terms close to the typed ones
+///
+/// Expansions come from `files_vocab`; results are not offset (page them
+/// afterwards, as `search_index` does).
+pub fn search_fts5_fuzzy(
+    conn: &Connection,
+    query: &SearchQuery,
+    max_results: usize,
+    file_path_filter: Option<&str>,
+    excluded_folders: &HashSet<String>,
+    ranking: &RankingSettings,
+) -> Result<Vec<SearchResult>, String> {
+    let search = |query: &SearchQuery| {
+        search_fts5(
+            conn,
+            query,
+            max_results,
+            0,
+            file_path_filter,
+            excluded_folders,
+            ranking,
+        )
+    };
+    let exact = search(query)?;
+    let Some((expanded, expansions)) = expand_query(query, |terms| {
+        with_fts5_vocabulary(conn, |vocabulary| expand_terms(terms, vocabulary))
+    })?
+    else {
+        return Ok(exact);
+    };
+
+    let (mut results, first_fuzzy) = merge_results(exact, search(&expanded)?, max_results);
+    let fuzzy = &mut results[first_fuzzy..];
+    if fuzzy.is_empty() {
+        return Ok(results);
+    }
+
+    // Which expansions each fuzzy-only file holds
+    let paths: Vec<&str> = fuzzy.iter().map(|r| r.file.path.as_str()).collect();
+    let paths = serde_json::to_string(&paths).map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT files.path FROM files_fts JOIN files ON files.rowid = files_fts.rowid
+             WHERE files_fts MATCH ?1 AND files.path IN (SELECT value FROM json_each(?2))",
+        )
+        .map_err(|e| e.to_string())?;
+    for expansion in &expansions {
+        let Some(fts_query) = compile_fts5(&QueryNode::Term(expansion.expansion.clone())) else {
+            continue;
+        };
+        let matched: HashSet<String> = stmt
+            .query_map(params![fts_query, paths], |row| row.get(0))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?;
+        for result in fuzzy.iter_mut() {
+            if matched.contains(&result.file.path) {
+                result.expansions.push(expansion.clone());
+            }
+        }
+    }
+    Ok(results)
+}
+
+/// `search_direct_content` that also matches terms close to the typed ones
+///
+/// The vocabulary is gathered from `files` (folded like the FTS5 index).
+pub fn search_direct_fuzzy(
+    query: &SearchQuery,
+    files: &[FileData],
+    max_results: usize,
+    file_path_filter: Option<&str>,
+    folding: &CharFolding,
+) -> Result<Vec<SearchResult>, String> {
+    let search = |query: &SearchQuery| {
+        search_direct_content(query, files, Some(max_results), file_path_filter, folding)
+    };
+    let exact = search(query)?;
+    let Some((expanded, expansions)) = expand_query(query, |terms| {
+        Ok(expand_terms(terms, files_vocabulary(files, folding)))
+    })?
+    else {
+        return Ok(exact);
+    };
+
+    let (mut results, first_fuzzy) = merge_results(exact, search(&expanded)?, max_results);
+    for result in &mut results[first_fuzzy..] {
+        let doc = SearchDocument::new(
+            &result.file,
+            &folding.fold(&result.file.name),
+            &folding.fold(&result.file.content),
+        );
+        let matched: Vec<TermExpansion> = expansions
+            .iter()
+            .filter(|e| {
+                SearchQuery {
+                    root: Some(QueryNode::Term(e.expansion.clone())),
+                    filters: Vec::new(),
+                }
+                .matches(&doc)
+            })
+            .cloned()
+            .collect();
+        result.expansions = matched;
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init_database;
+    use crate::models::ImplicitOperator;
+    use crate::search::{register_fold_function, register_stem_function};
+    use chrono::Utc;
+
+    fn distance(query: &str, term: &str) -> Option<usize> {
+        LevenshteinAutomaton::new(query, 2).distance(term)
+    }
+
+    #[test]
+    fn test_automaton_distances() {
+        assert_eq!(distance("client", "client"), Some(0));
+        assert_eq!(distance("clinet", "client"), Some(1));
+        assert_eq!(distance("client", "clients"), Some(1));
+        assert_eq!(distance("client", "cliet"), Some(1));
+        assert_eq!(distance("client", "clent"), Some(1));
+        assert_eq!(distance("client", "klient"), Some(1));
+        assert_eq!(distance("client", "cleint"), Some(1));
+        assert_eq!(distance("client", "glint"), Some(2));
+        assert_eq!(distance("client", "cli"), None);
+        assert_eq!(distance("client", "budget"), None);
+
+        // Rows for a shared prefix are reused in sorted order
+        let mut automaton = LevenshteinAutomaton::new("report", 1);
+        let found: Vec<&str> = [
+            "rep", "repair", "repoet", "report", "reports", "resort", "xeport",
+        ]
+        .into_iter()
+        .filter(|term| automaton.distance(term).is_some())
+        .collect();
+        assert_eq!(found, ["repoet", "report", "reports", "resort", "xeport"]);
+    }
+
+    #[test]
+    fn test_expansion_skips_short_and_excluded_terms() {
+        let query =
+            SearchQuery::parse("clinet -budgte tax type:word", ImplicitOperator::And).unwrap();
+        let mut terms = Vec::new();
+        fuzzy_terms(query.root.as_ref().unwrap(), &mut terms);
+        assert_eq!(terms, ["clinet"]);
+
+        let vocabulary = [
+            ("client", 3),
+            ("clients", 1),
+            ("cliente", 5),
+            ("climate", 9),
+        ]
+        .map(|(term, docs)| (term.to_string(), docs));
+        let expansions = expand_terms(&terms, vocabulary);
+        let found: Vec<(&str, usize)> = expansions
+            .iter()
+            .map(|e| (e.expansion.as_str(), e.distance))
+            .collect();
+        assert_eq!(found, [("client", 1)]);
+    }
+
+    #[test]
+    fn test_fuzzy_results_follow_exact_hits() {
+        let folding = CharFolding::default();
+        let conn = Connection::open_in_memory().unwrap();
+        register_fold_function(&conn, folding.clone()).unwrap();
+        register_stem_function(&conn).unwrap();
+        init_database(&conn).unwrap();
+
+        let docs = [
+            ("/docs/typo.txt", "clinet list"),
+            ("/docs/clients.txt", "Client list for the year"),
+            ("/docs/other.txt", "nothing relevant"),
+        ];
+        let mut files = Vec::new();
+        for (path, content) in docs {
+            let name = path.rsplit('/').next().unwrap();
+            conn.execute(
+                "INSERT INTO files (path, name, size, last_modified, file_type, content)
+                 VALUES (?1, ?2, 1, '2024-01-01T00:00:00+00:00', 'text', ?3)",
+                params![path, name, content],
+            )
+            .unwrap();
+            files.push(FileData {
+                path: path.to_string(),
+                name: name.to_string(),
+                size: 1,
+                last_modified: Utc::now(),
+                file_type: "text".to_string(),
+                content: content.to_string(),
+                language: "en".to_string(),
+                has_macros: false,
+            });
+        }
+
+        let query = SearchQuery::parse("clinet list", ImplicitOperator::And).unwrap();
+        let ranking = RankingSettings::default();
+        let fts = search_fts5_fuzzy(&conn, &query, 10, None, &HashSet::new(), &ranking).unwrap();
+        let direct = search_direct_fuzzy(&query, &files, 10, None, &folding).unwrap();
+
+        for results in [fts, direct] {
+            let paths: Vec<&str> = results.iter().map(|r| r.file.path.as_str()).collect();
+            assert_eq!(paths, ["/docs/typo.txt", "/docs/clients.txt"]);
+            assert!(results[0].expansions.is_empty());
+            assert!(results[1].score < results[0].score);
+            assert_eq!(
+                results[1].expansions,
+                [TermExpansion {
+                    term: "clinet".to_string(),
+                    expansion: "client".to_string(),
+                    distance: 1,
+                }]
+            );
+        }
+    }
+}
@@ -2,10 +2,17 @@
 //!
 //! This module provides search capabilities:
 //! - SQLite FTS5 full-text search (ALL languages including Arabic, Chinese)
+//! - Trigram index for substrings and Chinese/Japanese text
 //! - Direct content search (fallback)
-//! - Query parsing with AND/OR/NOT operators
+//! - One query language (AND/OR/NOT, phrases, grouping, field qualifiers)
+//!   for both engines
+//! - Opt-in fuzzy matching of misspelled terms
+//! - Smart mode: English and Arabic stemming, exact forms ranked first
+//! - "Did you mean" suggestions for queries that found nothing
 //! - Search history management
 //! - Search filters (date, type, size)
+//! - User-configurable character folding
+//! - Match context (snippets) for FTS5 results
 //!
 //! ARCHITECTURE:
 //! - FTS5: Primary search engine for all languages (instant, O(log n))
@@ -13,15 +20,32 @@
 
 mod direct_search;
 mod filters;
+pub mod folding;
 pub mod fts5_search;
+mod fuzzy;
 mod history;
+mod qualifiers;
 mod query_parser;
+mod query_plan;
+mod snippets;
+mod stemming;
+mod suggestions;
 
 pub use direct_search::search_direct_content;
-pub use filters::apply_filters;
-pub use fts5_search::{has_fts5_data, rebuild_fts5_index, search_fts5};
+pub use filters::{add_query_filters, apply_filters};
+pub use folding::{register_fold_function, CharFolding};
+pub use fts5_search::{
+    has_fts5_data, rebuild_fts5_index, rebuild_fts5_index_with_progress, search_fts5,
+};
+pub use fuzzy::{search_direct_fuzzy, search_fts5_fuzzy};
 pub use history::{SearchHistory, MAX_HISTORY_ENTRIES};
-pub use query_parser::{matches_parsed_query, parse_simple_query, ParsedQuery};
+pub use qualifiers::{Field, FieldFilter};
+pub use query_parser::{
+    normalize_for_search, QueryError, QueryNode, SearchDocument, SearchQuery, TextColumn,
+    TokenizedText,
+};
+pub use stemming::{register_stem_function, search_direct_stemmed, search_fts5_stemmed};
+pub use suggestions::{suggest_queries_direct, suggest_queries_fts5};
 
 use crate::models::Match;
 
@@ -39,6 +63,7 @@ pub fn find_matches_in_content(content: &str, name: &str, query_lower: &str) ->
             text: query_lower.to_string(),
             index: byte_idx,
             context,
+            highlights: Vec::new(),
         });
     }
 
@@ -48,6 +73,7 @@ pub fn find_matches_in_content(content: &str, name: &str, query_lower: &str) ->
             text: query_lower.to_string(),
             index: 0,
             context: format!("Filename: {}", name),
+            highlights: Vec::new(),
         });
     }
 
@@ -0,0 +1,374 @@
+//! Field qualifiers (`name:budget`, `ext:doc`, `modified:>2024-01-01`, ...)
+//!
+//! `name:`, `path:` and `type:` restrict text to one `files_fts` column.
+//! `ext:`, `modified:` and `size:` are metadata predicates on `files`, as
+//! are the language and macro filters of the search panel.
+//!
+//! Values:
+//! - `modified:2024`, `modified:2024-03`, `modified:2024-03-15` - that period
+//! - `modified:>2024-01-01` (also `>=`, `<`, `<=`), `modified:2024-01..2024-03`
+//! - `modified:last-30d` (`d`, `w`, `m`, `y`), `modified:today`, `modified:yesterday`
+//! - `size:>2MB` (also `>=`, `<`, `<=`), `size:10KB..2MB` - units B, KB, MB, GB
+//!
+//! Dates are in the user's local time zone.
+
+use chrono::{DateTime, Duration, Local, Months, NaiveDate, NaiveTime, TimeZone, Utc};
+
+use super::query_parser::TextColumn;
+use crate::models::FileData;
+
+/// A recognized field qualifier
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Field {
+    Name,
+    Path,
+    Type,
+    Ext,
+    Modified,
+    Size,
+}
+
+impl Field {
+    /// The qualifier for `name` (case-insensitive), if it is one
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "name" => Some(Field::Name),
+            "path" => Some(Field::Path),
+            "type" => Some(Field::Type),
+            "ext" => Some(Field::Ext),
+            "modified" => Some(Field::Modified),
+            "size" => Some(Field::Size),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::Name => "name",
+            Field::Path => "path",
+            Field::Type => "type",
+            Field::Ext => "ext",
+            Field::Modified => "modified",
+            Field::Size => "size",
+        }
+    }
+
+    /// The FTS5 column a text qualifier searches (`None` for predicates)
+    pub fn column(self) -> Option<TextColumn> {
+        match self {
+            Field::Name => Some(TextColumn::Name),
+            Field::Path => Some(TextColumn::Path),
+            Field::Type => Some(TextColumn::Type),
+            Field::Ext | Field::Modified | Field::Size => None,
+        }
+    }
+}
+
+/// A metadata condition from `ext:`, `modified:` or `size:` (or a search
+/// filter, see `add_query_filters`)
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldFilter {
+    /// Lowercase extension, without the dot
+    Extension(String),
+    /// Modified in `[from, to)`
+    Modified {
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    },
+    /// Size in bytes in `[min, max)`
+    Size { min: Option<u64>, max: Option<u64> },
+    /// Detected language is one of these codes
+    Language(Vec<String>),
+    /// Has (or doesn't have) macros
+    Macros(bool),
+}
+
+impl FieldFilter {
+    /// Parse the value of a predicate qualifier; the error is a message
+    pub fn parse(field: Field, value: &str, now: DateTime<Utc>) -> Result<Self, String> {
+        match field {
+            Field::Ext => parse_extension(value),
+            Field::Modified => parse_modified(value, now),
+            Field::Size => parse_size(value),
+            Field::Name | Field::Path | Field::Type => {
+                Err(format!("'{}:' is not a predicate", field.name()))
+            }
+        }
+    }
+
+    pub fn matches(&self, file: &FileData) -> bool {
+        match self {
+            FieldFilter::Extension(ext) => file.path.to_lowercase().ends_with(&format!(".{}", ext)),
+            FieldFilter::Modified { from, to } => {
+                !matches!(from, Some(from) if file.last_modified < *from)
+                    && !matches!(to, Some(to) if file.last_modified >= *to)
+            }
+            FieldFilter::Size { min, max } => {
+                !matches!(min, Some(min) if file.size < *min)
+                    && !matches!(max, Some(max) if file.size >= *max)
+            }
+            FieldFilter::Language(languages) => languages.contains(&file.language),
+            FieldFilter::Macros(has_macros) => file.has_macros == *has_macros,
+        }
+    }
+}
+
+/// A comparison prefix on a value (`>2MB`)
+#[derive(Clone, Copy)]
+enum Comparison {
+    After,
+    AtLeast,
+    Before,
+    AtMost,
+}
+
+fn split_comparison(value: &str) -> (Option<Comparison>, &str) {
+    for (prefix, comparison) in [
+        (">=", Comparison::AtLeast),
+        ("<=", Comparison::AtMost),
+        (">", Comparison::After),
+        ("<", Comparison::Before),
+    ] {
+        if let Some(rest) = value.strip_prefix(prefix) {
+            return (Some(comparison), rest);
+        }
+    }
+    (None, value)
+}
+
+fn parse_extension(value: &str) -> Result<FieldFilter, String> {
+    let ext = value.trim_start_matches('.').to_lowercase();
+    if ext.is_empty() || !ext.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(format!("'{}' is not a file extension", value));
+    }
+    Ok(FieldFilter::Extension(ext))
+}
+
+fn parse_modified(value: &str, now: DateTime<Utc>) -> Result<FieldFilter, String> {
+    let invalid = || {
+        format!(
+            "'{}' is not a date; use e.g. 2024-01-31, >2024-01, last-30d or today",
+            value
+        )
+    };
+    let period = |text: &str| parse_period(text, now).ok_or_else(invalid);
+
+    let (from, to) = match split_comparison(value) {
+        (Some(comparison), date) => {
+            let (start, end) = period(date)?;
+            match comparison {
+                Comparison::After => (Some(end), None),
+                Comparison::AtLeast => (Some(start), None),
+                Comparison::Before => (None, Some(start)),
+                Comparison::AtMost => (None, Some(end)),
+            }
+        }
+        (None, _) => match value.split_once("..") {
+            Some((first, last)) => (Some(period(first)?.0), Some(period(last)?.1)),
+            None => {
+                let (start, end) = period(value)?;
+                (Some(start), Some(end))
+            }
+        },
+    };
+    Ok(FieldFilter::Modified { from, to })
+}
+
+/// `[start, end)` of a date, month, year, `today`, `yesterday` or `last-N<unit>`
+fn parse_period(text: &str, now: DateTime<Utc>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let today = now.with_timezone(&Local).date_naive();
+    let day = |date: NaiveDate| Some((local_midnight(date), local_midnight(date.succ_opt()?)));
+
+    match text.to_lowercase().as_str() {
+        "today" => return day(today),
+        "yesterday" => return day(today.pred_opt()?),
+        _ => {}
+    }
+
+    if let Some(span) = text.to_lowercase().strip_prefix("last-") {
+        let unit = span.chars().last()?;
+        let count: u32 = span[..span.len() - unit.len_utf8()].parse().ok()?;
+        let from = match unit {
+            'd' => now.checked_sub_signed(Duration::try_days(count.into())?)?,
+            'w' => now.checked_sub_signed(Duration::try_weeks(count.into())?)?,
+            'm' => now.checked_sub_months(Months::new(count))?,
+            'y' => now.checked_sub_months(Months::new(count.checked_mul(12)?))?,
+            _ => return None,
+        };
+        return Some((from, now));
+    }
+
+    let parts: Vec<&str> = text.split('-').collect();
+    let number = |i: usize| -> Option<u32> {
+        let part = parts.get(i)?;
+        (!part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+            .then(|| part.parse().ok())
+            .flatten()
+    };
+    let year = i32::try_from(number(0)?).ok()?;
+    let (start, end) = match parts.len() {
+        1 => (
+            NaiveDate::from_ymd_opt(year, 1, 1)?,
+            NaiveDate::from_ymd_opt(year + 1, 1, 1)?,
+        ),
+        2 => {
+            let start = NaiveDate::from_ymd_opt(year, number(1)?, 1)?;
+            (start, start.checked_add_months(Months::new(1))?)
+        }
+        3 => {
+            let start = NaiveDate::from_ymd_opt(year, number(1)?, number(2)?)?;
+            (start, start.succ_opt()?)
+        }
+        _ => return None,
+    };
+    Some((local_midnight(start), local_midnight(end)))
+}
+
+/// Start of a local calendar day
+fn local_midnight(date: NaiveDate) -> DateTime<Utc> {
+    let midnight = date.and_time(NaiveTime::default());
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&midnight))
+}
+
+fn parse_size(value: &str) -> Result<FieldFilter, String> {
+    let invalid = || {
+        format!(
+            "'{}' is not a size; use e.g. >2MB, <500KB or 1MB..10MB",
+            value
+        )
+    };
+    let bytes = |text: &str| parse_bytes(text).ok_or_else(invalid);
+
+    let (min, max) = match split_comparison(value) {
+        (Some(comparison), size) => {
+            let size = bytes(size)?;
+            match comparison {
+                Comparison::After => (Some(size.saturating_add(1)), None),
+                Comparison::AtLeast => (Some(size), None),
+                Comparison::Before => (None, Some(size)),
+                Comparison::AtMost => (None, Some(size.saturating_add(1))),
+            }
+        }
+        (None, _) => match value.split_once("..") {
+            Some((low, high)) => (Some(bytes(low)?), Some(bytes(high)?.saturating_add(1))),
+            None => return Err(invalid()),
+        },
+    };
+    Ok(FieldFilter::Size { min, max })
+}
+
+/// `2MB`, `1.5gb`, `500` (bytes); units are powers of 1024
+fn parse_bytes(text: &str) -> Option<u64> {
+    let text = text.to_lowercase();
+    let digits = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(digits);
+    let multiplier: u64 = match unit {
+        "" | "b" => 1,
+        "k" | "kb" => 1 << 10,
+        "m" | "mb" => 1 << 20,
+        "g" | "gb" => 1 << 30,
+        _ => return None,
+    };
+    let number: f64 = number.parse().ok()?;
+    let bytes = number * multiplier as f64;
+    (bytes.is_finite() && bytes >= 0.0 && bytes < u64::MAX as f64).then_some(bytes as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        local_midnight(NaiveDate::from_ymd_opt(2024, 6, 15).unwrap()) + Duration::hours(12)
+    }
+
+    fn day(y: i32, m: u32, d: u32) -> DateTime<Utc> {
+        local_midnight(NaiveDate::from_ymd_opt(y, m, d).unwrap())
+    }
+
+    fn modified(value: &str) -> (Option<DateTime<Utc>>, Option<DateTime<Utc>>) {
+        match FieldFilter::parse(Field::Modified, value, now()).unwrap() {
+            FieldFilter::Modified { from, to } => (from, to),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_modified_values() {
+        assert_eq!(modified(">2024-01-01"), (Some(day(2024, 1, 2)), None));
+        assert_eq!(modified(">=2024-01"), (Some(day(2024, 1, 1)), None));
+        assert_eq!(modified("<=2023"), (None, Some(day(2024, 1, 1))));
+        assert_eq!(
+            modified("2024-02"),
+            (Some(day(2024, 2, 1)), Some(day(2024, 3, 1)))
+        );
+        assert_eq!(
+            modified("2024-01..2024-03-10"),
+            (Some(day(2024, 1, 1)), Some(day(2024, 3, 11)))
+        );
+        assert_eq!(
+            modified("yesterday"),
+            (Some(day(2024, 6, 14)), Some(day(2024, 6, 15)))
+        );
+        assert_eq!(
+            modified("last-30d"),
+            (Some(now() - Duration::days(30)), Some(now()))
+        );
+        assert_eq!(
+            modified("last-1y").0,
+            now().checked_sub_months(Months::new(12))
+        );
+
+        for bad in ["2024-13", "soon", "last-5x", ">", "2024-1-1-1"] {
+            assert!(
+                FieldFilter::parse(Field::Modified, bad, now()).is_err(),
+                "{}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_size_and_extension_values() {
+        let size = |value| FieldFilter::parse(Field::Size, value, now());
+        assert_eq!(
+            size(">2MB"),
+            Ok(FieldFilter::Size {
+                min: Some(2 * 1024 * 1024 + 1),
+                max: None
+            })
+        );
+        assert_eq!(
+            size("1.5kb..2KB"),
+            Ok(FieldFilter::Size {
+                min: Some(1536),
+                max: Some(2049)
+            })
+        );
+        assert!(size("2MB").is_err());
+        assert!(size(">2XB").is_err());
+
+        let ext = FieldFilter::parse(Field::Ext, ".DOC", now()).unwrap();
+        assert_eq!(ext, FieldFilter::Extension("doc".to_string()));
+        let file = |path: &str| FileData {
+            path: path.to_string(),
+            name: String::new(),
+            size: 0,
+            last_modified: now(),
+            file_type: "word".to_string(),
+            content: String::new(),
+            language: "en".to_string(),
+            has_macros: false,
+        };
+        assert!(ext.matches(&file("/a/Report.Doc")));
+        assert!(!ext.matches(&file("/a/report.docx")));
+        assert!(!ext.matches(&file("/a/doc")));
+        assert!(FieldFilter::parse(Field::Ext, "d*c", now()).is_err());
+    }
+}
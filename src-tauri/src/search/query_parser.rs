@@ -1,201 +1,1845 @@
+//! Search query language
+//!
+//! One parser for both engines. A query is parsed into a `SearchQuery` AST,
+//! which the FTS5 engine compiles to a `MATCH` expression (every term quoted,
+//! so user input can't produce FTS5 syntax errors) plus SQL predicates, and
+//! the direct-search fallback evaluates against documents in memory.
+//!
+//! Syntax:
+//! - `budget report` - both terms (implicit AND)
+//! - `"board meeting"` - exact phrase
+//! - `invoice OR receipt`, `budget AND 2023` - operators are uppercase
+//! - `budget NOT draft`, `budget -draft` - exclusion
+//! - `(invoice OR receipt) 2023` - grouping
+//! - `contr*`, `"board mee"*` - prefix of the (last) word
+//! - `"board meeting"~5`, `board NEAR/5 meeting` - words at most 5 apart, in
+//!   either order (`NEAR` alone allows 10, as in FTS5)
+//! - `*4471`, `*ware*` - text anywhere, even inside a word
+//! - `name:budget`, `path:clients/acme`, `type:excel`, `ext:doc`,
+//!   `modified:>2024-01-01`, `size:>2MB` - field qualifiers (see `qualifiers`)
+//!
+//! Terms are tokenized like FTS5's `unicode61 remove_diacritics 1` tokenizer:
+//! lowercased, Latin diacritics removed, split on anything that isn't a letter
+//! or digit, combining marks included (so `e-mail` is the phrase `"e mail"`). That tokenizer keeps a
+//! run of Chinese or Japanese text as one token, so those words are searched
+//! as substrings instead (see `QueryNode::Substring`). In smart mode, words
+//! are searched by their stems (see `stemming`).
+//!
+//! Precedence is NEAR > NOT > AND > OR. Adjacent terms combine with the configured
+//! implicit operator (AND by default). Malformed queries (unbalanced
+//! parentheses or quotes, dangling operators, nothing but exclusions, bad
+//! qualifier values) are rejected with a `QueryError` carrying the position
+//! of the problem.
+
+use chrono::Utc;
 use regex::Regex;
+use std::borrow::Cow;
+use std::sync::OnceLock;
+use unicode_normalization::char::decompose_canonical;
+
+use super::qualifiers::{Field, FieldFilter};
+use super::stemming::stem_word;
+use super::CharFolding;
+use crate::models::{FileData, FilterChip, ImplicitOperator, TextSpan};
+
+/// FTS5 columns searched by free text (`file_type` is left to filters)
+const FTS5_TEXT_COLUMNS: &str = "{path name content}";
+
+/// Distance of a bare `NEAR` or `"..."~` (FTS5's default)
+const DEFAULT_NEAR_DISTANCE: usize = 10;
+
+/// Shortest substring the trigram index can look up
+const TRIGRAM_MIN_CHARS: usize = 3;
 
-/// Parsed query structure for direct content search
-pub struct ParsedQuery {
-    pub required_terms: Vec<String>,      // AND terms (all must match)
-    pub optional_terms: Vec<String>,      // OR terms (at least one must match if no required)
-    pub excluded_terms: Vec<String>,      // NOT terms (must not match)
-    pub exact_phrases: Vec<String>,       // Exact phrase matches
-}
-
-/// Parse a simple query string into components
-/// 
-/// Supports:
-/// - AND: `hello AND world` or `+hello +world`
-/// - OR: `hello OR world` (explicit OR required)
-/// - NOT: `hello NOT world` or `-world`
-/// - Exact phrase: `"hello world"` or multi-word without operators (default)
-/// 
-/// NOTE: Multi-word queries WITHOUT operators are treated as EXACT PHRASE by default.
-/// Users can use OR to search for either word.
-pub fn parse_simple_query(query: &str) -> ParsedQuery {
-    let mut required = Vec::new();
-    let mut optional = Vec::new();
-    let mut excluded = Vec::new();
-    let mut exact_phrases = Vec::new();
-    
-    // Extract explicit exact phrases first (quoted strings)
-    let mut remaining = query.to_string();
-    let phrase_regex = Regex::new(r#""([^"]+)""#).unwrap();
-    let mut has_explicit_quotes = false;
-    for cap in phrase_regex.captures_iter(query) {
-        if let Some(phrase) = cap.get(1) {
-            exact_phrases.push(phrase.as_str().to_lowercase());
-            has_explicit_quotes = true;
-        }
-    }
-    remaining = phrase_regex.replace_all(&remaining, " ").to_string();
-    
-    // Check if query uses any operators
-    let has_operators = remaining.to_uppercase().contains(" AND ") 
-        || remaining.to_uppercase().contains(" OR ") 
-        || remaining.to_uppercase().contains(" NOT ")
-        || remaining.contains('+')
-        || remaining.contains('-');
-    
-    // If no operators and no quotes and multiple words, treat entire query as exact phrase
-    let trimmed = remaining.trim();
-    if !has_operators && !has_explicit_quotes && trimmed.contains(' ') {
-        exact_phrases.push(trimmed.to_lowercase());
-        return ParsedQuery {
-            required_terms: exact_phrases.clone(),
-            optional_terms: Vec::new(),
-            excluded_terms: Vec::new(),
-            exact_phrases,
+/// Columns searched by free text, as `FTS5_TEXT_COLUMNS`
+pub(super) const DEFAULT_COLUMNS: &[TextColumn] =
+    &[TextColumn::Path, TextColumn::Name, TextColumn::Content];
+
+/// A searchable text column of `files_fts`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextColumn {
+    Path,
+    Name,
+    Content,
+    Type,
+}
+
+impl TextColumn {
+    /// Column name in `files_fts`, `files_trigram` and `files`
+    pub(super) fn fts5_name(self) -> &'static str {
+        match self {
+            TextColumn::Path => "path",
+            TextColumn::Name => "name",
+            TextColumn::Content => "content",
+            TextColumn::Type => "file_type",
+        }
+    }
+}
+
+/// A node of a parsed query
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryNode {
+    /// A single normalized token
+    Term(String),
+    /// Consecutive normalized tokens
+    Phrase(Vec<String>),
+    /// Consecutive normalized tokens, the last one a prefix (`contr*`)
+    Prefix(Vec<String>),
+    /// Terms, phrases or prefixes in one column with at most this many
+    /// tokens between the first and the last
+    Near(Vec<QueryNode>, usize),
+    /// Normalized text anywhere in a column, even inside a token (`*4471`,
+    /// Chinese and Japanese words); served by the trigram index
+    Substring(String),
+    /// A word in any form with this stem (smart mode); served by the
+    /// stemmed index
+    Stem(String),
+    And(Vec<QueryNode>),
+    Or(Vec<QueryNode>),
+    Not(Box<QueryNode>),
+    /// Text restricted to one column (`name:budget`)
+    Column(TextColumn, Box<QueryNode>),
+    /// A metadata predicate (`size:>2MB`)
+    Filter(FieldFilter),
+}
+
+impl QueryNode {
+    /// Contains a metadata predicate (so FTS5 alone can't evaluate it)
+    pub fn has_filter(&self) -> bool {
+        match self {
+            QueryNode::Filter(_) => true,
+            QueryNode::And(children) | QueryNode::Or(children) => {
+                children.iter().any(QueryNode::has_filter)
+            }
+            QueryNode::Not(inner) => inner.has_filter(),
+            QueryNode::Term(_)
+            | QueryNode::Phrase(_)
+            | QueryNode::Prefix(_)
+            | QueryNode::Near(..)
+            | QueryNode::Substring(_)
+            | QueryNode::Stem(_)
+            | QueryNode::Column(..) => false,
+        }
+    }
+
+    /// Contains a substring (so the word index alone can't evaluate it)
+    pub fn has_substring(&self) -> bool {
+        self.contains(&|node| matches!(node, QueryNode::Substring(_)))
+    }
+
+    /// Contains a stem (so the word index alone can't evaluate it)
+    pub fn has_stem(&self) -> bool {
+        self.contains(&|node| matches!(node, QueryNode::Stem(_)))
+    }
+
+    fn contains(&self, leaf: &dyn Fn(&QueryNode) -> bool) -> bool {
+        match self {
+            QueryNode::And(children) | QueryNode::Or(children) => {
+                children.iter().any(|c| c.contains(leaf))
+            }
+            QueryNode::Not(inner) | QueryNode::Column(_, inner) => inner.contains(leaf),
+            node => leaf(node),
+        }
+    }
+
+    /// The words of a term, phrase or prefix, and whether the last one is a
+    /// prefix
+    fn as_phrase(&self) -> Option<(&[String], bool)> {
+        match self {
+            QueryNode::Term(term) => Some((std::slice::from_ref(term), false)),
+            QueryNode::Phrase(words) => Some((words, false)),
+            QueryNode::Prefix(words) => Some((words, true)),
+            _ => None,
+        }
+    }
+}
+
+/// A parsed search query
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchQuery {
+    /// `None` for an empty query
+    pub root: Option<QueryNode>,
+    /// Field qualifiers in the order they were typed
+    pub filters: Vec<FilterChip>,
+}
+
+impl SearchQuery {
+    /// Parse a user query, combining adjacent terms with `implicit`
+    pub fn parse(query: &str, implicit: ImplicitOperator) -> Result<Self, QueryError> {
+        Self::parse_folded(query, implicit, &CharFolding::default())
+    }
+
+    /// Parse a user query with the user's character equivalences applied to
+    /// the text it searches for (as the index applies them to names and
+    /// content); `path:` and `type:` values and metadata qualifiers are
+    /// matched as typed
+    pub fn parse_folded(
+        query: &str,
+        implicit: ImplicitOperator,
+        folding: &CharFolding,
+    ) -> Result<Self, QueryError> {
+        let mut parser = Parser {
+            tokens: lex(query)?,
+            pos: 0,
+            implicit,
+            source: query.encode_utf16().collect(),
+            negated: false,
+            field_column: None,
+            folding,
+            filters: Vec::new(),
         };
+        // Exclusions left on their own (next to unsearchable words) have
+        // nothing to exclude from
+        let root = parser
+            .parse_query()?
+            .and_then(simplify)
+            .filter(|root| !excludes_only(root));
+        Ok(Self {
+            root,
+            filters: parser.filters,
+        })
     }
-    
-    // Split by AND/OR keywords
-    let parts: Vec<&str> = remaining.split_whitespace().collect();
-    let mut i = 0;
-    let mut has_and = false;
-    
-    while i < parts.len() {
-        let part = parts[i];
-        
-        if part.eq_ignore_ascii_case("AND") {
-            has_and = true;
-            i += 1;
-            continue;
+
+    /// Also require `filter` of every result (no effect on an empty query)
+    pub fn require(&mut self, filter: FieldFilter) {
+        self.root = self.root.take().map(|root| match root {
+            QueryNode::And(mut children) => {
+                children.push(QueryNode::Filter(filter));
+                QueryNode::And(children)
+            }
+            root => QueryNode::And(vec![root, QueryNode::Filter(filter)]),
+        });
+    }
+
+    /// Nothing to search for
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Evaluate against a document; free text searches path, name and content
+    ///
+    /// Terms match whole tokens in any column; phrases must sit in one column.
+    pub fn matches(&self, doc: &SearchDocument) -> bool {
+        self.matches_in(doc, DEFAULT_COLUMNS)
+    }
+
+    /// Evaluate with free text restricted to `columns`
+    pub fn matches_in(&self, doc: &SearchDocument, columns: &[TextColumn]) -> bool {
+        self.root
+            .as_ref()
+            .is_some_and(|root| evaluate(root, doc, columns))
+    }
+
+    /// Terms and phrases that must or may match (not the excluded ones),
+    /// as text for highlighting
+    pub fn positive_terms(&self) -> Vec<String> {
+        let mut terms = Vec::new();
+        if let Some(root) = &self.root {
+            collect_positive(root, &mut terms);
         }
-        
-        if part.eq_ignore_ascii_case("OR") {
-            // OR is now explicit - just skip the keyword, terms are treated as optional
-            i += 1;
+        terms
+    }
+}
+
+/// Runs of unicode61's token characters: letters, numbers and private use
+/// characters. Everything else separates tokens, combining marks included
+/// (vocalized Arabic `مُحَمَّد` is the tokens `م`, `ح`, `م`, `د`).
+fn token_regex() -> &'static Regex {
+    static TOKEN: OnceLock<Regex> = OnceLock::new();
+    TOKEN.get_or_init(|| Regex::new(r"[\p{L}\p{N}\p{Co}]+").expect("valid token pattern"))
+}
+
+/// Text normalized and split into tokens the way the FTS5 index does it
+pub struct TokenizedText {
+    text: String,
+    tokens: Vec<(usize, usize)>,
+}
+
+impl TokenizedText {
+    pub fn new(text: &str) -> Self {
+        let text = normalize_for_search(text);
+        let tokens = token_regex()
+            .find_iter(&text)
+            .map(|token| (token.start(), token.end()))
+            .collect();
+        Self { text, tokens }
+    }
+
+    /// The normalized text
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The tokens, in order
+    pub fn tokens(&self) -> impl Iterator<Item = &str> {
+        (0..self.tokens.len()).map(|i| self.token(i))
+    }
+
+    fn token(&self, i: usize) -> &str {
+        let (start, end) = self.tokens[i];
+        &self.text[start..end]
+    }
+
+    /// Token indices where `phrase` starts; with `prefix`, its last word
+    /// only has to begin a token
+    fn phrase_starts<'p>(
+        &'p self,
+        phrase: &'p [String],
+        prefix: bool,
+    ) -> impl Iterator<Item = usize> + 'p {
+        let candidates = if phrase.is_empty() {
+            0
+        } else {
+            (self.tokens.len() + 1).saturating_sub(phrase.len())
+        };
+        (0..candidates).filter(move |&i| {
+            phrase.iter().enumerate().all(|(j, word)| {
+                let token = self.token(i + j);
+                if prefix && j == phrase.len() - 1 {
+                    token.starts_with(word.as_str())
+                } else {
+                    token == word
+                }
+            })
+        })
+    }
+
+    fn contains_phrase(&self, phrase: &[String], prefix: bool) -> bool {
+        self.phrase_starts(phrase, prefix).next().is_some()
+    }
+
+    /// Every phrase occurs with at most `distance` tokens between the end of
+    /// the first and the start of the last, in any order (as FTS5's NEAR)
+    fn contains_near(&self, phrases: &[(&[String], bool)], distance: usize) -> bool {
+        let starts: Vec<Vec<usize>> = phrases
+            .iter()
+            .map(|&(phrase, prefix)| self.phrase_starts(phrase, prefix).collect())
+            .collect();
+        // Try each occurrence as the first, with the earliest occurrence of
+        // every other phrase from there on
+        starts.iter().enumerate().any(|(i, anchors)| {
+            anchors.iter().any(|&anchor| {
+                let mut last = anchor;
+                for (j, others) in starts.iter().enumerate() {
+                    if j == i {
+                        continue;
+                    }
+                    match others.iter().find(|&&start| start >= anchor) {
+                        Some(&start) => last = last.max(start),
+                        None => return false,
+                    }
+                }
+                last <= anchor + phrases[i].0.len() + distance
+            })
+        })
+    }
+}
+
+/// A file as the in-memory evaluator sees it: each `files_fts` column
+/// tokenized (name and content already folded, as in the index)
+pub struct SearchDocument<'a> {
+    pub file: &'a FileData,
+    pub path: TokenizedText,
+    pub name: TokenizedText,
+    pub content: TokenizedText,
+    pub file_type: TokenizedText,
+}
+
+impl<'a> SearchDocument<'a> {
+    pub fn new(file: &'a FileData, folded_name: &str, folded_content: &str) -> Self {
+        Self {
+            file,
+            path: TokenizedText::new(&file.path),
+            name: TokenizedText::new(folded_name),
+            content: TokenizedText::new(folded_content),
+            file_type: TokenizedText::new(&file.file_type),
+        }
+    }
+
+    fn column(&self, column: TextColumn) -> &TokenizedText {
+        match column {
+            TextColumn::Path => &self.path,
+            TextColumn::Name => &self.name,
+            TextColumn::Content => &self.content,
+            TextColumn::Type => &self.file_type,
+        }
+    }
+}
+
+/// Lowercase and strip Latin diacritics (as `unicode61 remove_diacritics 1`)
+pub fn normalize_for_search(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars().flat_map(char::to_lowercase) {
+        // Combining diacritical marks
+        if ('\u{0300}'..='\u{036F}').contains(&c) {
             continue;
         }
-        
-        if part.eq_ignore_ascii_case("NOT") || part.starts_with('-') {
-            let term = if part.starts_with('-') {
-                &part[1..]
-            } else if i + 1 < parts.len() {
-                i += 1;
-                parts[i]
-            } else {
-                i += 1;
-                continue;
-            };
-            if !term.is_empty() {
-                excluded.push(term.to_lowercase());
+        out.push(remove_diacritic(c));
+    }
+    out
+}
+
+/// Base letter of a Latin letter with one diacritic (`é`, `ǎ`, `ạ`)
+///
+/// A letter whose canonical decomposition is an ASCII letter plus a single
+/// combining mark loses the mark. Like unicode61 with `remove_diacritics 1`,
+/// letters with two marks (Vietnamese `ế`) and other scripts (Greek `ά`)
+/// are kept as they are.
+fn remove_diacritic(c: char) -> char {
+    if c.is_ascii() {
+        return c;
+    }
+    let mut base = None;
+    let mut marks = 0;
+    decompose_canonical(c, |part| match base {
+        None => base = Some(part),
+        Some(_) => marks += 1,
+    });
+    match base {
+        Some(base) if marks == 1 && base.is_ascii_alphabetic() => base,
+        _ => c,
+    }
+}
+
+/// Normalized tokens of a word or phrase
+fn tokenize(text: &str) -> Vec<String> {
+    TokenizedText::new(text)
+        .tokens()
+        .map(str::to_string)
+        .collect()
+}
+
+/// A query syntax error
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryError {
+    pub message: String,
+    /// Offset into the query in UTF-16 code units (as the search box counts)
+    pub position: usize,
+}
+
+impl std::fmt::Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (at character {})", self.message, self.position + 1)
+    }
+}
+
+fn error<T>(message: impl Into<String>, position: usize) -> Result<T, QueryError> {
+    Err(QueryError {
+        message: message.into(),
+        position,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word(String),
+    Quoted(String),
+    And,
+    Or,
+    Not,
+    /// `NEAR` or `NEAR/N`
+    Near(usize),
+    /// `~N` directly after a quoted phrase
+    Slop(usize),
+    /// `-` directly before a word, phrase or group
+    Minus,
+    LParen,
+    RParen,
+    /// `name:` and friends, directly followed by their value
+    Field(Field),
+}
+
+impl TokenKind {
+    fn describe(&self) -> &'static str {
+        match self {
+            TokenKind::And => "'AND'",
+            TokenKind::Or => "'OR'",
+            TokenKind::Not => "'NOT'",
+            TokenKind::Near(_) => "'NEAR'",
+            TokenKind::Minus => "'-'",
+            _ => "this",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    /// UTF-16 offsets of the token in the query
+    start: usize,
+    end: usize,
+}
+
+fn utf16_len(text: &str) -> usize {
+    text.chars().map(char::len_utf16).sum()
+}
+
+fn lex(query: &str) -> Result<Vec<Token>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut chars = query.chars().peekable();
+    let mut pos = 0;
+
+    while let Some(&c) = chars.peek() {
+        let start = pos;
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                pos += c.len_utf16();
+            }
+            '(' | ')' => {
+                chars.next();
+                pos += 1;
+                let kind = if c == '(' {
+                    TokenKind::LParen
+                } else {
+                    TokenKind::RParen
+                };
+                tokens.push(Token {
+                    kind,
+                    start,
+                    end: pos,
+                });
+            }
+            '"' => {
+                chars.next();
+                pos += 1;
+                let mut phrase = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => {
+                            pos += 1;
+                            break;
+                        }
+                        Some(c) => {
+                            pos += c.len_utf16();
+                            phrase.push(c);
+                        }
+                        None => return error("Missing closing quote", start),
+                    }
+                }
+                // `"board mee"*` ends in a prefix, like `"board mee*"`
+                if chars.peek() == Some(&'*') {
+                    chars.next();
+                    pos += 1;
+                    phrase.push('*');
+                }
+                tokens.push(Token {
+                    kind: TokenKind::Quoted(phrase),
+                    start,
+                    end: pos,
+                });
+                if chars.peek() == Some(&'~') {
+                    let slop_start = pos;
+                    chars.next();
+                    pos += 1;
+                    let mut digits = String::new();
+                    while let Some(&d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+                        digits.push(d);
+                        chars.next();
+                        pos += 1;
+                    }
+                    let distance = if digits.is_empty() {
+                        DEFAULT_NEAR_DISTANCE
+                    } else {
+                        parse_distance(&digits, slop_start)?
+                    };
+                    tokens.push(Token {
+                        kind: TokenKind::Slop(distance),
+                        start: slop_start,
+                        end: pos,
+                    });
+                }
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '(' | ')' | '"') {
+                        break;
+                    }
+                    word.push(c);
+                    pos += c.len_utf16();
+                    chars.next();
+                }
+                let kind = match word.as_str() {
+                    "AND" => Some(TokenKind::And),
+                    "OR" => Some(TokenKind::Or),
+                    "NOT" => Some(TokenKind::Not),
+                    "NEAR" => Some(TokenKind::Near(DEFAULT_NEAR_DISTANCE)),
+                    _ => match word.strip_prefix("NEAR/") {
+                        Some(distance) => Some(TokenKind::Near(parse_distance(distance, start)?)),
+                        None => None,
+                    },
+                };
+                if let Some(kind) = kind {
+                    tokens.push(Token {
+                        kind,
+                        start,
+                        end: pos,
+                    });
+                    continue;
+                }
+
+                // `-x` / `-"a b"` / `-(...)` exclude; `+x` is the default
+                let mut word = word.as_str();
+                let mut word_start = start;
+                if word.starts_with('-')
+                    && (word.len() > 1 || matches!(chars.peek(), Some('"' | '(')))
+                {
+                    tokens.push(Token {
+                        kind: TokenKind::Minus,
+                        start,
+                        end: start + 1,
+                    });
+                    word = &word[1..];
+                    word_start += 1;
+                }
+                lex_word(word, word_start, chars.peek().copied(), &mut tokens)?;
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn parse_distance(digits: &str, position: usize) -> Result<usize, QueryError> {
+    match digits.parse() {
+        Ok(distance) => Ok(distance),
+        Err(_) => error("Expected a distance in words, like NEAR/5", position),
+    }
+}
+
+/// A bare word, or a field qualifier and its value
+fn lex_word(
+    word: &str,
+    start: usize,
+    next: Option<char>,
+    tokens: &mut Vec<Token>,
+) -> Result<(), QueryError> {
+    let end = start + utf16_len(word);
+    if let Some((name, value)) = word.split_once(':') {
+        if let Some(field) = Field::from_name(name) {
+            let value_start = start + utf16_len(name) + 1;
+            tokens.push(Token {
+                kind: TokenKind::Field(field),
+                start,
+                end: value_start,
+            });
+            if !value.is_empty() {
+                tokens.push(Token {
+                    kind: TokenKind::Word(value.to_string()),
+                    start: value_start,
+                    end,
+                });
+            } else if !matches!(next, Some('"' | '(')) {
+                return error(format!("Expected a value after '{}:'", name), start);
+            }
+            return Ok(());
+        }
+    }
+    if !word.is_empty() {
+        tokens.push(Token {
+            kind: TokenKind::Word(word.trim_start_matches('+').to_string()),
+            start,
+            end,
+        });
+    }
+    Ok(())
+}
+
+/// The words of a query searched for as single terms (not excluded,
+/// prefixes or metadata values), as typed and where they sit
+///
+/// For rewriting a query word by word, e.g. into a "did you mean" query.
+pub fn plain_words(query: &str) -> Result<Vec<(String, TextSpan)>, QueryError> {
+    let tokens = lex(query)?;
+    let mut words = Vec::new();
+    let mut depth = 0;
+    // Depth of the outermost excluded group we're in
+    let mut excluded_group = None;
+    for (i, token) in tokens.iter().enumerate() {
+        let after_exclusion =
+            i > 0 && matches!(tokens[i - 1].kind, TokenKind::Minus | TokenKind::Not);
+        match &token.kind {
+            TokenKind::LParen => {
+                depth += 1;
+                if after_exclusion && excluded_group.is_none() {
+                    excluded_group = Some(depth);
+                }
+            }
+            TokenKind::RParen => {
+                if excluded_group == Some(depth) {
+                    excluded_group = None;
+                }
+                depth -= 1;
+            }
+            TokenKind::Word(word) => {
+                let qualified = i > 0
+                    && matches!(tokens[i - 1].kind, TokenKind::Field(field) if field.column().is_none());
+                if after_exclusion
+                    || qualified
+                    || excluded_group.is_some()
+                    || word.starts_with('*')
+                    || word.ends_with('*')
+                {
+                    continue;
+                }
+                if tokenize(word).len() == 1 {
+                    words.push((
+                        word.clone(),
+                        TextSpan {
+                            start: token.start,
+                            end: token.end,
+                        },
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(words)
+}
+
+/// Recursive descent with precedence NEAR > NOT > AND > OR
+///
+/// Adjacent operands combine with the configured implicit operator;
+/// exclusions always apply to what they're ANDed with.
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    implicit: ImplicitOperator,
+    /// The query in UTF-16 units, for echoing qualifier values
+    source: Vec<u16>,
+    /// Inside an odd number of NOTs
+    negated: bool,
+    /// The text field whose value is being parsed
+    field_column: Option<TextColumn>,
+    /// Applied to searched text before it's tokenized
+    folding: &'a CharFolding,
+    filters: Vec<FilterChip>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&TokenKind> {
+        self.tokens.get(self.pos).map(|t| &t.kind)
+    }
+
+    fn current_pos(&self) -> usize {
+        self.tokens.get(self.pos).map_or(0, |t| t.start)
+    }
+
+    fn starts_operand(&self) -> bool {
+        matches!(
+            self.peek(),
+            Some(
+                TokenKind::Word(_)
+                    | TokenKind::Quoted(_)
+                    | TokenKind::LParen
+                    | TokenKind::Not
+                    | TokenKind::Minus
+                    | TokenKind::Field(_)
+            )
+        )
+    }
+
+    /// Consume a binary operator, requiring an operand after it
+    fn expect_operand_after(&mut self) -> Result<(), QueryError> {
+        let token = self.tokens[self.pos].clone();
+        self.pos += 1;
+        if !self.starts_operand() {
+            return error(
+                format!("Expected a term after {}", token.kind.describe()),
+                token.start,
+            );
+        }
+        Ok(())
+    }
+
+    fn parse_query(&mut self) -> Result<Option<QueryNode>, QueryError> {
+        let root = self.parse_or()?;
+        if let Some(token) = self.tokens.get(self.pos) {
+            // parse_or only stops early at a closing parenthesis
+            return error("Unexpected ')' without a matching '('", token.start);
+        }
+        if root.as_ref().is_some_and(excludes_only) {
+            return error("The query only excludes terms; add a term to search for", 0);
+        }
+        Ok(root)
+    }
+
+    fn parse_or(&mut self) -> Result<Option<QueryNode>, QueryError> {
+        let mut operands = vec![(self.current_pos(), self.parse_and()?)];
+        loop {
+            match self.peek() {
+                Some(TokenKind::Or) => {
+                    if operands.len() == 1 && operands[0].1.is_none() {
+                        return error("Expected a term before 'OR'", self.current_pos());
+                    }
+                    self.expect_operand_after()?;
+                }
+                _ if self.implicit == ImplicitOperator::Or && self.starts_operand() => {}
+                _ => break,
+            }
+            operands.push((self.current_pos(), self.parse_and()?));
+        }
+
+        if operands.len() > 1 {
+            if let Some((pos, _)) = operands
+                .iter()
+                .find(|(_, node)| node.as_ref().is_some_and(excludes_only))
+            {
+                return error(
+                    "An exclusion can't be an alternative on its own; combine it with a term",
+                    *pos,
+                );
             }
-            i += 1;
-            continue;
         }
-        
-        if part.starts_with('+') {
-            let term = &part[1..];
-            if !term.is_empty() {
-                required.push(term.to_lowercase());
+        Ok(combine(
+            operands.into_iter().filter_map(|(_, node)| node).collect(),
+            QueryNode::Or,
+        ))
+    }
+
+    fn parse_and(&mut self) -> Result<Option<QueryNode>, QueryError> {
+        let mut children = Vec::new();
+        let mut operands = 0;
+        loop {
+            match self.peek() {
+                None | Some(TokenKind::Or) | Some(TokenKind::RParen) => break,
+                Some(TokenKind::And) => {
+                    if operands == 0 {
+                        return error("Expected a term before 'AND'", self.current_pos());
+                    }
+                    self.expect_operand_after()?;
+                }
+                Some(TokenKind::Not) | Some(TokenKind::Minus) => {}
+                Some(TokenKind::Near(_)) => {
+                    return error(
+                        "Expected a word or phrase before 'NEAR'",
+                        self.current_pos(),
+                    );
+                }
+                Some(_) if operands > 0 && self.implicit == ImplicitOperator::Or => break,
+                Some(_) => {}
             }
+            children.extend(self.parse_unary()?);
+            operands += 1;
+        }
+        Ok(combine(children, QueryNode::And))
+    }
+
+    fn parse_unary(&mut self) -> Result<Option<QueryNode>, QueryError> {
+        match self.peek() {
+            Some(TokenKind::Not) | Some(TokenKind::Minus) => {
+                self.expect_operand_after()?;
+                self.negated = !self.negated;
+                let inner = self.parse_unary()?;
+                self.negated = !self.negated;
+                Ok(inner.map(|node| QueryNode::Not(Box::new(node))))
+            }
+            _ => self.parse_near(),
+        }
+    }
+
+    /// Operands joined by NEAR; `a NEAR/2 b NEAR/5 c` needs each
+    /// neighbouring pair within its distance
+    fn parse_near(&mut self) -> Result<Option<QueryNode>, QueryError> {
+        let mut operand_start = self.current_pos();
+        let mut operand = match self.peek() {
+            Some(&TokenKind::Field(field)) => self.parse_field(field)?,
+            _ => self.parse_primary()?,
+        };
+        let mut groups = Vec::new();
+        while let Some(&TokenKind::Near(distance)) = self.peek() {
+            let near_start = self.current_pos();
+            let Some(left) = operand.filter(|node| node.as_phrase().is_some()) else {
+                return error("NEAR can only join words and phrases", operand_start);
+            };
+            self.pos += 1;
+            operand_start = self.current_pos();
+            if !matches!(self.peek(), Some(TokenKind::Word(_) | TokenKind::Quoted(_))) {
+                return error("Expected a word or phrase after 'NEAR'", near_start);
+            }
+            let Some(right) = self
+                .parse_primary()?
+                .filter(|node| node.as_phrase().is_some())
+            else {
+                return error("NEAR can only join words and phrases", operand_start);
+            };
+            groups.push(QueryNode::Near(vec![left, right.clone()], distance));
+            operand = Some(right);
+        }
+        if groups.is_empty() {
+            Ok(operand)
         } else {
-            optional.push(part.to_lowercase());
+            Ok(combine(groups, QueryNode::And))
+        }
+    }
+
+    /// A qualifier and its value: a word, phrase or (for text fields) a group
+    fn parse_field(&mut self, field: Field) -> Result<Option<QueryNode>, QueryError> {
+        let qualifier = self.tokens[self.pos].clone();
+        if self.field_column.is_some() {
+            return error("Field qualifiers can't be nested", qualifier.start);
+        }
+        // The lexer guarantees a value token follows
+        self.pos += 1;
+        let value = self.tokens[self.pos].clone();
+
+        let node = match field.column() {
+            Some(column) => {
+                self.field_column = Some(column);
+                let inner = self.parse_primary();
+                self.field_column = None;
+                inner?
+                    .map(|node| match (column, node) {
+                        // `type:docx` means the type of .docx files
+                        (TextColumn::Type, QueryNode::Term(term)) => QueryNode::Term(
+                            crate::extractors::get_file_type(&term)
+                                .or_else(|| crate::extractors::known_text_type(&term))
+                                .map_or(term, str::to_string),
+                        ),
+                        (_, node) => node,
+                    })
+                    .map(|node| QueryNode::Column(column, Box::new(node)))
+            }
+            None => {
+                let (TokenKind::Word(text) | TokenKind::Quoted(text)) = &value.kind else {
+                    return error(
+                        format!("Expected a value after '{}:'", field.name()),
+                        qualifier.start,
+                    );
+                };
+                self.pos += 1;
+                let filter =
+                    FieldFilter::parse(field, text, Utc::now()).map_err(|message| QueryError {
+                        message,
+                        position: value.start,
+                    })?;
+                Some(QueryNode::Filter(filter))
+            }
+        };
+
+        let end = self.tokens[self.pos - 1].end;
+        self.filters.push(FilterChip {
+            field: field.name().to_string(),
+            value: String::from_utf16_lossy(&self.source[value.start..end]),
+            excluded: self.negated,
+            span: TextSpan {
+                start: qualifier.start,
+                end,
+            },
+        });
+        Ok(node)
+    }
+
+    /// Text as the index holds it: paths and file types aren't folded
+    fn fold<'t>(&self, text: &'t str) -> Cow<'t, str> {
+        match self.field_column {
+            Some(TextColumn::Path | TextColumn::Type) => Cow::Borrowed(text),
+            _ => self.folding.fold(text),
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Option<QueryNode>, QueryError> {
+        let Some(token) = self.tokens.get(self.pos).cloned() else {
+            return Ok(None);
+        };
+        self.pos += 1;
+        match token.kind {
+            TokenKind::LParen => {
+                if self.peek() == Some(&TokenKind::RParen) {
+                    return error("Empty parentheses", token.start);
+                }
+                let inner = self.parse_or()?;
+                if self.peek() != Some(&TokenKind::RParen) {
+                    return error("Missing ')' to close this '('", token.start);
+                }
+                self.pos += 1;
+                Ok(inner)
+            }
+            TokenKind::Word(text) | TokenKind::Quoted(text) if text.starts_with('*') => {
+                let text = normalize_for_search(&self.fold(text.trim_matches('*')));
+                Ok((!text.trim().is_empty()).then_some(QueryNode::Substring(text)))
+            }
+            TokenKind::Word(text) | TokenKind::Quoted(text) => {
+                let (text, prefix) = match text.strip_suffix('*') {
+                    Some(text) => (text, true),
+                    None => (text.as_str(), false),
+                };
+                // Words of punctuation only have no tokens to search for
+                let mut words = tokenize(&self.fold(text));
+                if let Some(&TokenKind::Slop(distance)) = self.peek() {
+                    self.pos += 1;
+                    if words.len() > 1 {
+                        let last = words.len() - 1;
+                        let operands = words
+                            .into_iter()
+                            .enumerate()
+                            .map(|(i, word)| {
+                                if prefix && i == last {
+                                    QueryNode::Prefix(vec![word])
+                                } else {
+                                    QueryNode::Term(word)
+                                }
+                            })
+                            .collect();
+                        return Ok(Some(QueryNode::Near(operands, distance)));
+                    }
+                }
+                Ok(match words.len() {
+                    0 => None,
+                    _ if prefix => Some(QueryNode::Prefix(words)),
+                    1 => words.pop().map(QueryNode::Term),
+                    _ => Some(QueryNode::Phrase(words)),
+                })
+            }
+            _ => Ok(None),
         }
-        
-        i += 1;
     }
-    
-    // If AND was used, treat all optional terms as required
-    if has_and {
-        required.extend(optional.drain(..));
+}
+
+/// A node that only excludes (`-a`, `-a -b`)
+fn excludes_only(node: &QueryNode) -> bool {
+    match node {
+        QueryNode::Not(_) => true,
+        QueryNode::And(children) => children.iter().all(excludes_only),
+        QueryNode::Column(_, inner) => excludes_only(inner),
+        _ => false,
     }
-    // If OR was used explicitly, keep as optional (any match works)
-    // If neither AND nor OR, single word goes to optional for flexibility
-    
-    // Exact phrases are always required
-    required.extend(exact_phrases.iter().cloned());
-    
-    ParsedQuery {
-        required_terms: required,
-        optional_terms: optional,
-        excluded_terms: excluded,
-        exact_phrases,
+}
+
+fn combine(
+    mut children: Vec<QueryNode>,
+    node: fn(Vec<QueryNode>) -> QueryNode,
+) -> Option<QueryNode> {
+    match children.len() {
+        0 => None,
+        1 => children.pop(),
+        _ => Some(node(children)),
     }
 }
 
-/// Check if text matches the parsed query
-pub fn matches_parsed_query(text: &str, query: &ParsedQuery) -> bool {
-    // Check excluded terms first
-    for term in &query.excluded_terms {
-        if text.contains(term) {
-            return false;
+/// Flatten nested groups (and drop anything left unsearchable, e.g. a group
+/// of punctuation-only words); Chinese and Japanese words become substrings
+///
+/// A group of exclusions joins the AND around it (`budget (-draft -menu)` is
+/// `budget -draft -menu`); on its own it stays all exclusions, which
+/// `SearchQuery::parse` drops.
+fn simplify(node: QueryNode) -> Option<QueryNode> {
+    match node {
+        QueryNode::Term(term) if has_cjk(&term) => Some(QueryNode::Substring(term)),
+        QueryNode::Phrase(words) | QueryNode::Prefix(words)
+            if words.iter().any(|word| has_cjk(word)) =>
+        {
+            Some(QueryNode::Substring(words.join(" ")))
+        }
+        QueryNode::Term(_)
+        | QueryNode::Prefix(_)
+        | QueryNode::Near(..)
+        | QueryNode::Substring(_)
+        | QueryNode::Stem(_)
+        | QueryNode::Filter(_) => Some(node),
+        QueryNode::Phrase(mut words) if words.len() == 1 => words.pop().map(QueryNode::Term),
+        QueryNode::Phrase(_) => Some(node),
+        QueryNode::Not(inner) => match simplify(*inner)? {
+            QueryNode::Not(double) => Some(*double),
+            // `-(-a -b)` is `a OR b`
+            QueryNode::And(children) if children.iter().all(excludes_only) => {
+                simplify(QueryNode::Or(children.into_iter().map(negate).collect()))
+            }
+            inner => Some(QueryNode::Not(Box::new(inner))),
+        },
+        QueryNode::And(children) => {
+            let mut flat = Vec::new();
+            for child in children.into_iter().filter_map(simplify) {
+                match child {
+                    QueryNode::And(grandchildren) => flat.extend(grandchildren),
+                    other => flat.push(other),
+                }
+            }
+            combine(flat, QueryNode::And)
         }
+        QueryNode::Or(children) => {
+            let mut flat = Vec::new();
+            for child in children.into_iter().filter_map(simplify) {
+                match child {
+                    QueryNode::Or(grandchildren) => flat.extend(grandchildren),
+                    // `a OR -b` can't be expressed in FTS5
+                    other if excludes_only(&other) => {}
+                    other => flat.push(other),
+                }
+            }
+            combine(flat, QueryNode::Or)
+        }
+        QueryNode::Column(column, inner) => Some(match simplify(*inner)? {
+            // `name:(-draft)` is `-name:draft`
+            QueryNode::Not(inner) => QueryNode::Not(Box::new(QueryNode::Column(column, inner))),
+            // `name:(-a -b)` is `-name:a -name:b`
+            QueryNode::And(children) if children.iter().all(excludes_only) => QueryNode::And(
+                children
+                    .into_iter()
+                    .map(|child| {
+                        let inner = negate(child);
+                        QueryNode::Not(Box::new(QueryNode::Column(column, Box::new(inner))))
+                    })
+                    .collect(),
+            ),
+            inner => QueryNode::Column(column, Box::new(inner)),
+        }),
+    }
+}
+
+/// What a simplified exclusion excludes
+fn negate(node: QueryNode) -> QueryNode {
+    match node {
+        QueryNode::Not(inner) => *inner,
+        node => QueryNode::Not(Box::new(node)),
+    }
+}
+
+/// Han, kana and other scripts written without spaces between words
+fn has_cjk(text: &str) -> bool {
+    text.chars().any(|c| {
+        matches!(c,
+            '\u{3040}'..='\u{30FF}'   // Hiragana, Katakana
+            | '\u{31F0}'..='\u{31FF}' // Katakana extensions
+            | '\u{3400}'..='\u{4DBF}' // CJK Extension A
+            | '\u{4E00}'..='\u{9FFF}' // CJK Unified Ideographs
+            | '\u{F900}'..='\u{FAFF}' // CJK Compatibility Ideographs
+            | '\u{FF66}'..='\u{FF9F}' // Halfwidth Katakana
+            | '\u{20000}'..='\u{2FA1F}')
+    })
+}
+
+fn quote_fts5(words: &[String]) -> String {
+    format!("\"{}\"", words.join(" ").replace('"', "\"\""))
+}
+
+/// A term, phrase or prefix as an FTS5 phrase (`"a b"`, `"a b"*`)
+fn fts5_phrase(node: &QueryNode) -> String {
+    match node.as_phrase() {
+        Some((words, true)) => format!("{}*", quote_fts5(words)),
+        Some((words, false)) => quote_fts5(words),
+        None => String::new(),
     }
-    
-    // Check exact phrases
-    for phrase in &query.exact_phrases {
-        if !text.contains(phrase) {
-            return false;
+}
+
+/// Compile a text-only node to an FTS5 `MATCH` expression
+///
+/// `None` if the node has metadata predicates, substrings or stems, which
+/// the word index can't evaluate.
+pub fn compile_fts5(node: &QueryNode) -> Option<String> {
+    (!node.has_filter() && !node.has_substring() && !node.has_stem())
+        .then(|| fts5_expression(node, FTS5_TEXT_COLUMNS))
+}
+
+/// Compile a node made only of substrings to a `MATCH` expression for the
+/// trigram index (same columns as `files_fts`)
+///
+/// `None` if anything else is in it, or a substring is too short to look up.
+pub fn compile_trigram(node: &QueryNode) -> Option<String> {
+    let leaf = |node: &QueryNode| match node {
+        QueryNode::Substring(text) => text.chars().count() >= TRIGRAM_MIN_CHARS,
+        _ => false,
+    };
+    made_of(node, &leaf).then(|| fts5_expression(node, FTS5_TEXT_COLUMNS))
+}
+
+/// Compile a node made only of stems to a `MATCH` expression for the
+/// stemmed index (same columns as `files_fts`)
+pub fn compile_stemmed(node: &QueryNode) -> Option<String> {
+    let leaf = |node: &QueryNode| matches!(node, QueryNode::Stem(_));
+    made_of(node, &leaf).then(|| fts5_expression(node, FTS5_TEXT_COLUMNS))
+}
+
+/// Only `leaf` nodes, combined in a way FTS5 can express
+fn made_of(node: &QueryNode, leaf: &dyn Fn(&QueryNode) -> bool) -> bool {
+    match node {
+        QueryNode::Or(children) => children.iter().all(|c| made_of(c, leaf)),
+        // FTS5's NOT is binary, so an AND needs something to exclude from
+        QueryNode::And(children) => {
+            children.iter().any(|c| !matches!(c, QueryNode::Not(_)))
+                && children.iter().all(|c| match c {
+                    QueryNode::Not(inner) => made_of(inner, leaf),
+                    c => made_of(c, leaf),
+                })
         }
+        QueryNode::Column(_, inner) => made_of(inner, leaf),
+        node => leaf(node),
     }
-    
-    // Check required terms (all must match)
-    for term in &query.required_terms {
-        if !text.contains(term) {
-            return false;
+}
+
+/// Every phrase carries its column filter, since FTS5 intersects nested ones
+fn fts5_expression(node: &QueryNode, columns: &str) -> String {
+    let compile = |node: &QueryNode| fts5_expression(node, columns);
+    match node {
+        QueryNode::Term(_) | QueryNode::Phrase(_) | QueryNode::Prefix(_) => {
+            format!("{} : {}", columns, fts5_phrase(node))
+        }
+        QueryNode::Near(operands, distance) => {
+            let phrases: Vec<String> = operands.iter().map(fts5_phrase).collect();
+            format!("{} : NEAR({}, {})", columns, phrases.join(" "), distance)
+        }
+        QueryNode::Substring(text) | QueryNode::Stem(text) => {
+            format!("{} : {}", columns, quote_fts5(std::slice::from_ref(text)))
+        }
+        QueryNode::Column(column, inner) => {
+            fts5_expression(inner, &format!("{{{}}}", column.fts5_name()))
+        }
+        QueryNode::Or(children) => {
+            let parts: Vec<String> = children.iter().map(compile).collect();
+            format!("({})", parts.join(" OR "))
         }
+        QueryNode::And(children) => {
+            // FTS5's NOT is binary: (positives) NOT (x) NOT (y)
+            let (excluded, included): (Vec<&QueryNode>, Vec<&QueryNode>) = children
+                .iter()
+                .partition(|c| matches!(c, QueryNode::Not(_)));
+            let parts: Vec<String> = included.iter().map(|c| compile(c)).collect();
+            let mut expr = format!("({})", parts.join(" AND "));
+            for node in excluded {
+                if let QueryNode::Not(inner) = node {
+                    expr = format!("({} NOT {})", expr, compile(inner));
+                }
+            }
+            expr
+        }
+        // Only reachable inside an AND (see `simplify`)
+        QueryNode::Not(inner) => compile(inner),
+        // Excluded by `compile_fts5`
+        QueryNode::Filter(_) => String::new(),
     }
-    
-    // If we have optional terms and no required terms, at least one optional must match
-    if query.required_terms.is_empty() && !query.optional_terms.is_empty() {
-        return query.optional_terms.iter().any(|term| text.contains(term));
+}
+
+fn evaluate(node: &QueryNode, doc: &SearchDocument, columns: &[TextColumn]) -> bool {
+    match node {
+        QueryNode::Term(_) | QueryNode::Phrase(_) | QueryNode::Prefix(_) => {
+            let Some((words, prefix)) = node.as_phrase() else {
+                return false;
+            };
+            columns
+                .iter()
+                .any(|&c| doc.column(c).contains_phrase(words, prefix))
+        }
+        QueryNode::Near(operands, distance) => {
+            let phrases: Vec<(&[String], bool)> =
+                operands.iter().filter_map(QueryNode::as_phrase).collect();
+            columns
+                .iter()
+                .any(|&c| doc.column(c).contains_near(&phrases, *distance))
+        }
+        QueryNode::Substring(text) => columns
+            .iter()
+            .any(|&c| doc.column(c).text().contains(text.as_str())),
+        QueryNode::Stem(stem) => columns.iter().any(|&c| {
+            doc.column(c)
+                .tokens()
+                .any(|token| stem_word(token) == *stem)
+        }),
+        QueryNode::And(children) => children.iter().all(|c| evaluate(c, doc, columns)),
+        QueryNode::Or(children) => children.iter().any(|c| evaluate(c, doc, columns)),
+        QueryNode::Not(inner) => !evaluate(inner, doc, columns),
+        QueryNode::Column(column, inner) => evaluate(inner, doc, std::slice::from_ref(column)),
+        QueryNode::Filter(filter) => filter.matches(doc.file),
+    }
+}
+
+fn collect_positive(node: &QueryNode, terms: &mut Vec<String>) {
+    match node {
+        QueryNode::Term(term) | QueryNode::Substring(term) | QueryNode::Stem(term) => {
+            terms.push(term.clone())
+        }
+        QueryNode::Phrase(words) | QueryNode::Prefix(words) => terms.push(words.join(" ")),
+        QueryNode::And(children) | QueryNode::Or(children) | QueryNode::Near(children, _) => {
+            children.iter().for_each(|c| collect_positive(c, terms))
+        }
+        QueryNode::Column(_, inner) => collect_positive(inner, terms),
+        QueryNode::Not(_) | QueryNode::Filter(_) => {}
     }
-    
-    true
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+    use crate::commands::init_database;
+    use crate::models::{CharEquivalence, FileData, RankingSettings};
+    use crate::search::{
+        register_fold_function, register_stem_function, search_direct_content, search_fts5,
+        CharFolding,
+    };
+    use chrono::{TimeZone, Utc};
+    use rusqlite::{params, Connection};
+    use std::collections::HashSet;
+
+    fn term(t: &str) -> QueryNode {
+        QueryNode::Term(t.to_string())
+    }
+
+    fn parse(query: &str) -> SearchQuery {
+        SearchQuery::parse(query, ImplicitOperator::And).unwrap()
+    }
+
+    fn root(query: &str) -> Option<QueryNode> {
+        parse(query).root
+    }
+
+    fn error_at(query: &str) -> (String, usize) {
+        let err = SearchQuery::parse(query, ImplicitOperator::And).unwrap_err();
+        (err.message, err.position)
+    }
+
     #[test]
     fn test_simple_single_word() {
-        let parsed = parse_simple_query("hello");
-        assert_eq!(parsed.optional_terms, vec!["hello"]);
-        assert!(parsed.required_terms.is_empty());
+        assert_eq!(root("Hello"), Some(term("hello")));
     }
-    
+
     #[test]
-    fn test_multi_word_becomes_exact_phrase() {
-        // Multi-word without operators becomes exact phrase
-        let parsed = parse_simple_query("hello world");
-        assert!(parsed.optional_terms.is_empty());
-        assert_eq!(parsed.exact_phrases, vec!["hello world"]);
-        assert_eq!(parsed.required_terms, vec!["hello world"]);
+    fn test_multi_word_is_implicit_and() {
+        assert_eq!(
+            root("hello world"),
+            Some(QueryNode::And(vec![term("hello"), term("world")]))
+        );
     }
-    
+
     #[test]
     fn test_explicit_or_query() {
-        let parsed = parse_simple_query("hello OR world");
-        assert_eq!(parsed.optional_terms.len(), 2);
-        assert!(parsed.required_terms.is_empty());
+        assert_eq!(
+            root("hello OR world"),
+            Some(QueryNode::Or(vec![term("hello"), term("world")]))
+        );
+        // Lowercase operators are plain words
+        assert_eq!(
+            root("rock and roll"),
+            Some(QueryNode::And(vec![
+                term("rock"),
+                term("and"),
+                term("roll")
+            ]))
+        );
     }
-    
+
     #[test]
     fn test_and_query() {
-        let parsed = parse_simple_query("hello AND world");
-        assert!(parsed.optional_terms.is_empty());
-        assert_eq!(parsed.required_terms.len(), 2);
+        assert_eq!(root("hello AND world"), root("hello world"));
     }
-    
+
     #[test]
     fn test_not_query() {
-        let parsed = parse_simple_query("hello -spam");
-        assert_eq!(parsed.excluded_terms, vec!["spam"]);
+        let expected = Some(QueryNode::And(vec![
+            term("hello"),
+            QueryNode::Not(Box::new(term("spam"))),
+        ]));
+        assert_eq!(root("hello -spam"), expected);
+        assert_eq!(root("hello NOT spam"), expected);
+    }
+
+    #[test]
+    fn test_precedence() {
+        // AND binds tighter than OR
+        assert_eq!(
+            root("a OR b c"),
+            Some(QueryNode::Or(vec![
+                term("a"),
+                QueryNode::And(vec![term("b"), term("c")]),
+            ]))
+        );
+        // NOT binds tightest
+        assert_eq!(
+            root("NOT a b OR c"),
+            Some(QueryNode::Or(vec![
+                QueryNode::And(vec![QueryNode::Not(Box::new(term("a"))), term("b")]),
+                term("c"),
+            ]))
+        );
+        assert_eq!(
+            root("a (b OR c)"),
+            Some(QueryNode::And(vec![
+                term("a"),
+                QueryNode::Or(vec![term("b"), term("c")]),
+            ]))
+        );
     }
-    
+
+    #[test]
+    fn test_implicit_or() {
+        let query = |q| SearchQuery::parse(q, ImplicitOperator::Or).unwrap().root;
+        assert_eq!(
+            query("a b AND c"),
+            Some(QueryNode::Or(vec![
+                term("a"),
+                QueryNode::And(vec![term("b"), term("c")]),
+            ]))
+        );
+        // Exclusions still narrow the term they follow
+        assert_eq!(
+            query("a b -c"),
+            Some(QueryNode::Or(vec![
+                term("a"),
+                QueryNode::And(vec![term("b"), QueryNode::Not(Box::new(term("c")))]),
+            ]))
+        );
+    }
+
     #[test]
     fn test_explicit_exact_phrase() {
-        let parsed = parse_simple_query("\"exact phrase\"");
-        assert_eq!(parsed.exact_phrases, vec!["exact phrase"]);
+        let phrase = QueryNode::Phrase(vec!["exact".to_string(), "phrase".to_string()]);
+        assert_eq!(root("\"Exact phrase\""), Some(phrase));
+        // Hyphenated words are phrases, as FTS5 tokenizes them
+        assert_eq!(
+            root("e-mail"),
+            Some(QueryNode::Phrase(vec!["e".to_string(), "mail".to_string()]))
+        );
+    }
+
+    #[test]
+    fn test_grouping() {
+        assert_eq!(
+            root("(invoice OR receipt) 2023"),
+            Some(QueryNode::And(vec![
+                QueryNode::Or(vec![term("invoice"), term("receipt")]),
+                term("2023"),
+            ]))
+        );
+        // Punctuation-only words have nothing to search for
+        assert_eq!(root("\"\" -- ..."), None);
+
+        // Grouped exclusions apply to the rest of the query
+        let not = |node: QueryNode| QueryNode::Not(Box::new(node));
+        let name = |t: &str| QueryNode::Column(TextColumn::Name, Box::new(term(t)));
+        assert_eq!(
+            root("budget (-draft -menu)"),
+            Some(QueryNode::And(vec![
+                term("budget"),
+                not(term("draft")),
+                not(term("menu")),
+            ]))
+        );
+        assert_eq!(
+            root("budget (NOT draft NOT menu)"),
+            root("budget (-draft -menu)")
+        );
+        assert_eq!(
+            root("name:(-a -b) c"),
+            Some(QueryNode::And(vec![
+                not(name("a")),
+                not(name("b")),
+                term("c")
+            ]))
+        );
+        assert_eq!(
+            root("c -(-a -b)"),
+            Some(QueryNode::And(vec![
+                term("c"),
+                QueryNode::Or(vec![term("a"), term("b")]),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(error_at("(budget AND plan").1, 0);
+        assert_eq!(error_at("budget) plan").1, 6);
+        assert_eq!(error_at("budget AND").1, 7);
+        assert_eq!(error_at("OR budget").1, 0);
+        assert_eq!(error_at("budget NOT").1, 7);
+        assert_eq!(error_at("a () b").1, 2);
+        assert_eq!(error_at("say \"hello").1, 4);
+        assert_eq!(error_at("-spam").1, 0);
+        assert_eq!(error_at("hello OR -spam").1, 9);
+        // Positions count UTF-16 units, like the search box
+        assert_eq!(error_at("😀 OR").1, 3);
+
+        let err = SearchQuery::parse("budget AND", ImplicitOperator::And).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Expected a term after 'AND' (at character 8)"
+        );
+    }
+
+    #[test]
+    fn test_prefix_and_near() {
+        let words = |w: &[&str]| w.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+        assert_eq!(root("Contr*"), Some(QueryNode::Prefix(words(&["contr"]))));
+        assert_eq!(root("\"board mee\"*"), root("\"board mee*\""));
+        assert_eq!(
+            root("\"board mee\"*"),
+            Some(QueryNode::Prefix(words(&["board", "mee"])))
+        );
+        assert_eq!(
+            root("\"board meeting\"~5"),
+            Some(QueryNode::Near(vec![term("board"), term("meeting")], 5))
+        );
+        assert_eq!(root("\"budget\"~5"), Some(term("budget")));
+        assert_eq!(
+            root("a NEAR b"),
+            Some(QueryNode::Near(vec![term("a"), term("b")], 10))
+        );
+        // Pairwise, and tighter than NOT
+        assert_eq!(
+            root("x -a NEAR/2 b NEAR/5 \"c d\""),
+            Some(QueryNode::And(vec![
+                term("x"),
+                QueryNode::Not(Box::new(QueryNode::And(vec![
+                    QueryNode::Near(vec![term("a"), term("b")], 2),
+                    QueryNode::Near(vec![term("b"), QueryNode::Phrase(words(&["c", "d"]))], 5),
+                ]))),
+            ]))
+        );
+
+        assert_eq!(error_at("NEAR budget").1, 0);
+        assert_eq!(error_at("budget NEAR").1, 7);
+        assert_eq!(error_at("budget NEAR/x plan").1, 7);
+        assert_eq!(error_at("(a b) NEAR c").1, 0);
+        assert_eq!(error_at("a NEAR (b OR c)").1, 2);
+        assert_eq!(error_at("a NEAR ... b").1, 7);
+    }
+
+    #[test]
+    fn test_substrings() {
+        let substring = |text: &str| QueryNode::Substring(text.to_string());
+        assert_eq!(root("*AB-4471"), Some(substring("ab-4471")));
+        assert_eq!(root("*ware*"), Some(substring("ware")));
+        assert_eq!(root("* **"), None);
+        // unicode61 keeps these runs whole, so they're always substrings
+        assert_eq!(root("会议记录"), Some(substring("会议记录")));
+        assert_eq!(root("\"会议 记录\""), Some(substring("会议 记录")));
+        assert_eq!(
+            root("name:*ware -カタログ"),
+            Some(QueryNode::And(vec![
+                QueryNode::Column(TextColumn::Name, Box::new(substring("ware"))),
+                QueryNode::Not(Box::new(substring("カタログ"))),
+            ]))
+        );
+
+        // Only substrings long enough for trigrams go to the trigram index
+        let trigram = |q| compile_trigram(&parse(q).root.unwrap());
+        assert_eq!(
+            trigram("*4471 -会议记录").unwrap(),
+            r#"(({path name content} : "4471") NOT {path name content} : "会议记录")"#
+        );
+        assert_eq!(trigram("会议"), None);
+        assert_eq!(trigram("*4471 budget"), None);
+        assert_eq!(compile_fts5(&parse("*4471 budget").root.unwrap()), None);
+    }
+
+    #[test]
+    fn test_field_qualifiers() {
+        let query = parse("name:budget -ext:tmp size:>2MB");
+        let chips: Vec<(&str, &str, bool, TextSpan)> = query
+            .filters
+            .iter()
+            .map(|c| (c.field.as_str(), c.value.as_str(), c.excluded, c.span))
+            .collect();
+        assert_eq!(
+            chips,
+            [
+                ("name", "budget", false, TextSpan { start: 0, end: 11 }),
+                ("ext", "tmp", true, TextSpan { start: 13, end: 20 }),
+                ("size", ">2MB", false, TextSpan { start: 21, end: 30 }),
+            ]
+        );
+
+        let column = |column, node| QueryNode::Column(column, Box::new(node));
+        assert_eq!(
+            root("NAME:\"Q3 report\" type:docx"),
+            Some(QueryNode::And(vec![
+                column(
+                    TextColumn::Name,
+                    QueryNode::Phrase(vec!["q3".to_string(), "report".to_string()])
+                ),
+                column(TextColumn::Type, term("word")),
+            ]))
+        );
+        let query = parse("path:(clients OR vendors)");
+        assert_eq!(query.filters[0].value, "(clients OR vendors)");
+        // Unknown prefixes are plain text
+        assert_eq!(
+            root(r"C:\docs"),
+            Some(QueryNode::Phrase(vec!["c".to_string(), "docs".to_string()]))
+        );
+
+        assert_eq!(error_at("budget name:").1, 7);
+        assert_eq!(error_at("size:2MB").1, 5);
+        assert_eq!(error_at("size:(1)").1, 0);
+        assert_eq!(error_at("name:(a path:b)").1, 8);
+        assert_eq!(error_at("-ext:tmp").1, 0);
+    }
+
+    #[test]
+    fn test_folding() {
+        let folding = CharFolding::new(&[
+            CharEquivalence {
+                from: "ة".to_string(),
+                to: "ه".to_string(),
+            },
+            CharEquivalence {
+                from: "ae".to_string(),
+                to: "e".to_string(),
+            },
+        ]);
+        let parse = |query: &str| SearchQuery::parse_folded(query, ImplicitOperator::And, &folding);
+
+        // Searched text is folded; paths, file types and metadata are not
+        let parsed = parse("مدرسة name:caesar path:مدرسة type:aetext ext:ae").unwrap();
+        let column = |column, text: &str| QueryNode::Column(column, Box::new(term(text)));
+        assert_eq!(
+            parsed.root.unwrap(),
+            QueryNode::And(vec![
+                term("مدرسه"),
+                column(TextColumn::Name, "cesar"),
+                column(TextColumn::Path, "مدرسة"),
+                column(TextColumn::Type, "aetext"),
+                QueryNode::Filter(FieldFilter::Extension("ae".to_string())),
+            ])
+        );
+        assert_eq!(parsed.filters[0].value, "caesar");
+        // Positions point into the query as typed
+        assert_eq!(parse("aeae aeae )").unwrap_err().position, 10);
+    }
+
+    #[test]
+    fn test_plain_words() {
+        let words = |q| {
+            plain_words(q)
+                .unwrap()
+                .into_iter()
+                .map(|(word, span)| (word, span.start))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            words("Budgte -draft NOT (a b) ext:doc name:plan contr* \"x y\" (q)"),
+            [
+                ("Budgte".to_string(), 0),
+                ("plan".to_string(), 37),
+                ("q".to_string(), 56),
+            ]
+        );
+        assert!(plain_words("*4471 ware*").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_fts5_compilation_is_escaped() {
+        let fts5 = |q| compile_fts5(&parse(q).root.unwrap());
+        assert_eq!(
+            fts5("near(x) \"a b\" -draft OR c*").unwrap(),
+            r#"((({path name content} : "near" AND {path name content} : "x" AND {path name content} : "a b") NOT {path name content} : "draft") OR {path name content} : "c"*)"#
+        );
+        assert_eq!(
+            fts5("name:\"board mee\"* \"q3 budget\"~5").unwrap(),
+            r#"({name} : "board mee"* AND {path name content} : NEAR("q3" "budget", 5))"#
+        );
+        assert_eq!(
+            fts5("contr* NEAR/3 \"e-mail\"").unwrap(),
+            r#"{path name content} : NEAR("contr"* "e mail", 3)"#
+        );
+        assert_eq!(
+            fts5("name:budget type:docx").unwrap(),
+            r#"({name} : "budget" AND {file_type} : "word")"#
+        );
+        assert_eq!(fts5("budget ext:doc"), None);
+    }
+
+    #[test]
+    fn test_engines_agree() {
+        let date = |y, m, d| Utc.with_ymd_and_hms(y, m, d, 12, 0, 0).unwrap();
+        let docs = [
+            (
+                "/docs/plan.txt",
+                "The board meeting covers the annual budget. E-mail the draft.",
+                100,
+                date(2024, 1, 15),
+            ),
+            (
+                "/docs/notes.md",
+                "Budget notes: the meeting was cancelled.",
+                900,
+                date(2024, 6, 1),
+            ),
+            (
+                "/docs/cafe.txt",
+                "Café menu and board games",
+                5_000,
+                date(2023, 6, 1),
+            ),
+            (
+                "/archive/old.doc",
+                "Draft budget for board approval",
+                3_000_000,
+                date(2022, 3, 1),
+            ),
+            (
+                "/docs/minutes.md",
+                "今天的会议记录已发送。Part AB-44712 shipped.",
+                200,
+                date(2021, 5, 1),
+            ),
+            ("/docs/letter.md", "رسالة إلى مُحَمَّد", 300, date(2020, 2, 1)),
+            (
+                "/docs/hanoi.md",
+                "Phạm Thị Hằng học tiếng Việt ở Hà Nội",
+                400,
+                date(2019, 4, 1),
+            ),
+        ];
+
+        let conn = Connection::open_in_memory().unwrap();
+        register_fold_function(&conn, CharFolding::default()).unwrap();
+        register_stem_function(&conn).unwrap();
+        init_database(&conn).unwrap();
+        let mut files = Vec::new();
+        for (path, content, size, modified) in docs {
+            let name = path.rsplit('/').next().unwrap();
+            let file_type = crate::extractors::file_type_for_path(
+                std::path::Path::new(path),
+                &crate::extractors::TextFileRules::default(),
+            );
+            // .doc rows store Unix seconds
+            let stored_modified = if path.ends_with(".doc") {
+                modified.timestamp().to_string()
+            } else {
+                modified.to_rfc3339()
+            };
+            conn.execute(
+                "INSERT INTO files (path, name, size, last_modified, file_type, content)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![path, name, size, stored_modified, file_type, content],
+            )
+            .unwrap();
+            files.push(FileData {
+                path: path.to_string(),
+                name: name.to_string(),
+                size,
+                last_modified: modified,
+                file_type: file_type.unwrap().to_string(),
+                content: content.to_string(),
+                language: "en".to_string(),
+                has_macros: false,
+            });
+        }
+
+        let folding = CharFolding::default();
+        let ranking = RankingSettings::default();
+        let paths = |results: Vec<crate::models::SearchResult>| {
+            let mut paths: Vec<String> = results.into_iter().map(|r| r.file.path).collect();
+            paths.sort();
+            paths
+        };
+
+        for query in [
+            "budget",
+            "board meeting",
+            "\"board meeting\"",
+            "budget -draft",
+            "budget NOT draft",
+            "meeting OR games",
+            "(board OR menu) AND NOT budget",
+            "e-mail",
+            "CAFE",
+            "café",
+            "archive",
+            "txt -notes",
+            "-\"board meeting\" budget",
+            "board OR menu draft",
+            "NOT (meeting OR menu) board",
+            "name:notes",
+            "name:(cafe OR old)",
+            "path:archive",
+            "path:docs/plan",
+            "type:word",
+            "type:md budget",
+            "ext:doc",
+            "ext:txt budget",
+            "ext:md OR ext:doc",
+            "budget OR ext:txt",
+            "budget -ext:txt",
+            "board -(ext:doc OR size:>1KB)",
+            "size:>2MB",
+            "size:<1KB board",
+            "modified:2024",
+            "modified:<2023-01-01 OR name:cafe",
+            "modified:2022-03..2023 -menu",
+            "budg*",
+            "mee* -draft",
+            "\"board mee\"*",
+            "name:no*",
+            "\"board budget\"~4",
+            "\"board budget\"~3",
+            "\"budget board\"~1",
+            "board NEAR/3 budget",
+            "cafe NEAR/1 games OR e-mail NEAR draft",
+            "\"meeting annual\"~3 -ca*",
+            "会议",
+            "会议记录 -发送",
+            "\"今天的会议\" OR games",
+            "*4471",
+            "*ab-447 OR *enu",
+            "*oar -*approv",
+            "*ca -board",
+            "name:*inut*",
+            "path:*cs/min",
+            "会议 ext:md",
+            "budget -会议",
+            "budget (-draft -menu)",
+            "board (NOT approval NOT games)",
+            "name:(-plan -old) board",
+            "budget -(-draft -cancelled)",
+            "م",
+            "مُحَمَّد",
+            "محمد",
+            "رسالة",
+            "pham thi",
+            "PHẠM",
+            "tiếng",
+            "tieng",
+            "\"hà nội\"",
+        ] {
+            let parsed = parse(query);
+            let fts = search_fts5(&conn, &parsed, 10, 0, None, &HashSet::new(), &ranking).unwrap();
+            let direct = search_direct_content(&parsed, &files, Some(10), None, &folding).unwrap();
+            assert_eq!(paths(fts), paths(direct), "query {:?}", query);
+        }
+
+        let fts = |query| {
+            paths(
+                search_fts5(&conn, &parse(query), 10, 0, None, &HashSet::new(), &ranking).unwrap(),
+            )
+        };
+        assert_eq!(fts("size:>2MB"), ["/archive/old.doc"]);
+        // One diacritic is dropped, two are kept (as unicode61 does)
+        assert_eq!(fts("pham thi"), ["/docs/hanoi.md"]);
+        assert_eq!(fts("tiếng"), ["/docs/hanoi.md"]);
+        assert!(fts("tieng").is_empty());
+        assert_eq!(fts("mee* -draft"), ["/docs/notes.md"]);
+        assert_eq!(fts("\"board budget\"~3"), ["/archive/old.doc"]);
+        assert_eq!(
+            fts("\"board budget\"~4"),
+            ["/archive/old.doc", "/docs/plan.txt"]
+        );
+        assert_eq!(fts("\"budget board\"~1"), ["/archive/old.doc"]);
+        assert_eq!(fts("modified:2024"), ["/docs/notes.md", "/docs/plan.txt"]);
+        assert_eq!(fts("会议"), ["/docs/minutes.md"]);
+        assert_eq!(fts("*4471"), ["/docs/minutes.md"]);
+        assert_eq!(fts("*ca -board"), ["/docs/notes.md"]);
+        assert_eq!(fts("*oar -*approv"), ["/docs/cafe.txt", "/docs/plan.txt"]);
+        assert_eq!(fts("board -(ext:doc OR size:>1KB)"), ["/docs/plan.txt"]);
+        assert_eq!(fts("budget (-draft -menu)"), ["/docs/notes.md"]);
+        // Harakat separate tokens, as in unicode61
+        assert_eq!(fts("م"), ["/docs/letter.md"]);
+        assert_eq!(fts("مُحَمَّد"), ["/docs/letter.md"]);
+        assert_eq!(fts("board (NOT approval NOT games)"), ["/docs/plan.txt"]);
+        assert_eq!(fts("name:(-plan -old) board"), ["/docs/cafe.txt"]);
+        assert_eq!(
+            fts("budget -(-draft -cancelled)"),
+            ["/archive/old.doc", "/docs/notes.md", "/docs/plan.txt"]
+        );
+        assert_eq!(
+            fts("budget OR ext:txt"),
+            [
+                "/archive/old.doc",
+                "/docs/cafe.txt",
+                "/docs/notes.md",
+                "/docs/plan.txt"
+            ]
+        );
     }
 }
@@ -0,0 +1,329 @@
+//! How the FTS5 engine runs a parsed query
+//!
+//! Text (terms, phrases, `name:`/`path:`/`type:`) compiles to one FTS5
+//! `MATCH` expression, which also ranks the results. Metadata qualifiers
+//! (`ext:`, `modified:`, `size:`, and the language and macro filters) become
+//! an SQL condition on `files`. An AND
+//! of text and predicates keeps the two apart; where they're mixed under OR
+//! or NOT, the text is checked by an FTS5 subquery inside the condition.
+//!
+//! Substrings (see `QueryNode::Substring`) live in the trigram index,
+//! `files_trigram`. A query made only of substrings matches and ranks there;
+//! otherwise each is a trigram subquery in the condition. Substrings shorter
+//! than a trigram fall back to `LIKE` on `files`, which scans. Stems (smart
+//! mode) work the same way against the stemmed index, `files_stemmed`.
+
+use rusqlite::types::Value;
+
+use super::qualifiers::FieldFilter;
+use super::query_parser::{
+    compile_fts5, compile_stemmed, compile_trigram, QueryNode, TextColumn, DEFAULT_COLUMNS,
+};
+use crate::extractors::UNDETERMINED;
+
+/// `files.last_modified` as Unix seconds (RFC 3339 text; older .doc rows
+/// hold Unix seconds)
+pub const MODIFIED_UNIX_SQL: &str = "(CASE WHEN instr(files.last_modified, '-') > 0 \
+     THEN unixepoch(files.last_modified) ELSE CAST(files.last_modified AS INTEGER) END)";
+
+/// An FTS5 table of the index
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum FtsIndex {
+    /// `files_fts`: unicode61 words
+    #[default]
+    Words,
+    /// `files_trigram`: every three characters, for substrings
+    Trigrams,
+    /// `files_stemmed`: the stem of each word, for smart mode
+    Stems,
+}
+
+impl FtsIndex {
+    pub fn table(self) -> &'static str {
+        match self {
+            FtsIndex::Words => "files_fts",
+            FtsIndex::Trigrams => "files_trigram",
+            FtsIndex::Stems => "files_stemmed",
+        }
+    }
+}
+
+/// A query split into its FTS5 and SQL parts
+#[derive(Debug, Default)]
+pub struct QueryPlan {
+    /// FTS5 expression every result matches (ranks the results)
+    pub match_expr: Option<String>,
+    /// The table `match_expr` runs against
+    pub index: FtsIndex,
+    /// SQL condition on `files` every result satisfies
+    pub condition: Option<String>,
+    /// Values for the condition's `?N` placeholders, in order
+    pub params: Vec<Value>,
+}
+
+/// Split a query; the condition's placeholders are numbered from `first_param`
+pub fn plan_query(root: &QueryNode, first_param: usize) -> QueryPlan {
+    let mut builder = SqlBuilder {
+        first_param,
+        params: Vec::new(),
+    };
+
+    // Substrings or stems, with any metadata predicates ANDed on, match and
+    // rank in their own index
+    let (text, filters) = split_filters(root);
+    if let Some(text) = text {
+        let index_expr = compile_trigram(&text)
+            .map(|expr| (FtsIndex::Trigrams, expr))
+            .or_else(|| compile_stemmed(&text).map(|expr| (FtsIndex::Stems, expr)));
+        if let Some((index, match_expr)) = index_expr {
+            let conditions: Vec<String> = filters
+                .iter()
+                .map(|filter| builder.filter_sql(filter))
+                .collect();
+            return QueryPlan {
+                match_expr: Some(match_expr),
+                index,
+                condition: (!conditions.is_empty()).then(|| conditions.join(" AND ")),
+                params: builder.params,
+            };
+        }
+    }
+
+    let (match_expr, condition) = match builder.compile(root) {
+        Part::Text(text) => (Some(text), None),
+        Part::Sql(sql) => (None, Some(sql)),
+        Part::Both(text, sql) => (Some(text), Some(sql)),
+    };
+    QueryPlan {
+        match_expr,
+        index: FtsIndex::Words,
+        condition,
+        params: builder.params,
+    }
+}
+
+/// A query as its text and the metadata predicates ANDed onto it at the top
+fn split_filters(root: &QueryNode) -> (Option<QueryNode>, Vec<&FieldFilter>) {
+    let QueryNode::And(children) = root else {
+        return match root {
+            QueryNode::Filter(filter) => (None, vec![filter]),
+            root => (Some(root.clone()), Vec::new()),
+        };
+    };
+    let mut text = Vec::new();
+    let mut filters = Vec::new();
+    for child in children {
+        match child {
+            QueryNode::Filter(filter) => filters.push(filter),
+            child => text.push(child.clone()),
+        }
+    }
+    let text = match text.len() {
+        0 => None,
+        1 => text.pop(),
+        _ => Some(QueryNode::And(text)),
+    };
+    (text, filters)
+}
+
+/// Needs more than the word index: metadata predicates, substrings or stems
+fn needs_sql(node: &QueryNode) -> bool {
+    node.has_filter() || node.has_substring() || node.has_stem()
+}
+
+/// A compiled node: FTS5 text, an SQL condition, or both (ANDed)
+enum Part {
+    Text(String),
+    Sql(String),
+    Both(String, String),
+}
+
+struct SqlBuilder {
+    first_param: usize,
+    params: Vec<Value>,
+}
+
+impl SqlBuilder {
+    fn bind(&mut self, value: Value) -> String {
+        self.params.push(value);
+        format!("?{}", self.first_param + self.params.len() - 1)
+    }
+
+    fn compile(&mut self, node: &QueryNode) -> Part {
+        if let Some(trigram_expr) = compile_trigram(node) {
+            return Part::Sql(self.match_sql(FtsIndex::Trigrams, trigram_expr));
+        }
+        if let Some(stemmed_expr) = compile_stemmed(node) {
+            return Part::Sql(self.match_sql(FtsIndex::Stems, stemmed_expr));
+        }
+        match node {
+            QueryNode::Filter(filter) => Part::Sql(self.filter_sql(filter)),
+            // FTS5 has no unary NOT
+            QueryNode::Not(inner) => {
+                let inner = self.compile(inner);
+                Part::Sql(format!("NOT {}", self.condition_for(inner)))
+            }
+            QueryNode::Or(children) if needs_sql(node) => {
+                let parts: Vec<String> = children
+                    .iter()
+                    .map(|child| {
+                        let part = self.compile(child);
+                        self.condition_for(part)
+                    })
+                    .collect();
+                Part::Sql(format!("({})", parts.join(" OR ")))
+            }
+            QueryNode::And(children) if needs_sql(node) => self.compile_and(children),
+            // Too short for the trigram index
+            QueryNode::Substring(text) => Part::Sql(self.like_sql(text, DEFAULT_COLUMNS)),
+            QueryNode::Column(column, inner) if needs_sql(inner) => match &**inner {
+                QueryNode::Substring(text) => Part::Sql(self.like_sql(text, &[*column])),
+                inner => self.compile(&within_column(*column, inner)),
+            },
+            _ => Part::Text(compile_fts5(node).unwrap_or_default()),
+        }
+    }
+
+    /// Text children stay in FTS5 (FTS5 needs one positive term for NOT);
+    /// the rest join the SQL condition
+    fn compile_and(&mut self, children: &[QueryNode]) -> Part {
+        let (text, rest): (Vec<&QueryNode>, Vec<&QueryNode>) =
+            children.iter().partition(|c| !needs_sql(c));
+        let has_positive = text.iter().any(|c| !matches!(c, QueryNode::Not(_)));
+
+        let mut texts = Vec::new();
+        let mut sqls = Vec::new();
+        if has_positive {
+            let group = QueryNode::And(text.into_iter().cloned().collect());
+            texts.extend(compile_fts5(&group));
+        } else {
+            for node in text {
+                if let QueryNode::Not(inner) = node {
+                    if let Some(inner) = compile_fts5(inner) {
+                        sqls.push(format!("NOT {}", self.match_sql(FtsIndex::Words, inner)));
+                    }
+                }
+            }
+        }
+        for child in rest {
+            match self.compile(child) {
+                Part::Text(text) => texts.push(text),
+                Part::Sql(sql) => sqls.push(sql),
+                Part::Both(text, sql) => {
+                    texts.push(text);
+                    sqls.push(sql);
+                }
+            }
+        }
+
+        let text = (!texts.is_empty()).then(|| format!("({})", texts.join(" AND ")));
+        let sql = (!sqls.is_empty()).then(|| format!("({})", sqls.join(" AND ")));
+        match (text, sql) {
+            (Some(text), Some(sql)) => Part::Both(text, sql),
+            (Some(text), None) => Part::Text(text),
+            (None, sql) => Part::Sql(sql.unwrap_or_else(|| "1".to_string())),
+        }
+    }
+
+    fn condition_for(&mut self, part: Part) -> String {
+        match part {
+            Part::Text(text) => self.match_sql(FtsIndex::Words, text),
+            Part::Sql(sql) => sql,
+            Part::Both(text, sql) => {
+                format!("({} AND {})", self.match_sql(FtsIndex::Words, text), sql)
+            }
+        }
+    }
+
+    fn match_sql(&mut self, index: FtsIndex, fts_expr: String) -> String {
+        let table = index.table();
+        format!(
+            "files.rowid IN (SELECT rowid FROM {} WHERE {} MATCH {})",
+            table,
+            table,
+            self.bind(Value::Text(fts_expr))
+        )
+    }
+
+    /// `text` anywhere in one of `columns`, by scanning (`LIKE` folds ASCII
+    /// case only; `files` holds the text unfolded)
+    fn like_sql(&mut self, text: &str, columns: &[TextColumn]) -> String {
+        let escaped = text
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        let param = self.bind(Value::Text(format!("%{}%", escaped)));
+        let checks: Vec<String> = columns
+            .iter()
+            .map(|column| format!("files.{} LIKE {} ESCAPE '\\'", column.fts5_name(), param))
+            .collect();
+        format!("({})", checks.join(" OR "))
+    }
+
+    fn filter_sql(&mut self, filter: &FieldFilter) -> String {
+        let mut conditions = Vec::new();
+        match filter {
+            // Extensions are ASCII alphanumerics, so LIKE has no wildcards to escape
+            FieldFilter::Extension(ext) => conditions.push(format!(
+                "files.path LIKE {}",
+                self.bind(Value::Text(format!("%.{}", ext)))
+            )),
+            FieldFilter::Modified { from, to } => {
+                if let Some(from) = from {
+                    let param = self.bind(Value::Integer(from.timestamp()));
+                    conditions.push(format!("{} >= {}", MODIFIED_UNIX_SQL, param));
+                }
+                if let Some(to) = to {
+                    let param = self.bind(Value::Integer(to.timestamp()));
+                    conditions.push(format!("{} < {}", MODIFIED_UNIX_SQL, param));
+                }
+            }
+            FieldFilter::Size { min, max } => {
+                if let Some(min) = min {
+                    let param = self.bind(Value::Integer(saturating_i64(*min)));
+                    conditions.push(format!("files.size >= {}", param));
+                }
+                if let Some(max) = max {
+                    let param = self.bind(Value::Integer(saturating_i64(*max)));
+                    conditions.push(format!("files.size < {}", param));
+                }
+            }
+            // Rows without a detected language read as undetermined
+            FieldFilter::Language(languages) => {
+                let params: Vec<String> = languages
+                    .iter()
+                    .map(|language| self.bind(Value::Text(language.clone())))
+                    .collect();
+                conditions.push(format!(
+                    "COALESCE(files.language, '{}') IN ({})",
+                    UNDETERMINED,
+                    params.join(", ")
+                ));
+            }
+            FieldFilter::Macros(has_macros) => conditions.push(format!(
+                "COALESCE(files.has_macros, 0) = {}",
+                self.bind(Value::Integer(i64::from(*has_macros)))
+            )),
+        }
+        if conditions.is_empty() {
+            return "1".to_string();
+        }
+        format!("({})", conditions.join(" AND "))
+    }
+}
+
+/// `column:(a OR b)` as `column:a OR column:b`, so each substring knows
+/// its column
+fn within_column(column: TextColumn, node: &QueryNode) -> QueryNode {
+    let wrap = |child: &QueryNode| within_column(column, child);
+    match node {
+        QueryNode::And(children) => QueryNode::And(children.iter().map(wrap).collect()),
+        QueryNode::Or(children) => QueryNode::Or(children.iter().map(wrap).collect()),
+        QueryNode::Not(inner) => QueryNode::Not(Box::new(wrap(inner))),
+        node => QueryNode::Column(column, Box::new(node.clone())),
+    }
+}
+
+fn saturating_i64(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
@@ -0,0 +1,222 @@
+//! Match context for FTS5 results
+//!
+//! FTS5 `highlight()` marks every token the query matched in a result's stored
+//! text (prefixes, phrases and folded spellings included, exactly as FTS5
+//! matched them); we cut context windows around the marked tokens.
+//!
+//! Snippets are built in rank order under a per-query time budget so a large
+//! page of results stays interactive. Results past the budget keep a
+//! placeholder and get their context when opened.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::time::Duration;
+
+use crate::models::{Match, TextSpan};
+
+/// Time allowed for building snippets per search
+pub const SNIPPET_BUDGET: Duration = Duration::from_millis(150);
+
+/// Most context windows returned per file
+const MAX_SNIPPETS_PER_FILE: usize = 3;
+
+/// Characters of context on each side of a match
+const CONTEXT_CHARS: usize = 60;
+
+/// Markers passed to `highlight()` (private use, never in extracted text)
+const OPEN: char = '\u{E000}';
+const CLOSE: char = '\u{E001}';
+
+/// Context windows for one row of an FTS5 table (`files_fts` or
+/// `files_trigram`), with every matched term marked
+///
+/// Falls back to the file name when only the name (or path) matched.
+pub fn snippet_matches(
+    conn: &Connection,
+    table: &str,
+    fts_query: &str,
+    rowid: i64,
+) -> rusqlite::Result<Vec<Match>> {
+    let open = OPEN.to_string();
+    let close = CLOSE.to_string();
+    let highlighted: Option<(Option<String>, Option<String>)> = conn
+        .query_row(
+            &format!(
+                "SELECT highlight({table}, 2, ?3, ?4), highlight({table}, 1, ?3, ?4)
+                 FROM {table} WHERE {table} MATCH ?1 AND rowid = ?2",
+                table = table
+            ),
+            params![fts_query, rowid, open, close],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+
+    let Some((content, name)) = highlighted else {
+        return Ok(Vec::new());
+    };
+
+    let (content, spans) = strip_markers(&content.unwrap_or_default());
+    if !spans.is_empty() {
+        return Ok(context_matches(&content, &spans));
+    }
+
+    let (name, spans) = strip_markers(&name.unwrap_or_default());
+    let Some(&(first_start, first_end)) = spans.first() else {
+        return Ok(Vec::new());
+    };
+    let prefix = "Filename: ";
+    let offset = utf16_len(prefix);
+    Ok(vec![Match {
+        text: name[first_start..first_end].to_string(),
+        index: 0,
+        highlights: spans
+            .iter()
+            .map(|&(start, end)| TextSpan {
+                start: offset + utf16_len(&name[..start]),
+                end: offset + utf16_len(&name[..end]),
+            })
+            .collect(),
+        context: format!("{}{}", prefix, name),
+    }])
+}
+
+/// Remove the highlight markers, returning the text and the marked byte ranges
+fn strip_markers(marked: &str) -> (String, Vec<(usize, usize)>) {
+    let mut text = String::with_capacity(marked.len());
+    let mut spans = Vec::new();
+    let mut open_at = None;
+
+    for c in marked.chars() {
+        match c {
+            OPEN => open_at = Some(text.len()),
+            CLOSE => {
+                if let Some(start) = open_at.take() {
+                    if start < text.len() {
+                        spans.push((start, text.len()));
+                    }
+                }
+            }
+            _ => text.push(c),
+        }
+    }
+
+    (text, spans)
+}
+
+/// One `Match` per window of context, each marking every span it contains
+fn context_matches(content: &str, spans: &[(usize, usize)]) -> Vec<Match> {
+    let mut matches = Vec::new();
+    let mut i = 0;
+
+    while i < spans.len() && matches.len() < MAX_SNIPPETS_PER_FILE {
+        let (first_start, first_end) = spans[i];
+        let window_start = chars_back(content, first_start, CONTEXT_CHARS);
+        let mut window_end = chars_forward(content, first_end, CONTEXT_CHARS);
+
+        let mut highlights = Vec::new();
+        while i < spans.len() && spans[i].0 < window_end {
+            let (start, end) = spans[i];
+            window_end = window_end.max(end);
+            let start16 = utf16_len(&content[window_start..start]);
+            highlights.push(TextSpan {
+                start: start16,
+                end: start16 + utf16_len(&content[start..end]),
+            });
+            i += 1;
+        }
+
+        matches.push(Match {
+            text: content[first_start..first_end].to_string(),
+            index: first_start,
+            context: content[window_start..window_end].to_string(),
+            highlights,
+        });
+    }
+
+    matches
+}
+
+/// Byte index `n` characters before `pos`
+fn chars_back(text: &str, pos: usize, n: usize) -> usize {
+    text[..pos]
+        .char_indices()
+        .rev()
+        .nth(n.saturating_sub(1))
+        .map_or(0, |(i, _)| i)
+}
+
+/// Byte index `n` characters after `pos`
+fn chars_forward(text: &str, pos: usize, n: usize) -> usize {
+    text[pos..]
+        .char_indices()
+        .nth(n)
+        .map_or(text.len(), |(i, _)| pos + i)
+}
+
+fn utf16_len(text: &str) -> usize {
+    text.chars().map(char::len_utf16).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marked(text: &str) -> String {
+        text.replace('[', &OPEN.to_string())
+            .replace(']', &CLOSE.to_string())
+    }
+
+    #[test]
+    fn test_windows_and_offsets() {
+        let filler = "x".repeat(200);
+        let text = marked(&format!(
+            "The [budget] and [plan]. {} Final [budget] 😀 [plan]",
+            filler
+        ));
+        let (content, spans) = strip_markers(&text);
+        assert_eq!(spans.len(), 4);
+
+        let matches = context_matches(&content, &spans);
+        assert_eq!(matches.len(), 2);
+
+        // Nearby terms share a window
+        let first = &matches[0];
+        assert_eq!((first.text.as_str(), first.index), ("budget", 4));
+        assert!(first.context.starts_with("The budget and plan."));
+        assert_eq!(first.highlights.len(), 2);
+        assert_eq!(first.highlights[1], TextSpan { start: 15, end: 19 });
+
+        // Offsets are UTF-16 units (the emoji counts as two)
+        let second = &matches[1];
+        assert_eq!(&content[second.index..second.index + 6], "budget");
+        let units: Vec<u16> = second.context.encode_utf16().collect();
+        let plan = second.highlights[1];
+        assert_eq!(
+            String::from_utf16(&units[plan.start..plan.end]).unwrap(),
+            "plan"
+        );
+    }
+
+    #[test]
+    fn test_fts5_highlight() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE VIRTUAL TABLE files_fts USING fts5(path, name, content, file_type);
+             INSERT INTO files_fts(rowid, path, name, content, file_type)
+             VALUES (1, '/a.txt', 'report.txt', 'Quarterly reports are due. The report is late.', 'text');",
+        )
+        .unwrap();
+
+        let matches = snippet_matches(&conn, "files_fts", "report*", 1).unwrap();
+        assert_eq!(matches.len(), 1);
+        let words: Vec<&str> = matches[0]
+            .highlights
+            .iter()
+            .map(|span| &matches[0].context[span.start..span.end])
+            .collect();
+        assert_eq!(words, ["reports", "report"]);
+
+        let by_name = snippet_matches(&conn, "files_fts", "name:txt", 1).unwrap();
+        assert_eq!(by_name[0].context, "Filename: report.txt");
+        assert_eq!(by_name[0].highlights, [TextSpan { start: 17, end: 20 }]);
+    }
+}
@@ -0,0 +1,609 @@
+//! Stemming for "smart" matching
+//!
+//! Words are reduced to a stem so other forms of a word find each other:
+//! English with Porter's algorithm (`contracts`, `contracting` → `contract`),
+//! Arabic with light stemming (Larkey's Light10: the conjunction و, the
+//! article ال and its prefixed forms, and suffixes such as ـها, ـات, ـون
+//! come off). Words in other scripts, or with digits, are kept as they are.
+//!
+//! Stems are indexed in a shadow FTS5 table, `files_stemmed`, written by
+//! `docufind_stem()` from the same triggers as `files_fts`. Each word becomes
+//! exactly one stem, so token positions (and FTS5 highlights) line up with
+//! the original text. In smart mode every required or alternative word of a
+//! query is searched by its stem; files that only match that way follow the
+//! exact-form hits. With fuzzy matching on as well, files only found through
+//! a typo expansion come last.
+
+use rusqlite::functions::FunctionFlags;
+use rusqlite::Connection;
+use std::collections::HashSet;
+
+use super::direct_search::search_direct_content;
+use super::fts5_search::search_fts5;
+use super::fuzzy::{merge_results, search_direct_fuzzy, search_fts5_fuzzy};
+use super::query_parser::{QueryNode, SearchQuery, TextColumn, TokenizedText};
+use super::CharFolding;
+use crate::models::{FileData, RankingSettings, SearchResult};
+
+/// Name of the SQL function registered on write connections
+pub const STEM_FUNCTION_NAME: &str = "docufind_stem";
+
+/// Register `docufind_stem(text)` on a connection
+///
+/// Must be called on every connection that writes to `files`, because the
+/// FTS5 sync triggers call it.
+pub fn register_stem_function(conn: &Connection) -> rusqlite::Result<()> {
+    conn.create_scalar_function(
+        STEM_FUNCTION_NAME,
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            let text: Option<String> = ctx.get(0)?;
+            Ok(text.map(|t| stem_text(&t)))
+        },
+    )
+}
+
+/// Text as its stems: normalized and tokenized like the FTS5 index, each
+/// token stemmed, joined by spaces
+pub fn stem_text(text: &str) -> String {
+    TokenizedText::new(text)
+        .tokens()
+        .map(stem_word)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Stem of one normalized token (never empty for a non-empty token)
+pub fn stem_word(token: &str) -> String {
+    let stem = if token.chars().any(is_arabic) {
+        arabic_light_stem(token)
+    } else if token.bytes().all(|b| b.is_ascii_lowercase()) {
+        porter_stem(token)
+    } else {
+        return token.to_string();
+    };
+    if stem.is_empty() {
+        token.to_string()
+    } else {
+        stem
+    }
+}
+
+/// `search_fts5` in smart mode: exact-form hits first, then files that
+/// only match by stem, then (with `fuzzy`) files only found through a typo
+/// expansion (see `search_fts5_fuzzy`)
+///
+/// Results are not offset (page them afterwards, as `search_index` does).
+pub fn search_fts5_stemmed(
+    conn: &Connection,
+    query: &SearchQuery,
+    max_results: usize,
+    file_path_filter: Option<&str>,
+    excluded_folders: &HashSet<String>,
+    ranking: &RankingSettings,
+    fuzzy: bool,
+) -> Result<Vec<SearchResult>, String> {
+    let search = |query: &SearchQuery| {
+        search_fts5(
+            conn,
+            query,
+            max_results,
+            0,
+            file_path_filter,
+            excluded_folders,
+            ranking,
+        )
+    };
+    let mut results = search(query)?;
+    if let Some(stemmed) = stem_query(query) {
+        results = merge_results(results, search(&stemmed)?, max_results).0;
+    }
+    if fuzzy {
+        let typos = search_fts5_fuzzy(
+            conn,
+            query,
+            max_results,
+            file_path_filter,
+            excluded_folders,
+            ranking,
+        )?;
+        results = merge_results(results, typos, max_results).0;
+    }
+    Ok(results)
+}
+
+/// `search_direct_content` in smart mode (see `search_fts5_stemmed`)
+pub fn search_direct_stemmed(
+    query: &SearchQuery,
+    files: &[FileData],
+    max_results: usize,
+    file_path_filter: Option<&str>,
+    folding: &CharFolding,
+    fuzzy: bool,
+) -> Result<Vec<SearchResult>, String> {
+    let search = |query: &SearchQuery| {
+        search_direct_content(query, files, Some(max_results), file_path_filter, folding)
+    };
+    let mut results = search(query)?;
+    if let Some(stemmed) = stem_query(query) {
+        results = merge_results(results, search(&stemmed)?, max_results).0;
+    }
+    if fuzzy {
+        let typos = search_direct_fuzzy(query, files, max_results, file_path_filter, folding)?;
+        results = merge_results(results, typos, max_results).0;
+    }
+    Ok(results)
+}
+
+/// The query with each required or alternative word replaced by its stem
+/// (phrases, prefixes, exclusions and `type:` stay exact); `None` if it has
+/// no such word
+fn stem_query(query: &SearchQuery) -> Option<SearchQuery> {
+    let root = query.root.as_ref()?;
+    let mut stemmed = false;
+    let root = stem_node(root, &mut stemmed);
+    stemmed.then(|| SearchQuery {
+        root: Some(root),
+        filters: query.filters.clone(),
+    })
+}
+
+fn stem_node(node: &QueryNode, stemmed: &mut bool) -> QueryNode {
+    match node {
+        QueryNode::Term(term) => {
+            *stemmed = true;
+            QueryNode::Stem(stem_word(term))
+        }
+        QueryNode::And(children) => {
+            QueryNode::And(children.iter().map(|c| stem_node(c, stemmed)).collect())
+        }
+        QueryNode::Or(children) => {
+            QueryNode::Or(children.iter().map(|c| stem_node(c, stemmed)).collect())
+        }
+        QueryNode::Column(column, inner) if *column != TextColumn::Type => {
+            QueryNode::Column(*column, Box::new(stem_node(inner, stemmed)))
+        }
+        _ => node.clone(),
+    }
+}
+
+fn is_arabic(c: char) -> bool {
+    ('\u{0600}'..='\u{06FF}').contains(&c)
+}
+
+/// Arabic text in its base spelling: hamza forms of alef, hamza carriers,
+/// alef maqsura and ta marbuta unified; harakat and tatweel dropped
+pub(super) fn arabic_key(text: &str) -> String {
+    text.chars()
+        .filter_map(|c| match c {
+            '\u{064B}'..='\u{065F}' | '\u{0670}' | '\u{0640}' => None,
+            'أ' | 'إ' | 'آ' | 'ٱ' => Some('ا'),
+            'ى' | 'ئ' => Some('ي'),
+            'ؤ' => Some('و'),
+            'ة' => Some('ه'),
+            c => Some(c),
+        })
+        .collect()
+}
+
+/// Article prefixes, longest first
+const ARABIC_PREFIXES: &[&str] = &["وال", "بال", "كال", "فال", "ال", "لل"];
+
+/// Suffixes (in base spelling), removed in this order
+const ARABIC_SUFFIXES: &[&str] = &["ها", "ان", "ات", "ون", "ين", "يه", "ه", "ي"];
+
+/// Light10: drop و, then one article, then each suffix in turn, keeping at
+/// least two letters
+fn arabic_light_stem(word: &str) -> String {
+    let mut chars: Vec<char> = arabic_key(word).chars().collect();
+    if chars.len() > 3 && chars[0] == 'و' {
+        chars.remove(0);
+    }
+    for prefix in ARABIC_PREFIXES {
+        let prefix: Vec<char> = prefix.chars().collect();
+        if chars.starts_with(&prefix) && chars.len() >= prefix.len() + 2 {
+            chars.drain(..prefix.len());
+            break;
+        }
+    }
+    for suffix in ARABIC_SUFFIXES {
+        let suffix: Vec<char> = suffix.chars().collect();
+        if chars.ends_with(&suffix) && chars.len() >= suffix.len() + 2 {
+            chars.truncate(chars.len() - suffix.len());
+        }
+    }
+    chars.into_iter().collect()
+}
+
+/// Porter's stemmer (the 1980 algorithm) for a lowercase ASCII word
+fn porter_stem(word: &str) -> String {
+    if word.len() <= 2 {
+        return word.to_string();
+    }
+    let mut porter = Porter {
+        b: word.as_bytes().to_vec(),
+        j: 0,
+    };
+    porter.step1ab();
+    if porter.b.len() > 1 {
+        porter.step1c();
+        porter.step2();
+        porter.step3();
+        porter.step4();
+        porter.step5();
+    }
+    String::from_utf8_lossy(&porter.b).into_owned()
+}
+
+struct Porter {
+    b: Vec<u8>,
+    /// Length of the stem before the suffix `ends` last matched
+    j: usize,
+}
+
+impl Porter {
+    fn cons(&self, i: usize) -> bool {
+        match self.b[i] {
+            b'a' | b'e' | b'i' | b'o' | b'u' => false,
+            b'y' => i == 0 || !self.cons(i - 1),
+            _ => true,
+        }
+    }
+
+    /// Vowel-consonant sequences in the stem: `[C](VC){m}[V]`
+    fn m(&self) -> usize {
+        let mut n = 0;
+        let mut i = 0;
+        while i < self.j && self.cons(i) {
+            i += 1;
+        }
+        loop {
+            while i < self.j && !self.cons(i) {
+                i += 1;
+            }
+            if i >= self.j {
+                return n;
+            }
+            while i < self.j && self.cons(i) {
+                i += 1;
+            }
+            n += 1;
+            if i >= self.j {
+                return n;
+            }
+        }
+    }
+
+    fn vowel_in_stem(&self) -> bool {
+        (0..self.j).any(|i| !self.cons(i))
+    }
+
+    fn double_consonant(&self, i: usize) -> bool {
+        i >= 1 && self.b[i] == self.b[i - 1] && self.cons(i)
+    }
+
+    /// Consonant-vowel-consonant ending at `i`, the last not w, x or y
+    fn cvc(&self, i: usize) -> bool {
+        i >= 2
+            && self.cons(i)
+            && !self.cons(i - 1)
+            && self.cons(i - 2)
+            && !matches!(self.b[i], b'w' | b'x' | b'y')
+    }
+
+    fn last(&self) -> usize {
+        self.b.len() - 1
+    }
+
+    fn ends(&mut self, suffix: &str) -> bool {
+        if !self.b.ends_with(suffix.as_bytes()) {
+            return false;
+        }
+        self.j = self.b.len() - suffix.len();
+        true
+    }
+
+    fn set_to(&mut self, replacement: &str) {
+        self.b.truncate(self.j);
+        self.b.extend_from_slice(replacement.as_bytes());
+    }
+
+    /// Replace the first matching suffix if the stem has a measure above 0
+    fn replace_first(&mut self, rules: &[(&str, &str)]) {
+        for (suffix, replacement) in rules {
+            if self.ends(suffix) {
+                if self.m() > 0 {
+                    self.set_to(replacement);
+                }
+                return;
+            }
+        }
+    }
+
+    /// Plurals and -ed / -ing
+    fn step1ab(&mut self) {
+        if self.b[self.last()] == b's' {
+            if self.ends("sses") {
+                self.b.truncate(self.b.len() - 2);
+            } else if self.ends("ies") {
+                self.set_to("i");
+            } else if self.b[self.last() - 1] != b's' {
+                self.b.pop();
+            }
+        }
+        if self.ends("eed") {
+            if self.m() > 0 {
+                self.b.pop();
+            }
+        } else if (self.ends("ed") || self.ends("ing")) && self.vowel_in_stem() {
+            self.b.truncate(self.j);
+            if self.ends("at") {
+                self.set_to("ate");
+            } else if self.ends("bl") {
+                self.set_to("ble");
+            } else if self.ends("iz") {
+                self.set_to("ize");
+            } else if self.double_consonant(self.last()) {
+                if !matches!(self.b[self.last()], b'l' | b's' | b'z') {
+                    self.b.pop();
+                }
+            } else if self.m() == 1 && self.cvc(self.last()) {
+                self.set_to("e");
+            }
+        }
+    }
+
+    /// Terminal y to i when there is another vowel in the stem
+    fn step1c(&mut self) {
+        if self.ends("y") && self.vowel_in_stem() {
+            let last = self.last();
+            self.b[last] = b'i';
+        }
+    }
+
+    /// Double suffixes to single ones (-ization to -ize, ...)
+    fn step2(&mut self) {
+        let rules: &[(&str, &str)] = match self.b[self.last() - 1] {
+            b'a' => &[("ational", "ate"), ("tional", "tion")],
+            b'c' => &[("enci", "ence"), ("anci", "ance")],
+            b'e' => &[("izer", "ize")],
+            b'l' => &[
+                ("bli", "ble"),
+                ("alli", "al"),
+                ("entli", "ent"),
+                ("eli", "e"),
+                ("ousli", "ous"),
+            ],
+            b'o' => &[("ization", "ize"), ("ation", "ate"), ("ator", "ate")],
+            b's' => &[
+                ("alism", "al"),
+                ("iveness", "ive"),
+                ("fulness", "ful"),
+                ("ousness", "ous"),
+            ],
+            b't' => &[("aliti", "al"), ("iviti", "ive"), ("biliti", "ble")],
+            b'g' => &[("logi", "log")],
+            _ => &[],
+        };
+        self.replace_first(rules);
+    }
+
+    /// -ic-, -full, -ness and friends
+    fn step3(&mut self) {
+        let rules: &[(&str, &str)] = match self.b[self.last()] {
+            b'e' => &[("icate", "ic"), ("ative", ""), ("alize", "al")],
+            b'i' => &[("iciti", "ic")],
+            b'l' => &[("ical", "ic"), ("ful", "")],
+            b's' => &[("ness", "")],
+            _ => &[],
+        };
+        self.replace_first(rules);
+    }
+
+    /// -ant, -ence and the like, when the stem is long enough
+    fn step4(&mut self) {
+        let suffixes: &[&str] = match self.b[self.last() - 1] {
+            b'a' => &["al"],
+            b'c' => &["ance", "ence"],
+            b'e' => &["er"],
+            b'i' => &["ic"],
+            b'l' => &["able", "ible"],
+            b'n' => &["ant", "ement", "ment", "ent"],
+            b'o' => &["ion", "ou"],
+            b's' => &["ism"],
+            b't' => &["ate", "iti"],
+            b'u' => &["ous"],
+            b'v' => &["ive"],
+            b'z' => &["ize"],
+            _ => &[],
+        };
+        for suffix in suffixes {
+            if self.ends(suffix) {
+                // -ion only comes off after s or t
+                if *suffix == "ion" && !(self.j > 0 && matches!(self.b[self.j - 1], b's' | b't')) {
+                    return;
+                }
+                if self.m() > 1 {
+                    self.b.truncate(self.j);
+                }
+                return;
+            }
+        }
+    }
+
+    /// Final -e, and -ll to -l
+    fn step5(&mut self) {
+        self.j = self.b.len();
+        if self.b[self.last()] == b'e' {
+            let m = self.m();
+            if m > 1 || (m == 1 && !self.cvc(self.last() - 1)) {
+                self.b.pop();
+            }
+        }
+        if self.b[self.last()] == b'l' && self.double_consonant(self.last()) && self.m() > 1 {
+            self.b.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::init_database;
+    use crate::models::ImplicitOperator;
+    use crate::search::register_fold_function;
+    use chrono::Utc;
+
+    #[test]
+    fn test_porter() {
+        for (word, stem) in [
+            ("caresses", "caress"),
+            ("ponies", "poni"),
+            ("cats", "cat"),
+            ("feed", "feed"),
+            ("agreed", "agre"),
+            ("plastered", "plaster"),
+            ("motoring", "motor"),
+            ("sing", "sing"),
+            ("hopping", "hop"),
+            ("falling", "fall"),
+            ("filing", "file"),
+            ("happy", "happi"),
+            ("relational", "relat"),
+            ("conditional", "condit"),
+            ("digitizer", "digit"),
+            ("hopeful", "hope"),
+            ("goodness", "good"),
+            ("adjustment", "adjust"),
+            ("adoption", "adopt"),
+            ("generalizations", "gener"),
+            ("controll", "control"),
+            ("contracts", "contract"),
+            ("contracting", "contract"),
+            ("is", "is"),
+        ] {
+            assert_eq!(porter_stem(word), stem, "{}", word);
+        }
+    }
+
+    #[test]
+    fn test_arabic_light_stemming() {
+        // Conjunction, article and suffixes come off
+        for word in ["والكتاب", "الكتاب", "كتابها", "كتابات"] {
+            assert_eq!(arabic_light_stem(word), "كتاب", "{}", word);
+        }
+        for word in ["المعلمون", "معلمين", "والمعلم"] {
+            assert_eq!(arabic_light_stem(word), "معلم", "{}", word);
+        }
+        // Short words keep two letters
+        assert_eq!(arabic_light_stem("وها"), "وها");
+        assert_eq!(
+            stem_text("The Contracts, والكتاب 2024"),
+            "the contract كتاب 2024"
+        );
+        assert_eq!(stem_word("ـ"), "ـ");
+    }
+
+    #[test]
+    fn test_smart_search_ranks_exact_forms_first() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_fold_function(&conn, CharFolding::default()).unwrap();
+        register_stem_function(&conn).unwrap();
+        init_database(&conn).unwrap();
+        let docs = [
+            ("/a.txt", "Contracting terms for the new office"),
+            ("/b.txt", "Signed contracts archive"),
+            ("/c.txt", "The contract was renewed, contract attached"),
+            ("/d.txt", "تم توقيع العقود والكتاب"),
+            ("/e.txt", "unrelated notes"),
+        ];
+        let mut files = Vec::new();
+        for (path, content) in docs {
+            conn.execute(
+                "INSERT INTO files (path, name, size, last_modified, file_type, content)
+                 VALUES (?1, ?1, 1, '2024-01-01T00:00:00+00:00', 'text', ?2)",
+                [path, content],
+            )
+            .unwrap();
+            files.push(FileData {
+                path: path.to_string(),
+                name: path.to_string(),
+                size: 1,
+                last_modified: Utc::now(),
+                file_type: "text".to_string(),
+                content: content.to_string(),
+                language: "en".to_string(),
+                has_macros: false,
+            });
+        }
+
+        let folding = CharFolding::default();
+        let ranking = RankingSettings::default();
+        let excluded = HashSet::new();
+        let paths = |results: Vec<SearchResult>| -> Vec<String> {
+            results.into_iter().map(|r| r.file.path).collect()
+        };
+        for (query, exact_first) in [
+            ("contracts", vec!["/b.txt"]),
+            ("contract -renewed", vec![]),
+            ("كتاب", vec![]),
+            ("\"signed contracts\"", vec!["/b.txt"]),
+        ] {
+            let parsed = SearchQuery::parse(query, ImplicitOperator::And).unwrap();
+            let fts = paths(
+                search_fts5_stemmed(&conn, &parsed, 10, None, &excluded, &ranking, false).unwrap(),
+            );
+            let direct =
+                paths(search_direct_stemmed(&parsed, &files, 10, None, &folding, false).unwrap());
+            assert_eq!(fts[..exact_first.len()], exact_first[..], "{}", query);
+            let sorted = |mut paths: Vec<String>| {
+                paths.sort();
+                paths
+            };
+            assert_eq!(sorted(fts), sorted(direct), "query {:?}", query);
+        }
+
+        let parsed = SearchQuery::parse("contracts", ImplicitOperator::And).unwrap();
+        let results =
+            search_fts5_stemmed(&conn, &parsed, 10, None, &excluded, &ranking, false).unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results[0].score > results[1].score);
+        // Highlights line up with the words that matched by stem
+        let highlighted: Vec<String> = results[1..]
+            .iter()
+            .map(|r| {
+                let m = &r.matches[0];
+                let units: Vec<u16> = m.context.encode_utf16().collect();
+                let span = m.highlights[0];
+                String::from_utf16(&units[span.start..span.end]).unwrap()
+            })
+            .collect();
+        assert!(highlighted.contains(&"Contracting".to_string()));
+        assert!(highlighted.contains(&"contract".to_string()));
+
+        let parsed = SearchQuery::parse("كتاب", ImplicitOperator::And).unwrap();
+        assert_eq!(
+            paths(
+                search_fts5_stemmed(&conn, &parsed, 10, None, &excluded, &ranking, false).unwrap()
+            ),
+            ["/d.txt"]
+        );
+
+        // A typo finds nothing by stem; with fuzzy on it is expanded too
+        let parsed = SearchQuery::parse("contrcts", ImplicitOperator::And).unwrap();
+        for fuzzy in [false, true] {
+            let fts =
+                search_fts5_stemmed(&conn, &parsed, 10, None, &excluded, &ranking, fuzzy).unwrap();
+            let direct = search_direct_stemmed(&parsed, &files, 10, None, &folding, fuzzy).unwrap();
+            for results in [fts, direct] {
+                if fuzzy {
+                    assert!(results.iter().any(|r| r.file.path == "/b.txt"));
+                    assert!(results.iter().all(|r| !r.expansions.is_empty()));
+                } else {
+                    assert!(results.is_empty());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,292 @@
+//! "Did you mean" suggestions for queries that found nothing
+//!
+//! Each plain word of the query (see `plain_words`) that isn't an index term
+//! is replaced by the closest one: its spelling variant under Arabic
+//! normalization (hamza forms, alef maqsura, ta marbuta, harakat, tatweel)
+//! when the index has one, else the most common term within the fuzzy edit
+//! distance. Earlier searches that found files and read almost the same as
+//! the query are suggested after that.
+
+use rusqlite::Connection;
+
+use super::fuzzy::{files_vocabulary, max_edits, with_fts5_vocabulary, LevenshteinAutomaton};
+use super::query_parser::{normalize_for_search, plain_words, TokenizedText};
+use super::stemming::arabic_key;
+use super::CharFolding;
+use crate::models::{FileData, QuerySuggestion, SearchHistoryEntry, SuggestionSource, TextSpan};
+
+/// Suggestions returned for one query
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Suggestions from the FTS5 index vocabulary and `history`
+pub fn suggest_queries_fts5(
+    conn: &Connection,
+    query: &str,
+    folding: &CharFolding,
+    history: &[SearchHistoryEntry],
+) -> Result<Vec<QuerySuggestion>, String> {
+    with_fts5_vocabulary(conn, |vocabulary| {
+        suggest_queries(query, folding, vocabulary, history)
+    })
+}
+
+/// Suggestions from the vocabulary of `files` and `history`
+pub fn suggest_queries_direct(
+    query: &str,
+    files: &[FileData],
+    folding: &CharFolding,
+    history: &[SearchHistoryEntry],
+) -> Vec<QuerySuggestion> {
+    suggest_queries(query, folding, files_vocabulary(files, folding), history)
+}
+
+fn suggest_queries(
+    query: &str,
+    folding: &CharFolding,
+    vocabulary: impl IntoIterator<Item = (String, i64)>,
+    history: &[SearchHistoryEntry],
+) -> Vec<QuerySuggestion> {
+    let mut suggestions = Vec::new();
+    if let Some(corrected) = correct_words(query, folding, vocabulary) {
+        suggestions.push(QuerySuggestion {
+            query: corrected,
+            source: SuggestionSource::Vocabulary,
+        });
+    }
+    for past in similar_searches(query, folding, history) {
+        let duplicate = suggestions
+            .iter()
+            .any(|s| s.query.to_lowercase() == past.to_lowercase());
+        if !duplicate {
+            suggestions.push(QuerySuggestion {
+                query: past,
+                source: SuggestionSource::History,
+            });
+        }
+    }
+    suggestions.truncate(MAX_SUGGESTIONS);
+    suggestions
+}
+
+/// One query word and the best replacement seen so far
+struct Correction {
+    span: TextSpan,
+    term: String,
+    /// `term` with Arabic variants unified, if it has Arabic letters
+    arabic: Option<String>,
+    automaton: LevenshteinAutomaton,
+    /// The term itself is in the index
+    known: bool,
+    /// (files, term) of the most common Arabic spelling variant
+    variant: Option<(i64, String)>,
+    /// (edits, files, term) of the closest, most common term
+    closest: Option<(usize, i64, String)>,
+}
+
+/// The query with each word the index doesn't have replaced by its closest
+/// index term; `None` if nothing could be replaced
+fn correct_words(
+    query: &str,
+    folding: &CharFolding,
+    vocabulary: impl IntoIterator<Item = (String, i64)>,
+) -> Option<String> {
+    let mut corrections: Vec<Correction> = plain_words(query)
+        .ok()?
+        .into_iter()
+        .filter_map(|(word, span)| {
+            // Folding may split a word; leave those alone
+            let folded = TokenizedText::new(&folding.fold(&word));
+            let mut terms = folded.tokens();
+            let term = terms.next()?.to_string();
+            if terms.next().is_some() {
+                return None;
+            }
+            Some(Correction {
+                span,
+                arabic: has_arabic(&term).then(|| arabic_key(&term)),
+                automaton: LevenshteinAutomaton::new(&term, max_edits(&term)),
+                term,
+                known: false,
+                variant: None,
+                closest: None,
+            })
+        })
+        .collect();
+    if corrections.is_empty() {
+        return None;
+    }
+    let any_arabic = corrections.iter().any(|c| c.arabic.is_some());
+
+    for (word, files) in vocabulary {
+        let word_key = (any_arabic && has_arabic(&word)).then(|| arabic_key(&word));
+        for correction in &mut corrections {
+            if word == correction.term {
+                correction.known = true;
+            } else if correction.arabic.is_some() && correction.arabic == word_key {
+                if !matches!(&correction.variant, Some((most, _)) if *most >= files) {
+                    correction.variant = Some((files, word.clone()));
+                }
+            } else if let Some(edits) = correction.automaton.distance(&word) {
+                let better = match &correction.closest {
+                    Some((best_edits, best_files, _)) => {
+                        edits < *best_edits || (edits == *best_edits && files > *best_files)
+                    }
+                    None => true,
+                };
+                if better {
+                    correction.closest = Some((edits, files, word.clone()));
+                }
+            }
+        }
+    }
+
+    let replacements: Vec<(TextSpan, String)> = corrections
+        .into_iter()
+        .filter(|c| !c.known)
+        .filter_map(|c| {
+            let term = c
+                .variant
+                .map(|(_, term)| term)
+                .or(c.closest.map(|(_, _, term)| term))?;
+            Some((c.span, term))
+        })
+        .collect();
+    (!replacements.is_empty()).then(|| replace_spans(query, &replacements))
+}
+
+/// Earlier searches that found files and read like `query` (the same but
+/// for Arabic variants, or within the fuzzy edit distance), closest first,
+/// then most recent
+fn similar_searches(
+    query: &str,
+    folding: &CharFolding,
+    history: &[SearchHistoryEntry],
+) -> Vec<String> {
+    let normalize = |query: &str| {
+        normalize_for_search(&folding.fold(query))
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let normalized = normalize(query);
+    let key = arabic_key(&normalized);
+    let mut automaton = LevenshteinAutomaton::new(&key, max_edits(&key));
+
+    let mut similar: Vec<(usize, &str)> = history
+        .iter()
+        .filter(|entry| entry.result_count > 0)
+        .filter_map(|entry| {
+            let other = normalize(&entry.query);
+            if other == normalized {
+                return None;
+            }
+            let edits = automaton.distance(&arabic_key(&other))?;
+            Some((edits, entry.query.as_str()))
+        })
+        .collect();
+    // Stable, so equally close searches stay most recent first
+    similar.sort_by_key(|(edits, _)| *edits);
+    similar
+        .into_iter()
+        .map(|(_, query)| query.to_string())
+        .collect()
+}
+
+fn has_arabic(text: &str) -> bool {
+    text.chars().any(|c| ('\u{0600}'..='\u{06FF}').contains(&c))
+}
+
+/// `query` with each span (UTF-16 units, in order) replaced
+fn replace_spans(query: &str, replacements: &[(TextSpan, String)]) -> String {
+    let units: Vec<u16> = query.encode_utf16().collect();
+    let mut out = Vec::with_capacity(units.len());
+    let mut pos = 0;
+    for (span, text) in replacements {
+        out.extend_from_slice(&units[pos..span.start]);
+        out.extend(text.encode_utf16());
+        pos = span.end;
+    }
+    out.extend_from_slice(&units[pos..]);
+    String::from_utf16_lossy(&out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn vocabulary(terms: &[(&str, i64)]) -> Vec<(String, i64)> {
+        let mut vocabulary: Vec<(String, i64)> =
+            terms.iter().map(|&(t, n)| (t.to_string(), n)).collect();
+        vocabulary.sort();
+        vocabulary
+    }
+
+    fn past(query: &str, result_count: usize) -> SearchHistoryEntry {
+        SearchHistoryEntry {
+            query: query.to_string(),
+            timestamp: Utc::now(),
+            result_count,
+        }
+    }
+
+    #[test]
+    fn test_corrects_unknown_words() {
+        let folding = CharFolding::default();
+        let terms = vocabulary(&[("budget", 5), ("budgets", 9), ("meeting", 3), ("draft", 2)]);
+        let correct = |query| correct_words(query, &folding, terms.clone());
+
+        // Only the unknown word changes, and the rest of the query is kept
+        assert_eq!(
+            correct("Budgte meeting -draft").as_deref(),
+            Some("budget meeting -draft")
+        );
+        assert_eq!(correct("budgetz").as_deref(), Some("budgets"));
+        assert_eq!(correct("budget meeting"), None);
+        assert_eq!(correct("xyzzy"), None);
+    }
+
+    #[test]
+    fn test_arabic_variants() {
+        let folding = CharFolding::default();
+        // The variant wins over a more common word one edit away
+        let terms = vocabulary(&[("احمد", 1), ("أحمر", 10), ("المدرسه", 4), ("تقرير", 2)]);
+        assert_eq!(
+            correct_words("أحمد", &folding, terms.clone()).as_deref(),
+            Some("احمد")
+        );
+        assert_eq!(
+            correct_words("تقرير المدرسة", &folding, terms).as_deref(),
+            Some("تقرير المدرسه")
+        );
+        assert_eq!(arabic_key("مَدْرَسَة"), "مدرسه");
+    }
+
+    #[test]
+    fn test_suggestions_from_history() {
+        let folding = CharFolding::default();
+        let history = [
+            past("budget report", 4),
+            past("budgt reports", 0),
+            past("weather", 3),
+            past("مدرسة", 2),
+        ];
+        let suggest = |query| {
+            suggest_queries(query, &folding, vocabulary(&[("budget", 5)]), &history)
+                .into_iter()
+                .map(|s| (s.query, s.source))
+                .collect::<Vec<_>>()
+        };
+
+        // The past search is the same as the correction
+        assert_eq!(
+            suggest("budgt report"),
+            [("budget report".to_string(), SuggestionSource::Vocabulary)]
+        );
+        assert_eq!(
+            suggest("مدرسه"),
+            [("مدرسة".to_string(), SuggestionSource::History)]
+        );
+        assert!(suggest("weather").is_empty());
+    }
+}
@@ -5,14 +5,20 @@
 //! - SQLite FTS5 search index
 //! - Folder tracking
 //! - Search history
+//! - Character folding table
+//! - Indexing options
+//! - Search ranking options
+//! - Query parsing options
 
 use rusqlite::Connection;
 use std::collections::HashSet;
 use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
 use std::sync::{Arc, Mutex, RwLock};
 
-use crate::models::FileData;
-use crate::search::SearchHistory;
+use crate::extractors::TextFileRules;
+use crate::models::{FileData, IndexingSettings, QuerySettings, RankingSettings};
+use crate::search::{CharFolding, SearchHistory};
 
 /// Main application state
 pub struct AppState {
@@ -36,6 +42,28 @@ pub struct AppState {
 
     /// Search history
     pub search_history: Mutex<SearchHistory>,
+
+    /// User-defined character equivalences applied to queries
+    pub char_folding: RwLock<CharFolding>,
+
+    /// Indexing options (email attachments, ...)
+    pub indexing_settings: RwLock<IndexingSettings>,
+
+    /// "Treat as text" rules built from the indexing options
+    pub text_file_rules: RwLock<TextFileRules>,
+
+    /// Search ranking options (BM25 column weights, recency boost)
+    pub ranking_settings: RwLock<RankingSettings>,
+
+    /// Query parsing options (implicit operator)
+    pub query_settings: RwLock<QuerySettings>,
+
+    /// Held while writing `files` (and so the FTS5 tables): saves, clears and
+    /// FTS5 rebuilds take turns
+    pub index_writes: Mutex<()>,
+
+    /// An FTS5 rebuild for new character equivalences is running
+    pub fts_rebuild_running: AtomicBool,
 }
 
 impl Default for AppState {
@@ -48,6 +76,13 @@ impl Default for AppState {
             db: Mutex::new(None),
             data_dir: Mutex::new(None),
             search_history: Mutex::new(SearchHistory::new()),
+            char_folding: RwLock::new(CharFolding::default()),
+            indexing_settings: RwLock::new(IndexingSettings::default()),
+            text_file_rules: RwLock::new(TextFileRules::default()),
+            ranking_settings: RwLock::new(RankingSettings::default()),
+            query_settings: RwLock::new(QuerySettings::default()),
+            index_writes: Mutex::new(()),
+            fts_rebuild_running: AtomicBool::new(false),
         }
     }
 }
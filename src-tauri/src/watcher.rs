@@ -8,9 +8,9 @@ use std::thread;
 use std::time::Duration;
 
 use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 
-use crate::extractors::ALL_EXTENSIONS;
+use crate::extractors::is_supported_file;
 use crate::state::AppState;
 
 /// Start watching folders for changes
@@ -73,41 +73,44 @@ pub async fn start_watching(app: AppHandle, state: State<'_, AppState>) -> Resul
                         }
                         debounce_map.insert(path_str.clone(), now);
 
-                        // Check if it's a supported file
-                        if let Some(ext) = path.extension() {
-                            let ext_str = ext.to_string_lossy().to_lowercase();
-                            if ALL_EXTENSIONS.contains(&ext_str.as_str()) {
-                                let file_name = path
-                                    .file_name()
-                                    .map(|n| n.to_string_lossy().to_string())
-                                    .unwrap_or_default();
-
-                                // Skip temp files
-                                if file_name.starts_with('.') || file_name.starts_with("~$") {
-                                    continue;
-                                }
+                        // Check if it's a supported file (built-in or configured text type)
+                        let supported = app_handle
+                            .state::<AppState>()
+                            .text_file_rules
+                            .read()
+                            .map(|rules| is_supported_file(&path, &rules))
+                            .unwrap_or(false);
+                        if supported {
+                            let file_name = path
+                                .file_name()
+                                .map(|n| n.to_string_lossy().to_string())
+                                .unwrap_or_default();
+
+                            // Skip temp files
+                            if file_name.starts_with('.') || file_name.starts_with("~$") {
+                                continue;
+                            }
 
-                                match event.kind {
-                                    notify::EventKind::Create(_) | notify::EventKind::Modify(_) => {
-                                        let _ = app_handle.emit(
-                                            "file-changed",
-                                            serde_json::json!({
-                                                "type": "modified",
-                                                "path": path_str
-                                            }),
-                                        );
-                                    }
-                                    notify::EventKind::Remove(_) => {
-                                        let _ = app_handle.emit(
-                                            "file-changed",
-                                            serde_json::json!({
-                                                "type": "removed",
-                                                "path": path_str
-                                            }),
-                                        );
-                                    }
-                                    _ => {}
+                            match event.kind {
+                                notify::EventKind::Create(_) | notify::EventKind::Modify(_) => {
+                                    let _ = app_handle.emit(
+                                        "file-changed",
+                                        serde_json::json!({
+                                            "type": "modified",
+                                            "path": path_str
+                                        }),
+                                    );
+                                }
+                                notify::EventKind::Remove(_) => {
+                                    let _ = app_handle.emit(
+                                        "file-changed",
+                                        serde_json::json!({
+                                            "type": "removed",
+                                            "path": path_str
+                                        }),
+                                    );
                                 }
+                                _ => {}
                             }
                         }
                     }
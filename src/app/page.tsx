@@ -1,6 +1,6 @@
 'use client'
 
-import { useState, useEffect, useCallback, useRef } from 'react'
+import { useState, useEffect, useCallback, useRef, type ReactNode } from 'react'
 import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '@/components/ui/card'
 import { Button } from '@/components/ui/button'
 import { Input } from '@/components/ui/input'
@@ -54,7 +54,7 @@ import { AppSidebar } from '@/components/AppSidebar'
 import { FilePreviewPane } from '@/components/FilePreviewPane'
 import { FolderTree } from '@/components/FolderTree'
 
-import { tauriAPI, DocumentContent } from '@/lib/tauri-adapter'
+import { tauriAPI, DocumentContent, FileType, FilterChip, MatchMode, QuerySuggestion } from '@/lib/tauri-adapter'
 import { checkForUpdates, downloadAndInstallUpdate, UpdateInfo, UpdateProgress } from '@/lib/updater'
 import { initAnalytics, Analytics } from '@/lib/firebase'
 import { SearchLoader } from '@/components/SearchLoader'
@@ -63,7 +63,7 @@ import { ElapsedTime } from '@/components/ElapsedTime'
 interface FileData {
   path: string
   name: string
-  type: 'word' | 'powerpoint' | 'text' | 'excel'
+  type: FileType
   size: number
   lastModified: Date
 }
@@ -74,8 +74,10 @@ interface SearchResult {
     text: string
     index: number
     context: string
+    highlights?: Array<{ start: number; end: number }>
   }>
   score: number
+  expansions?: Array<{ term: string; expansion: string; distance: number }>
 }
 
 interface SearchHistoryItem {
@@ -97,6 +99,9 @@ export default function Home() {
   const [excludedFolders, setExcludedFolders] = useState<string[]>([]) // Folders excluded from search
   const [files, setFiles] = useState<FileData[]>([])
   const [searchQuery, setSearchQuery] = useState('')
+  const [queryFilters, setQueryFilters] = useState<FilterChip[]>([])
+  const [querySuggestions, setQuerySuggestions] = useState<QuerySuggestion[]>([]) // "Did you mean" for empty searches
+  const [matchMode, setMatchMode] = useState<MatchMode>('exact') // Smart also matches other forms of words
   const [searchResults, setSearchResults] = useState<SearchResult[]>([])
   const [filteredResults, setFilteredResults] = useState<SearchResult[]>([])
   const [isIndexing, setIsIndexing] = useState(false)
@@ -581,6 +586,13 @@ export default function Home() {
     setStats(stats)
   }
 
+  // Drop a field qualifier (and its '-') from the query; spans are UTF-16 like JS strings
+  const removeQueryFilter = (chip: FilterChip) => {
+    const start = chip.excluded && searchQuery[chip.span.start - 1] === '-' ? chip.span.start - 1 : chip.span.start
+    setSearchQuery((searchQuery.slice(0, start) + searchQuery.slice(chip.span.end)).replace(/\s+/g, ' ').trim())
+    setQueryFilters(filters => filters.filter(f => f !== chip))
+  }
+
   const searchFiles = async (loadMore = false) => {
     if (!searchQuery.trim() || files.length === 0) return
 
@@ -616,9 +628,10 @@ export default function Home() {
 
     try {
       // Build search options based on scope
-      const searchOptions: { filePath?: string; maxResults?: number; offset?: number } = {
+      const searchOptions: { filePath?: string; maxResults?: number; offset?: number; matchMode?: MatchMode } = {
         maxResults: maxResults + 1, // Get one extra to check if there are more
         offset,
+        matchMode,
       }
 
       if (searchScope.type === 'file' && searchScope.path) {
@@ -655,6 +668,8 @@ export default function Home() {
           setLoadingMessage(`Found ${displayResults.length}${hasMore ? '+' : ''} results!`)
           setSearchResults(displayResults)
           setTotalResultsFound(displayResults.length)
+          tauriAPI.parseSearchQuery(searchQuery).then(parsed => setQueryFilters(parsed.filters || []))
+          setQuerySuggestions(result.suggestions || [])
           setCurrentOffset(displayResults.length)
           addToSearchHistory(searchQuery, displayResults.length)
           // Track search analytics
@@ -664,6 +679,8 @@ export default function Home() {
       } else {
         console.error('❌ Search failed:', result.error)
         setError(result.error || 'Search failed')
+        setQueryFilters([])
+        setQuerySuggestions([])
         // if (!loadMore) setShowLoadingOverlay(false)
       }
     } catch (err) {
@@ -734,7 +751,7 @@ export default function Home() {
     }
   }
 
-  const getFileIcon = (type: 'word' | 'powerpoint' | 'text' | 'excel') => {
+  const getFileIcon = (type: FileType) => {
     switch (type) {
       case 'word': return <FileText className="h-4 w-4 text-blue-600" />
       case 'powerpoint': return <FileText className="h-4 w-4 text-orange-600" />
@@ -764,7 +781,8 @@ export default function Home() {
     // Remove quotes and operators, then extract remaining words
     let remaining = query
       .replace(/"[^"]+"/g, '') // Remove quoted phrases
-      .replace(/\b(AND|OR|NOT)\b/gi, '') // Remove operators
+      .replace(/\b(AND|OR|NOT|NEAR(\/\d+)?)\b/gi, '') // Remove operators
+      .replace(/~\d*/g, '') // Remove proximity distances
       .replace(/[+\-*?:]/g, ' ') // Remove special chars
       .trim()
 
@@ -795,6 +813,20 @@ export default function Home() {
     )
   }
 
+  // Mark the exact spans the backend matched (UTF-16 offsets into text)
+  const markSpans = (text: string, spans: Array<{ start: number; end: number }>) => {
+    const parts: ReactNode[] = []
+    let last = 0
+    spans.forEach((span, i) => {
+      if (span.start < last || span.end > text.length) return
+      parts.push(text.slice(last, span.start))
+      parts.push(<mark key={i} className="bg-yellow-200 dark:bg-yellow-500/40 px-1 rounded text-black dark:text-white font-medium">{text.slice(span.start, span.end)}</mark>)
+      last = span.end
+    })
+    parts.push(text.slice(last))
+    return parts
+  }
+
   const openFile = async (filePath: string, withSearchTerm?: string) => {
     try {
       // Show loading spinner
@@ -1056,7 +1088,7 @@ export default function Home() {
                           <Input
                             ref={searchInputRef}
                             placeholder="Search documents... (Ctrl+F)"
-                            className="h-12 pl-12 pr-12 text-base bg-background/50 border-border/50 focus:bg-background transition-all"
+                            className="h-12 pl-12 pr-28 text-base bg-background/50 border-border/50 focus:bg-background transition-all"
                             value={searchQuery}
                             onChange={(e) => setSearchQuery(e.target.value)}
                             onKeyPress={(e) => e.key === 'Enter' && searchFiles()}
@@ -1068,6 +1100,17 @@ export default function Home() {
                                 <X className="h-4 w-4" />
                               </button>
                             )}
+                            <button
+                              onClick={() => setMatchMode(matchMode === 'exact' ? 'smart' : 'exact')}
+                              className={`text-xs font-medium px-1.5 py-0.5 rounded transition-colors ${
+                                matchMode === 'smart' ? 'bg-primary/10 text-primary' : 'text-muted-foreground hover:text-foreground'
+                              }`}
+                              title={matchMode === 'smart'
+                                ? 'Smart: also finds other forms of words (contract, contracting), after exact matches'
+                                : 'Exact: finds words as typed'}
+                            >
+                              {matchMode === 'smart' ? 'Smart' : 'Exact'}
+                            </button>
                             <Popover>
                               <PopoverTrigger asChild>
                                 <button className="text-muted-foreground hover:text-primary transition-colors p-1" title="Search syntax help">
@@ -1098,6 +1141,14 @@ export default function Home() {
                                       <code className="bg-muted px-1.5 py-0.5 rounded font-mono">repo*</code>
                                       <span className="text-muted-foreground">Wildcard prefix</span>
                                     </div>
+                                    <div className="flex justify-between items-center gap-2">
+                                      <code className="bg-muted px-1.5 py-0.5 rounded font-mono">*4471</code>
+                                      <span className="text-muted-foreground">Anywhere inside words</span>
+                                    </div>
+                                    <div className="flex justify-between items-center gap-2">
+                                      <code className="bg-muted px-1.5 py-0.5 rounded font-mono">&quot;board budget&quot;~5</code>
+                                      <span className="text-muted-foreground">Within 5 words</span>
+                                    </div>
                                     <div className="flex justify-between items-center gap-2">
                                       <code className="bg-muted px-1.5 py-0.5 rounded font-mono">name:report</code>
                                       <span className="text-muted-foreground">Search filename</span>
@@ -1146,6 +1197,42 @@ export default function Home() {
                         </div>
                       )}
 
+                      {/* Field qualifiers from the last search (name:, ext:, size:, ...) */}
+                      {queryFilters.length > 0 && (
+                        <div className="flex flex-wrap items-center gap-2 px-1">
+                          {queryFilters.map((chip, i) => (
+                            <Badge key={i} variant={chip.excluded ? 'destructive' : 'secondary'} className="gap-1 font-mono text-xs">
+                              {chip.excluded && <span>not</span>}
+                              <span>{chip.field}:{chip.value}</span>
+                              <button onClick={() => removeQueryFilter(chip)} className="hover:opacity-70" title="Remove filter">
+                                <X className="h-3 w-3" />
+                              </button>
+                            </Badge>
+                          ))}
+                        </div>
+                      )}
+
+                      {/* "Did you mean" for a search that found nothing */}
+                      {querySuggestions.length > 0 && searchResults.length === 0 && (
+                        <div className="flex flex-wrap items-center gap-2 px-1 text-sm">
+                          <span className="text-muted-foreground">Did you mean:</span>
+                          {querySuggestions.map((suggestion, i) => (
+                            <button
+                              key={i}
+                              onClick={() => {
+                                setSearchQuery(suggestion.query)
+                                setQuerySuggestions([])
+                                searchInputRef.current?.focus()
+                              }}
+                              className="font-medium text-primary hover:underline"
+                              title={suggestion.source === 'history' ? 'A similar earlier search' : 'Closest words in your documents'}
+                            >
+                              {suggestion.query}
+                            </button>
+                          ))}
+                        </div>
+                      )}
+
                       {/* Loading saved index indicator */}
                       {isLoadingIndex && (
                         <div className="flex items-center gap-2 text-sm text-muted-foreground px-1 animate-pulse">
@@ -1312,6 +1399,15 @@ export default function Home() {
                                   <SelectItem value="powerpoint">PowerPoint</SelectItem>
                                   <SelectItem value="excel">Excel</SelectItem>
                                   <SelectItem value="text">Text</SelectItem>
+                                  <SelectItem value="code">Code</SelectItem>
+                                  <SelectItem value="html">Web Page</SelectItem>
+                                  <SelectItem value="epub">E-book</SelectItem>
+                                  <SelectItem value="csv">CSV</SelectItem>
+                                  <SelectItem value="email">Email</SelectItem>
+                                  <SelectItem value="notebook">Notebook</SelectItem>
+                                  <SelectItem value="subtitles">Subtitles</SelectItem>
+                                  <SelectItem value="latex">LaTeX</SelectItem>
+                                  <SelectItem value="image">Image</SelectItem>
                                 </SelectContent>
                               </Select>
                             </div>
@@ -1398,11 +1494,20 @@ export default function Home() {
                               <div className="space-y-2 bg-muted/30 rounded-xl p-3 border border-border/10">
                                 {(result.matches || []).slice(0, 2).map((match, i) => (
                                   <p key={i} className="text-sm text-muted-foreground leading-relaxed font-mono">
-                                    ...{highlightText(match?.context || '', searchQuery)}...
+                                    ...{match?.highlights?.length
+                                      ? markSpans(match.context, match.highlights)
+                                      : highlightText(match?.context || '', searchQuery)}...
                                   </p>
                                 ))}
                               </div>
 
+                              {/* Fuzzy matches that stood in for typed terms */}
+                              {result.expansions && result.expansions.length > 0 && (
+                                <p className="mt-2 text-xs text-muted-foreground">
+                                  Similar match: {result.expansions.map(e => `${e.term} → ${e.expansion}`).join(', ')}
+                                </p>
+                              )}
+
                               {/* Search in this file button */}
                               <div className="mt-3 pt-3 border-t border-border/20 flex justify-end" onClick={(e) => e.stopPropagation()}>
                                 <Button
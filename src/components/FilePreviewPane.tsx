@@ -6,12 +6,12 @@ import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from '@
 import { ExternalLink, FolderOpen, ChevronUp, ChevronDown, X, FileText, File, Eye, AlignLeft, Loader2, Type } from 'lucide-react'
 import { cn } from '@/lib/utils'
 import { StructuredContentRenderer } from './StructuredContentRenderer'
-import type { DocumentContent } from '@/lib/tauri-adapter'
+import type { DocumentContent, FileType } from '@/lib/tauri-adapter'
 
 interface FileData {
     path: string
     name: string
-    type: 'word' | 'powerpoint' | 'text' | 'pdf' | 'excel'
+    type: FileType | 'pdf'
     size: number
     lastModified: Date
 }
@@ -199,7 +199,7 @@ export function FilePreviewPane({
         }
     }
 
-    const getFileIcon = (type: 'word' | 'powerpoint' | 'text' | 'pdf' | 'excel') => {
+    const getFileIcon = (type: FileType | 'pdf') => {
         switch (type) {
             case 'word': return <FileText className="h-5 w-5 text-blue-600" />
             case 'powerpoint': return <FileText className="h-5 w-5 text-orange-600" />
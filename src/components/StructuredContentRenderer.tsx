@@ -13,6 +13,7 @@ interface StructuredContentRendererProps {
   onMatchCountChange?: (count: number) => void;
   currentMatchIndex?: number;
   isRTL?: boolean;
+  scrollToSection?: number; // Outline node's section_index to bring into view
 }
 
 // Helper to extract search terms from query
@@ -52,6 +53,8 @@ interface HighlightedTextProps {
   matchRefs: React.MutableRefObject<(HTMLElement | null)[]>;
   style?: React.CSSProperties;
   className?: string;
+  dir?: string;
+  lang?: string;
 }
 
 const HighlightedText: React.FC<HighlightedTextProps> = ({
@@ -61,16 +64,18 @@ const HighlightedText: React.FC<HighlightedTextProps> = ({
   matchCountRef,
   matchRefs,
   style,
-  className
+  className,
+  dir,
+  lang
 }) => {
   if (!searchRegex) {
-    return <span style={style} className={className}>{text}</span>;
+    return <span style={style} className={className} dir={dir} lang={lang}>{text}</span>;
   }
 
   const parts = text.split(searchRegex);
 
   return (
-    <span style={style} className={className}>
+    <span style={style} className={className} dir={dir} lang={lang}>
       {parts.map((part, i) => {
         if (searchRegex.test(part)) {
           const index = matchCountRef.current++;
@@ -143,6 +148,8 @@ const TextRunRenderer: React.FC<TextRunRendererProps> = ({
       matchRefs={matchRefs}
       style={style}
       className={className.trim()}
+      dir={run.style.direction ?? undefined}
+      lang={run.style.language ?? undefined}
     />
   );
 };
@@ -167,6 +174,12 @@ const SectionRenderer: React.FC<SectionRendererProps> = ({
 }) => {
   const sectionType = section.section_type;
 
+  // Direction and language of text blocks (mixed Arabic/English paragraphs)
+  const textAttrs = {
+    dir: section.properties?.direction ?? undefined,
+    lang: section.properties?.language ?? undefined
+  };
+
   const renderRuns = () => {
     if (section.runs && section.runs.length > 0) {
       return section.runs.map((run, i) => (
@@ -209,6 +222,16 @@ const SectionRenderer: React.FC<SectionRendererProps> = ({
     ));
   };
 
+  // Source location label (subtitle cue time, notebook cell)
+  const renderLocation = () => {
+    if (!section.properties?.location) return null;
+    return (
+      <div className="text-xs font-mono text-muted-foreground mb-1">
+        {section.properties.location}
+      </div>
+    );
+  };
+
   // Helper function to render heading with proper level
   const renderHeading = (level: number, children: React.ReactNode) => {
     const sizeClasses: Record<number, string> = {
@@ -222,12 +245,12 @@ const SectionRenderer: React.FC<SectionRendererProps> = ({
     const className = cn("text-foreground", sizeClasses[level] || sizeClasses[6]);
 
     switch (level) {
-      case 1: return <h1 className={className}>{children}</h1>;
-      case 2: return <h2 className={className}>{children}</h2>;
-      case 3: return <h3 className={className}>{children}</h3>;
-      case 4: return <h4 className={className}>{children}</h4>;
-      case 5: return <h5 className={className}>{children}</h5>;
-      default: return <h6 className={className}>{children}</h6>;
+      case 1: return <h1 className={className} {...textAttrs}>{children}</h1>;
+      case 2: return <h2 className={className} {...textAttrs}>{children}</h2>;
+      case 3: return <h3 className={className} {...textAttrs}>{children}</h3>;
+      case 4: return <h4 className={className} {...textAttrs}>{children}</h4>;
+      case 5: return <h5 className={className} {...textAttrs}>{children}</h5>;
+      default: return <h6 className={className} {...textAttrs}>{children}</h6>;
     }
   };
 
@@ -239,16 +262,19 @@ const SectionRenderer: React.FC<SectionRendererProps> = ({
 
     case 'Paragraph':
       return (
-        <p className="text-foreground/90 leading-7 mb-4">
-          {renderRuns()}
-        </p>
+        <>
+          {renderLocation()}
+          <p className="text-foreground/90 leading-7 mb-4" {...textAttrs}>
+            {renderRuns()}
+          </p>
+        </>
       );
 
     case 'ListItem': {
       const { ordered, depth: listDepth } = sectionType;
       const indent = `pl-${Math.min(listDepth * 4 + 4, 16)}`;
       return (
-        <li className={cn("text-foreground/90 leading-7 mb-1", indent)}>
+        <li className={cn("text-foreground/90 leading-7 mb-1", indent)} {...textAttrs}>
           {renderRuns()}
         </li>
       );
@@ -258,7 +284,7 @@ const SectionRenderer: React.FC<SectionRendererProps> = ({
       return (
         <div className="my-6 overflow-hidden rounded-xl border border-border/40 shadow-sm bg-card/30 backdrop-blur-sm">
           <div className="overflow-x-auto">
-            <table className="min-w-full divide-y divide-border/40">
+            <table className="min-w-full divide-y divide-border/40" dir={textAttrs.dir}>
               <tbody className="divide-y divide-border/40">
                 {renderChildren()}
               </tbody>
@@ -269,14 +295,22 @@ const SectionRenderer: React.FC<SectionRendererProps> = ({
 
     case 'TableRow':
       return (
-        <tr className="hover:bg-muted/40 transition-colors group">
+        <tr
+          className={cn(
+            "hover:bg-muted/40 transition-colors group",
+            section.properties?.header && "bg-muted/40 font-semibold"
+          )}
+        >
           {renderChildren()}
         </tr>
       );
 
     case 'TableCell':
       return (
-        <td className="px-6 py-4 text-sm align-top leading-relaxed group-first:font-semibold group-first:bg-muted/20">
+        <td
+          className="px-6 py-4 text-sm align-top leading-relaxed group-first:font-semibold group-first:bg-muted/20"
+          {...textAttrs}
+        >
           {renderRuns()}
         </td>
       );
@@ -299,14 +333,33 @@ const SectionRenderer: React.FC<SectionRendererProps> = ({
         </div>
       );
 
+    case 'Chart':
+    case 'Diagram':
+      return (
+        <div className="my-6 p-4 bg-muted/20 border border-border/40 rounded-lg">
+          <div className="flex items-center gap-2 mb-3 text-xs uppercase tracking-wide text-muted-foreground font-medium">
+            <span>{sectionType.type === 'Chart' ? 'Chart' : 'Diagram'}</span>
+            {sectionType.type === 'Chart' && sectionType.title && (
+              <span className="normal-case tracking-normal text-sm text-foreground">
+                {sectionType.title}
+              </span>
+            )}
+          </div>
+          {renderChildren()}
+        </div>
+      );
+
     case 'HorizontalRule':
       return <hr className="my-6 border-border/50" />;
 
     case 'CodeBlock':
       return (
-        <pre className="my-4 p-4 bg-muted rounded-lg overflow-x-auto font-mono text-sm">
-          {renderRuns()}
-        </pre>
+        <>
+          {renderLocation()}
+          <pre className="my-4 p-4 bg-muted rounded-lg overflow-x-auto font-mono text-sm">
+            {renderRuns()}
+          </pre>
+        </>
       );
 
     case 'Link':
@@ -325,6 +378,7 @@ const SectionRenderer: React.FC<SectionRendererProps> = ({
       if (section.properties?.image_data) {
         return (
           <figure className="my-4">
+            {renderLocation()}
             <img
               src={section.properties.image_data}
               alt={section.properties.alt_text || 'Image'}
@@ -363,8 +417,10 @@ export const StructuredContentRenderer: React.FC<StructuredContentRendererProps>
   searchQuery,
   onMatchCountChange,
   currentMatchIndex = 0,
-  isRTL = false
+  isRTL = false,
+  scrollToSection
 }) => {
+  const containerRef = useRef<HTMLDivElement>(null);
   const matchRefs = useRef<(HTMLElement | null)[]>([]);
   const matchCountRef = useRef(0);
   const [searchRegex, setSearchRegex] = useState<RegExp | null>(null);
@@ -411,6 +467,16 @@ export const StructuredContentRenderer: React.FC<StructuredContentRendererProps>
     }
   }, [currentMatchIndex, searchRegex]);
 
+  // Scroll to the section picked in the outline
+  useEffect(() => {
+    if (scrollToSection === undefined || !containerRef.current) return;
+    // Wrappers use display: contents, so scroll their first rendered element
+    const wrapper = containerRef.current.querySelector(
+      `[data-section-index="${scrollToSection}"]`
+    );
+    wrapper?.firstElementChild?.scrollIntoView({ behavior: 'smooth', block: 'start' });
+  }, [scrollToSection, content]);
+
   // Render metadata if available
   const renderMetadata = () => {
     const { metadata } = content;
@@ -465,9 +531,12 @@ export const StructuredContentRenderer: React.FC<StructuredContentRendererProps>
   };
 
   const grouped = groupSections(content.sections);
+  // Position of each section in content.sections (outline section_index)
+  const sectionIndexes = new Map(content.sections.map((section, i) => [section, i]));
 
   return (
     <div
+      ref={containerRef}
       className={cn("structured-content max-w-none", isRTL && "text-right")}
       dir={isRTL ? "rtl" : "ltr"}
     >
@@ -499,14 +568,15 @@ export const StructuredContentRenderer: React.FC<StructuredContentRendererProps>
         }
 
         return (
-          <SectionRenderer
-            key={i}
-            section={item}
-            searchRegex={searchRegex}
-            currentMatchIndex={currentMatchIndex}
-            matchCountRef={matchCountRef}
-            matchRefs={matchRefs}
-          />
+          <div key={i} className="contents" data-section-index={sectionIndexes.get(item)}>
+            <SectionRenderer
+              section={item}
+              searchRegex={searchRegex}
+              currentMatchIndex={currentMatchIndex}
+              matchCountRef={matchCountRef}
+              matchRefs={matchRefs}
+            />
+          </div>
         );
       })}
     </div>
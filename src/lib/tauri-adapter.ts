@@ -6,15 +6,36 @@ interface RustFileData {
   last_modified: string;
   file_type: string;
   content: string;
+  language?: string;
+  has_macros?: boolean;
 }
 
+// File type category (matches Rust get_file_type)
+export type FileType = "word" | "powerpoint" | "text" | "excel" | "html" | "epub" | "csv" | "email" | "code" | "notebook" | "subtitles" | "latex" | "image";
+
 export interface FileData {
   path: string;
   name: string;
-  type: "word" | "powerpoint" | "text" | "excel";
+  type: FileType;
   size: number;
   lastModified: Date;
   content?: string;
+  language?: string; // Detected language code ("en", "ar", "ur", "gu", "und", ...)
+  hasMacros?: boolean; // OOXML package contains a VBA project
+}
+
+// Range of a string in UTF-16 code units (matches Rust TextSpan)
+export interface TextSpan {
+  start: number;
+  end: number;
+}
+
+// Field qualifier parsed from a query (matches Rust FilterChip)
+export interface FilterChip {
+  field: string; // "name", "path", "type", "ext", "modified" or "size"
+  value: string; // As typed, e.g. ">2MB"
+  excluded: boolean; // Inside an exclusion, e.g. -ext:tmp
+  span: TextSpan; // Where the qualifier sits in the query
 }
 
 export interface SearchResult {
@@ -23,10 +44,29 @@ export interface SearchResult {
     text: string;
     index: number;
     context: string;
+    highlights?: TextSpan[]; // Matched terms within context
   }>;
   score: number;
+  expansions?: TermExpansion[]; // Fuzzy matches standing in for typed terms
+}
+
+// A query term expanded to a similar index term (matches Rust TermExpansion)
+export interface TermExpansion {
+  term: string; // As typed, e.g. "clinet"
+  expansion: string; // Index term it matched, e.g. "client"
+  distance: number; // Edits between the two
+}
+
+// A "did you mean" query offered when a search finds nothing (matches Rust QuerySuggestion)
+export interface QuerySuggestion {
+  query: string; // Full query to search instead
+  source: "vocabulary" | "history"; // Spelling correction or a past search
 }
 
+// How query words match indexed words (matches Rust MatchMode)
+// "smart" also finds other forms (contract, contracting), after the exact ones
+export type MatchMode = "exact" | "smart";
+
 export interface FolderInfo {
   path: string;
   fileCount: number;
@@ -40,6 +80,7 @@ export interface IndexStats {
   textFiles: number;
   totalSize: number;
   folderCount: number;
+  languages: Record<string, number>;
 }
 
 export interface LoadIndexResult {
@@ -67,6 +108,34 @@ export interface SearchHistoryEntry {
   resultCount: number;
 }
 
+// Indexing options (matches Rust IndexingSettings)
+export interface IndexingSettings {
+  index_email_attachments: boolean; // Index supported email attachments as child entries
+  text_extensions: string[]; // Extra extensions read as text/code, e.g. ["log", "py"]
+  text_file_patterns: string[]; // File name patterns read as text, e.g. ["Dockerfile", "*.conf"]
+}
+
+// Search ranking options (matches Rust RankingSettings)
+export interface RankingSettings {
+  name_weight: number; // BM25 column weights (name above content, path low)
+  content_weight: number;
+  path_weight: number;
+  recency_boost: boolean; // Rank recently modified files higher
+  recency_half_life_days: number; // Age at which the boost has halved
+}
+
+// Query language options (matches Rust QuerySettings)
+export interface QuerySettings {
+  implicit_operator: "and" | "or"; // How adjacent terms without an operator combine
+  fuzzy: boolean; // Also match index terms a typo or two away
+}
+
+// User-defined search equivalence (matches Rust CharEquivalence)
+export interface CharEquivalence {
+  from: string;
+  to: string;
+}
+
 // ========== Structured Document Content Types ==========
 
 // Text formatting style for a run of text
@@ -80,8 +149,13 @@ export interface TextStyle {
   highlight?: string;
   color?: string;
   font_size?: number;
+  direction?: TextDirection; // Explicit run direction (DOCX w:rtl)
+  language?: string; // Language tag, e.g. "ar-SA"
 }
 
+// Base text direction (used for the `dir` attribute)
+export type TextDirection = "ltr" | "rtl";
+
 // A run of text with consistent formatting
 export interface TextRun {
   text: string;
@@ -95,6 +169,10 @@ export interface SectionProperties {
   alt_text?: string;
   width?: number;
   height?: number;
+  header?: boolean; // Table row is the header row
+  location?: string; // Subtitle cue time range, notebook cell ("In [3]")
+  direction?: TextDirection; // Declared by the source or from the first strong character
+  language?: string; // Language tag shared by all runs
 }
 
 // Type of content section (matches Rust SectionType with serde(tag = "type"))
@@ -110,7 +188,9 @@ export type SectionType =
   | { type: "SlideBreak"; slide_number: number }
   | { type: "CodeBlock" }
   | { type: "HorizontalRule" }
-  | { type: "Link"; url: string };
+  | { type: "Link"; url: string }
+  | { type: "Chart"; title?: string | null }
+  | { type: "Diagram" };
 
 // A section of document content (paragraph, heading, table, etc.)
 export interface ContentSection {
@@ -130,6 +210,8 @@ export interface DocumentMetadata {
   page_count?: number;
   slide_count?: number;
   sheet_count?: number;
+  language?: string; // Declared language (e.g. EPUB dc:language)
+  recipients?: string; // Email To and Cc
 }
 
 // Full structured document content
@@ -139,6 +221,15 @@ export interface DocumentContent {
   metadata: DocumentMetadata;
 }
 
+// Document outline (table of contents) node
+export interface OutlineNode {
+  title: string;
+  level: number;
+  // Index into DocumentContent.sections (null for XLSX sheets)
+  section_index: number | null;
+  children: OutlineNode[];
+}
+
 // ========== End Structured Document Content Types ==========
 
 // Search filters
@@ -149,6 +240,8 @@ export interface SearchFilters {
   minSize?: number;
   maxSize?: number;
   folderPath?: string;
+  languages?: string[];
+  hasMacros?: boolean;
 }
 
 // Event listeners
@@ -217,6 +310,18 @@ const setupTauriListeners = async () => {
         emit("doc-indexing-complete", event.payload);
       }
     );
+
+    // Listen for search index rebuilds (e.g. after equivalences change)
+    await listen<IndexingProgressPayload>("fts-rebuild-progress", (event) => {
+      emit("fts-rebuild-progress", event.payload);
+    });
+
+    listen<{ success: boolean; error?: string }>(
+      "fts-rebuild-complete",
+      (event) => {
+        emit("fts-rebuild-complete", event.payload);
+      }
+    );
   } catch (e) {
     console.error("Failed to setup Tauri listeners:", e);
   }
@@ -274,7 +379,9 @@ export const tauriAPI = {
         size: f.size,
         content: f.content,
         lastModified: new Date(f.last_modified),
-        type: f.file_type as "word" | "powerpoint" | "text" | "excel",
+        type: f.file_type as FileType,
+        language: f.language,
+        hasMacros: f.has_macros,
       }));
 
       emit("indexing-status", { isIndexing: false });
@@ -386,7 +493,9 @@ export const tauriAPI = {
         name: f.name,
         size: f.size,
         lastModified: new Date(f.last_modified),
-        type: f.file_type as "word" | "powerpoint" | "text" | "excel",
+        type: f.file_type as FileType,
+        language: f.language,
+        hasMacros: f.has_macros,
       }));
       return { success: true, files: mappedFiles };
     } catch (e: any) {
@@ -532,6 +641,9 @@ export const tauriAPI = {
    * @param options.folderPath - Search only in this folder
    * @param options.maxResults - Maximum results to return (default 100)
    * @param options.offset - Skip first N results (for "Load More")
+   * @param options.languages - Only return documents in these languages
+   * @param options.hasMacros - Only return documents with (true) or without (false) macros
+   * @param options.matchMode - "exact" (default) or "smart" (stemmed) matching
    */
   searchFiles: async (
     query: string,
@@ -540,6 +652,9 @@ export const tauriAPI = {
       filePath?: string;
       maxResults?: number;
       offset?: number;
+      languages?: string[];
+      hasMacros?: boolean;
+      matchMode?: MatchMode;
     }
   ) => {
     if (typeof window === "undefined") {
@@ -551,8 +666,20 @@ export const tauriAPI = {
       // Rust returns SearchResult with RustFileData
       interface RustSearchResult {
         file: RustFileData;
-        matches: Array<{ text: string; index: number; context: string }>;
+        matches: Array<{
+          text: string;
+          index: number;
+          context: string;
+          highlights?: TextSpan[];
+        }>;
         score: number;
+        expansions?: TermExpansion[];
+      }
+
+      // Rust returns the results along with suggestions for empty searches
+      interface RustSearchResponse {
+        results: RustSearchResult[];
+        suggestions: QuerySuggestion[];
       }
 
       // Build filters object for Rust
@@ -562,15 +689,18 @@ export const tauriAPI = {
             folder_path: folderPath || null,
             max_results: options.maxResults || null,
             offset: options.offset || null,
+            languages: options.languages?.length ? options.languages : null,
+            has_macros: options.hasMacros ?? null,
+            match_mode: options.matchMode || null,
           }
         : null;
 
-      const results = await invoke<RustSearchResult[]>("search_index", {
+      const response = await invoke<RustSearchResponse>("search_index", {
         query,
         filters,
       });
 
-      const mappedResults: SearchResult[] = results.map((r) => ({
+      const mappedResults: SearchResult[] = (response.results || []).map((r) => ({
         file: {
           path: r.file?.path || "",
           name: r.file?.name || "",
@@ -580,24 +710,49 @@ export const tauriAPI = {
             ? new Date(r.file.last_modified)
             : new Date(),
           type:
-            (r.file?.file_type as "word" | "powerpoint" | "text" | "excel") ||
+            (r.file?.file_type as FileType) ||
             "text",
+          language: r.file?.language,
+          hasMacros: r.file?.has_macros,
         },
         matches: (r.matches || []).map((m) => ({
           text: m?.text || "",
           index: m?.index || 0,
           context: m?.context || "",
+          highlights: m?.highlights || [],
         })),
         score: r.score || 0,
+        expansions: r.expansions || [],
       }));
 
-      return { success: true, results: mappedResults };
+      return {
+        success: true,
+        results: mappedResults,
+        suggestions: response.suggestions || [],
+      };
     } catch (e: any) {
       console.error("Search error:", e);
       return { success: false, error: e.message || String(e) };
     }
   },
 
+  // Parse a query without searching, returning its field qualifiers
+  parseSearchQuery: async (
+    query: string
+  ): Promise<{ success: boolean; filters?: FilterChip[]; error?: string }> => {
+    if (typeof window === "undefined") {
+      return { success: false, error: "Not available during SSR" };
+    }
+    const { invoke } = await import("@tauri-apps/api/core");
+
+    try {
+      const filters = await invoke<FilterChip[]>("parse_search_query", { query });
+      return { success: true, filters };
+    } catch (e: any) {
+      return { success: false, error: e.message || String(e) };
+    }
+  },
+
   extractContent: async (filePath: string) => {
     if (typeof window === "undefined") {
       return { success: false, error: "Not available during SSR" };
@@ -640,6 +795,45 @@ export const tauriAPI = {
     }
   },
 
+  /**
+   * Get a document's outline (headings, slides or sheets) for navigation
+   * section_index points into the structured preview's sections
+   */
+  getDocumentOutline: async (filePath: string) => {
+    if (typeof window === "undefined") {
+      return { success: false, error: "Not available during SSR" };
+    }
+    const { invoke } = await import("@tauri-apps/api/core");
+
+    try {
+      const outline = await invoke<OutlineNode[]>("get_document_outline", {
+        filePath,
+      });
+      return { success: true, outline };
+    } catch (e: any) {
+      console.error("Get document outline error:", e);
+      return { success: false, error: e.message || String(e) };
+    }
+  },
+
+  /**
+   * Get a thumbnail for a result card as a data: URI (cached by the backend)
+   * Returns null when the file has no embedded PNG/JPEG thumbnail
+   */
+  getFileThumbnail: async (filePath: string): Promise<string | null> => {
+    if (typeof window === "undefined") {
+      return null;
+    }
+    const { invoke } = await import("@tauri-apps/api/core");
+
+    try {
+      return await invoke<string | null>("get_file_thumbnail", { filePath });
+    } catch (e: any) {
+      console.error("Get thumbnail error:", e);
+      return null;
+    }
+  },
+
   startWatching: async () => {
     if (typeof window === "undefined") {
       return { success: false, error: "Not available during SSR" };
@@ -753,6 +947,14 @@ export const tauriAPI = {
       listeners["doc-indexing-complete"] || [];
     listeners["doc-indexing-complete"].push(cb);
   },
+  onFtsRebuildProgress: (cb: Function) => {
+    listeners["fts-rebuild-progress"] = listeners["fts-rebuild-progress"] || [];
+    listeners["fts-rebuild-progress"].push(cb);
+  },
+  onFtsRebuildComplete: (cb: Function) => {
+    listeners["fts-rebuild-complete"] = listeners["fts-rebuild-complete"] || [];
+    listeners["fts-rebuild-complete"].push(cb);
+  },
   removeAllListeners: (channel: string) => {
     delete listeners[channel];
   },
@@ -904,4 +1106,152 @@ export const tauriAPI = {
       return { success: false, error: e.message || e };
     }
   },
+
+  // ========== Settings ==========
+
+  // Get user-defined character equivalences
+  getCharEquivalences: async (): Promise<{
+    success: boolean;
+    equivalences?: CharEquivalence[];
+    error?: string;
+  }> => {
+    if (typeof window === "undefined") {
+      return { success: false, error: "Not available during SSR" };
+    }
+    const { invoke } = await import("@tauri-apps/api/core");
+
+    try {
+      const equivalences = await invoke<CharEquivalence[]>(
+        "get_char_equivalences"
+      );
+      return { success: true, equivalences };
+    } catch (e: any) {
+      return { success: false, error: e.message || e };
+    }
+  },
+
+  // Replace character equivalences (search index rebuilds in background)
+  setCharEquivalences: async (
+    equivalences: CharEquivalence[]
+  ): Promise<{ success: boolean; error?: string }> => {
+    if (typeof window === "undefined") {
+      return { success: false, error: "Not available during SSR" };
+    }
+    const { invoke } = await import("@tauri-apps/api/core");
+
+    try {
+      await invoke("set_char_equivalences", { equivalences });
+      return { success: true };
+    } catch (e: any) {
+      return { success: false, error: e.message || e };
+    }
+  },
+
+  // Get indexing options
+  getIndexingSettings: async (): Promise<{
+    success: boolean;
+    settings?: IndexingSettings;
+    error?: string;
+  }> => {
+    if (typeof window === "undefined") {
+      return { success: false, error: "Not available during SSR" };
+    }
+    const { invoke } = await import("@tauri-apps/api/core");
+
+    try {
+      const settings = await invoke<IndexingSettings>("get_indexing_settings");
+      return { success: true, settings };
+    } catch (e: any) {
+      return { success: false, error: e.message || e };
+    }
+  },
+
+  // Replace indexing options (applies to files indexed from now on)
+  setIndexingSettings: async (
+    settings: IndexingSettings
+  ): Promise<{ success: boolean; error?: string }> => {
+    if (typeof window === "undefined") {
+      return { success: false, error: "Not available during SSR" };
+    }
+    const { invoke } = await import("@tauri-apps/api/core");
+
+    try {
+      await invoke("set_indexing_settings", { settings });
+      return { success: true };
+    } catch (e: any) {
+      return { success: false, error: e.message || e };
+    }
+  },
+
+  // Get search ranking options
+  getRankingSettings: async (): Promise<{
+    success: boolean;
+    settings?: RankingSettings;
+    error?: string;
+  }> => {
+    if (typeof window === "undefined") {
+      return { success: false, error: "Not available during SSR" };
+    }
+    const { invoke } = await import("@tauri-apps/api/core");
+
+    try {
+      const settings = await invoke<RankingSettings>("get_ranking_settings");
+      return { success: true, settings };
+    } catch (e: any) {
+      return { success: false, error: e.message || e };
+    }
+  },
+
+  // Replace search ranking options (applies to the next search)
+  setRankingSettings: async (
+    settings: RankingSettings
+  ): Promise<{ success: boolean; error?: string }> => {
+    if (typeof window === "undefined") {
+      return { success: false, error: "Not available during SSR" };
+    }
+    const { invoke } = await import("@tauri-apps/api/core");
+
+    try {
+      await invoke("set_ranking_settings", { settings });
+      return { success: true };
+    } catch (e: any) {
+      return { success: false, error: e.message || e };
+    }
+  },
+
+  // Get query language options
+  getQuerySettings: async (): Promise<{
+    success: boolean;
+    settings?: QuerySettings;
+    error?: string;
+  }> => {
+    if (typeof window === "undefined") {
+      return { success: false, error: "Not available during SSR" };
+    }
+    const { invoke } = await import("@tauri-apps/api/core");
+
+    try {
+      const settings = await invoke<QuerySettings>("get_query_settings");
+      return { success: true, settings };
+    } catch (e: any) {
+      return { success: false, error: e.message || e };
+    }
+  },
+
+  // Replace query language options (applies to the next search)
+  setQuerySettings: async (
+    settings: QuerySettings
+  ): Promise<{ success: boolean; error?: string }> => {
+    if (typeof window === "undefined") {
+      return { success: false, error: "Not available during SSR" };
+    }
+    const { invoke } = await import("@tauri-apps/api/core");
+
+    try {
+      await invoke("set_query_settings", { settings });
+      return { success: true };
+    } catch (e: any) {
+      return { success: false, error: e.message || e };
+    }
+  },
 };